@@ -0,0 +1,160 @@
+//! Benchmarks of the parsing paths that dominate bulk downloads and busy
+//! streams.
+//!
+//! The payloads are generated rather than checked in, with the same shape and
+//! field values of the same magnitude as the responses they stand in for:
+//!
+//! * the grouped daily bars of the whole US stock market, about 10,000 bars
+//! * a full page of 50,000 trades from `/v3/trades/{stocksTicker}`
+//! * a WebSocket frame of 1,000 trades and quotes
+//!
+//! Run with `cargo bench --bench parsing`. See `benches/README.md` for
+//! reference numbers.
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use polygon_client::bench::decode_json;
+use polygon_client::events::{parse_events, ParseOptions};
+use polygon_client::types::{
+    ClosesOnly, SlimResponse, StockEquitiesGroupedDailyResponse, StockEquitiesTradesResponseV3,
+};
+
+const GROUPED_DAILY_BARS: usize = 10_000;
+const TRADES_PAGE_SIZE: usize = 50_000;
+const FRAME_EVENTS: usize = 1_000;
+
+/// Returns a price that varies with `i` but stays in a realistic range.
+fn price(i: usize) -> f64 {
+    10.0 + (i * 7919 % 50_000) as f64 / 100.0
+}
+
+fn grouped_daily() -> String {
+    let results: Vec<_> = (0..GROUPED_DAILY_BARS)
+        .map(|i| {
+            let p = price(i);
+            format!(
+                r#"{{"T":"T{:05}","v":{},"vw":{:.4},"o":{:.2},"c":{:.2},"h":{:.2},"l":{:.2},"t":1704402000000,"n":{}}}"#,
+                i,
+                1_000 + i * 37,
+                p * 1.001,
+                p,
+                p * 1.01,
+                p * 1.02,
+                p * 0.98,
+                10 + i % 5_000
+            )
+        })
+        .collect();
+    format!(
+        r#"{{"queryCount":{n},"resultsCount":{n},"adjusted":true,"results":[{}],"status":"OK","request_id":"1","count":{n}}}"#,
+        results.join(","),
+        n = GROUPED_DAILY_BARS
+    )
+}
+
+fn trades_page() -> String {
+    let results: Vec<_> = (0..TRADES_PAGE_SIZE)
+        .map(|i| {
+            let t = 1_704_292_200_000_000_000u64 + i as u64 * 1_000_017;
+            format!(
+                r#"{{"conditions":[12,37],"exchange":{},"id":"{}","participant_timestamp":{},"price":{:.4},"sequence_number":{},"sip_timestamp":{},"size":{},"tape":3}}"#,
+                i % 20,
+                52_983_525_034_825u64 + i as u64,
+                t - 250,
+                price(i % 100),
+                1_490_672 + i,
+                t,
+                1 + i % 500
+            )
+        })
+        .collect();
+    format!(
+        r#"{{"results":[{}],"status":"OK","request_id":"1","next_url":"https://api.polygon.io/v3/trades/AAPL?cursor=abc"}}"#,
+        results.join(",")
+    )
+}
+
+fn ws_frame() -> String {
+    let events: Vec<_> = (0..FRAME_EVENTS)
+        .map(|i| {
+            let t = 1_704_292_200_000u64 + i as u64;
+            let p = price(i % 100);
+            if i % 2 == 0 {
+                format!(
+                    r#"{{"ev":"T","sym":"T{:05}","x":4,"i":"{}","z":3,"p":{:.4},"s":{},"c":[12,37],"t":{},"q":{}}}"#,
+                    i % 100,
+                    i,
+                    p,
+                    1 + i % 500,
+                    t,
+                    i
+                )
+            } else {
+                format!(
+                    r#"{{"ev":"Q","sym":"T{:05}","bx":4,"bp":{:.2},"bs":{},"ax":7,"ap":{:.2},"as":{},"c":0,"t":{},"q":{},"z":3}}"#,
+                    i % 100,
+                    p,
+                    1 + i % 10,
+                    p + 0.01,
+                    1 + i % 7,
+                    t,
+                    i
+                )
+            }
+        })
+        .collect();
+    format!("[{}]", events.join(","))
+}
+
+fn bench_grouped_daily(c: &mut Criterion) {
+    let body = grouped_daily();
+    let resp = decode_json::<StockEquitiesGroupedDailyResponse>(body.as_bytes()).unwrap();
+    assert_eq!(resp.results.len(), GROUPED_DAILY_BARS);
+
+    let mut group = c.benchmark_group("grouped_daily");
+    group.throughput(Throughput::Bytes(body.len() as u64));
+    group.bench_function("full", |b| {
+        b.iter(|| decode_json::<StockEquitiesGroupedDailyResponse>(black_box(body.as_bytes())))
+    });
+    group.bench_function("closes_only", |b| {
+        b.iter(|| decode_json::<SlimResponse<ClosesOnly>>(black_box(body.as_bytes())))
+    });
+    group.finish();
+}
+
+fn bench_trades_page(c: &mut Criterion) {
+    let body = trades_page();
+    let resp = decode_json::<StockEquitiesTradesResponseV3>(body.as_bytes()).unwrap();
+    assert_eq!(resp.results.len(), TRADES_PAGE_SIZE);
+
+    let mut group = c.benchmark_group("trades_page");
+    group.throughput(Throughput::Bytes(body.len() as u64));
+    group.sample_size(20);
+    group.bench_function("full", |b| {
+        b.iter(|| decode_json::<StockEquitiesTradesResponseV3>(black_box(body.as_bytes())))
+    });
+    group.finish();
+}
+
+fn bench_ws_frame(c: &mut Criterion) {
+    let frame = ws_frame();
+    let events = parse_events(&frame, ParseOptions::default()).unwrap();
+    assert_eq!(events.len(), FRAME_EVENTS);
+
+    let mut group = c.benchmark_group("ws_frame");
+    group.throughput(Throughput::Elements(FRAME_EVENTS as u64));
+    group.bench_function("parse_events", |b| {
+        b.iter(|| parse_events(black_box(&frame), ParseOptions::default()))
+    });
+    group.bench_function("parse_events_tagged", |b| {
+        b.iter(|| parse_events(black_box(&frame), ParseOptions { tag_sessions: true }))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_grouped_daily,
+    bench_trades_page,
+    bench_ws_frame
+);
+criterion_main!(benches);
@@ -0,0 +1,218 @@
+//! A terminal watchlist with live prices and daily changes.
+//!
+//! Streams trades of the given tickers over the WebSocket and redraws the
+//! board as they arrive. Tickers without a trade in the last few seconds,
+//! including every ticker while the connection is being re-established, are
+//! filled in with the last trade from the REST API.
+//!
+//! Run with `cargo run --example watchlist --features display -- MSFT AAPL`.
+use std::env;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::future::join_all;
+use polygon_client::display::DisplayFormat;
+use polygon_client::quote_board::QuoteBoard;
+use polygon_client::rest::{RESTClient, RetryPolicy};
+use polygon_client::ticker::StockTicker;
+use polygon_client::types::AssetClass;
+use polygon_client::websocket::dispatcher::Dispatcher;
+use polygon_client::websocket::STOCKS_CLUSTER;
+use polygon_client::Error;
+
+/// How long a ticker can go without a streamed trade before its price is
+/// fetched from the REST API.
+const STALE_AFTER: Duration = Duration::from_secs(10);
+/// How often stale tickers are checked.
+const FALLBACK_INTERVAL: Duration = Duration::from_secs(5);
+/// The least time between redraws.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(250);
+/// The longest wait before reconnecting.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// The most stale tickers fetched from the REST API at once.
+const MAX_FALLBACK_IN_FLIGHT: usize = 4;
+
+struct Watchlist {
+    client: RESTClient,
+    /// A partition of `client` for filling in stale tickers, so that the
+    /// fallback cannot use up the budget of the client while the connection
+    /// is down.
+    fallback: RESTClient,
+    board: QuoteBoard,
+    format: DisplayFormat,
+    status: String,
+}
+
+impl Watchlist {
+    fn new(client: RESTClient, fallback: RESTClient, tickers: &[String]) -> Self {
+        Watchlist {
+            client,
+            fallback,
+            board: QuoteBoard::new(tickers),
+            format: DisplayFormat::for_asset_class(AssetClass::Stocks),
+            status: String::from("connecting"),
+        }
+    }
+
+    /// Fetches the previous close of every ticker.
+    async fn load_previous_closes(&mut self) {
+        let no_params = Default::default();
+        let tickers: Vec<_> = self.board.tickers().map(String::from).collect();
+        let (client, no_params) = (&self.client, &no_params);
+        let closes = join_all(tickers.iter().map(|t| {
+            let ticker = StockTicker::new(t);
+            async move {
+                client
+                    .stock_equities_previous_close(ticker?, no_params)
+                    .await
+            }
+        }))
+        .await;
+
+        for (ticker, close) in tickers.iter().zip(closes) {
+            match close.map(|r| r.results.into_iter().next()) {
+                Ok(Some(bar)) => self.board.set_previous_close(ticker, bar.c),
+                Ok(None) => {}
+                Err(e) => self.status = format!("previous close of {}: {}", ticker, e),
+            }
+        }
+    }
+
+    /// Fetches the last trade of every ticker that has gone quiet.
+    async fn fill_stale(&mut self) {
+        let stale: Vec<_> = self
+            .board
+            .stale(STALE_AFTER, now_ms())
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let prices = join_all(stale.iter().map(|t| self.fallback.last_price(t))).await;
+
+        for price in prices {
+            match price {
+                Ok(price) => {
+                    self.board.apply_last_price(&price);
+                }
+                Err(e) => self.status = format!("REST fallback: {}", e),
+            }
+        }
+    }
+
+    fn draw(&self) {
+        // Clear the screen and move the cursor to the top left.
+        print!("\x1b[2J\x1b[H");
+        println!("polygon.io watchlist ({})\n", self.status);
+        for line in self.board.render(&self.format) {
+            println!("{}", line);
+        }
+    }
+
+    /// Streams trades until the connection is closed or fails, filling in
+    /// stale tickers and redrawing the board along the way.
+    async fn stream(&mut self, dispatcher: &mut Dispatcher) -> Result<(), Error> {
+        let tickers: Vec<_> = self.board.tickers().map(String::from).collect();
+        dispatcher.set_watchlist(&tickers).await?;
+        self.status = String::from("live");
+        self.draw();
+
+        let mut fallback = tokio::time::interval(FALLBACK_INTERVAL);
+        let mut redraw = tokio::time::interval(REDRAW_INTERVAL);
+        let mut dirty = false;
+
+        loop {
+            tokio::select! {
+                event = dispatcher.next_event() => match event {
+                    Some(Ok(event)) => dirty |= self.board.apply_event(&event),
+                    Some(Err(e)) => return Err(e),
+                    None => return Ok(()),
+                },
+                _ = fallback.tick() => {
+                    self.fill_stale().await;
+                    dirty = true;
+                }
+                _ = redraw.tick(), if dirty => {
+                    self.draw();
+                    dirty = false;
+                }
+            }
+        }
+    }
+
+    /// Waits before reconnecting, keeping the board up to date through the
+    /// REST API in the meantime.
+    async fn wait(&mut self, backoff: Duration) {
+        let deadline = tokio::time::Instant::now() + backoff;
+        while tokio::time::Instant::now() < deadline {
+            self.fill_stale().await;
+            self.draw();
+            tokio::time::sleep_until(deadline.min(tokio::time::Instant::now() + FALLBACK_INTERVAL))
+                .await;
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[tokio::main]
+async fn main() {
+    let tickers: Vec<String> = env::args().skip(1).collect();
+
+    if tickers.is_empty() {
+        println!("Usage: watchlist <ticker1> <ticker2> <ticker3> ...");
+        return;
+    }
+
+    let client = match RESTClient::builder().build() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    // Stale tickers are checked again shortly, so their requests are not
+    // retried.
+    let fallback = client
+        .partition_builder()
+        .max_in_flight(MAX_FALLBACK_IN_FLIGHT)
+        .retry_policy(RetryPolicy::disabled())
+        .build();
+    let fallback = match fallback {
+        Ok(fallback) => fallback,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    let mut watchlist = Watchlist::new(client, fallback, &tickers);
+    watchlist.load_previous_closes().await;
+    watchlist.fill_stale().await;
+    watchlist.draw();
+
+    let mut backoff = Duration::from_secs(1);
+    loop {
+        match Dispatcher::connect(STOCKS_CLUSTER, None).await {
+            Ok(mut dispatcher) => {
+                backoff = Duration::from_secs(1);
+                watchlist.status = match watchlist.stream(&mut dispatcher).await {
+                    Ok(()) => String::from("connection closed"),
+                    Err(e) => format!("connection failed: {}", e),
+                };
+            }
+            Err(e) => watchlist.status = format!("could not connect: {}", e),
+        }
+
+        watchlist.status = format!(
+            "{}, reconnecting in {}s",
+            watchlist.status,
+            backoff.as_secs()
+        );
+        watchlist.wait(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,219 @@
+//! Utilities for adjusting historical bars for splits and dividends.
+//!
+//! Each split or dividend is reduced to a [`PriceAdjustment`]: factors that
+//! are applied to the prices and volumes of every bar before its ex-date.
+//! Split factors come from the split ratio. Dividend factors follow the
+//! common total return convention of `1 - amount / close`, where `close` is
+//! the unadjusted close of the last bar before the ex-date.
+use chrono::{DateTime, NaiveDate};
+
+use crate::types::{
+    parse_date, ReferenceStockDividendsResultV2, ReferenceStockSplitsResultV2,
+    StockEquitiesAggregates,
+};
+
+/// The corporate actions that historical prices are adjusted for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Adjustment {
+    /// Prices are left as they were traded.
+    None,
+    /// Prices and volumes are adjusted for splits.
+    Splits,
+    /// Prices and volumes are adjusted for splits, and prices are further
+    /// adjusted for cash dividends.
+    SplitsAndDividends,
+}
+
+impl Adjustment {
+    /// Returns whether splits are adjusted for.
+    pub fn includes_splits(&self) -> bool {
+        !matches!(self, Adjustment::None)
+    }
+
+    /// Returns whether dividends are adjusted for.
+    pub fn includes_dividends(&self) -> bool {
+        matches!(self, Adjustment::SplitsAndDividends)
+    }
+}
+
+/// Factors applied to every bar before an ex-date.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceAdjustment {
+    pub ex_date: NaiveDate,
+    pub price_factor: f64,
+    pub volume_factor: f64,
+}
+
+impl PriceAdjustment {
+    /// Returns the adjustment for a split, or `None` if the split has an
+    /// invalid ex-date or ratio.
+    pub fn from_split(split: &ReferenceStockSplitsResultV2) -> Option<Self> {
+        let ex_date = parse_date(&split.ex_date)?;
+        let ratio = match (split.forfactor, split.tofactor) {
+            (Some(from), Some(to)) if from > 0 && to > 0 => from as f64 / to as f64,
+            _ => split.ratio,
+        };
+
+        if !ratio.is_finite() || ratio <= 0.0 {
+            return None;
+        }
+
+        Some(PriceAdjustment {
+            ex_date,
+            price_factor: ratio,
+            volume_factor: 1.0 / ratio,
+        })
+    }
+
+    /// Returns the adjustment for a cash dividend given the unadjusted close
+    /// before its ex-date, or `None` if the dividend has an invalid ex-date or
+    /// is not smaller than the close.
+    pub fn from_dividend(
+        dividend: &ReferenceStockDividendsResultV2,
+        previous_close: f64,
+    ) -> Option<Self> {
+        let ex_date = parse_date(&dividend.ex_date)?;
+
+        if previous_close <= 0.0 || dividend.amount < 0.0 || dividend.amount >= previous_close {
+            return None;
+        }
+
+        Some(PriceAdjustment {
+            ex_date,
+            price_factor: 1.0 - dividend.amount / previous_close,
+            volume_factor: 1.0,
+        })
+    }
+}
+
+/// Returns the date of a daily bar.
+///
+/// Daily bars start at midnight Eastern time, which is the same date in UTC.
+pub fn bar_date(bar: &StockEquitiesAggregates) -> Option<NaiveDate> {
+    let t = bar.t?;
+    DateTime::from_timestamp_millis(t as i64).map(|dt| dt.date_naive())
+}
+
+/// Adjusts unadjusted daily bars, sorted by time, for the given splits and
+/// dividends.
+pub fn adjust_bars(
+    bars: &mut [StockEquitiesAggregates],
+    splits: &[ReferenceStockSplitsResultV2],
+    dividends: &[ReferenceStockDividendsResultV2],
+    adjustment: Adjustment,
+) {
+    let dates: Vec<Option<NaiveDate>> = bars.iter().map(bar_date).collect();
+    let mut adjustments = Vec::new();
+
+    if adjustment.includes_splits() {
+        adjustments.extend(splits.iter().filter_map(PriceAdjustment::from_split));
+    }
+
+    // Dividend factors are computed from the unadjusted closes, so they must
+    // be collected before any adjustment is applied.
+    if adjustment.includes_dividends() {
+        for dividend in dividends {
+            let ex_date = match parse_date(&dividend.ex_date) {
+                Some(d) => d,
+                None => continue,
+            };
+            let previous_close = bars
+                .iter()
+                .zip(dates.iter())
+                .rev()
+                .find(|(_, date)| date.is_some_and(|d| d < ex_date))
+                .map(|(bar, _)| bar.c);
+
+            if let Some(close) = previous_close {
+                adjustments.extend(PriceAdjustment::from_dividend(dividend, close));
+            }
+        }
+    }
+
+    for adj in &adjustments {
+        for (bar, date) in bars.iter_mut().zip(dates.iter()) {
+            if date.is_some_and(|d| d < adj.ex_date) {
+                apply(bar, adj);
+            }
+        }
+    }
+}
+
+fn apply(bar: &mut StockEquitiesAggregates, adj: &PriceAdjustment) {
+    bar.o *= adj.price_factor;
+    bar.h *= adj.price_factor;
+    bar.l *= adj.price_factor;
+    bar.c *= adj.price_factor;
+    bar.vw = bar.vw.map(|vw| vw * adj.price_factor);
+    bar.v *= adj.volume_factor;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adjust::*;
+
+    fn bar(date: &str, close: f64) -> StockEquitiesAggregates {
+        let t = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(4, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp_millis() as u64;
+        StockEquitiesAggregates {
+            T: None,
+            av: None,
+            c: close,
+            h: close,
+            l: close,
+            n: None,
+            o: close,
+            t: Some(t),
+            v: 100.0,
+            vw: Some(close),
+            otc: false,
+        }
+    }
+
+    #[test]
+    fn test_adjust_bars() {
+        let mut bars = vec![
+            bar("2020-08-27", 400.0),
+            bar("2020-08-28", 500.0),
+            bar("2020-08-31", 125.0),
+            bar("2020-09-01", 100.0),
+        ];
+        let splits = vec![ReferenceStockSplitsResultV2 {
+            ticker: String::from("AAPL"),
+            ex_date: String::from("2020-08-31"),
+            payment_date: String::from("2020-08-28"),
+            declared_date: None,
+            ratio: 0.25,
+            tofactor: Some(4),
+            forfactor: Some(1),
+        }];
+        let dividends = vec![ReferenceStockDividendsResultV2 {
+            ticker: String::from("AAPL"),
+            ex_date: String::from("2020-09-01"),
+            payment_date: String::from("2020-09-10"),
+            record_date: String::from("2020-09-02"),
+            amount: 1.25,
+        }];
+
+        let mut split_only = bars.clone();
+        adjust_bars(&mut split_only, &splits, &dividends, Adjustment::Splits);
+        assert_eq!(split_only[1].c, 125.0);
+        assert_eq!(split_only[1].v, 400.0);
+        assert_eq!(split_only[2].c, 125.0);
+
+        adjust_bars(
+            &mut bars,
+            &splits,
+            &dividends,
+            Adjustment::SplitsAndDividends,
+        );
+        assert!((bars[0].c - 99.0).abs() < 1e-9);
+        assert!((bars[2].c - 123.75).abs() < 1e-9);
+        assert_eq!(bars[3].c, 100.0);
+        assert_eq!(bars[3].v, 100.0);
+    }
+}
@@ -0,0 +1,180 @@
+//! Rolling windows of recent bars per ticker.
+//!
+//! Streamed aggregates can arrive late, be repeated, or be corrected by a
+//! later message for the same interval. [`BarWindows`] keeps the most recent
+//! bars of each ticker in time order, replacing a bar when an update for the
+//! same interval arrives and ignoring bars older than the latest one.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::aggregation::BarWindows;
+//! use polygon_client::events::{parse_events, ParseOptions, PolygonEvent};
+//!
+//! let msg = r#"[{"ev":"AM","sym":"MSFT","v":100,"o":1.0,"c":2.0,"h":2.0,"l":1.0,"s":0,"e":60000},
+//!     {"ev":"AM","sym":"MSFT","v":100,"o":2.0,"c":3.0,"h":3.0,"l":2.0,"s":60000,"e":120000}]"#;
+//!
+//! let mut windows = BarWindows::new(10);
+//! for event in parse_events(msg, ParseOptions::default()).unwrap() {
+//!     if let PolygonEvent::MinuteBar(bar) = event {
+//!         windows.push(bar);
+//!     }
+//! }
+//! assert_eq!(windows.get("MSFT").unwrap().closes(), vec![2.0, 3.0]);
+//! ```
+use std::collections::{HashMap, VecDeque};
+
+use crate::events::BarEvent;
+
+/// The most recent bars of a single ticker, oldest first.
+#[derive(Clone, Debug)]
+pub struct BarWindow {
+    capacity: usize,
+    bars: VecDeque<BarEvent>,
+}
+
+impl BarWindow {
+    /// Returns an empty window that holds up to `capacity` bars. A capacity of
+    /// zero holds one bar.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        BarWindow {
+            capacity,
+            bars: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Adds a bar to the window and returns whether it was accepted.
+    ///
+    /// A bar with the same start time as the latest bar replaces it, and a
+    /// bar that starts before the latest bar is ignored. Once the window is
+    /// full, the oldest bar is dropped.
+    pub fn push(&mut self, bar: BarEvent) -> bool {
+        if let Some(last) = self.bars.back_mut() {
+            if bar.start_timestamp < last.start_timestamp {
+                return false;
+            }
+            if bar.start_timestamp == last.start_timestamp {
+                *last = bar;
+                return true;
+            }
+        }
+
+        if self.bars.len() == self.capacity {
+            self.bars.pop_front();
+        }
+        self.bars.push_back(bar);
+        true
+    }
+
+    /// Returns the maximum number of bars kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of bars in the window.
+    pub fn len(&self) -> usize {
+        self.bars.len()
+    }
+
+    /// Returns whether the window has no bars.
+    pub fn is_empty(&self) -> bool {
+        self.bars.is_empty()
+    }
+
+    /// Returns whether the window holds `capacity` bars.
+    pub fn is_full(&self) -> bool {
+        self.bars.len() == self.capacity
+    }
+
+    /// Returns the bars in the window, oldest first.
+    pub fn bars(&self) -> impl Iterator<Item = &BarEvent> + '_ {
+        self.bars.iter()
+    }
+
+    /// Returns the latest bar.
+    pub fn last(&self) -> Option<&BarEvent> {
+        self.bars.back()
+    }
+
+    /// Returns the closing prices of the bars, oldest first.
+    pub fn closes(&self) -> Vec<f64> {
+        self.bars.iter().map(|b| b.close).collect()
+    }
+}
+
+/// A [`BarWindow`] for each ticker that bars have been received for.
+#[derive(Clone, Debug)]
+pub struct BarWindows {
+    capacity: usize,
+    windows: HashMap<String, BarWindow>,
+}
+
+impl BarWindows {
+    /// Returns an empty set of windows that each hold up to `capacity` bars. A
+    /// capacity of zero holds one bar.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        BarWindows {
+            capacity,
+            windows: HashMap::new(),
+        }
+    }
+
+    /// Adds a bar to the window of its ticker. Returns the window if the bar
+    /// was accepted, or `None` if it was older than the latest bar.
+    pub fn push(&mut self, bar: BarEvent) -> Option<&BarWindow> {
+        let capacity = self.capacity;
+        let window = self
+            .windows
+            .entry(bar.symbol.clone())
+            .or_insert_with(|| BarWindow::new(capacity));
+
+        if window.push(bar) {
+            Some(window)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the window of a ticker.
+    pub fn get(&self, ticker: &str) -> Option<&BarWindow> {
+        self.windows.get(ticker)
+    }
+
+    /// Removes the window of a ticker, such as after unsubscribing from it.
+    pub fn remove(&mut self, ticker: &str) -> Option<BarWindow> {
+        self.windows.remove(ticker)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregation::BarWindow;
+    use crate::events::BarEvent;
+
+    fn bar(start: u64, close: f64) -> BarEvent {
+        BarEvent::new("MSFT", start, start + 60_000)
+            .with_ohlc(close, close, close, close)
+            .with_volume(100.0)
+    }
+
+    #[test]
+    fn test_bar_window() {
+        let mut window = BarWindow::new(3);
+        assert!(window.push(bar(0, 1.0)));
+        assert!(window.push(bar(60_000, 2.0)));
+        // An update for the latest interval replaces it.
+        assert!(window.push(bar(60_000, 2.5)));
+        // A late bar is ignored.
+        assert!(!window.push(bar(0, 9.0)));
+        assert_eq!(window.closes(), vec![1.0, 2.5]);
+        assert!(!window.is_full());
+
+        window.push(bar(120_000, 3.0));
+        window.push(bar(180_000, 4.0));
+        assert!(window.is_full());
+        assert_eq!(window.closes(), vec![2.5, 3.0, 4.0]);
+        assert_eq!(window.last().unwrap().start_timestamp, 180_000);
+    }
+}
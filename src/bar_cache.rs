@@ -0,0 +1,391 @@
+//! A compressed on-disk cache of aggregate bars.
+//!
+//! Bars are stored in one file per ticker and trading day, so a backtest can
+//! read only the days it needs. Each file holds the bars of the day in a
+//! columnar layout compressed with zstd. Reading a day decompresses the columns
+//! directly into bars, which is much faster than deserializing cached JSON
+//! responses.
+//!
+//! The cache is enabled by the `bar-cache` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use chrono::NaiveDate;
+//! use polygon_client::bar_cache::BarCache;
+//!
+//! let cache = BarCache::new("/var/cache/polygon/minute");
+//! let from = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+//! let to = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+//!
+//! for day in cache.scan("MSFT", from, to) {
+//!     let (date, bars) = day.unwrap();
+//!     println!("{}: {} bars", date, bars.len());
+//! }
+//! ```
+//!
+//! # File format
+//!
+//! A file starts with the magic bytes `PCBC` and a format version byte,
+//! followed by a single zstd frame. The frame contains the number of bars as a
+//! little-endian `u32` followed by these columns, each holding one
+//! little-endian value per bar:
+//!
+//! * `t` - the start of the bar as a `u64`, stored as the difference from the
+//!   previous bar
+//! * `o`, `h`, `l`, `c`, `v`, `vw`, and `n` - as `f64`, with `NaN` for missing
+//!   values
+//!
+//! A frame that decompresses to more than [`MAX_DAY_LEN`] bytes is rejected
+//! as corrupt.
+use std::convert::TryFrom;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, NaiveDate};
+use tempfile::NamedTempFile;
+
+use crate::error::Error;
+use crate::types::StockEquitiesAggregates;
+
+const MAGIC: &[u8; 4] = b"PCBC";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+/// The number of `f64` columns that follow the timestamp column.
+const F64_COLUMNS: usize = 7;
+
+/// The largest decompressed size of a day, enough for a bar every second.
+pub const MAX_DAY_LEN: usize = 4 + 86_400 * 8 * (F64_COLUMNS + 1);
+
+/// The zstd compression level used unless another is configured.
+const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+/// A cache of bars stored under a root directory.
+#[derive(Clone, Debug)]
+pub struct BarCache {
+    root: PathBuf,
+    compression_level: i32,
+}
+
+impl BarCache {
+    /// Returns a cache stored under `root`. The directory is created when bars
+    /// are first written.
+    ///
+    /// Bars of different sizes, such as minute and daily bars, should be
+    /// stored under different roots.
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        BarCache {
+            root: root.into(),
+            compression_level: DEFAULT_COMPRESSION_LEVEL,
+        }
+    }
+
+    /// Sets the zstd compression level used when writing bars. Higher levels
+    /// produce smaller files but take longer to write. Reads are unaffected.
+    pub fn compression_level(mut self, level: i32) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    /// Returns the root directory of the cache.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns whether bars are cached for a ticker on a day.
+    pub fn contains(&self, ticker: &str, date: NaiveDate) -> bool {
+        self.path(ticker, date).is_file()
+    }
+
+    /// Stores the bars of a ticker on a day, replacing any bars already cached
+    /// for that day.
+    ///
+    /// The bars are sorted by start time. Each bar must have a start time, and
+    /// the ticker in `T` and the accumulated volume in `av` are not stored.
+    pub fn write_day(
+        &self,
+        ticker: &str,
+        date: NaiveDate,
+        bars: &[StockEquitiesAggregates],
+    ) -> Result<(), Error> {
+        let mut bars: Vec<_> = bars.iter().collect();
+        bars.sort_by_key(|b| b.t);
+        let data = encode(&bars)?;
+        let compressed = zstd::bulk::compress(&data, self.compression_level)?;
+
+        let path = self.path(ticker, date);
+        let dir = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(dir)?;
+
+        // Write to a uniquely named temporary file and rename it into place so
+        // that readers never see a partially written day, even when several
+        // writers store the same day at once.
+        let mut file = NamedTempFile::new_in(dir)?;
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION])?;
+        file.write_all(&compressed)?;
+        file.as_file().sync_all()?;
+        file.persist(&path).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Returns the bars of a ticker on a day, or `None` if the day is not
+    /// cached.
+    pub fn read_day(
+        &self,
+        ticker: &str,
+        date: NaiveDate,
+    ) -> Result<Option<Vec<StockEquitiesAggregates>>, Error> {
+        let file = match fs::read(self.path(ticker, date)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        if file.len() < HEADER_LEN || &file[..MAGIC.len()] != MAGIC {
+            return Err(invalid_data("not a bar cache file").into());
+        }
+        if file[MAGIC.len()] != VERSION {
+            return Err(invalid_data("unsupported bar cache version").into());
+        }
+
+        let data = decompress(&file[HEADER_LEN..])?;
+        Ok(Some(decode(&data)?))
+    }
+
+    /// Returns the bars of a ticker from `from` to `to`, inclusive, in time
+    /// order. Days that are not cached are skipped.
+    pub fn read_range(
+        &self,
+        ticker: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<StockEquitiesAggregates>, Error> {
+        let mut bars = Vec::new();
+        for day in self.scan(ticker, from, to) {
+            bars.extend(day?.1);
+        }
+        Ok(bars)
+    }
+
+    /// Returns an iterator over the cached days of a ticker from `from` to
+    /// `to`, inclusive, and their bars. Each day is read when the iterator
+    /// reaches it, so long ranges can be processed without holding every bar
+    /// in memory.
+    pub fn scan<'a>(
+        &'a self,
+        ticker: &'a str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> impl Iterator<Item = Result<(NaiveDate, Vec<StockEquitiesAggregates>), Error>> + 'a {
+        let days = (0..)
+            .map(move |i| from + Duration::days(i))
+            .take_while(move |date| *date <= to);
+
+        days.filter_map(move |date| match self.read_day(ticker, date) {
+            Ok(Some(bars)) => Some(Ok((date, bars))),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        })
+    }
+
+    fn path(&self, ticker: &str, date: NaiveDate) -> PathBuf {
+        self.root
+            .join(escape_ticker(ticker))
+            .join(format!("{}.bars", date.format("%Y-%m-%d")))
+    }
+}
+
+/// Returns a ticker with characters that are not safe in file names, such as
+/// the `:` in `X:BTCUSD`, percent encoded.
+///
+/// A leading `.` is also encoded so that tickers such as `..` cannot name a
+/// directory outside of the cache root.
+fn escape_ticker(ticker: &str) -> String {
+    let mut escaped = String::with_capacity(ticker.len());
+    for (i, b) in ticker.bytes().enumerate() {
+        let dot = b == b'.' && i > 0;
+        if b.is_ascii_alphanumeric() || dot || b == b'-' || b == b'_' {
+            escaped.push(b as char);
+        } else {
+            escaped.push_str(&format!("%{:02X}", b));
+        }
+    }
+    escaped
+}
+
+/// Decompresses a zstd frame, failing if it holds more than [`MAX_DAY_LEN`]
+/// bytes.
+fn decompress(compressed: &[u8]) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    zstd::stream::Decoder::new(compressed)?
+        .take(MAX_DAY_LEN as u64 + 1)
+        .read_to_end(&mut data)?;
+    if data.len() > MAX_DAY_LEN {
+        return Err(invalid_data("bar cache file is too large"));
+    }
+    Ok(data)
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn encode(bars: &[&StockEquitiesAggregates]) -> Result<Vec<u8>, Error> {
+    let count = u32::try_from(bars.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many bars"))?;
+    let mut data = Vec::with_capacity(4 + bars.len() * 8 * (F64_COLUMNS + 1));
+    data.extend_from_slice(&count.to_le_bytes());
+
+    let mut prev = 0;
+    for bar in bars {
+        let t = bar
+            .t
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "bar has no start time"))?;
+        data.extend_from_slice(&(t - prev).to_le_bytes());
+        prev = t;
+    }
+
+    let columns: [fn(&StockEquitiesAggregates) -> f64; F64_COLUMNS] = [
+        |b| b.o,
+        |b| b.h,
+        |b| b.l,
+        |b| b.c,
+        |b| b.v,
+        |b| b.vw.unwrap_or(f64::NAN),
+        |b| b.n.unwrap_or(f64::NAN),
+    ];
+    for column in &columns {
+        for bar in bars {
+            data.extend_from_slice(&column(bar).to_le_bytes());
+        }
+    }
+
+    Ok(data)
+}
+
+fn decode(data: &[u8]) -> Result<Vec<StockEquitiesAggregates>, Error> {
+    let count = match data.get(..4) {
+        Some(b) => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize,
+        None => return Err(invalid_data("truncated bar cache file").into()),
+    };
+    if data.len() != 4 + count * 8 * (F64_COLUMNS + 1) {
+        return Err(invalid_data("truncated bar cache file").into());
+    }
+
+    let word = |column: usize, i: usize| {
+        let start = 4 + (column * count + i) * 8;
+        let mut b = [0; 8];
+        b.copy_from_slice(&data[start..start + 8]);
+        b
+    };
+    let float = |column: usize, i: usize| f64::from_le_bytes(word(column + 1, i));
+    let optional = |v: f64| if v.is_nan() { None } else { Some(v) };
+
+    let mut bars = Vec::with_capacity(count);
+    let mut t = 0;
+    for i in 0..count {
+        t += u64::from_le_bytes(word(0, i));
+        let mut bar = StockEquitiesAggregates::new(
+            t,
+            float(0, i),
+            float(1, i),
+            float(2, i),
+            float(3, i),
+            float(4, i),
+        );
+        bar.vw = optional(float(5, i));
+        bar.n = optional(float(6, i));
+        bars.push(bar);
+    }
+
+    Ok(bars)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bar_cache::{escape_ticker, BarCache, MAX_DAY_LEN};
+    use crate::types::StockEquitiesAggregates;
+    use chrono::NaiveDate;
+    use std::fs;
+
+    fn temp_cache(name: &str) -> BarCache {
+        let root = std::env::temp_dir().join(format!(
+            "polygon-client-bar-cache-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        BarCache::new(root)
+    }
+
+    #[test]
+    fn test_bar_cache_round_trip() {
+        let cache = temp_cache("round-trip");
+        let day = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        let next = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+
+        let mut first = StockEquitiesAggregates::new(1704292200000, 1.0, 2.0, 0.5, 1.5, 100.0);
+        first.vw = Some(1.25);
+        first.n = Some(12.0);
+        let second = StockEquitiesAggregates::new(1704292260000, 1.5, 1.75, 1.25, 1.5, 50.0);
+
+        // Bars are sorted before they are written.
+        cache
+            .write_day("X:BTCUSD", day, &[second, first.clone()])
+            .unwrap();
+        cache.write_day("X:BTCUSD", next, &[first]).unwrap();
+        assert!(cache.contains("X:BTCUSD", day));
+        assert!(cache.root().join("X%3ABTCUSD").is_dir());
+
+        let bars = cache.read_day("X:BTCUSD", day).unwrap().unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].t, Some(1704292200000));
+        assert_eq!(bars[0].vw, Some(1.25));
+        assert_eq!(bars[0].n, Some(12.0));
+        assert_eq!(bars[1].t, Some(1704292260000));
+        assert_eq!(bars[1].vw, None);
+        assert_eq!(bars[1].v, 50.0);
+
+        let range = cache
+            .read_range(
+                "X:BTCUSD",
+                day,
+                NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(range.len(), 3);
+        assert!(cache.read_day("MSFT", day).unwrap().is_none());
+
+        fs::write(cache.root().join("X%3ABTCUSD/2024-01-05.bars"), b"junk").unwrap();
+        assert!(cache.read_day("X:BTCUSD", next).is_err());
+
+        fs::remove_dir_all(cache.root()).unwrap();
+    }
+
+    #[test]
+    fn test_escape_ticker() {
+        assert_eq!(escape_ticker("BRK.A"), "BRK.A");
+        assert_eq!(escape_ticker("C:EUR/USD"), "C%3AEUR%2FUSD");
+        assert_eq!(escape_ticker(".."), "%2E.");
+        assert_eq!(escape_ticker("."), "%2E");
+    }
+
+    #[test]
+    fn test_bar_cache_rejects_oversized_day() {
+        let cache = temp_cache("oversized");
+        let day = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        let dir = cache.root().join("MSFT");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut file = b"PCBC\x01".to_vec();
+        let zeros = vec![0; MAX_DAY_LEN + 1];
+        file.extend(zstd::bulk::compress(&zeros, 3).unwrap());
+        fs::write(dir.join("2024-01-03.bars"), file).unwrap();
+        assert!(cache.read_day("MSFT", day).is_err());
+
+        fs::remove_dir_all(cache.root()).unwrap();
+    }
+}
@@ -0,0 +1,354 @@
+//! The trading calendar of the US stock market.
+//!
+//! Trading days are computed from the holiday rules of the New York Stock
+//! Exchange, along with the unscheduled closures since 2000, so no requests
+//! are needed to tell whether the market is open on a given day. The trading
+//! [`Session`] at any point in time can be computed in the same way.
+//!
+//! # Example
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use polygon_client::calendar;
+//!
+//! let christmas = NaiveDate::from_ymd_opt(2023, 12, 25).unwrap();
+//! assert!(calendar::is_holiday(christmas));
+//!
+//! let week = calendar::trading_days(christmas, NaiveDate::from_ymd_opt(2023, 12, 31).unwrap());
+//! assert_eq!(week.len(), 4);
+//! ```
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, Timelike, Weekday};
+
+/// Days on which the market closed outside of the regular holiday schedule.
+const UNSCHEDULED_CLOSURES: &[(i32, u32, u32)] = &[
+    // September 11 attacks.
+    (2001, 9, 11),
+    (2001, 9, 12),
+    (2001, 9, 13),
+    (2001, 9, 14),
+    // National day of mourning for President Reagan.
+    (2004, 6, 11),
+    // National day of mourning for President Ford.
+    (2007, 1, 2),
+    // Hurricane Sandy.
+    (2012, 10, 29),
+    (2012, 10, 30),
+    // National day of mourning for President George H.W. Bush.
+    (2018, 12, 5),
+    // National day of mourning for President Carter.
+    (2025, 1, 9),
+];
+
+/// A trading session of the US stock market.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Session {
+    /// From 4:00 to 9:30 Eastern time.
+    PreMarket,
+    /// From 9:30 to 16:00 Eastern time, or to 13:00 on early close days.
+    Regular,
+    /// From the end of the regular session to 20:00 Eastern time, or to
+    /// 17:00 on early close days.
+    AfterHours,
+    /// Outside of the extended trading hours, or on a day the market is
+    /// closed.
+    Closed,
+}
+
+/// Returns the trading session at a time given in Unix milliseconds.
+pub fn session_at(timestamp: u64) -> Session {
+    let utc = match DateTime::from_timestamp_millis(timestamp as i64) {
+        Some(dt) => dt.naive_utc(),
+        None => return Session::Closed,
+    };
+    let local = to_eastern(utc);
+    let date = local.date();
+
+    if !is_trading_day(date) {
+        return Session::Closed;
+    }
+
+    let time = local.time().num_seconds_from_midnight();
+    let hm = |h: u32, m: u32| h * 3600 + m * 60;
+    let (close, after_hours_close) = if is_early_close(date) {
+        (hm(13, 0), hm(17, 0))
+    } else {
+        (hm(16, 0), hm(20, 0))
+    };
+
+    if time < hm(4, 0) {
+        Session::Closed
+    } else if time < hm(9, 30) {
+        Session::PreMarket
+    } else if time < close {
+        Session::Regular
+    } else if time < after_hours_close {
+        Session::AfterHours
+    } else {
+        Session::Closed
+    }
+}
+
+/// Returns the date in US Eastern time at a time given in Unix milliseconds,
+/// which is the trading day that a trade or quote at that time belongs to.
+pub fn eastern_date(timestamp: u64) -> Option<NaiveDate> {
+    let utc = DateTime::from_timestamp_millis(timestamp as i64)?.naive_utc();
+    Some(to_eastern(utc).date())
+}
+
+/// Returns whether the regular session closes early, at 13:00 Eastern time,
+/// on `date`.
+pub fn is_early_close(date: NaiveDate) -> bool {
+    if !is_trading_day(date) {
+        return false;
+    }
+
+    let year = date.year();
+    let after_thanksgiving = nth_weekday(year, 11, Weekday::Thu, 4).and_then(|d| d.succ_opt());
+
+    after_thanksgiving == Some(date)
+        || (date.month() == 7 && date.day() == 3)
+        || (date.month() == 12 && date.day() == 24)
+}
+
+/// Converts a UTC time to Eastern time, observing daylight saving time from
+/// 2:00 local time on the second Sunday of March to 2:00 local time on the
+/// first Sunday of November.
+fn to_eastern(utc: NaiveDateTime) -> NaiveDateTime {
+    let year = utc.year();
+    let dst_start = nth_weekday(year, 3, Weekday::Sun, 2).and_then(|d| d.and_hms_opt(7, 0, 0));
+    let dst_end = nth_weekday(year, 11, Weekday::Sun, 1).and_then(|d| d.and_hms_opt(6, 0, 0));
+
+    // Both dates exist for every year supported by chrono.
+    let is_dst = match (dst_start, dst_end) {
+        (Some(start), Some(end)) => utc >= start && utc < end,
+        _ => false,
+    };
+
+    if is_dst {
+        utc - Duration::hours(4)
+    } else {
+        utc - Duration::hours(5)
+    }
+}
+
+/// Returns whether the market is open on `date`.
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    !is_weekend(date) && !is_holiday(date)
+}
+
+/// Returns whether `date` is a weekday on which the market is closed.
+pub fn is_holiday(date: NaiveDate) -> bool {
+    if is_weekend(date) {
+        return false;
+    }
+
+    let year = date.year();
+    let unscheduled = UNSCHEDULED_CLOSURES
+        .iter()
+        .any(|&(y, m, d)| y == year && m == date.month() && d == date.day());
+
+    unscheduled || holidays(year).contains(&date)
+}
+
+/// Returns the trading days between `from` and `to`, inclusive.
+pub fn trading_days(from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+    let mut days = Vec::new();
+    let mut date = from;
+
+    while date <= to {
+        if is_trading_day(date) {
+            days.push(date);
+        }
+        date += Duration::days(1);
+    }
+
+    days
+}
+
+/// Returns the first trading day after `date`.
+pub fn next_trading_day(date: NaiveDate) -> NaiveDate {
+    let mut next = date + Duration::days(1);
+    while !is_trading_day(next) {
+        next += Duration::days(1);
+    }
+    next
+}
+
+/// Returns the last trading day before `date`.
+pub fn previous_trading_day(date: NaiveDate) -> NaiveDate {
+    let mut previous = date - Duration::days(1);
+    while !is_trading_day(previous) {
+        previous -= Duration::days(1);
+    }
+    previous
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Returns the dates on which the regular holidays of `year` are observed.
+///
+/// Dates that fall outside of the range supported by chrono are omitted.
+fn holidays(year: i32) -> Vec<NaiveDate> {
+    let ymd = |month, day| NaiveDate::from_ymd_opt(year, month, day);
+    let mut days = Vec::with_capacity(10);
+
+    // New Year's Day is not observed on the preceding Friday when it falls on
+    // a Saturday.
+    if let Some(new_years) = ymd(1, 1) {
+        match new_years.weekday() {
+            Weekday::Sat => {}
+            Weekday::Sun => days.extend(new_years.succ_opt()),
+            _ => days.push(new_years),
+        }
+    }
+
+    if year >= 1998 {
+        days.extend(nth_weekday(year, 1, Weekday::Mon, 3));
+    }
+    days.extend(nth_weekday(year, 2, Weekday::Mon, 3));
+    days.extend(easter(year).and_then(|d| d.checked_sub_signed(Duration::days(2))));
+    days.extend(last_weekday(year, 5, Weekday::Mon));
+    if year >= 2022 {
+        days.extend(ymd(6, 19).map(observed));
+    }
+    days.extend(ymd(7, 4).map(observed));
+    days.extend(nth_weekday(year, 9, Weekday::Mon, 1));
+    days.extend(nth_weekday(year, 11, Weekday::Thu, 4));
+    days.extend(ymd(12, 25).map(observed));
+
+    days
+}
+
+/// Moves a holiday that falls on a weekend to the nearest weekday.
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+/// Returns the `n`th occurrence of `weekday` in a month.
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u32) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8)
+}
+
+/// Returns the last occurrence of `weekday` in a month.
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> Option<NaiveDate> {
+    let mut date = NaiveDate::from_ymd_opt(year, month, 1)?
+        .checked_add_months(Months::new(1))?
+        .pred_opt()?;
+    while date.weekday() != weekday {
+        date = date.pred_opt()?;
+    }
+    Some(date)
+}
+
+/// Returns the date of Easter Sunday using the anonymous Gregorian algorithm.
+fn easter(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::*;
+    use chrono::NaiveDate;
+
+    fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_holidays() {
+        let closed = [
+            ymd(2022, 1, 17),  // Martin Luther King Jr. Day
+            ymd(2022, 4, 15),  // Good Friday
+            ymd(2022, 6, 20),  // Juneteenth observed on Monday
+            ymd(2022, 12, 26), // Christmas observed on Monday
+            ymd(2023, 1, 2),   // New Year's Day observed on Monday
+            ymd(2024, 3, 29),  // Good Friday
+            ymd(2024, 11, 28), // Thanksgiving
+            ymd(2026, 7, 3),   // Independence Day observed on Friday
+            ymd(2018, 12, 5),  // Unscheduled closure
+        ];
+        for date in closed.iter() {
+            assert!(is_holiday(*date), "{} should be a holiday", date);
+        }
+
+        // New Year's Day on a Saturday is not observed on the Friday before.
+        assert!(is_trading_day(ymd(2021, 12, 31)));
+        // Juneteenth was not a market holiday before 2022.
+        assert!(is_trading_day(ymd(2021, 6, 18)));
+    }
+
+    fn millis(year: i32, month: u32, day: u32, hour: u32, min: u32) -> u64 {
+        ymd(year, month, day)
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp_millis() as u64
+    }
+
+    #[test]
+    fn test_session_at() {
+        // Standard time, UTC-5.
+        assert_eq!(session_at(millis(2024, 1, 3, 8, 59)), Session::Closed);
+        assert_eq!(session_at(millis(2024, 1, 3, 9, 0)), Session::PreMarket);
+        assert_eq!(session_at(millis(2024, 1, 3, 14, 30)), Session::Regular);
+        assert_eq!(session_at(millis(2024, 1, 3, 21, 0)), Session::AfterHours);
+        assert_eq!(session_at(millis(2024, 1, 4, 1, 0)), Session::Closed);
+        // Daylight saving time, UTC-4.
+        assert_eq!(session_at(millis(2024, 7, 1, 13, 29)), Session::PreMarket);
+        assert_eq!(session_at(millis(2024, 7, 1, 13, 30)), Session::Regular);
+        assert_eq!(session_at(millis(2024, 7, 1, 20, 0)), Session::AfterHours);
+        // Early close on the day after Thanksgiving.
+        assert_eq!(
+            session_at(millis(2024, 11, 29, 18, 30)),
+            Session::AfterHours
+        );
+        assert_eq!(session_at(millis(2024, 11, 29, 22, 30)), Session::Closed);
+        // Holidays and weekends.
+        assert_eq!(session_at(millis(2024, 12, 25, 15, 0)), Session::Closed);
+        assert_eq!(session_at(millis(2024, 1, 6, 15, 0)), Session::Closed);
+        // After hours in UTC falls on the next day.
+        assert_eq!(
+            eastern_date(millis(2024, 1, 4, 0, 30)),
+            Some(ymd(2024, 1, 3))
+        );
+    }
+
+    #[test]
+    fn test_trading_days() {
+        let days = trading_days(ymd(2023, 12, 22), ymd(2024, 1, 3));
+        assert_eq!(
+            days,
+            vec![
+                ymd(2023, 12, 22),
+                ymd(2023, 12, 26),
+                ymd(2023, 12, 27),
+                ymd(2023, 12, 28),
+                ymd(2023, 12, 29),
+                ymd(2024, 1, 2),
+                ymd(2024, 1, 3),
+            ]
+        );
+        assert_eq!(next_trading_day(ymd(2023, 12, 22)), ymd(2023, 12, 26));
+        assert_eq!(previous_trading_day(ymd(2024, 1, 2)), ymd(2023, 12, 29));
+    }
+}
@@ -0,0 +1,215 @@
+//! Order book depth shared by the REST and streaming APIs.
+//!
+//! A [`DepthBook`] can be built from the crypto L2 book snapshot returned by
+//! [`RESTClient::crypto_l2_book()`](crate::rest::RESTClient::crypto_l2_book)
+//! or from an `XL2` book event received over the WebSocket, so the same code
+//! can inspect the book regardless of where it came from.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::depth::{DepthBook, PriceLevel};
+//!
+//! let book = DepthBook::new(
+//!     vec![PriceLevel::new(100.0, 2.0, Some(1)), PriceLevel::new(100.0, 1.0, Some(2))],
+//!     vec![PriceLevel::new(100.5, 3.0, Some(1))],
+//! );
+//! assert_eq!(book.bid_size_at(100.0), 3.0);
+//! assert_eq!(book.spread(), Some(0.5));
+//! ```
+use std::cmp::Ordering;
+
+use crate::events::CryptoBookEvent;
+use crate::types::{CryptoL2Book, CryptoL2BookLevel};
+
+/// The size available at a price, optionally on a single exchange.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceLevel {
+    pub price: f64,
+    pub size: f64,
+    pub exchange: Option<u64>,
+}
+
+impl PriceLevel {
+    /// Returns a new price level.
+    pub fn new(price: f64, size: f64, exchange: Option<u64>) -> Self {
+        PriceLevel {
+            price,
+            size,
+            exchange,
+        }
+    }
+}
+
+/// The bid and ask sides of an order book.
+///
+/// Bids are kept sorted from the highest to the lowest price and asks from the
+/// lowest to the highest price, so the best price of each side comes first.
+/// A price may appear more than once on a side if the size is broken down by
+/// exchange.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DepthBook {
+    bids: Vec<PriceLevel>,
+    asks: Vec<PriceLevel>,
+}
+
+impl DepthBook {
+    /// Returns a book with the given levels, which may be in any order.
+    pub fn new(mut bids: Vec<PriceLevel>, mut asks: Vec<PriceLevel>) -> Self {
+        bids.sort_by(|a, b| cmp_price(b.price, a.price));
+        asks.sort_by(|a, b| cmp_price(a.price, b.price));
+        DepthBook { bids, asks }
+    }
+
+    /// Returns the bid levels, best first.
+    pub fn bids(&self) -> &[PriceLevel] {
+        &self.bids
+    }
+
+    /// Returns the ask levels, best first.
+    pub fn asks(&self) -> &[PriceLevel] {
+        &self.asks
+    }
+
+    /// Returns the highest bid price.
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.first().map(|l| l.price)
+    }
+
+    /// Returns the lowest ask price.
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.first().map(|l| l.price)
+    }
+
+    /// Returns the difference between the best ask and best bid prices.
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()? - self.best_bid()?)
+    }
+
+    /// Returns the best `n` bid prices with the size aggregated across
+    /// exchanges.
+    pub fn top_bids(&self, n: usize) -> Vec<PriceLevel> {
+        top_levels(&self.bids, n)
+    }
+
+    /// Returns the best `n` ask prices with the size aggregated across
+    /// exchanges.
+    pub fn top_asks(&self, n: usize) -> Vec<PriceLevel> {
+        top_levels(&self.asks, n)
+    }
+
+    /// Returns the total bid size at `price` across exchanges.
+    pub fn bid_size_at(&self, price: f64) -> f64 {
+        size_at(&self.bids, price)
+    }
+
+    /// Returns the total ask size at `price` across exchanges.
+    pub fn ask_size_at(&self, price: f64) -> f64 {
+        size_at(&self.asks, price)
+    }
+}
+
+fn cmp_price(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
+
+fn size_at(levels: &[PriceLevel], price: f64) -> f64 {
+    levels
+        .iter()
+        .filter(|l| l.price == price)
+        .map(|l| l.size)
+        .sum()
+}
+
+fn top_levels(levels: &[PriceLevel], n: usize) -> Vec<PriceLevel> {
+    let mut top: Vec<PriceLevel> = Vec::with_capacity(n);
+
+    for level in levels {
+        let full = top.len() == n;
+        match top.last_mut() {
+            Some(last) if last.price == level.price => {
+                last.size += level.size;
+                if last.exchange != level.exchange {
+                    last.exchange = None;
+                }
+            }
+            _ if full => break,
+            _ => top.push(*level),
+        }
+    }
+
+    top
+}
+
+impl From<&CryptoL2Book> for DepthBook {
+    /// Converts a REST book snapshot, with one level per price and exchange.
+    fn from(book: &CryptoL2Book) -> Self {
+        let levels = |side: &[CryptoL2BookLevel]| {
+            side.iter()
+                .flat_map(|level| {
+                    level.x.iter().map(move |(exchange, size)| {
+                        PriceLevel::new(level.p, *size, exchange.parse().ok())
+                    })
+                })
+                .collect()
+        };
+        DepthBook::new(levels(&book.bids), levels(&book.asks))
+    }
+}
+
+impl From<&CryptoBookEvent> for DepthBook {
+    /// Converts a streamed book event, whose levels all belong to the event's
+    /// exchange.
+    fn from(event: &CryptoBookEvent) -> Self {
+        let levels = |side: &[[f64; 2]]| {
+            side.iter()
+                .map(|[price, size]| PriceLevel::new(*price, *size, event.exchange))
+                .collect()
+        };
+        DepthBook::new(levels(&event.bids), levels(&event.asks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::depth::{DepthBook, PriceLevel};
+    use crate::events::{parse_events, ParseOptions, PolygonEvent};
+    use crate::types::CryptoL2BookResponse;
+
+    #[test]
+    fn test_depth_from_rest_book() {
+        let resp: CryptoL2BookResponse = serde_json::from_str(
+            r#"{"status":"OK","data":{"ticker":"X:BTCUSD","bidCount":2,"askCount":1,"spread":1.5,"updated":1605295074162,
+                "bids":[{"p":16300.5,"x":{"1":0.5}},{"p":16301.0,"x":{"1":0.25,"2":0.75}}],
+                "asks":[{"p":16302.5,"x":{"2":2.0}}]}}"#,
+        )
+        .unwrap();
+        let book = DepthBook::from(&resp.data);
+
+        assert_eq!(book.best_bid(), Some(16301.0));
+        assert_eq!(book.spread(), Some(1.5));
+        assert_eq!(book.bid_size_at(16301.0), 1.0);
+        assert_eq!(book.top_bids(1), vec![PriceLevel::new(16301.0, 1.0, None)]);
+        assert_eq!(book.top_bids(5).len(), 2);
+        assert_eq!(
+            book.top_asks(5),
+            vec![PriceLevel::new(16302.5, 2.0, Some(2))]
+        );
+    }
+
+    #[test]
+    fn test_depth_from_stream_event() {
+        let msg = r#"[{"ev":"XL2","pair":"BTC-USD","t":1598045764700,"r":1598045764706,"x":1,
+            "b":[[11541.6,0.1],[11542.9,0.5]],"a":[[11543.0,0.2],[11543.5,1.0]]}]"#;
+        let events = parse_events(msg, ParseOptions::default()).unwrap();
+        let book = match &events[0] {
+            PolygonEvent::CryptoBook(e) => DepthBook::from(e),
+            e => panic!("expected a book event, got {:?}", e),
+        };
+
+        assert_eq!(book.best_bid(), Some(11542.9));
+        assert_eq!(book.best_ask(), Some(11543.0));
+        assert_eq!(book.ask_size_at(11543.5), 1.0);
+        assert_eq!(book.top_asks(1)[0].exchange, Some(1));
+    }
+}
@@ -0,0 +1,323 @@
+//! Health checks of the streamed data path against the REST API.
+//!
+//! A [`StreamMonitor`] remembers the last trade streamed for each of a few
+//! tracked symbols, along with the time it was received. Sampling compares
+//! each of those trades with the last trade returned by the REST API, with
+//! [`QualityReport::new()`] or with
+//! [`RESTClient::sample_data_quality()`](crate::rest::RESTClient::sample_data_quality)
+//! which also fetches the REST trades, and reports how far the prices diverge,
+//! how far the stream lags behind the REST API, and how long streamed trades
+//! take to arrive.
+//!
+//! The monitor can be cloned, so that one copy records trades in the task
+//! that consumes the stream while another is sampled periodically, such as
+//! with [`RESTClient::data_quality_checks()`](crate::rest::RESTClient::data_quality_checks).
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::diagnostics::{QualityReport, QualityThresholds, StreamMonitor};
+//! use polygon_client::events::TradeEvent;
+//!
+//! let monitor = StreamMonitor::new(&["MSFT"]);
+//! monitor.record_at(&TradeEvent::new("MSFT", 370.0, 100.0, 1_000), 1_050);
+//!
+//! // The REST API reports a later trade at a different price.
+//! let report = QualityReport::new(&monitor, vec![("MSFT", Some((370.5, 9_000)))]);
+//! let sample = &report.samples[0];
+//! assert_eq!(sample.latency_millis(), Some(50));
+//! assert_eq!(sample.staleness_millis(), Some(8_000));
+//! assert_eq!(report.unhealthy(QualityThresholds::default()).len(), 1);
+//! ```
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::events::TradeEvent;
+use crate::runtime::{SystemTime, UNIX_EPOCH};
+
+/// The last trade streamed for a symbol.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StreamedTrade {
+    pub price: f64,
+    /// The SIP timestamp of the trade in Unix milliseconds.
+    pub timestamp: u64,
+    /// The time the trade was received in Unix milliseconds.
+    pub received_at: u64,
+}
+
+/// Records the last streamed trade of each tracked symbol.
+///
+/// Clones share the same records.
+#[derive(Clone, Debug, Default)]
+pub struct StreamMonitor {
+    symbols: Vec<String>,
+    trades: Arc<Mutex<HashMap<String, StreamedTrade>>>,
+}
+
+// Ensure the monitor can be moved to, and shared between, threads.
+assert_send_sync!(StreamMonitor);
+
+impl StreamMonitor {
+    /// Returns a monitor that tracks `symbols`.
+    pub fn new<S: AsRef<str>>(symbols: &[S]) -> Self {
+        StreamMonitor {
+            symbols: symbols.iter().map(|s| String::from(s.as_ref())).collect(),
+            trades: Arc::default(),
+        }
+    }
+
+    /// Returns the tracked symbols.
+    pub fn symbols(&self) -> &[String] {
+        &self.symbols
+    }
+
+    /// Records a streamed trade received now. Trades of symbols that are not
+    /// tracked are ignored.
+    pub fn record(&self, trade: &TradeEvent) {
+        self.record_at(trade, now_ms());
+    }
+
+    /// Records a streamed trade received at `received_at`, in Unix
+    /// milliseconds.
+    ///
+    /// A trade older than the one already recorded for its symbol, such as a
+    /// late report, is ignored.
+    pub fn record_at(&self, trade: &TradeEvent, received_at: u64) {
+        if !self.symbols.contains(&trade.symbol) {
+            return;
+        }
+
+        let streamed = StreamedTrade {
+            price: trade.price,
+            timestamp: trade.timestamp,
+            received_at,
+        };
+        let mut trades = self.trades.lock().unwrap_or_else(PoisonError::into_inner);
+        trades
+            .entry(trade.symbol.clone())
+            .and_modify(|last| {
+                if streamed.timestamp >= last.timestamp {
+                    *last = streamed;
+                }
+            })
+            .or_insert(streamed);
+    }
+
+    /// Returns the last trade streamed for a symbol.
+    pub fn last_trade(&self, symbol: &str) -> Option<StreamedTrade> {
+        let trades = self.trades.lock().unwrap_or_else(PoisonError::into_inner);
+        trades.get(symbol).copied()
+    }
+}
+
+/// A comparison of the last streamed trade of a symbol with its last trade
+/// from the REST API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QualitySample {
+    pub symbol: String,
+    /// The last streamed trade, or `None` if no trade has been streamed.
+    pub streamed: Option<StreamedTrade>,
+    /// The price of the last trade from the REST API, or `None` if there was
+    /// none.
+    pub rest_price: Option<f64>,
+    /// The time of the last trade from the REST API in Unix milliseconds.
+    pub rest_timestamp: Option<u64>,
+    /// The error of the request for the last trade, if it failed.
+    pub rest_error: Option<String>,
+}
+
+impl QualitySample {
+    /// Returns the difference between the streamed and REST prices as a
+    /// fraction of the REST price.
+    pub fn divergence(&self) -> Option<f64> {
+        let streamed = self.streamed?.price;
+        let rest = self.rest_price?;
+        if rest == 0.0 {
+            return None;
+        }
+        Some((streamed - rest).abs() / rest)
+    }
+
+    /// Returns how many milliseconds the last streamed trade is behind the
+    /// last REST trade. A negative value means the stream is ahead.
+    pub fn staleness_millis(&self) -> Option<i64> {
+        let streamed = self.streamed?.timestamp as i64;
+        Some(self.rest_timestamp? as i64 - streamed)
+    }
+
+    /// Returns how many milliseconds the last streamed trade took to arrive
+    /// after its SIP timestamp.
+    pub fn latency_millis(&self) -> Option<i64> {
+        let streamed = self.streamed?;
+        Some(streamed.received_at as i64 - streamed.timestamp as i64)
+    }
+}
+
+/// Thresholds beyond which a [`QualitySample`] is reported as unhealthy.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityThresholds {
+    /// The largest allowed [`QualitySample::divergence()`].
+    pub divergence: f64,
+    /// The largest allowed [`QualitySample::staleness_millis()`].
+    pub staleness: Duration,
+    /// The largest allowed [`QualitySample::latency_millis()`].
+    pub latency: Duration,
+}
+
+impl Default for QualityThresholds {
+    /// Allows prices to diverge by 0.5%, the stream to lag the REST API by
+    /// five seconds, and trades to take one second to arrive.
+    fn default() -> Self {
+        QualityThresholds {
+            divergence: 0.005,
+            staleness: Duration::from_secs(5),
+            latency: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The samples taken of every tracked symbol at one point in time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QualityReport {
+    /// The time the report was made in Unix milliseconds.
+    pub sampled_at: u64,
+    pub samples: Vec<QualitySample>,
+}
+
+impl QualityReport {
+    /// Compares the last streamed trade of each symbol with its last REST
+    /// trade, given as a price and a timestamp in Unix milliseconds.
+    pub fn new<S: AsRef<str>>(
+        monitor: &StreamMonitor,
+        rest_trades: Vec<(S, Option<(f64, u64)>)>,
+    ) -> Self {
+        let samples = rest_trades
+            .into_iter()
+            .map(|(symbol, rest)| QualitySample {
+                symbol: String::from(symbol.as_ref()),
+                streamed: monitor.last_trade(symbol.as_ref()),
+                rest_price: rest.map(|(price, _)| price),
+                rest_timestamp: rest.map(|(_, timestamp)| timestamp),
+                rest_error: None,
+            })
+            .collect();
+
+        QualityReport {
+            sampled_at: now_ms(),
+            samples,
+        }
+    }
+
+    /// Returns the symbols for which no trade has been streamed.
+    pub fn missing(&self) -> Vec<&str> {
+        self.samples
+            .iter()
+            .filter(|s| s.streamed.is_none())
+            .map(|s| s.symbol.as_str())
+            .collect()
+    }
+
+    /// Returns the largest divergence between streamed and REST prices.
+    pub fn max_divergence(&self) -> Option<f64> {
+        self.samples
+            .iter()
+            .filter_map(|s| s.divergence())
+            .fold(None, |max, d| Some(max.map_or(d, |m: f64| m.max(d))))
+    }
+
+    /// Returns the largest staleness of the stream in milliseconds.
+    pub fn max_staleness_millis(&self) -> Option<i64> {
+        self.samples
+            .iter()
+            .filter_map(|s| s.staleness_millis())
+            .max()
+    }
+
+    /// Returns the mean latency of the streamed trades in milliseconds.
+    pub fn mean_latency_millis(&self) -> Option<f64> {
+        let latencies: Vec<_> = self
+            .samples
+            .iter()
+            .filter_map(|s| s.latency_millis())
+            .collect();
+        if latencies.is_empty() {
+            return None;
+        }
+        Some(latencies.iter().sum::<i64>() as f64 / latencies.len() as f64)
+    }
+
+    /// Returns the samples that exceed any of the `thresholds`, including
+    /// those of symbols for which no trade has been streamed or whose last
+    /// REST trade could not be fetched.
+    pub fn unhealthy(&self, thresholds: QualityThresholds) -> Vec<&QualitySample> {
+        let staleness = thresholds.staleness.as_millis() as i64;
+        let latency = thresholds.latency.as_millis() as i64;
+
+        self.samples
+            .iter()
+            .filter(|s| {
+                s.streamed.is_none()
+                    || s.rest_error.is_some()
+                    || s.divergence().is_some_and(|d| d > thresholds.divergence)
+                    || s.staleness_millis().is_some_and(|m| m > staleness)
+                    || s.latency_millis().is_some_and(|m| m > latency)
+            })
+            .collect()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{QualityReport, QualityThresholds, StreamMonitor};
+    use crate::events::TradeEvent;
+
+    #[test]
+    fn test_record_keeps_latest_tracked_trade() {
+        let monitor = StreamMonitor::new(&["MSFT"]);
+        let shared = monitor.clone();
+        shared.record_at(&TradeEvent::new("MSFT", 2.0, 1.0, 2_000), 2_100);
+        shared.record_at(&TradeEvent::new("MSFT", 1.0, 1.0, 1_000), 2_200);
+        shared.record_at(&TradeEvent::new("AAPL", 3.0, 1.0, 3_000), 3_100);
+
+        let last = monitor.last_trade("MSFT").unwrap();
+        assert_eq!(last.price, 2.0);
+        assert_eq!(last.received_at, 2_100);
+        assert_eq!(monitor.last_trade("AAPL"), None);
+    }
+
+    #[test]
+    fn test_report() {
+        let monitor = StreamMonitor::new(&["MSFT", "AAPL", "NVDA"]);
+        monitor.record_at(&TradeEvent::new("MSFT", 100.0, 1.0, 10_000), 10_020);
+        monitor.record_at(&TradeEvent::new("AAPL", 200.0, 1.0, 10_000), 10_080);
+
+        let report = QualityReport::new(
+            &monitor,
+            vec![
+                ("MSFT", Some((100.1, 10_000))),
+                ("AAPL", Some((202.0, 20_000))),
+                ("NVDA", None),
+            ],
+        );
+
+        assert_eq!(report.missing(), vec!["NVDA"]);
+        assert!((report.max_divergence().unwrap() - 0.0099).abs() < 1e-4);
+        assert_eq!(report.max_staleness_millis(), Some(10_000));
+        assert_eq!(report.mean_latency_millis(), Some(50.0));
+
+        let unhealthy: Vec<_> = report
+            .unhealthy(QualityThresholds::default())
+            .iter()
+            .map(|s| s.symbol.as_str())
+            .collect();
+        assert_eq!(unhealthy, vec!["AAPL", "NVDA"]);
+    }
+}
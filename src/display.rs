@@ -0,0 +1,262 @@
+//! Formatting of prices, volumes, and changes for display.
+//!
+//! A [`DisplayFormat`] rounds prices to the number of decimal places usual
+//! for an asset class, shortens volumes such as `17842313` to `17.8M`, and
+//! formats signed changes. It can format any bar that implements [`Ohlcv`],
+//! such as the bars returned by the aggregates endpoints and the bars received
+//! over the WebSocket.
+//!
+//! The helpers are enabled by the `display` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::display::DisplayFormat;
+//! use polygon_client::types::{AssetClass, StockEquitiesAggregates};
+//!
+//! let format = DisplayFormat::for_asset_class(AssetClass::Stocks);
+//! let bar = StockEquitiesAggregates::new(0, 296.22, 296.64, 292.75, 294.6, 17842313.0);
+//!
+//! assert_eq!(format.price(0.12345), "0.1235");
+//! assert_eq!(format.volume(bar.v), "17.8M");
+//! assert_eq!(
+//!     format.bar(&bar),
+//!     "O 296.22  H 296.64  L 292.75  C 294.60  V 17.8M  -1.62 (-0.55%)"
+//! );
+//! ```
+use crate::events::BarEvent;
+use crate::types::*;
+
+/// The suffixes used to shorten volumes, by power of one thousand.
+const VOLUME_SUFFIXES: [&str; 5] = ["", "K", "M", "B", "T"];
+
+/// A bar with an open, high, low, close, and volume.
+pub trait Ohlcv {
+    fn open(&self) -> f64;
+    fn high(&self) -> f64;
+    fn low(&self) -> f64;
+    fn close(&self) -> f64;
+    fn volume(&self) -> f64;
+}
+
+macro_rules! impl_ohlcv {
+    ($($t:ty => $o:ident, $h:ident, $l:ident, $c:ident, $v:ident;)+) => {
+        $(
+            impl Ohlcv for $t {
+                fn open(&self) -> f64 {
+                    self.$o
+                }
+
+                fn high(&self) -> f64 {
+                    self.$h
+                }
+
+                fn low(&self) -> f64 {
+                    self.$l
+                }
+
+                fn close(&self) -> f64 {
+                    self.$c
+                }
+
+                fn volume(&self) -> f64 {
+                    self.$v
+                }
+            }
+        )+
+    };
+}
+
+impl_ohlcv!(
+    Aggregate => o, h, l, c, v;
+    SnapshotMinuteBar => open, high, low, close, volume;
+    DailyBar => open, high, low, close, volume;
+    BarEvent => open, high, low, close, volume;
+);
+
+/// How prices, volumes, and percentages are rounded for display.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayFormat {
+    price_decimals: usize,
+    small_price_decimals: usize,
+    small_price_threshold: f64,
+    percent_decimals: usize,
+}
+
+impl DisplayFormat {
+    /// Returns the usual format for prices of an asset class:
+    ///
+    /// * stocks, options, and other asset classes - 2 decimal places, or 4
+    ///   below $1
+    /// * indices - 2 decimal places
+    /// * forex - 5 decimal places
+    /// * crypto - 2 decimal places, or 8 below 1
+    pub fn for_asset_class(asset_class: AssetClass) -> Self {
+        let (price_decimals, small_price_decimals) = match asset_class {
+            AssetClass::Indices => (2, 2),
+            AssetClass::Forex => (5, 5),
+            AssetClass::Crypto => (2, 8),
+            _ => (2, 4),
+        };
+
+        DisplayFormat {
+            price_decimals,
+            small_price_decimals,
+            small_price_threshold: 1.0,
+            percent_decimals: 2,
+        }
+    }
+
+    /// Sets the number of decimal places of prices.
+    pub fn price_decimals(mut self, decimals: usize) -> Self {
+        self.price_decimals = decimals;
+        self
+    }
+
+    /// Sets the number of decimal places of prices whose magnitude is below
+    /// `threshold`.
+    pub fn small_price_decimals(mut self, decimals: usize, threshold: f64) -> Self {
+        self.small_price_decimals = decimals;
+        self.small_price_threshold = threshold;
+        self
+    }
+
+    /// Sets the number of decimal places of percentages.
+    pub fn percent_decimals(mut self, decimals: usize) -> Self {
+        self.percent_decimals = decimals;
+        self
+    }
+
+    /// Returns a price rounded to the number of decimal places for its size.
+    pub fn price(&self, price: f64) -> String {
+        format!("{:.*}", self.decimals_for(price), price)
+    }
+
+    /// Returns a volume shortened to three significant digits with a `K`,
+    /// `M`, `B`, or `T` suffix, such as `17.8M`. Volumes below one thousand
+    /// are rounded to a whole number.
+    pub fn volume(&self, volume: f64) -> String {
+        let mut value = volume;
+        let mut power = 0;
+        while value.abs() >= 999.5 && power < VOLUME_SUFFIXES.len() - 1 {
+            value /= 1000.0;
+            power += 1;
+        }
+
+        let decimals = match (power, value.abs()) {
+            (0, _) => 0,
+            (_, v) if v < 9.995 => 2,
+            (_, v) if v < 99.95 => 1,
+            _ => 0,
+        };
+        format!("{:.*}{}", decimals, value, VOLUME_SUFFIXES[power])
+    }
+
+    /// Returns a percentage with an explicit sign, such as `+0.41%`.
+    pub fn percent(&self, percent: f64) -> String {
+        format!("{:+.*}%", self.percent_decimals, percent)
+    }
+
+    /// Returns the change from `from` to `to` as a signed price followed by a
+    /// signed percentage, such as `+1.50 (+0.41%)`. The percentage is left out
+    /// if `from` is zero.
+    pub fn change(&self, from: f64, to: f64) -> String {
+        let change = to - from;
+        let decimals = self.decimals_for(from).max(self.decimals_for(to));
+        let price = format!("{:+.*}", decimals, change);
+
+        if from == 0.0 {
+            price
+        } else {
+            format!("{} ({})", price, self.percent(change / from * 100.0))
+        }
+    }
+
+    /// Returns a bar's prices, volume, and change from open to close on a
+    /// single line.
+    pub fn bar<B: Ohlcv + ?Sized>(&self, bar: &B) -> String {
+        format!(
+            "O {}  H {}  L {}  C {}  V {}  {}",
+            self.price(bar.open()),
+            self.price(bar.high()),
+            self.price(bar.low()),
+            self.price(bar.close()),
+            self.volume(bar.volume()),
+            self.change(bar.open(), bar.close()),
+        )
+    }
+
+    /// Returns the change of a snapshot since the previous close, such as
+    /// `+1.50 (+0.41%)`.
+    pub fn snapshot_change(&self, snapshot: &StockEquitiesTickerSnapshot) -> String {
+        format!(
+            "{:+.*} ({})",
+            self.decimals_for(snapshot.prev_day.c),
+            snapshot.todays_change,
+            self.percent(snapshot.todays_change_perc)
+        )
+    }
+
+    fn decimals_for(&self, price: f64) -> usize {
+        if price != 0.0 && price.abs() < self.small_price_threshold {
+            self.small_price_decimals
+        } else {
+            self.price_decimals
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::display::DisplayFormat;
+    use crate::events::BarEvent;
+    use crate::types::*;
+
+    #[test]
+    fn test_price() {
+        let stocks = DisplayFormat::for_asset_class(AssetClass::Stocks);
+        assert_eq!(stocks.price(294.6), "294.60");
+        assert_eq!(stocks.price(0.0512), "0.0512");
+        assert_eq!(stocks.price(0.0), "0.00");
+
+        let forex = DisplayFormat::for_asset_class(AssetClass::Forex);
+        assert_eq!(forex.price(1.174391), "1.17439");
+        let jpy = forex.price_decimals(3).small_price_decimals(3, 1.0);
+        assert_eq!(jpy.price(149.4567), "149.457");
+
+        let crypto = DisplayFormat::for_asset_class(AssetClass::Crypto);
+        assert_eq!(crypto.price(0.000012346), "0.00001235");
+    }
+
+    #[test]
+    fn test_volume() {
+        let format = DisplayFormat::for_asset_class(AssetClass::Stocks);
+        assert_eq!(format.volume(950.0), "950");
+        assert_eq!(format.volume(999.6), "1.00K");
+        assert_eq!(format.volume(1234.0), "1.23K");
+        assert_eq!(format.volume(99_960.0), "100K");
+        assert_eq!(format.volume(17_842_313.0), "17.8M");
+        assert_eq!(format.volume(2.5e9), "2.50B");
+    }
+
+    #[test]
+    fn test_change() {
+        let format = DisplayFormat::for_asset_class(AssetClass::Stocks).percent_decimals(1);
+        assert_eq!(format.change(370.0, 371.5), "+1.50 (+0.4%)");
+        assert_eq!(format.change(0.0, 1.0), "+1.00");
+        assert_eq!(format.percent(-2.0), "-2.0%");
+
+        let bar = BarEvent::new("MSFT", 0, 60_000)
+            .with_ohlc(0.5, 0.6, 0.4, 0.45)
+            .with_volume(1500.0);
+        assert_eq!(
+            format.bar(&bar),
+            "O 0.5000  H 0.6000  L 0.4000  C 0.4500  V 1.50K  -0.0500 (-10.0%)"
+        );
+
+        let snapshot: StockEquitiesSnapshotAllTickersResponse =
+            serde_json::from_str(include_str!("../fixtures/ticker_snapshot.json")).unwrap();
+        let change = format.snapshot_change(&snapshot.tickers[0]);
+        assert!(change.starts_with('+') || change.starts_with('-'));
+    }
+}
@@ -0,0 +1,543 @@
+//! Error types returned by the clients.
+//!
+//! The REST and WebSocket clients both return [`Error`]. Its variants describe
+//! exactly what went wrong and may gain fields or change between releases,
+//! while [`Error::kind()`] sorts every error into one of a small, stable set
+//! of [`ErrorKind`] categories. Applications that use both clients can handle
+//! errors in one place by matching on the kind:
+//!
+//! ```
+//! use polygon_client::{Error, ErrorKind};
+//!
+//! fn should_retry(err: &Error) -> bool {
+//!     matches!(err.kind(), ErrorKind::RateLimited | ErrorKind::Transport)
+//! }
+//!
+//! assert!(!should_retry(&Error::MissingAuthKey));
+//! ```
+//!
+//! Code written against the `reqwest::Error` previously returned by the REST
+//! client can use [`Error::reqwest()`] to get at the underlying error while it
+//! is migrated.
+use std::fmt;
+#[cfg(feature = "rest")]
+use std::time::Duration;
+
+use crate::ticker::TickerError;
+#[cfg(feature = "rest")]
+use crate::types::ErrorResponse;
+#[cfg(feature = "websocket")]
+use crate::websocket::protocol::ParseChannelError;
+
+/// The maximum number of bytes of a response body that are included in a
+/// [`DecodeError`].
+const BODY_SNIPPET_LEN: usize = 256;
+
+/// The size of the smallest body decoded with simd-json, below which the cost
+/// of copying the body outweighs the faster parsing.
+#[cfg(feature = "simd-json")]
+const SIMD_JSON_MIN_LEN: usize = 64 * 1024;
+
+/// An error returned by the clients.
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or the response could not be read.
+    #[cfg(feature = "rest")]
+    Transport(reqwest::Error),
+    /// The request timed out, such as after the duration set with
+    /// [`RESTClientBuilder::timeout()`](crate::rest::RESTClientBuilder::timeout).
+    #[cfg(feature = "rest")]
+    Timeout(reqwest::Error),
+    /// The server returned an unsuccessful status code other than
+    /// `429 Too Many Requests`.
+    ///
+    /// A body that reports a failure with a `200 OK` status code is returned
+    /// as this error as well.
+    #[cfg(feature = "rest")]
+    Api {
+        /// The HTTP status code.
+        status: u16,
+        /// The body of the response, if it is an [`ErrorResponse`].
+        response: Option<ErrorResponse>,
+    },
+    /// The server rate limited the request.
+    ///
+    /// `retry_after` contains the duration provided by the `Retry-After`
+    /// header, if any, and `response` the body of the response, if it is an
+    /// [`ErrorResponse`].
+    #[cfg(feature = "rest")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        response: Option<ErrorResponse>,
+    },
+    /// The `limit` query parameter is not a number between 1 and the maximum
+    /// supported by the endpoint.
+    #[cfg(feature = "rest")]
+    InvalidLimit { limit: String, max: u32 },
+    /// The request was not sent because the API failed repeatedly, as set by
+    /// [`RESTClientBuilder::circuit_breaker()`](crate::rest::RESTClientBuilder::circuit_breaker).
+    ///
+    /// `retry_after` is the time left until requests are sent again, or zero
+    /// while the request probing the API after the cooldown is in flight.
+    #[cfg(feature = "rest")]
+    CircuitOpen { retry_after: Duration },
+    /// A client option is out of range, such as a
+    /// [`max_in_flight()`](crate::rest::RESTClientBuilder::max_in_flight) of
+    /// zero.
+    #[cfg(feature = "rest")]
+    Config(String),
+    /// The response body could not be deserialized into the expected type.
+    Decode(DecodeError),
+    /// No API key was provided and the `POLYGON_AUTH_KEY` environment variable
+    /// is not set.
+    MissingAuthKey,
+    /// A ticker passed to the client is not valid for the request.
+    InvalidTicker(TickerError),
+    /// The WebSocket connection failed or was closed.
+    #[cfg(feature = "websocket")]
+    WebSocket(Box<tungstenite::Error>),
+    /// A file could not be read or written.
+    Io(std::io::Error),
+    /// A WebSocket channel is not of the form `PREFIX.TICKER` or uses an
+    /// unknown prefix.
+    #[cfg(feature = "websocket")]
+    InvalidChannel(ParseChannelError),
+}
+
+/// A stable category of [`Error`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// No API key was provided or the server rejected it.
+    Auth,
+    /// The server rate limited the request.
+    RateLimited,
+    /// The connection failed, timed out, or was closed, or the server returned
+    /// an unexpected status code.
+    Transport,
+    /// The server broke the HTTP or WebSocket protocol.
+    Protocol,
+    /// A response or message could not be deserialized.
+    Decode,
+    /// The requested resource does not exist.
+    NotFound,
+    /// The API key is valid but its plan does not include the requested data.
+    Entitlement,
+    /// An argument passed to the client was not valid, such as a malformed
+    /// ticker or an out of range `limit`.
+    InvalidRequest,
+    /// An error that does not fit any other category, such as a failure to
+    /// read a local file.
+    Other,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ErrorKind::Auth => "authentication failed",
+            ErrorKind::RateLimited => "rate limited",
+            ErrorKind::Transport => "transport error",
+            ErrorKind::Protocol => "protocol error",
+            ErrorKind::Decode => "decode error",
+            ErrorKind::NotFound => "not found",
+            ErrorKind::Entitlement => "not entitled",
+            ErrorKind::InvalidRequest => "invalid request",
+            ErrorKind::Other => "other error",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl ErrorKind {
+    /// Returns the kind of error indicated by an HTTP status code, if the
+    /// status code identifies one.
+    #[cfg(any(feature = "rest", feature = "websocket"))]
+    fn from_status(status: u16) -> Option<Self> {
+        match status {
+            401 => Some(ErrorKind::Auth),
+            403 => Some(ErrorKind::Entitlement),
+            404 => Some(ErrorKind::NotFound),
+            429 => Some(ErrorKind::RateLimited),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Returns the category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            #[cfg(feature = "rest")]
+            Error::Transport(e) => {
+                if e.is_decode() {
+                    ErrorKind::Decode
+                } else {
+                    e.status()
+                        .and_then(|s| ErrorKind::from_status(s.as_u16()))
+                        .unwrap_or(ErrorKind::Transport)
+                }
+            }
+            #[cfg(feature = "rest")]
+            Error::Timeout(_) => ErrorKind::Transport,
+            #[cfg(feature = "rest")]
+            Error::Api { status, .. } => {
+                ErrorKind::from_status(*status).unwrap_or(if (400..500).contains(status) {
+                    ErrorKind::InvalidRequest
+                } else {
+                    ErrorKind::Transport
+                })
+            }
+            #[cfg(feature = "rest")]
+            Error::RateLimited { .. } => ErrorKind::RateLimited,
+            #[cfg(feature = "rest")]
+            Error::InvalidLimit { .. } => ErrorKind::InvalidRequest,
+            #[cfg(feature = "rest")]
+            Error::CircuitOpen { .. } => ErrorKind::Transport,
+            #[cfg(feature = "rest")]
+            Error::Config(_) => ErrorKind::InvalidRequest,
+            Error::Decode(_) => ErrorKind::Decode,
+            Error::MissingAuthKey => ErrorKind::Auth,
+            Error::InvalidTicker(_) => ErrorKind::InvalidRequest,
+            #[cfg(feature = "websocket")]
+            Error::WebSocket(e) => match e.as_ref() {
+                tungstenite::Error::ConnectionClosed
+                | tungstenite::Error::AlreadyClosed
+                | tungstenite::Error::Io(_)
+                | tungstenite::Error::Tls(_)
+                | tungstenite::Error::Url(_) => ErrorKind::Transport,
+                tungstenite::Error::Http(resp) => {
+                    ErrorKind::from_status(resp.status().as_u16()).unwrap_or(ErrorKind::Transport)
+                }
+                _ => ErrorKind::Protocol,
+            },
+            Error::Io(_) => ErrorKind::Other,
+            #[cfg(feature = "websocket")]
+            Error::InvalidChannel(_) => ErrorKind::InvalidRequest,
+        }
+    }
+}
+
+impl Error {
+    /// Returns the underlying `reqwest::Error` of a transport error or a
+    /// timeout.
+    #[cfg(feature = "rest")]
+    pub fn reqwest(&self) -> Option<&reqwest::Error> {
+        match self {
+            Error::Transport(e) | Error::Timeout(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Returns the body of an unsuccessful response, if the server sent one.
+    #[cfg(feature = "rest")]
+    pub fn error_response(&self) -> Option<&ErrorResponse> {
+        match self {
+            Error::Api { response, .. } | Error::RateLimited { response, .. } => response.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the HTTP status code of an unsuccessful response.
+    #[cfg(feature = "rest")]
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Api { status, .. } => Some(*status),
+            Error::RateLimited { .. } => Some(429),
+            Error::Transport(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "rest")]
+            Error::Transport(e) => write!(f, "transport error: {}", e),
+            #[cfg(feature = "rest")]
+            Error::Timeout(e) => write!(f, "request timed out: {}", e),
+            #[cfg(feature = "rest")]
+            Error::Api { status, response } => {
+                write!(f, "API error: HTTP {}", status)?;
+                if let Some(response) = response {
+                    if let Some(polygon_status) = &response.status {
+                        write!(f, " {}", polygon_status)?;
+                    }
+                    if let Some(reason) = response.reason() {
+                        write!(f, ": {}", reason)?;
+                    }
+                    if let Some(request_id) = &response.request_id {
+                        write!(f, " (request {})", request_id)?;
+                    }
+                }
+                Ok(())
+            }
+            #[cfg(feature = "rest")]
+            Error::RateLimited {
+                retry_after,
+                response,
+            } => {
+                write!(f, "rate limited")?;
+                if let Some(reason) = response.as_ref().and_then(|r| r.reason()) {
+                    write!(f, ": {}", reason)?;
+                }
+                if let Some(d) = retry_after {
+                    write!(f, ", retry after {}s", d.as_secs())?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "rest")]
+            Error::InvalidLimit { limit, max } => write!(
+                f,
+                "invalid limit {:?}: must be a number between 1 and {}",
+                limit, max
+            ),
+            #[cfg(feature = "rest")]
+            Error::CircuitOpen { retry_after } => write!(
+                f,
+                "circuit open after repeated API failures, retry after {}s",
+                retry_after.as_secs()
+            ),
+            #[cfg(feature = "rest")]
+            Error::Config(msg) => write!(f, "invalid configuration: {}", msg),
+            Error::Decode(e) => write!(f, "{}", e),
+            Error::MissingAuthKey => write!(f, "POLYGON_AUTH_KEY not set"),
+            Error::InvalidTicker(e) => write!(f, "invalid ticker: {}", e),
+            #[cfg(feature = "websocket")]
+            Error::WebSocket(e) => write!(f, "websocket error: {}", e),
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            #[cfg(feature = "websocket")]
+            Error::InvalidChannel(e) => write!(f, "invalid channel: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "rest")]
+            Error::Transport(e) | Error::Timeout(e) => Some(e),
+            Error::Decode(e) => Some(&e.source),
+            Error::InvalidTicker(e) => Some(e),
+            #[cfg(feature = "websocket")]
+            Error::WebSocket(e) => Some(e),
+            Error::Io(e) => Some(e),
+            #[cfg(feature = "websocket")]
+            Error::InvalidChannel(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "rest")]
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Error::Timeout(e)
+        } else {
+            Error::Transport(e)
+        }
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl From<tungstenite::Error> for Error {
+    fn from(e: tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(e))
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl From<ParseChannelError> for Error {
+    fn from(e: ParseChannelError) -> Self {
+        Error::InvalidChannel(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<TickerError> for Error {
+    fn from(e: TickerError) -> Self {
+        Error::InvalidTicker(e)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(e: DecodeError) -> Self {
+        Error::Decode(e)
+    }
+}
+
+/// Returns an [`Error::Api`] for an unsuccessful response, with the details
+/// found in its body.
+#[cfg(feature = "rest")]
+pub(crate) fn api_error(status: u16, body: &[u8]) -> Error {
+    Error::Api {
+        status,
+        response: serde_json::from_slice::<ErrorResponse>(body).ok(),
+    }
+}
+
+/// Returns an [`Error::Api`] if a successful response that could not be
+/// decoded has a body that reports a failure.
+#[cfg(feature = "rest")]
+pub(crate) fn error_in_body(status: u16, body: &[u8]) -> Option<Error> {
+    let response = serde_json::from_slice::<ErrorResponse>(body).ok()?;
+    if !response.is_error() {
+        return None;
+    }
+    Some(api_error(status, body))
+}
+
+/// Details about a response body that failed to deserialize.
+#[derive(Debug)]
+pub struct DecodeError {
+    /// The path to the offending JSON value, such as `results[3].lastQuote.P`.
+    pub path: String,
+    /// The underlying deserialization error.
+    pub source: serde_json::Error,
+    /// The beginning of the response body, truncated to a small number of
+    /// bytes.
+    pub snippet: String,
+}
+
+impl DecodeError {
+    pub(crate) fn new(path: String, source: serde_json::Error, body: &[u8]) -> Self {
+        let snippet = if body.len() > BODY_SNIPPET_LEN {
+            format!("{}...", String::from_utf8_lossy(&body[..BODY_SNIPPET_LEN]))
+        } else {
+            String::from_utf8_lossy(body).into_owned()
+        };
+
+        DecodeError {
+            path,
+            source,
+            snippet,
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "failed to decode response at {}: {} (body: {})",
+            self.path, self.source, self.snippet
+        )
+    }
+}
+
+/// Deserializes a JSON body, reporting the path of the offending value on
+/// failure.
+///
+/// With the `simd-json` feature, bodies of at least 64 KiB are decoded with
+/// simd-json instead. Bodies that it fails to decode are decoded again with
+/// serde_json, so that errors still report the path of the offending value.
+pub(crate) fn decode_json<T>(body: &[u8]) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    #[cfg(feature = "simd-json")]
+    if body.len() >= SIMD_JSON_MIN_LEN {
+        // simd-json parses in place, so it needs a copy of the body.
+        let mut body = body.to_vec();
+        if let Ok(value) = simd_json::serde::from_slice::<T>(&mut body) {
+            return Ok(value);
+        }
+    }
+
+    let de = &mut serde_json::Deserializer::from_slice(body);
+    serde_path_to_error::deserialize(de).map_err(|e| {
+        let path = e.path().to_string();
+        Error::Decode(DecodeError::new(path, e.into_inner(), body))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{decode_json, Error, ErrorKind};
+    use crate::types::*;
+
+    #[test]
+    fn test_decode_json_reports_path() {
+        let body = br#"{"status":"OK","results":{"types":{"CS":"Common Stock"},"indexTypes":{"INDEX":1}}}"#;
+        let err = decode_json::<ReferenceTickerTypesResponse>(body).unwrap_err();
+        match err {
+            Error::Decode(e) => {
+                assert_eq!(e.path, "results.indexTypes.INDEX");
+                assert!(e.snippet.starts_with("{\"status\":\"OK\""));
+            }
+            #[allow(unreachable_patterns)]
+            _ => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_decode_json_large_body() {
+        let results: Vec<_> = (0..2_000)
+            .map(|i| {
+                format!(
+                    r#"{{"T":"T{}","v":1000,"o":1.5,"c":2.5,"h":3.0,"l":1.0,"t":0}}"#,
+                    i
+                )
+            })
+            .collect();
+        let body = format!(
+            r#"{{"adjusted":true,"queryCount":2000,"resultsCount":2000,"status":"OK","results":[{}]}}"#,
+            results.join(",")
+        );
+        assert!(body.len() > 64 * 1024);
+
+        let resp = decode_json::<StockEquitiesGroupedDailyResponse>(body.as_bytes()).unwrap();
+        assert_eq!(resp.results.len(), 2_000);
+        assert_eq!(resp.results[1_999].c, 2.5);
+
+        let bad = body.replacen(r#""c":2.5"#, r#""c":"bad""#, 1);
+        match decode_json::<StockEquitiesGroupedDailyResponse>(bad.as_bytes()).unwrap_err() {
+            Error::Decode(e) => assert_eq!(e.path, "results[0].c"),
+            #[allow(unreachable_patterns)]
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_decode_json_truncates_snippet() {
+        let body = format!("[{}]", "1,".repeat(512));
+        let err = decode_json::<Vec<String>>(body.as_bytes()).unwrap_err();
+        match err {
+            Error::Decode(e) => {
+                assert_eq!(e.path, "[0]");
+                assert!(e.snippet.ends_with("..."));
+                assert_eq!(e.snippet.len(), super::BODY_SNIPPET_LEN + 3);
+            }
+            #[allow(unreachable_patterns)]
+            _ => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_error_kind() {
+        assert_eq!(Error::MissingAuthKey.kind(), ErrorKind::Auth);
+        let err = decode_json::<Vec<String>>(b"[1]").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Decode);
+        let err = Error::from(crate::ticker::TickerError::Empty);
+        assert_eq!(err.kind(), ErrorKind::InvalidRequest);
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn test_websocket_error_kind() {
+        let resp = tungstenite::http::Response::builder()
+            .status(401)
+            .body(None)
+            .unwrap();
+        let err = Error::from(tungstenite::Error::Http(resp));
+        assert_eq!(err.kind(), ErrorKind::Auth);
+        let err = Error::from(tungstenite::Error::ConnectionClosed);
+        assert_eq!(err.kind(), ErrorKind::Transport);
+    }
+}
@@ -0,0 +1,597 @@
+//! Typed market data events.
+//!
+//! These mirror the trade, quote, and aggregate messages sent by the
+//! streaming API. Events can also be synthesized from REST responses, such as
+//! ticker snapshots, so that applications that alternate between polling and
+//! streaming can feed both into a single event-processing pipeline.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::events::PolygonEvent;
+//! use polygon_client::types::StockEquitiesTickerSnapshot;
+//!
+//! fn handle(event: &PolygonEvent) {
+//!     match event {
+//!         PolygonEvent::Trade(t) => println!("{} traded at {}", t.symbol, t.price),
+//!         PolygonEvent::Quote(q) => println!("{} bid {} ask {}", q.symbol, q.bid_price, q.ask_price),
+//!         PolygonEvent::MinuteBar(b) | PolygonEvent::SecondBar(b) => {
+//!             println!("{} closed at {}", b.symbol, b.close)
+//!         }
+//!         PolygonEvent::CryptoBook(b) => println!("{} book has {} bids", b.pair, b.bids.len()),
+//!     }
+//! }
+//!
+//! fn handle_snapshot(snapshot: StockEquitiesTickerSnapshot) {
+//!     for event in Vec::<PolygonEvent>::from(snapshot) {
+//!         handle(&event);
+//!     }
+//! }
+//! ```
+use serde;
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::{self, Session};
+use crate::error::{decode_json, DecodeError, Error};
+use crate::tick;
+use crate::types::StockEquitiesTickerSnapshot;
+
+/// The number of nanoseconds in a millisecond.
+const NANOS_PER_MILLI: u64 = 1_000_000;
+
+/// The length of a minute bar in milliseconds.
+const MINUTE_MILLIS: u64 = 60_000;
+
+/// A single trade.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[non_exhaustive]
+pub struct TradeEvent {
+    #[serde(rename = "sym")]
+    pub symbol: String,
+    #[serde(rename = "x")]
+    pub exchange: Option<u64>,
+    #[serde(rename = "i", default)]
+    pub id: String,
+    #[serde(rename = "z")]
+    pub tape: Option<u64>,
+    #[serde(rename = "p")]
+    pub price: f64,
+    #[serde(rename = "s")]
+    pub size: f64,
+    #[serde(rename = "c")]
+    pub conditions: Option<Vec<u64>>,
+    /// The SIP timestamp in Unix milliseconds.
+    #[serde(rename = "t")]
+    pub timestamp: u64,
+    #[serde(rename = "q")]
+    pub sequence_number: Option<u64>,
+    /// The trading session of the trade, if sessions were tagged when the
+    /// event was parsed.
+    #[serde(skip)]
+    pub session: Option<Session>,
+}
+
+impl TradeEvent {
+    /// Returns a trade with the given price and size. The remaining fields are
+    /// empty and can be set directly.
+    pub fn new(symbol: &str, price: f64, size: f64, timestamp: u64) -> Self {
+        TradeEvent {
+            symbol: String::from(symbol),
+            exchange: None,
+            id: String::new(),
+            tape: None,
+            price,
+            size,
+            conditions: None,
+            timestamp,
+            sequence_number: None,
+            session: None,
+        }
+    }
+}
+
+/// A National Best Bid and Offer quote.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[non_exhaustive]
+pub struct QuoteEvent {
+    #[serde(rename = "sym")]
+    pub symbol: String,
+    #[serde(rename = "bx")]
+    pub bid_exchange: Option<u64>,
+    #[serde(rename = "bp")]
+    pub bid_price: f64,
+    #[serde(rename = "bs")]
+    pub bid_size: f64,
+    #[serde(rename = "ax")]
+    pub ask_exchange: Option<u64>,
+    #[serde(rename = "ap")]
+    pub ask_price: f64,
+    #[serde(rename = "as")]
+    pub ask_size: f64,
+    #[serde(rename = "c")]
+    pub condition: Option<u64>,
+    /// The SIP timestamp in Unix milliseconds.
+    #[serde(rename = "t")]
+    pub timestamp: u64,
+    #[serde(rename = "q")]
+    pub sequence_number: Option<u64>,
+    #[serde(rename = "z")]
+    pub tape: Option<u64>,
+    /// The trading session of the quote, if sessions were tagged when the
+    /// event was parsed.
+    #[serde(skip)]
+    pub session: Option<Session>,
+}
+
+impl QuoteEvent {
+    /// Returns a quote with the given bid and ask. The remaining fields are
+    /// empty and can be set directly.
+    pub fn new(
+        symbol: &str,
+        bid_price: f64,
+        bid_size: f64,
+        ask_price: f64,
+        ask_size: f64,
+        timestamp: u64,
+    ) -> Self {
+        QuoteEvent {
+            symbol: String::from(symbol),
+            bid_exchange: None,
+            bid_price,
+            bid_size,
+            ask_exchange: None,
+            ask_price,
+            ask_size,
+            condition: None,
+            timestamp,
+            sequence_number: None,
+            tape: None,
+            session: None,
+        }
+    }
+
+    /// Returns the difference between the ask and bid prices.
+    pub fn spread(&self) -> f64 {
+        self.ask_price - self.bid_price
+    }
+
+    /// Returns the spread measured in ticks of the US equity tick size.
+    pub fn spread_in_ticks(&self) -> f64 {
+        tick::spread_in_ticks(self.bid_price, self.ask_price)
+    }
+
+    /// Returns the midpoint between the bid and ask prices.
+    pub fn midpoint(&self) -> f64 {
+        (self.bid_price + self.ask_price) / 2.0
+    }
+}
+
+/// An aggregate bar over a second or a minute.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[non_exhaustive]
+pub struct BarEvent {
+    #[serde(rename = "sym")]
+    pub symbol: String,
+    #[serde(rename = "v")]
+    pub volume: f64,
+    #[serde(rename = "av")]
+    pub accumulated_volume: Option<f64>,
+    #[serde(rename = "op")]
+    pub official_open: Option<f64>,
+    #[serde(rename = "vw")]
+    pub vwap: Option<f64>,
+    #[serde(rename = "o")]
+    pub open: f64,
+    #[serde(rename = "c")]
+    pub close: f64,
+    #[serde(rename = "h")]
+    pub high: f64,
+    #[serde(rename = "l")]
+    pub low: f64,
+    #[serde(rename = "a")]
+    pub average: Option<f64>,
+    /// The start of the bar in Unix milliseconds.
+    #[serde(rename = "s")]
+    pub start_timestamp: u64,
+    /// The end of the bar in Unix milliseconds.
+    #[serde(rename = "e")]
+    pub end_timestamp: u64,
+    /// The trading session at the start of the bar, if sessions were tagged
+    /// when the event was parsed.
+    #[serde(skip)]
+    pub session: Option<Session>,
+}
+
+impl BarEvent {
+    /// Returns a bar over the given interval with zero prices and volume.
+    /// Use [`BarEvent::with_ohlc()`] and [`BarEvent::with_volume()`] to fill
+    /// it in.
+    pub fn new(symbol: &str, start_timestamp: u64, end_timestamp: u64) -> Self {
+        BarEvent {
+            symbol: String::from(symbol),
+            volume: 0.0,
+            accumulated_volume: None,
+            official_open: None,
+            vwap: None,
+            open: 0.0,
+            close: 0.0,
+            high: 0.0,
+            low: 0.0,
+            average: None,
+            start_timestamp,
+            end_timestamp,
+            session: None,
+        }
+    }
+
+    /// Sets the open, high, low, and close prices.
+    pub fn with_ohlc(mut self, open: f64, high: f64, low: f64, close: f64) -> Self {
+        self.open = open;
+        self.high = high;
+        self.low = low;
+        self.close = close;
+        self
+    }
+
+    /// Sets the volume.
+    pub fn with_volume(mut self, volume: f64) -> Self {
+        self.volume = volume;
+        self
+    }
+}
+
+/// A level 2 book update for a crypto pair on a single exchange.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[non_exhaustive]
+pub struct CryptoBookEvent {
+    pub pair: String,
+    /// The bid levels as `[price, size]` pairs.
+    #[serde(rename = "b")]
+    pub bids: Vec<[f64; 2]>,
+    /// The ask levels as `[price, size]` pairs.
+    #[serde(rename = "a")]
+    pub asks: Vec<[f64; 2]>,
+    #[serde(rename = "x")]
+    pub exchange: Option<u64>,
+    /// The exchange timestamp in Unix milliseconds.
+    #[serde(rename = "t")]
+    pub timestamp: u64,
+    /// The time the update was received by polygon.io in Unix milliseconds.
+    #[serde(rename = "r")]
+    pub received_timestamp: Option<u64>,
+    /// The trading session at the time of the update, if sessions were tagged
+    /// when the event was parsed.
+    #[serde(skip)]
+    pub session: Option<Session>,
+}
+
+impl CryptoBookEvent {
+    /// Returns a book update with the given `[price, size]` levels.
+    pub fn new(
+        pair: &str,
+        exchange: Option<u64>,
+        timestamp: u64,
+        bids: Vec<[f64; 2]>,
+        asks: Vec<[f64; 2]>,
+    ) -> Self {
+        CryptoBookEvent {
+            pair: String::from(pair),
+            bids,
+            asks,
+            exchange,
+            timestamp,
+            received_timestamp: None,
+            session: None,
+        }
+    }
+}
+
+/// An event from the streaming API, or one synthesized from a REST response.
+///
+/// Streaming messages can be deserialized directly into this type, as the
+/// variant is selected by the `ev` field of each message. Events serialize
+/// back into the same form, except that the session tag is not included.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(tag = "ev")]
+pub enum PolygonEvent {
+    #[serde(rename = "T")]
+    Trade(TradeEvent),
+    #[serde(rename = "Q")]
+    Quote(QuoteEvent),
+    #[serde(rename = "AM")]
+    MinuteBar(BarEvent),
+    #[serde(rename = "A")]
+    SecondBar(BarEvent),
+    #[serde(rename = "XL2")]
+    CryptoBook(CryptoBookEvent),
+}
+
+impl PolygonEvent {
+    /// Returns the ticker symbol of the event.
+    pub fn symbol(&self) -> &str {
+        match self {
+            PolygonEvent::Trade(t) => &t.symbol,
+            PolygonEvent::Quote(q) => &q.symbol,
+            PolygonEvent::MinuteBar(b) | PolygonEvent::SecondBar(b) => &b.symbol,
+            PolygonEvent::CryptoBook(b) => &b.pair,
+        }
+    }
+
+    /// Returns the timestamp of the event in Unix milliseconds. For bars, this
+    /// is the start of the bar.
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            PolygonEvent::Trade(t) => t.timestamp,
+            PolygonEvent::Quote(q) => q.timestamp,
+            PolygonEvent::MinuteBar(b) | PolygonEvent::SecondBar(b) => b.start_timestamp,
+            PolygonEvent::CryptoBook(b) => b.timestamp,
+        }
+    }
+
+    /// Returns the trading session of the event, if it has been tagged.
+    pub fn session(&self) -> Option<Session> {
+        match self {
+            PolygonEvent::Trade(t) => t.session,
+            PolygonEvent::Quote(q) => q.session,
+            PolygonEvent::MinuteBar(b) | PolygonEvent::SecondBar(b) => b.session,
+            PolygonEvent::CryptoBook(b) => b.session,
+        }
+    }
+
+    /// Tags the event with the trading session at its timestamp.
+    pub fn tag_session(&mut self) {
+        let session = Some(calendar::session_at(self.timestamp()));
+        match self {
+            PolygonEvent::Trade(t) => t.session = session,
+            PolygonEvent::Quote(q) => q.session = session,
+            PolygonEvent::MinuteBar(b) | PolygonEvent::SecondBar(b) => b.session = session,
+            PolygonEvent::CryptoBook(b) => b.session = session,
+        }
+    }
+}
+
+/// Options that control how streaming messages are parsed by
+/// [`parse_events()`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParseOptions {
+    /// Tag each event with the trading session at its timestamp.
+    pub tag_sessions: bool,
+}
+
+/// Parses a message from the streaming API into events.
+///
+/// Each message is a JSON array of objects. Objects whose `ev` field is not a
+/// trade, quote, aggregate, or book update, such as status messages, are
+/// skipped.
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::calendar::Session;
+/// use polygon_client::events::{parse_events, ParseOptions};
+///
+/// let msg = r#"[{"ev":"status","status":"connected","message":"Connected Successfully"},
+///     {"ev":"T","sym":"MSFT","i":"1","x":4,"p":114.125,"s":100,"t":1536068000000}]"#;
+/// let options = ParseOptions { tag_sessions: true };
+/// let events = parse_events(msg, options).unwrap();
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].session(), Some(Session::Regular));
+/// ```
+pub fn parse_events(msg: &str, options: ParseOptions) -> Result<Vec<PolygonEvent>, Error> {
+    let values: Vec<serde_json::Value> = decode_json(msg.as_bytes())?;
+    let mut events = Vec::with_capacity(values.len());
+
+    for (i, value) in values.into_iter().enumerate() {
+        let ev = value
+            .get("ev")
+            .and_then(|ev| ev.as_str())
+            .unwrap_or_default();
+
+        // The event types are deserialized directly rather than through the
+        // tagged enum so that decode errors report the path of the bad field.
+        let mut event = match ev {
+            "T" => PolygonEvent::Trade(decode_event(value, i, msg)?),
+            "Q" => PolygonEvent::Quote(decode_event(value, i, msg)?),
+            "AM" => PolygonEvent::MinuteBar(decode_event(value, i, msg)?),
+            "A" => PolygonEvent::SecondBar(decode_event(value, i, msg)?),
+            "XL2" => PolygonEvent::CryptoBook(decode_event(value, i, msg)?),
+            _ => continue,
+        };
+
+        if options.tag_sessions {
+            event.tag_session();
+        }
+
+        events.push(event);
+    }
+
+    Ok(events)
+}
+
+fn decode_event<T>(value: serde_json::Value, index: usize, msg: &str) -> Result<T, DecodeError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_path_to_error::deserialize(value).map_err(|e| {
+        let path = format!("[{}].{}", index, e.path());
+        DecodeError::new(path, e.into_inner(), msg.as_bytes())
+    })
+}
+
+/// Converts a snapshot into synthetic events for its last trade, last quote,
+/// and current minute bar, in that order.
+///
+/// Snapshot trade and quote timestamps are in nanoseconds and are converted
+/// to milliseconds to match the streaming API. Parts of the snapshot that are
+/// empty, such as a last trade without a price or a minute bar without a
+/// start time, do not produce an event.
+impl From<&StockEquitiesTickerSnapshot> for Vec<PolygonEvent> {
+    fn from(snapshot: &StockEquitiesTickerSnapshot) -> Self {
+        let mut events = Vec::with_capacity(3);
+
+        let trade = &snapshot.last_trade;
+        if let (Some(price), Some(timestamp)) = (trade.price, trade.sip_timestamp) {
+            events.push(PolygonEvent::Trade(TradeEvent {
+                symbol: snapshot.ticker.clone(),
+                exchange: trade.exchange,
+                id: trade.id.clone().unwrap_or_default(),
+                tape: trade.tape,
+                price,
+                size: trade.size.unwrap_or(0.0),
+                conditions: trade.conditions.clone(),
+                timestamp: timestamp / NANOS_PER_MILLI,
+                sequence_number: trade.sequence_number,
+                session: None,
+            }));
+        }
+
+        let quote = &snapshot.last_quote;
+        if let Some(timestamp) = quote.sip_timestamp.filter(|t| *t != 0) {
+            events.push(PolygonEvent::Quote(QuoteEvent {
+                symbol: snapshot.ticker.clone(),
+                bid_exchange: quote.bid_exchange,
+                bid_price: quote.bid_price.unwrap_or(0.0),
+                bid_size: quote.bid_size.unwrap_or(0.0),
+                ask_exchange: quote.ask_exchange,
+                ask_price: quote.ask_price.unwrap_or(0.0),
+                ask_size: quote.ask_size.unwrap_or(0.0),
+                condition: None,
+                timestamp: timestamp / NANOS_PER_MILLI,
+                sequence_number: quote.sequence_number,
+                tape: quote.tape,
+                session: None,
+            }));
+        }
+
+        let bar = &snapshot.min;
+        if let Some(start) = bar.timestamp {
+            events.push(PolygonEvent::MinuteBar(BarEvent {
+                symbol: snapshot.ticker.clone(),
+                volume: bar.volume,
+                accumulated_volume: bar.accumulated_volume,
+                official_open: Some(snapshot.day.o),
+                vwap: bar.vwap,
+                open: bar.open,
+                close: bar.close,
+                high: bar.high,
+                low: bar.low,
+                average: None,
+                start_timestamp: start,
+                end_timestamp: start + MINUTE_MILLIS,
+                session: None,
+            }));
+        }
+
+        events
+    }
+}
+
+impl From<StockEquitiesTickerSnapshot> for Vec<PolygonEvent> {
+    fn from(snapshot: StockEquitiesTickerSnapshot) -> Self {
+        Vec::from(&snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::Session;
+    use crate::error::Error;
+    use crate::events::{parse_events, ParseOptions, PolygonEvent};
+    use crate::types::StockEquitiesSnapshotAllTickersResponse;
+
+    #[test]
+    fn test_snapshot_to_events() {
+        let resp: StockEquitiesSnapshotAllTickersResponse =
+            serde_json::from_str(include_str!("../fixtures/ticker_snapshot.json")).unwrap();
+        let events: Vec<PolygonEvent> = resp.tickers[0].clone().into();
+
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|e| e.symbol() == "MSFT"));
+
+        match &events[0] {
+            PolygonEvent::Trade(t) => {
+                assert_eq!(t.price, 294.9);
+                assert_eq!(t.size, 25.0);
+                assert_eq!(t.timestamp, 1633728000123);
+            }
+            e => panic!("expected a trade, got {:?}", e),
+        }
+        match &events[1] {
+            PolygonEvent::Quote(q) => {
+                assert_eq!(q.bid_price, 294.88);
+                assert_eq!(q.ask_price, 294.91);
+                assert_eq!(q.timestamp, 1633728000124);
+            }
+            e => panic!("expected a quote, got {:?}", e),
+        }
+        match &events[2] {
+            PolygonEvent::MinuteBar(b) => {
+                assert_eq!(b.close, 294.9);
+                assert_eq!(b.start_timestamp, 1633727940000);
+                assert_eq!(b.end_timestamp, 1633728000000);
+                assert_eq!(b.accumulated_volume, Some(17842313.0));
+            }
+            e => panic!("expected a minute bar, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_without_minute_bar() {
+        let mut resp: serde_json::Value =
+            serde_json::from_str(include_str!("../fixtures/ticker_snapshot.json")).unwrap();
+        resp["tickers"][0]["min"] = serde_json::json!({});
+        let resp: StockEquitiesSnapshotAllTickersResponse = serde_json::from_value(resp).unwrap();
+
+        let snapshot = &resp.tickers[0];
+        assert!(snapshot.min.timestamp.is_none());
+        let events: Vec<PolygonEvent> = snapshot.into();
+        assert!(events
+            .iter()
+            .all(|e| !matches!(e, PolygonEvent::MinuteBar(_))));
+    }
+
+    #[test]
+    fn test_deserialize_stream_events() {
+        let msg = r#"[
+            {"ev":"T","sym":"MSFT","x":4,"i":"12345","z":3,"p":114.125,"s":100,"c":[0,12],"t":1536036818784,"q":3681328},
+            {"ev":"Q","sym":"MSFT","bx":4,"bp":114.125,"bs":100,"ax":7,"ap":114.128,"as":160,"c":0,"t":1536036818784,"q":50385480,"z":3},
+            {"ev":"AM","sym":"MSFT","v":10204,"av":200304,"op":114.04,"vw":114.4040,"o":114.11,"c":114.14,"h":114.19,"l":114.09,"a":114.1314,"s":1536036818784,"e":1536036818784}
+        ]"#;
+        let events: Vec<PolygonEvent> = serde_json::from_str(msg).unwrap();
+        assert!(matches!(events[0], PolygonEvent::Trade(_)));
+        assert!(matches!(events[1], PolygonEvent::Quote(_)));
+        assert!(matches!(events[2], PolygonEvent::MinuteBar(_)));
+        assert_eq!(events[1].timestamp(), 1536036818784);
+        match &events[1] {
+            PolygonEvent::Quote(q) => assert_eq!(q.spread_in_ticks(), 0.3),
+            e => panic!("expected a quote, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_parse_events_tags_sessions() {
+        // 2024-01-03 08:00 and 10:00 Eastern time.
+        let msg = r#"[
+            {"ev":"status","status":"auth_success","message":"authenticated"},
+            {"ev":"T","sym":"MSFT","i":"1","p":370.0,"s":10,"t":1704286800000},
+            {"ev":"Q","sym":"MSFT","bp":370.0,"bs":1,"ap":370.02,"as":1,"t":1704294000000}
+        ]"#;
+
+        let events = parse_events(msg, ParseOptions::default()).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.session().is_none()));
+
+        let events = parse_events(msg, ParseOptions { tag_sessions: true }).unwrap();
+        assert_eq!(events[0].session(), Some(Session::PreMarket));
+        assert_eq!(events[1].session(), Some(Session::Regular));
+    }
+
+    #[test]
+    fn test_parse_events_reports_path() {
+        let msg = r#"[{"ev":"status"},{"ev":"T","sym":"MSFT","p":"bad","s":1,"t":0}]"#;
+        match parse_events(msg, ParseOptions::default()) {
+            Err(Error::Decode(e)) => assert_eq!(e.path, "[1].p"),
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+    }
+}
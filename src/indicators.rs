@@ -0,0 +1,76 @@
+//! Technical indicators computed over a series of values, oldest first.
+//!
+//! Each indicator returns `None` when there are not enough values to compute
+//! it, so it can be called on a window that is still filling up.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::indicators;
+//!
+//! let closes = [10.0, 11.0, 12.0, 13.0];
+//! assert_eq!(indicators::sma(&closes, 2), Some(12.5));
+//! assert_eq!(indicators::sma(&closes, 5), None);
+//! ```
+
+/// Returns the simple moving average of the last `period` values.
+pub fn sma(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+
+    let window = &values[values.len() - period..];
+    Some(window.iter().sum::<f64>() / period as f64)
+}
+
+/// Returns the exponential moving average over `period` values.
+///
+/// The average is seeded with the simple moving average of the first `period`
+/// values and then updated with each remaining value.
+pub fn ema(values: &[f64], period: usize) -> Option<f64> {
+    let seed = sma(values.get(..period)?, period)?;
+    let k = 2.0 / (period as f64 + 1.0);
+
+    Some(
+        values[period..]
+            .iter()
+            .fold(seed, |ema, value| value * k + ema * (1.0 - k)),
+    )
+}
+
+/// Returns the percentage change of the last value from the value `period`
+/// values before it.
+pub fn rate_of_change(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() <= period {
+        return None;
+    }
+
+    let last = values[values.len() - 1];
+    let base = values[values.len() - 1 - period];
+    if base == 0.0 {
+        return None;
+    }
+
+    Some((last - base) / base * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::indicators::*;
+
+    #[test]
+    fn test_indicators() {
+        let values = [2.0, 4.0, 6.0, 8.0, 10.0];
+
+        assert_eq!(sma(&values, 5), Some(6.0));
+        assert_eq!(sma(&values, 0), None);
+
+        // Seeded with (2 + 4 + 6) / 3 = 4, then k = 0.5.
+        assert_eq!(ema(&values, 3), Some(8.0));
+        assert_eq!(ema(&values, 6), None);
+
+        assert_eq!(rate_of_change(&values, 4), Some(400.0));
+        assert_eq!(rate_of_change(&values, 5), None);
+        assert_eq!(rate_of_change(&[0.0, 1.0], 1), None);
+    }
+}
@@ -1,9 +1,230 @@
 //! Client library for [polygon.io](https://www.polygon.io).
+//!
+//! # Features
+//!
+//! The REST and WebSocket clients are enabled through the `rest` and
+//! `websocket` features. REST endpoints are further split into groups so that
+//! only the endpoints that are used need to be compiled:
+//!
+//! * `reference` - reference data such as tickers, news, splits, and dividends
+//! * `stocks` - stock equities trades, quotes, aggregates, and snapshots
+//! * `forex` - forex aggregates
+//! * `crypto` - crypto exchanges and aggregates
+//! * `options` - options contracts and snapshots
+//! * `futures` - futures products, contracts, trades, and quotes
+//! * `indices` - index snapshots
+//! * `economy` - economic data such as inflation
+//! * `partners` - partner data such as Benzinga
+//! * `analytics` - higher-level helpers built on top of the endpoint groups
+//!
+//! The TLS stack used by the REST client is chosen with one of two features:
+//!
+//! * `native-tls` - the platform's TLS library, such as OpenSSL on Linux
+//! * `rustls` - [rustls](https://docs.rs/rustls), which needs no system
+//!   library, such as for static musl binaries
+//!
+//! All of the above but `rustls` are enabled by default, and `native-tls` is
+//! used if both are enabled. To use rustls, disable the default features and
+//! enable `rustls` along with the others that are needed. Without either, the
+//! REST client cannot send requests over HTTPS. The following features are
+//! not enabled by default either:
+//!
+//! * `bar-cache` - a compressed on-disk cache of aggregate bars
+//! * `chrono-accessors` - accessors that return the date and time fields of
+//!   REST responses, such as `last_updated_utc` and `ex_dividend_date`, as
+//!   `chrono::DateTime<Utc>` and `chrono::NaiveDate`. The fields themselves
+//!   stay strings rather than being typed as chrono values, because features
+//!   are shared by every crate in a build, and a feature that changed field
+//!   types would break crates that read them as strings
+//! * `display` - formatting of prices, volumes, and changes for display
+//! * `jsonl-sink` - capture of events to rotating, optionally compressed,
+//!   JSON Lines files
+//! * `metrics` - request and WebSocket accounting through the
+//!   [`metrics`](https://docs.rs/metrics) facade, see [`telemetry`]
+//! * `no-panic` - denies `panic!`, `unwrap()`, and `expect()` in the library
+//!   at build time, see [Panics](#panics)
+//!
+//! # WebAssembly
+//!
+//! The REST client also builds for `wasm32-unknown-unknown`, such as for a
+//! dashboard running in the browser, with the default features disabled and
+//! `rest` enabled along with the endpoint groups that are needed. Requests
+//! are then sent with the browser's `fetch` API, which handles TLS, proxies,
+//! and compression itself, so
+//! [`RESTClientBuilder::timeout()`](rest::RESTClientBuilder::timeout) and
+//! [`RESTClientBuilder::proxy()`](rest::RESTClientBuilder::proxy) have no
+//! effect. The WebSocket client is not available on this target.
+//!
+//! # Stability
+//!
+//! Response and event types are marked `#[non_exhaustive]` so that fields
+//! added by polygon.io can be supported without a breaking release. Their
+//! fields can be read as usual, but the types cannot be built with a struct
+//! expression outside of this crate. In tests, either deserialize them from
+//! JSON or use the constructors provided for the types most often built by
+//! hand, such as [`events::BarEvent::new()`]:
+//!
+//! ```
+//! use polygon_client::events::BarEvent;
+//!
+//! let mut bar = BarEvent::new("MSFT", 0, 60_000).with_ohlc(1.0, 2.0, 0.5, 1.5);
+//! bar.vwap = Some(1.2);
+//! ```
+//!
+//! # Panics
+//!
+//! Failures such as a missing API key, a failed connection, or a response that
+//! cannot be decoded are returned as an [`Error`]. Arguments out of range are
+//! either rejected with [`Error::Config`], such as a
+//! [`rest::RESTClientBuilder::max_in_flight()`] of zero, or rounded up to the
+//! smallest valid value, such as a window capacity of zero. The only functions
+//! that panic are the convenience constructors [`rest::RESTClient::new()`] and
+//! [`websocket::WebSocketClient::new()`], whose fallible counterparts are
+//! [`rest::RESTClient::try_new()`] and [`websocket::WebSocketClient::connect()`].
+//!
+//! The `no-panic` feature enforces this policy: when it is enabled, clippy
+//! denies `panic!`, `unwrap()`, `expect()`, and `unreachable!` anywhere else
+//! in the library. A unit test also fails if any of these, or an `assert!`,
+//! appears in library code.
+#![cfg_attr(
+    all(feature = "no-panic", not(test)),
+    deny(
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::unreachable,
+        clippy::todo,
+        clippy::unimplemented
+    )
+)]
+/// Asserts at compile time that each of the given types is `Send + Sync`.
+#[allow(unused_macros)]
+macro_rules! assert_send_sync {
+    ($($t:ty),+ $(,)?) => {
+        const _: fn() = || {
+            fn assert_send_sync<T: ?Sized + Send + Sync>() {}
+            $(assert_send_sync::<$t>();)+
+        };
+    };
+}
+
+pub mod adjust;
+pub mod aggregation;
+#[cfg(feature = "bar-cache")]
+pub mod bar_cache;
+pub mod calendar;
+pub mod depth;
+pub mod diagnostics;
+#[cfg(feature = "display")]
+pub mod display;
+pub mod error;
+pub mod events;
+pub mod indicators;
+pub mod quote_board;
+pub mod reconcile;
 #[cfg(feature = "rest")]
 pub mod rest;
+mod runtime;
+pub mod schedule;
+pub mod secret;
+pub mod signals;
+#[cfg(feature = "jsonl-sink")]
+pub mod sink;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
+pub mod tick;
+pub mod ticker;
 pub mod types;
 #[cfg(feature = "websocket")]
 pub mod websocket;
 
+pub use error::{Error, ErrorKind};
+
+/// Entry points used by the benchmarks, which are not part of the public API.
+#[doc(hidden)]
+pub mod bench {
+    use crate::error::Error;
+
+    /// Decodes a body the way every REST response is decoded.
+    pub fn decode_json<T>(body: &[u8]) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        crate::error::decode_json(body)
+    }
+}
+
 #[macro_use]
 extern crate lazy_static;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    /// Macros and methods that panic, or may panic, when called.
+    const PANICKING: &[&str] = &[
+        "panic!(",
+        "assert!(",
+        "assert_eq!(",
+        "assert_ne!(",
+        "unreachable!(",
+        "todo!(",
+        "unimplemented!(",
+        ".unwrap()",
+        ".expect(",
+    ];
+
+    /// Appends the lines of library code under `dir` that may panic to
+    /// `found`. Tests, test servers, comments, and items marked
+    /// `#[allow(clippy::panic)]` are skipped.
+    fn find_panics(dir: &Path, found: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                find_panics(&path, found);
+                continue;
+            }
+            if path.file_name().unwrap() == "test_server.rs" {
+                continue;
+            }
+
+            let source = fs::read_to_string(&path).unwrap();
+            let mut allowed_until = None;
+            for (i, line) in source.lines().enumerate() {
+                let code = line.trim_start();
+                if code == "mod tests {" {
+                    break;
+                }
+                if let Some(end) = &allowed_until {
+                    if line == end {
+                        allowed_until = None;
+                    }
+                    continue;
+                }
+                if code == "#[allow(clippy::panic)]" {
+                    let indent = &line[..line.len() - code.len()];
+                    allowed_until = Some(format!("{}}}", indent));
+                    continue;
+                }
+                if !code.starts_with("//") && PANICKING.iter().any(|p| code.contains(p)) {
+                    found.push(format!("{}:{}: {}", path.display(), i + 1, code));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_library_does_not_panic() {
+        let mut found = Vec::new();
+        find_panics(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut found,
+        );
+        assert!(
+            found.is_empty(),
+            "library code may panic:\n{}",
+            found.join("\n")
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Live prices and daily changes of a list of tickers.
+//!
+//! A [`QuoteBoard`] keeps one row per ticker with its latest price and
+//! previous close, fed by streamed trades and bars and, for tickers whose
+//! stream has gone quiet, by prices fetched from the REST API. It keeps track
+//! of where each price came from and when it was received, so that an
+//! application can tell which tickers need a REST fallback with
+//! [`QuoteBoard::stale()`], such as while the WebSocket connection is being
+//! re-established.
+//!
+//! With the `display` feature, [`QuoteBoard::render()`] formats the board as
+//! lines of text for a terminal. The `watchlist` example puts these pieces
+//! together into a live terminal watchlist.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::events::{PolygonEvent, TradeEvent};
+//! use polygon_client::quote_board::{PriceSource, QuoteBoard};
+//!
+//! let mut board = QuoteBoard::new(&["MSFT", "AAPL"]);
+//! board.set_previous_close("MSFT", 370.0);
+//! board.apply_event(&PolygonEvent::Trade(TradeEvent::new("MSFT", 373.7, 100.0, 1_000)));
+//!
+//! let msft = board.row("MSFT").unwrap();
+//! assert_eq!(msft.source, Some(PriceSource::Stream));
+//! assert!((msft.change_percent().unwrap() - 1.0).abs() < 1e-9);
+//! assert_eq!(board.row("AAPL").unwrap().price, None);
+//! ```
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[cfg(feature = "display")]
+use crate::display::DisplayFormat;
+use crate::events::PolygonEvent;
+use crate::runtime::{SystemTime, UNIX_EPOCH};
+use crate::types::LastPrice;
+
+/// Where the price of a [`QuoteRow`] came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PriceSource {
+    /// A trade or bar received over the WebSocket.
+    Stream,
+    /// The last trade or quote fetched from the REST API.
+    Rest,
+}
+
+/// The latest price of a ticker on a [`QuoteBoard`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct QuoteRow {
+    pub ticker: String,
+    /// The latest price, or `None` if no price has been received.
+    pub price: Option<f64>,
+    /// The time of the latest price in Unix milliseconds.
+    pub timestamp: Option<u64>,
+    pub source: Option<PriceSource>,
+    /// The time the latest price was received in Unix milliseconds.
+    pub received_at: Option<u64>,
+    pub previous_close: Option<f64>,
+}
+
+impl QuoteRow {
+    fn new(ticker: &str) -> Self {
+        QuoteRow {
+            ticker: String::from(ticker),
+            price: None,
+            timestamp: None,
+            source: None,
+            received_at: None,
+            previous_close: None,
+        }
+    }
+
+    /// Returns the change of the price since the previous close.
+    pub fn change(&self) -> Option<f64> {
+        Some(self.price? - self.previous_close?)
+    }
+
+    /// Returns the change of the price since the previous close as a
+    /// percentage of the previous close.
+    pub fn change_percent(&self) -> Option<f64> {
+        let previous_close = self.previous_close?;
+        if previous_close == 0.0 {
+            return None;
+        }
+        Some(self.change()? / previous_close * 100.0)
+    }
+
+    /// Records a price unless it is older than the current one, returning
+    /// whether it was recorded.
+    fn update(
+        &mut self,
+        price: f64,
+        timestamp: u64,
+        source: PriceSource,
+        received_at: u64,
+    ) -> bool {
+        if self.timestamp.is_some_and(|t| timestamp < t) {
+            return false;
+        }
+
+        self.price = Some(price);
+        self.timestamp = Some(timestamp);
+        self.source = Some(source);
+        self.received_at = Some(received_at);
+        true
+    }
+}
+
+/// The latest prices of a list of tickers, in the order they were given.
+#[derive(Clone, Debug, Default)]
+pub struct QuoteBoard {
+    rows: Vec<QuoteRow>,
+    index: HashMap<String, usize>,
+}
+
+impl QuoteBoard {
+    /// Returns a board with a row for each of `tickers`. Duplicate tickers
+    /// share a row.
+    pub fn new<S: AsRef<str>>(tickers: &[S]) -> Self {
+        let mut board = QuoteBoard::default();
+        for ticker in tickers {
+            let ticker = ticker.as_ref();
+            if !board.index.contains_key(ticker) {
+                board.index.insert(String::from(ticker), board.rows.len());
+                board.rows.push(QuoteRow::new(ticker));
+            }
+        }
+        board
+    }
+
+    /// Returns the tickers on the board.
+    pub fn tickers(&self) -> impl Iterator<Item = &str> + '_ {
+        self.rows.iter().map(|r| r.ticker.as_str())
+    }
+
+    /// Returns the rows of the board.
+    pub fn rows(&self) -> &[QuoteRow] {
+        &self.rows
+    }
+
+    /// Returns the row of a ticker.
+    pub fn row(&self, ticker: &str) -> Option<&QuoteRow> {
+        self.index.get(ticker).map(|&i| &self.rows[i])
+    }
+
+    fn row_mut(&mut self, ticker: &str) -> Option<&mut QuoteRow> {
+        let i = *self.index.get(ticker)?;
+        self.rows.get_mut(i)
+    }
+
+    /// Sets the previous close of a ticker, against which its change is
+    /// measured.
+    pub fn set_previous_close(&mut self, ticker: &str, close: f64) {
+        if let Some(row) = self.row_mut(ticker) {
+            row.previous_close = Some(close);
+        }
+    }
+
+    /// Records the price of a streamed trade or the close of a streamed bar
+    /// received now. Returns whether a row changed.
+    pub fn apply_event(&mut self, event: &PolygonEvent) -> bool {
+        self.apply_event_at(event, now_ms())
+    }
+
+    /// Records the price of a streamed trade or the close of a streamed bar
+    /// received at `received_at`, in Unix milliseconds. Quotes, books, events
+    /// of tickers that are not on the board, and prices older than a row's
+    /// current price are ignored. Returns whether a row changed.
+    pub fn apply_event_at(&mut self, event: &PolygonEvent, received_at: u64) -> bool {
+        let (price, timestamp) = match event {
+            PolygonEvent::Trade(t) => (t.price, t.timestamp),
+            PolygonEvent::MinuteBar(b) | PolygonEvent::SecondBar(b) => (b.close, b.end_timestamp),
+            PolygonEvent::Quote(_) | PolygonEvent::CryptoBook(_) => return false,
+        };
+
+        match self.row_mut(event.symbol()) {
+            Some(row) => row.update(price, timestamp, PriceSource::Stream, received_at),
+            None => false,
+        }
+    }
+
+    /// Records a price fetched from the REST API, such as with
+    /// [`RESTClient::last_price()`](crate::rest::RESTClient::last_price),
+    /// unless the row already has a later price. Returns whether a row
+    /// changed.
+    pub fn apply_last_price(&mut self, last: &LastPrice) -> bool {
+        let received_at = now_ms();
+        match self.row_mut(last.ticker()) {
+            Some(row) => row.update(
+                last.price(),
+                last.timestamp(),
+                PriceSource::Rest,
+                received_at,
+            ),
+            None => false,
+        }
+    }
+
+    /// Returns the tickers that have not received a price in the last
+    /// `max_age` as of `now`, in Unix milliseconds, including those that have
+    /// never received one.
+    pub fn stale(&self, max_age: Duration, now: u64) -> Vec<&str> {
+        let max_age = max_age.as_millis() as u64;
+        self.rows
+            .iter()
+            .filter(|r| {
+                r.received_at
+                    .is_none_or(|t| now.saturating_sub(t) > max_age)
+            })
+            .map(|r| r.ticker.as_str())
+            .collect()
+    }
+
+    /// Returns a line of text for each row with the ticker, price, change
+    /// since the previous close, and source of the price, with the columns
+    /// aligned.
+    #[cfg(feature = "display")]
+    pub fn render(&self, format: &DisplayFormat) -> Vec<String> {
+        let width = self.tickers().map(str::len).max().unwrap_or(0);
+
+        self.rows
+            .iter()
+            .map(|row| {
+                let price = row
+                    .price
+                    .map_or_else(|| String::from("-"), |p| format.price(p));
+                let change = match (row.previous_close, row.price) {
+                    (Some(from), Some(to)) => format.change(from, to),
+                    _ => String::new(),
+                };
+                let source = match row.source {
+                    Some(PriceSource::Stream) => "live",
+                    Some(PriceSource::Rest) => "rest",
+                    None => "",
+                };
+                format!(
+                    "{:<width$}  {:>10}  {:<18}  {}",
+                    row.ticker,
+                    price,
+                    change,
+                    source,
+                    width = width
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::events::{BarEvent, PolygonEvent, TradeEvent};
+    use crate::quote_board::{PriceSource, QuoteBoard};
+
+    fn trade(symbol: &str, price: f64, timestamp: u64) -> PolygonEvent {
+        PolygonEvent::Trade(TradeEvent::new(symbol, price, 100.0, timestamp))
+    }
+
+    #[test]
+    fn test_apply_event() {
+        let mut board = QuoteBoard::new(&["MSFT", "AAPL", "MSFT"]);
+        assert_eq!(board.tickers().collect::<Vec<_>>(), vec!["MSFT", "AAPL"]);
+
+        assert!(board.apply_event_at(&trade("MSFT", 2.0, 2_000), 2_100));
+        // Late trades and tickers that are not on the board are ignored.
+        assert!(!board.apply_event_at(&trade("MSFT", 1.0, 1_000), 2_200));
+        assert!(!board.apply_event_at(&trade("NVDA", 3.0, 3_000), 3_100));
+
+        let bar = BarEvent::new("AAPL", 0, 60_000).with_ohlc(1.0, 1.0, 1.0, 1.5);
+        assert!(board.apply_event_at(&PolygonEvent::MinuteBar(bar), 60_100));
+
+        let msft = board.row("MSFT").unwrap();
+        assert_eq!(msft.price, Some(2.0));
+        assert_eq!(msft.received_at, Some(2_100));
+        assert_eq!(msft.source, Some(PriceSource::Stream));
+        assert_eq!(board.row("AAPL").unwrap().timestamp, Some(60_000));
+    }
+
+    #[test]
+    fn test_change_and_stale() {
+        let mut board = QuoteBoard::new(&["MSFT", "AAPL"]);
+        board.set_previous_close("MSFT", 200.0);
+        board.apply_event_at(&trade("MSFT", 210.0, 1_000), 1_000);
+
+        let msft = board.row("MSFT").unwrap();
+        assert_eq!(msft.change(), Some(10.0));
+        assert_eq!(msft.change_percent(), Some(5.0));
+        assert_eq!(board.row("AAPL").unwrap().change(), None);
+
+        let max_age = Duration::from_secs(5);
+        assert_eq!(board.stale(max_age, 6_000), vec!["AAPL"]);
+        assert_eq!(board.stale(max_age, 6_001), vec!["MSFT", "AAPL"]);
+    }
+
+    #[cfg(feature = "display")]
+    #[test]
+    fn test_render() {
+        use crate::display::DisplayFormat;
+        use crate::types::AssetClass;
+
+        let mut board = QuoteBoard::new(&["MSFT", "GOOGL"]);
+        board.set_previous_close("MSFT", 370.0);
+        board.apply_event_at(&trade("MSFT", 371.5, 1_000), 1_000);
+
+        let lines = board.render(&DisplayFormat::for_asset_class(AssetClass::Stocks));
+        assert_eq!(
+            lines,
+            vec![
+                "MSFT       371.50  +1.50 (+0.41%)      live",
+                "GOOGL           -",
+            ]
+        );
+    }
+}
@@ -0,0 +1,360 @@
+//! End-of-day reconciliation of streamed trades against official daily bars.
+//!
+//! A [`DailyStatsAccumulator`] builds daily open, high, low, close, volume,
+//! and VWAP for each symbol from the trades received over the WebSocket. At
+//! the end of the day, the accumulated [`DailyStats`] can be compared with the
+//! official daily bar using [`compare()`], or with
+//! [`RESTClient::reconcile_daily()`](crate::rest::RESTClient::reconcile_daily)
+//! which also fetches the bar, to find trades that were missed or received
+//! twice.
+//!
+//! Every trade that is pushed is included. The consolidated daily bar excludes
+//! some trades from some of its values, such as odd lots from the high and
+//! low, so trades with such conditions should be filtered out before they are
+//! pushed if they cause discrepancies.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::events::TradeEvent;
+//! use polygon_client::reconcile::{compare, DailyStatsAccumulator, Tolerance};
+//! use polygon_client::types::StockEquitiesAggregates;
+//!
+//! let mut acc = DailyStatsAccumulator::new();
+//! // 2024-01-03 at 10:00 and 15:59 Eastern time.
+//! acc.push(&TradeEvent::new("MSFT", 370.0, 100.0, 1704294000000));
+//! acc.push(&TradeEvent::new("MSFT", 371.0, 50.0, 1704315540000));
+//!
+//! let stats = &acc.stats()[0];
+//! let official = StockEquitiesAggregates::new(1704258000000, 370.0, 371.0, 370.0, 371.0, 200.0);
+//! let discrepancies = compare(stats, &official, Tolerance::default());
+//! assert_eq!(discrepancies.len(), 1);
+//! assert_eq!(discrepancies[0].streamed, 150.0);
+//! ```
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::calendar;
+use crate::events::TradeEvent;
+use crate::types::StockEquitiesAggregates;
+
+/// Daily statistics accumulated from streamed trades.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyStats {
+    pub symbol: String,
+    /// The trading day in US Eastern time.
+    pub date: NaiveDate,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub vwap: f64,
+    /// The number of trades.
+    pub trades: u64,
+    /// The time of the first trade in Unix milliseconds.
+    pub first_timestamp: u64,
+    /// The time of the last trade in Unix milliseconds.
+    pub last_timestamp: u64,
+}
+
+impl DailyStats {
+    fn new(symbol: &str, date: NaiveDate, trade: &TradeEvent) -> Self {
+        DailyStats {
+            symbol: String::from(symbol),
+            date,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.size,
+            vwap: trade.price,
+            trades: 1,
+            first_timestamp: trade.timestamp,
+            last_timestamp: trade.timestamp,
+        }
+    }
+
+    fn push(&mut self, trade: &TradeEvent) {
+        // Trades can arrive out of order, so the open and close are taken
+        // from the earliest and latest timestamps rather than arrival order.
+        if trade.timestamp < self.first_timestamp {
+            self.open = trade.price;
+            self.first_timestamp = trade.timestamp;
+        }
+        if trade.timestamp >= self.last_timestamp {
+            self.close = trade.price;
+            self.last_timestamp = trade.timestamp;
+        }
+
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+
+        let volume = self.volume + trade.size;
+        if volume > 0.0 {
+            self.vwap = (self.vwap * self.volume + trade.price * trade.size) / volume;
+        }
+        self.volume = volume;
+        self.trades += 1;
+    }
+}
+
+/// Accumulates streamed trades into [`DailyStats`] per symbol and trading day.
+#[derive(Clone, Debug, Default)]
+pub struct DailyStatsAccumulator {
+    stats: HashMap<(String, NaiveDate), DailyStats>,
+}
+
+impl DailyStatsAccumulator {
+    /// Returns an empty accumulator.
+    pub fn new() -> Self {
+        DailyStatsAccumulator::default()
+    }
+
+    /// Adds a trade to the statistics of its symbol and trading day.
+    pub fn push(&mut self, trade: &TradeEvent) {
+        let date = match calendar::eastern_date(trade.timestamp) {
+            Some(date) => date,
+            None => return,
+        };
+
+        self.stats
+            .entry((trade.symbol.clone(), date))
+            .and_modify(|stats| stats.push(trade))
+            .or_insert_with(|| DailyStats::new(&trade.symbol, date, trade));
+    }
+
+    /// Returns the statistics of a symbol on a trading day.
+    pub fn get(&self, symbol: &str, date: NaiveDate) -> Option<&DailyStats> {
+        self.stats.get(&(String::from(symbol), date))
+    }
+
+    /// Returns the statistics of every symbol and trading day, sorted by
+    /// symbol and then by date.
+    pub fn stats(&self) -> Vec<DailyStats> {
+        let mut stats: Vec<_> = self.stats.values().cloned().collect();
+        stats.sort_by(|a, b| (&a.symbol, a.date).cmp(&(&b.symbol, b.date)));
+        stats
+    }
+
+    /// Removes the statistics of trading days before `date`, such as after
+    /// they have been reconciled.
+    pub fn clear_before(&mut self, date: NaiveDate) {
+        self.stats.retain(|(_, d), _| *d >= date);
+    }
+}
+
+/// A value of a daily bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Field {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+    Vwap,
+    Trades,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Field::Open => "open",
+            Field::High => "high",
+            Field::Low => "low",
+            Field::Close => "close",
+            Field::Volume => "volume",
+            Field::Vwap => "vwap",
+            Field::Trades => "trades",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A value that differs between the streamed statistics and the official
+/// daily bar by more than the [`Tolerance`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Discrepancy {
+    pub field: Field,
+    pub streamed: f64,
+    pub official: f64,
+}
+
+/// How far streamed statistics may differ from the official daily bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// The absolute difference allowed in the open, high, low, and close.
+    pub price: f64,
+    /// The absolute difference allowed in the VWAP.
+    pub vwap: f64,
+    /// The difference allowed in the volume and number of trades, as a
+    /// fraction of the official value.
+    pub volume: f64,
+}
+
+impl Default for Tolerance {
+    /// Allows prices to differ by less than a sub-penny tick, the VWAP by
+    /// less than a penny, and volumes not at all.
+    fn default() -> Self {
+        Tolerance {
+            price: 0.00005,
+            vwap: 0.005,
+            volume: 0.0,
+        }
+    }
+}
+
+/// Compares streamed statistics with the official daily bar and returns the
+/// values that differ by more than `tolerance`.
+///
+/// The VWAP and number of trades are only compared if the official bar
+/// includes them.
+pub fn compare(
+    stats: &DailyStats,
+    official: &StockEquitiesAggregates,
+    tolerance: Tolerance,
+) -> Vec<Discrepancy> {
+    let mut checks = vec![
+        (Field::Open, stats.open, official.o, tolerance.price),
+        (Field::High, stats.high, official.h, tolerance.price),
+        (Field::Low, stats.low, official.l, tolerance.price),
+        (Field::Close, stats.close, official.c, tolerance.price),
+        (
+            Field::Volume,
+            stats.volume,
+            official.v,
+            official.v * tolerance.volume,
+        ),
+    ];
+    if let Some(vwap) = official.vw {
+        checks.push((Field::Vwap, stats.vwap, vwap, tolerance.vwap));
+    }
+    if let Some(trades) = official.n {
+        checks.push((
+            Field::Trades,
+            stats.trades as f64,
+            trades,
+            trades * tolerance.volume,
+        ));
+    }
+
+    checks
+        .into_iter()
+        .filter(|(_, streamed, official, allowed)| (streamed - official).abs() > *allowed)
+        .map(|(field, streamed, official, _)| Discrepancy {
+            field,
+            streamed,
+            official,
+        })
+        .collect()
+}
+
+/// The result of reconciling streamed statistics with the official daily bar.
+#[derive(Clone, Debug)]
+pub struct Reconciliation {
+    pub stats: DailyStats,
+    /// The official daily bar, or `None` if there is no bar for the day.
+    pub official: Option<StockEquitiesAggregates>,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl Reconciliation {
+    /// Returns the result of comparing `stats` with `official`.
+    pub fn new(
+        stats: DailyStats,
+        official: Option<StockEquitiesAggregates>,
+        tolerance: Tolerance,
+    ) -> Self {
+        let discrepancies = match &official {
+            Some(bar) => compare(&stats, bar, tolerance),
+            None => Vec::new(),
+        };
+
+        Reconciliation {
+            stats,
+            official,
+            discrepancies,
+        }
+    }
+
+    /// Returns whether the official bar was found and matches the streamed
+    /// statistics.
+    pub fn is_consistent(&self) -> bool {
+        self.official.is_some() && self.discrepancies.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::events::TradeEvent;
+    use crate::reconcile::*;
+
+    // 2024-01-03 at 9:30, 12:00, and 16:00 Eastern time, and 2024-01-04 at
+    // 9:30.
+    const OPEN: u64 = 1704292200000;
+    const NOON: u64 = 1704301200000;
+    const CLOSE: u64 = 1704315600000;
+    const NEXT_DAY: u64 = 1704378600000;
+
+    #[test]
+    fn test_accumulate_trades() {
+        let mut acc = DailyStatsAccumulator::new();
+        // The closing trade arrives before the midday trade.
+        acc.push(&TradeEvent::new("MSFT", 100.0, 10.0, OPEN));
+        acc.push(&TradeEvent::new("MSFT", 104.0, 30.0, CLOSE));
+        acc.push(&TradeEvent::new("MSFT", 98.0, 60.0, NOON));
+        acc.push(&TradeEvent::new("MSFT", 105.0, 10.0, NEXT_DAY));
+        acc.push(&TradeEvent::new("AAPL", 180.0, 5.0, NOON));
+
+        let day = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        let stats = acc.get("MSFT", day).unwrap();
+        assert_eq!(
+            (stats.open, stats.high, stats.low, stats.close),
+            (100.0, 104.0, 98.0, 104.0)
+        );
+        assert_eq!(stats.volume, 100.0);
+        assert!((stats.vwap - 100.0).abs() < 1e-9);
+        assert_eq!(stats.trades, 3);
+
+        let all = acc.stats();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].symbol, "AAPL");
+
+        acc.clear_before(NaiveDate::from_ymd_opt(2024, 1, 4).unwrap());
+        assert_eq!(acc.stats().len(), 1);
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut acc = DailyStatsAccumulator::new();
+        acc.push(&TradeEvent::new("MSFT", 100.0, 10.0, OPEN));
+        acc.push(&TradeEvent::new("MSFT", 102.0, 10.0, CLOSE));
+        let stats = acc.stats().remove(0);
+
+        let mut official = StockEquitiesAggregates::new(OPEN, 100.0, 102.0, 99.5, 102.0, 20.0);
+        official.vw = Some(101.0);
+        official.n = Some(3.0);
+
+        let report = Reconciliation::new(stats, Some(official), Tolerance::default());
+        assert!(!report.is_consistent());
+        assert_eq!(
+            report.discrepancies,
+            vec![
+                Discrepancy {
+                    field: Field::Low,
+                    streamed: 100.0,
+                    official: 99.5,
+                },
+                Discrepancy {
+                    field: Field::Trades,
+                    streamed: 2.0,
+                    official: 3.0,
+                },
+            ]
+        );
+    }
+}
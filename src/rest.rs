@@ -4,7 +4,9 @@
 //!
 //! Use an [API key](https://polygon.io/dashboard/api-keys) to authenticate.
 //! This can be provided through the `auth_key` parameter to
-//! [`RESTClient::new()`] or through the `POLYGON_AUTH_KEY` environment variable.
+//! [`RESTClient::try_new()`] or through the `POLYGON_AUTH_KEY` environment variable.
+//! The key is sent in an `Authorization` header, or in the `apiKey` query
+//! parameter with [`AuthMode::QueryParameter`].
 //!
 //! # Example
 //!
@@ -15,7 +17,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() {
-//!     let client = RESTClient::new(None, None);
+//!     let client = RESTClient::try_new(None, None).expect("failed to create client");
 //!     let query_params = HashMap::new();
 //!     let resp = client.reference_tickers(&query_params)
 //!         .await
@@ -27,1012 +29,1387 @@
 //! ```
 use std::collections::HashMap;
 use std::env;
-
-use crate::types::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::Utc;
+use reqwest::header::{ACCEPT_ENCODING, RETRY_AFTER};
+use reqwest::StatusCode;
+use tokio::sync::Semaphore;
+
+use crate::error::{api_error, decode_json, error_in_body, Error};
+use crate::rest::budget::RequestPermit;
+use crate::rest::meta::ResponseLog;
+use crate::runtime::Instant;
+use crate::secret::{self, ApiKey};
+#[cfg(feature = "metrics")]
+use crate::telemetry;
+use crate::types::ErrorResponse;
+
+mod aggregates;
+#[cfg(feature = "analytics")]
+mod analytics;
+#[cfg(feature = "stocks")]
+mod as_of;
+mod budget;
+mod circuit;
+mod composite;
+#[cfg(feature = "crypto")]
+mod crypto;
+#[cfg(all(feature = "stocks", feature = "forex", feature = "crypto"))]
+mod diagnostics;
+#[cfg(feature = "economy")]
+mod economy;
+mod edge;
+#[cfg(feature = "forex")]
+mod forex;
+#[cfg(feature = "futures")]
+mod futures;
+#[cfg(feature = "indices")]
+mod indices;
+#[cfg(all(feature = "stocks", feature = "forex", feature = "crypto"))]
+mod last_price;
+mod limits;
+mod meta;
+#[cfg(feature = "stocks")]
+mod most_active;
+#[cfg(feature = "options")]
+mod options;
+#[cfg(all(feature = "reference", feature = "stocks"))]
+mod overview;
+mod pagination;
+#[cfg(feature = "partners")]
+mod partners;
+mod proxy;
+#[cfg(feature = "stocks")]
+mod reconcile;
+#[cfg(feature = "reference")]
+mod reference;
+#[cfg(any(feature = "reference", feature = "stocks"))]
+mod reference_cache;
+mod response_cache;
+mod retry;
+#[cfg(feature = "stocks")]
+mod slim;
+#[cfg(feature = "stocks")]
+mod stocks;
+mod streaming;
+#[cfg(test)]
+mod test_server;
+mod throttle;
+#[cfg(feature = "stocks")]
+mod today;
+
+pub use budget::PartitionBuilder;
+pub use circuit::CircuitBreakerPolicy;
+pub use composite::CompositePolicy;
+pub use edge::EdgeHeaders;
+pub use limits::{
+    LimitPolicy, AGGREGATES_MAX_LIMIT, OPTIONS_CHAIN_SNAPSHOT_MAX_LIMIT, QUOTES_MAX_LIMIT,
+    REFERENCE_STOCK_DIVIDENDS_MAX_LIMIT, REFERENCE_STOCK_FINANCIALS_VX_MAX_LIMIT,
+    REFERENCE_TICKERS_MAX_LIMIT, REFERENCE_TICKER_NEWS_MAX_LIMIT, TRADES_MAX_LIMIT,
+};
+pub use meta::{ApiResponse, ResponseMeta, IDENTITY_ENCODING};
+#[cfg(feature = "options")]
+pub use options::OptionsChainFilter;
+pub use pagination::{CollectOptions, PageCursor, Paginated};
+pub use proxy::ProxyConfig;
+#[cfg(feature = "reference")]
+pub use reference::ReferenceTickersOptions;
+#[cfg(any(feature = "reference", feature = "stocks"))]
+pub use reference_cache::{ReferenceCache, ReferenceKind};
+pub use response_cache::{EndpointClass, ResponseCache};
+pub use retry::{RateLimitPolicy, RetryPolicy};
+#[cfg(feature = "stocks")]
+pub use stocks::GroupedDailyOptions;
 
 static DEFAULT_API_URL: &str = "https://api.polygon.io";
 
-pub struct RESTClient {
-    /// The API key to use for requests.
-    pub auth_key: String,
-    /// The API URL to use for requests.
+/// How the API key is sent with each request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuthMode {
+    /// Sends the API key in an `Authorization: Bearer` header.
+    #[default]
+    BearerHeader,
+    /// Sends the API key in the `apiKey` query parameter, for proxies that
+    /// strip the `Authorization` header.
     ///
-    /// The default API URL is <https://api.polygon.io>.
-    pub api_url: String,
-    client: reqwest::Client,
+    /// The API key then appears in request URLs, which proxies and servers
+    /// often log. It is removed from the URLs of the errors returned by the
+    /// client.
+    QueryParameter,
 }
 
-impl RESTClient {
-    /// Returns a new REST client.
-    ///
-    /// The `auth_key` parameter optionally provides the API key to use for
-    /// authentication. If `None` is provided, then the API key specified in the
-    /// `POLYGON_AUTH_KEY` environment variable is used.
-    ///
-    /// The `timeout` parameter optionally provides the duration to wait for a
-    /// response to a request.
-    ///
-    /// # Panics
-    ///
-    /// This function will panic if `auth_key` is `None` and the
-    /// `POLYGON_AUTH_KEY` environment variable is not set.
-    pub fn new(auth_key: Option<&str>, timeout: Option<core::time::Duration>) -> Self {
-        let api_url = match env::var("POLYGON_API_URL") {
-            Ok(v) => v,
-            _ => String::from(DEFAULT_API_URL),
-        };
+/// A client for the polygon.io REST APIs.
+///
+/// Cloning a `RESTClient` is cheap: all of its state lives behind an [`Arc`],
+/// so clones share the same connection pool and configuration, as well as any
+/// other state kept by the client such as rate limiting and caching. A single
+/// client can therefore be created once and cloned into each task or request
+/// handler instead of being wrapped in an `Arc<RESTClient>`.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::collections::HashMap;
+///
+/// use polygon_client::rest::RESTClient;
+/// use polygon_client::ticker::StockTicker;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+///     let handles: Vec<_> = ["MSFT", "AAPL"]
+///         .iter()
+///         .map(|ticker| {
+///             let ticker = StockTicker::new(ticker).unwrap();
+///             let client = client.clone();
+///             tokio::spawn(async move {
+///                 client
+///                     .stock_equities_previous_close(ticker, &HashMap::new())
+///                     .await
+///             })
+///         })
+///         .collect();
+///     for handle in handles {
+///         println!("{:?}", handle.await.unwrap().map(|r| r.results));
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RESTClient {
+    inner: Arc<ClientInner>,
+    partition: Option<Arc<budget::Partition>>,
+    edge_headers: Option<Arc<EdgeHeaders>>,
+}
 
-        let auth_key_actual = match auth_key {
-            Some(v) => String::from(v),
-            _ => match env::var("POLYGON_AUTH_KEY") {
-                Ok(v) => v,
-                _ => panic!("POLYGON_AUTH_KEY not set"),
-            },
-        };
+struct ClientInner {
+    auth_key: ApiKey,
+    api_url: String,
+    client: reqwest::Client,
+    rate_limit_policy: RateLimitPolicy,
+    limit_policy: LimitPolicy,
+    decode_offload_threshold: Option<usize>,
+    max_in_flight: Option<Arc<Semaphore>>,
+    #[cfg(any(feature = "reference", feature = "stocks"))]
+    reference_cache: ReferenceCache,
+    force_identity_encoding: bool,
+    responses: ResponseLog,
+    #[cfg(all(feature = "reference", feature = "stocks"))]
+    composite_policy: CompositePolicy,
+    retry_rate_limited: bool,
+    retry_policy: RetryPolicy,
+    auth_mode: AuthMode,
+    throttle: Option<throttle::Throttle>,
+    response_cache: ResponseCache,
+    edge_headers: Option<EdgeHeaders>,
+    circuit_breaker: Option<circuit::CircuitBreaker>,
+}
 
-        let mut client = reqwest::ClientBuilder::new();
+// The client is commonly shared across tasks and embedded in services that
+// require `Send + Sync` state, so ensure this holds at compile time.
+assert_send_sync!(
+    RESTClient,
+    RESTClientBuilder,
+    RateLimitPolicy,
+    RetryPolicy,
+    CircuitBreakerPolicy,
+    LimitPolicy,
+    CompositePolicy,
+    Error
+);
+
+/// A builder used to configure a [`RESTClient`].
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use polygon_client::rest::{RESTClient, RateLimitPolicy};
+///
+/// let client = RESTClient::builder()
+///     .auth_key("my-api-key")
+///     .timeout(Duration::from_secs(10))
+///     .rate_limit_policy(RateLimitPolicy::new(3, Duration::from_secs(30)))
+///     .build()
+///     .expect("failed to build client");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RESTClientBuilder {
+    auth_key: Option<ApiKey>,
+    api_url: Option<String>,
+    timeout: Option<Duration>,
+    rate_limit_policy: RateLimitPolicy,
+    limit_policy: LimitPolicy,
+    decode_offload_threshold: Option<usize>,
+    max_in_flight: Option<usize>,
+    force_identity_encoding: bool,
+    composite_policy: CompositePolicy,
+    retry_rate_limited: bool,
+    retry_policy: RetryPolicy,
+    proxy: Option<ProxyConfig>,
+    http_client: Option<reqwest::Client>,
+    auth_mode: AuthMode,
+    throttle: Option<(u32, Duration)>,
+    cache_responses: bool,
+    no_compression: bool,
+    edge_headers: Option<EdgeHeaders>,
+    circuit_breaker: Option<CircuitBreakerPolicy>,
+}
 
-        if let Some(timeout) = timeout {
-            client = client.timeout(timeout);
-        }
+impl RESTClientBuilder {
+    /// Sets the API key to use for authentication.
+    ///
+    /// If no API key is provided, then the API key specified in the
+    /// `POLYGON_AUTH_KEY` environment variable is used.
+    pub fn auth_key(mut self, auth_key: &str) -> Self {
+        self.auth_key = Some(ApiKey::from(auth_key));
+        self
+    }
 
-        RESTClient {
-            auth_key: auth_key_actual,
-            api_url,
-            client: client.build().unwrap(),
-        }
+    /// Sets the API URL to send requests to, such as that of a mock server
+    /// or a staging environment.
+    ///
+    /// If no API URL is provided, then the URL specified in the
+    /// `POLYGON_API_URL` environment variable is used, or else
+    /// <https://api.polygon.io>.
+    pub fn api_url(mut self, api_url: &str) -> Self {
+        self.api_url = Some(String::from(api_url.trim_end_matches('/')));
+        self
     }
 
-    async fn send_request<RespType>(
-        &self,
-        uri: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<RespType, reqwest::Error>
-    where
-        RespType: serde::de::DeserializeOwned,
-    {
-        let res = self
-            .client
-            .get(format!("{}{}", self.api_url, uri))
-            .bearer_auth(&self.auth_key)
-            .query(query_params)
-            .send()
-            .await;
+    /// Sets how the API key is sent with each request. By default, it is sent
+    /// in an `Authorization: Bearer` header.
+    pub fn auth_mode(mut self, mode: AuthMode) -> Self {
+        self.auth_mode = mode;
+        self
+    }
 
-        match res {
-            Ok(res) => {
-                if res.status() == 200 {
-                    res.json::<RespType>().await
-                } else {
-                    Err(res.error_for_status().err().unwrap())
-                }
-            }
-            Err(e) => Err(e),
-        }
+    /// Sends the given Launchpad headers with every request. Use
+    /// [`RESTClient::with_edge_headers()`] to send the headers of another
+    /// user.
+    pub fn edge_headers(mut self, headers: EdgeHeaders) -> Self {
+        self.edge_headers = Some(headers);
+        self
     }
 
-    //
-    // Reference APIs
-    //
+    /// Sets the duration to wait for a response to a request.
+    ///
+    /// Ignored on `wasm32`, where the browser decides how long to wait.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 
-    /// Query all ticker symbols supported by polygon.io using the
-    /// [/v3/reference/tickers](https://polygon.io/docs/get_v3_reference_tickers_anchor)
-    /// API.
-    pub async fn reference_tickers(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceTickersResponse, reqwest::Error> {
-        self.send_request::<ReferenceTickersResponse>("/v3/reference/tickers", query_params)
-            .await
+    /// Sends every request through an explicit proxy instead of the proxy
+    /// given by the `HTTP_PROXY`, `HTTPS_PROXY`, and `NO_PROXY` environment
+    /// variables.
+    ///
+    /// Ignored on `wasm32`, where requests use the browser's proxy settings.
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
     }
 
-    /// Get a mapping of ticker types to their descriptive names using the
-    /// [/v2/reference/types](https://polygon.io/docs/get_v2_reference_types_anchor)
-    /// API.
-    pub async fn reference_ticker_types(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceTickerTypesResponse, reqwest::Error> {
-        self.send_request::<ReferenceTickerTypesResponse>("/v2/reference/types", query_params)
-            .await
+    /// Sends requests with a pre-configured HTTP client, such as one that
+    /// shares its connection pool with the rest of the application or that
+    /// uses a custom DNS resolver or client certificates.
+    ///
+    /// The client's own configuration is used as is, so the
+    /// [`timeout()`](Self::timeout) and [`proxy()`](Self::proxy) of this
+    /// builder are ignored.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
     }
 
-    /// Get details for a ticker symbol's company/entity using the
-    /// [/v1/meta/symbols/{stocks_ticker}/company](https://polygon.io/docs/get_v1_meta_symbols__stocksTicker__company_anchor)
-    /// API.
-    pub async fn reference_ticker_details(
-        &self,
-        stocks_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceTickerDetailsResponse, reqwest::Error> {
-        let uri = format!("/v1/meta/symbols/{}/company", stocks_ticker);
-        self.send_request::<ReferenceTickerDetailsResponse>(&uri, query_params)
-            .await
+    /// Sets the policy used when the server rate limits a request.
+    pub fn rate_limit_policy(mut self, policy: RateLimitPolicy) -> Self {
+        self.rate_limit_policy = policy;
+        self
     }
 
-    /// Get details for a ticker symbol's company/entity using the
-    /// [/vX/reference/tickers/{stocks_ticker}](https://polygon.io/docs/get_vX_reference_tickers__ticker__anchor)
-    /// API.
-    pub async fn reference_ticker_details_vx(
-        &self,
-        stocks_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceTickerDetailsResponseVX, reqwest::Error> {
-        let uri = format!("/vX/reference/tickers/{}", stocks_ticker);
-        self.send_request::<ReferenceTickerDetailsResponseVX>(&uri, query_params)
-            .await
+    /// Sets the policy used when a request fails with a transient error, such
+    /// as a `503 Service Unavailable` response or a dropped connection.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
     }
 
-    /// Get the most recent news articles related to a stock ticker symbol using
-    /// the [/v2/reference/news](https://polygon.io/docs/get_v2_reference_news_anchor) API.
-    pub async fn reference_ticker_news(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceTickerNewsResponse, reqwest::Error> {
-        self.send_request::<ReferenceTickerNewsResponse>("/v2/reference/news", query_params)
-            .await
+    /// Paces requests so that the client and its clones send at most
+    /// `requests` requests per `window`, such as 5 per minute for a free API
+    /// key. Requests beyond the budget wait instead of being rate limited by
+    /// the server.
+    ///
+    /// Retries of failed requests count against the budget as well. By
+    /// default, requests are not paced.
+    ///
+    /// [`build()`](Self::build) returns [`Error::Config`] if `requests` is
+    /// zero or `window` is empty.
+    pub fn throttle(mut self, requests: u32, window: Duration) -> Self {
+        self.throttle = Some((requests, window));
+        self
     }
 
-    /// Get a list of markets that are currently supported by polygon.io using
-    /// the [/v2/reference/markets](https://polygon.io/docs/get_v2_reference_markets_anchor) API.
-    pub async fn reference_markets(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceMarketsResponse, reqwest::Error> {
-        self.send_request::<ReferenceMarketsResponse>("/v2/reference/markets", query_params)
-            .await
+    /// Keeps successful responses in the client's [`ResponseCache`] and
+    /// answers repeated requests from it, with the default time to live of
+    /// each [`EndpointClass`]. By default, no response is kept.
+    pub fn cache_responses(mut self, cache: bool) -> Self {
+        self.cache_responses = cache;
+        self
     }
 
-    /// Get a list of locales currently supported by polygon.io using the
-    /// [/v2/reference/locales](https://polygon.io/docs/get_v2_reference_locales_anchor) API.
-    pub async fn reference_locales(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceLocalesResponse, reqwest::Error> {
-        self.send_request::<ReferenceLocalesResponse>("/v2/reference/locales", query_params)
-            .await
+    /// Fails requests right away, with [`Error::CircuitOpen`], for a while
+    /// after the API has failed repeatedly. By default, every request is
+    /// sent.
+    pub fn circuit_breaker(mut self, policy: CircuitBreakerPolicy) -> Self {
+        self.circuit_breaker = Some(policy);
+        self
     }
 
-    /// Get a list of historical stock splits for a ticker symbol using the
-    /// [/v2/reference/splits/{stocks_ticker}](https://polygon.io/docs/get_v2_reference_splits__stocksTicker__anchor) API.
-    pub async fn reference_stock_splits(
-        &self,
-        stocks_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceStockSplitsResponse, reqwest::Error> {
-        let uri = format!("/v2/reference/splits/{}", stocks_ticker);
-        self.send_request::<ReferenceStockSplitsResponse>(&uri, query_params)
-            .await
+    /// Retries every rate limited request according to the
+    /// [`RateLimitPolicy`], waiting for the duration given by the
+    /// `Retry-After` header of the response.
+    ///
+    /// By default, only the requests made on behalf of paginated streams and
+    /// helpers that send several requests are retried, and other requests
+    /// return [`Error::RateLimited`] right away.
+    pub fn retry_rate_limited(mut self, retry: bool) -> Self {
+        self.retry_rate_limited = retry;
+        self
     }
 
-    /// Get a list of historical dividends for a stock using the
-    /// [/v2/reference/dividends/{stocks_ticker}](https://polygon.io/docs/get_v2_reference_dividends__stocksTicker__anchor) API.
-    pub async fn reference_stock_dividends(
-        &self,
-        stocks_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceStockDividendsResponse, reqwest::Error> {
-        let uri = format!("/v2/reference/dividends/{}", stocks_ticker);
-        self.send_request::<ReferenceStockDividendsResponse>(&uri, query_params)
-            .await
+    /// Sets what to do when the `limit` query parameter of a request exceeds
+    /// the maximum supported by the endpoint, such as
+    /// [`AGGREGATES_MAX_LIMIT`].
+    pub fn limit_policy(mut self, policy: LimitPolicy) -> Self {
+        self.limit_policy = policy;
+        self
     }
 
-    /// Get historical financial data for a stock ticker using the
-    /// [/v2/reference/financials/{stocks_ticker}](https://polygon.io/docs/get_v2_reference_financials__stocksTicker__anchor) API.
-    pub async fn reference_stock_financials(
-        &self,
-        stocks_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceStockFinancialsResponse, reqwest::Error> {
-        let uri = format!("/v2/reference/financials/{}", stocks_ticker);
-        self.send_request::<ReferenceStockFinancialsResponse>(&uri, query_params)
-            .await
+    /// Deserializes response bodies larger than `bytes` on the blocking
+    /// thread pool instead of the async runtime thread.
+    ///
+    /// Large responses, such as grouped daily bars or pages of trades, can
+    /// take long enough to deserialize that other tasks on the same runtime
+    /// thread see latency spikes. Offloading has a small fixed cost, so it is
+    /// only worthwhile for bodies of at least a few hundred kilobytes. By
+    /// default, bodies are always deserialized on the runtime thread.
+    pub fn decode_offload_threshold(mut self, bytes: usize) -> Self {
+        self.decode_offload_threshold = Some(bytes);
+        self
+    }
+
+    /// Limits the number of requests that the client and its clones have in
+    /// flight at once. Further requests wait until an earlier request
+    /// completes. By default, the number of requests in flight is unlimited.
+    ///
+    /// Use [`RESTClient::partition()`] to cap the share of this limit used by
+    /// a particular subsystem.
+    ///
+    /// [`build()`](Self::build) returns [`Error::Config`] if `max_in_flight`
+    /// is zero.
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
     }
 
-    /// Get historical financial data for a stock ticker using the
-    /// [/vX/reference/financials](https://polygon.io/docs/get_vX_reference_financials_anchor) API.
-    pub async fn reference_stock_financials_vx(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceStockFinancialsVXResponse, reqwest::Error> {
-        self.send_request::<ReferenceStockFinancialsVXResponse>(
-            "/vX/reference/financials",
-            query_params,
-        )
-        .await
+    /// Asks for response bodies not to be compressed by sending
+    /// `Accept-Encoding: identity` with every request.
+    ///
+    /// Use this when a proxy mangles compressed responses. The encoding of
+    /// each response can be checked with
+    /// [`RESTClient::last_response_meta()`] and
+    /// [`RESTClient::content_encoding_counts()`].
+    pub fn force_identity_encoding(mut self, force: bool) -> Self {
+        self.force_identity_encoding = force;
+        self
     }
 
-    /// Get upcoming market holidays and their open/close items using the
-    /// [/v1/marketstatus/upcoming](https://polygon.io/docs/get_v1_marketstatus_upcoming_anchor) API.
-    pub async fn reference_market_holidays(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceMarketStatusUpcomingResponse, reqwest::Error> {
-        self.send_request::<ReferenceMarketStatusUpcomingResponse>(
-            "/v1/marketstatus/upcoming",
-            query_params,
-        )
-        .await
+    /// Whether to ask for compressed responses and decompress them, which is
+    /// enabled by default when the `gzip` or `brotli` features are enabled.
+    ///
+    /// Large responses such as grouped daily bars and snapshots compress
+    /// well, so this mostly matters on slow links. Like
+    /// [`timeout()`](Self::timeout), this does not apply to a client given to
+    /// [`http_client()`](Self::http_client).
+    pub fn compression(mut self, enabled: bool) -> Self {
+        self.no_compression = !enabled;
+        self
     }
 
-    /// Get the current trading status of the exchanges and overall financial
-    /// markets using the [/v1/marketstatus/now](https://polygon.io/docs/get_v1_marketstatus_now_anchor) API.
-    pub async fn reference_market_status(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ReferenceMarketStatusNowResponse, reqwest::Error> {
-        self.send_request::<ReferenceMarketStatusNowResponse>("/v1/marketstatus/now", query_params)
-            .await
+    /// Sets what helpers that combine several endpoints, such as
+    /// [`RESTClient::ticker_overview()`], do when one of their requests
+    /// fails. By default, they return the error.
+    pub fn composite_policy(mut self, policy: CompositePolicy) -> Self {
+        self.composite_policy = policy;
+        self
     }
 
-    //
-    // Stock equities APIs
-    //
+    /// Builds the REST client.
+    ///
+    /// Returns [`Error::MissingAuthKey`] if no API key was provided and the
+    /// `POLYGON_AUTH_KEY` environment variable is not set, and
+    /// [`Error::Config`] if an option is out of range.
+    pub fn build(self) -> Result<RESTClient, Error> {
+        if self.max_in_flight == Some(0) {
+            return Err(Error::Config(String::from(
+                "max_in_flight must be greater than zero",
+            )));
+        }
+        if let Some((requests, window)) = self.throttle {
+            if requests == 0 || window.is_zero() {
+                return Err(Error::Config(String::from(
+                    "throttle must allow at least one request per non-empty window",
+                )));
+            }
+        }
 
-    /// Get a list of stock exchanges which are supported by polygon.io using
-    /// the [/v1/meta/exchanges](https://polygon.io/docs/get_v1_meta_exchanges_anchor) API.
-    pub async fn stock_equities_exchanges(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesExchangesResponse, reqwest::Error> {
-        self.send_request::<StockEquitiesExchangesResponse>("/v1/meta/exchanges", query_params)
-            .await
+        let api_url = match self.api_url {
+            Some(v) => v,
+            _ => match env::var("POLYGON_API_URL") {
+                Ok(v) => v,
+                _ => String::from(DEFAULT_API_URL),
+            },
+        };
+
+        let auth_key = match self.auth_key {
+            Some(v) => v,
+            _ => match env::var("POLYGON_AUTH_KEY") {
+                Ok(v) => ApiKey::from(v),
+                _ => return Err(Error::MissingAuthKey),
+            },
+        };
+
+        let client = match self.http_client {
+            Some(client) => client,
+            None => configure_client(
+                reqwest::ClientBuilder::new(),
+                self.timeout,
+                self.proxy.as_ref(),
+                self.no_compression,
+            )?
+            .build()?,
+        };
+
+        Ok(RESTClient {
+            inner: Arc::new(ClientInner {
+                auth_key,
+                api_url,
+                client,
+                rate_limit_policy: self.rate_limit_policy,
+                limit_policy: self.limit_policy,
+                decode_offload_threshold: self.decode_offload_threshold,
+                max_in_flight: self.max_in_flight.map(|n| Arc::new(Semaphore::new(n))),
+                #[cfg(any(feature = "reference", feature = "stocks"))]
+                reference_cache: ReferenceCache::default(),
+                force_identity_encoding: self.force_identity_encoding,
+                responses: ResponseLog::default(),
+                #[cfg(all(feature = "reference", feature = "stocks"))]
+                composite_policy: self.composite_policy,
+                retry_rate_limited: self.retry_rate_limited,
+                retry_policy: self.retry_policy,
+                auth_mode: self.auth_mode,
+                throttle: self
+                    .throttle
+                    .map(|(requests, window)| throttle::Throttle::new(requests, window)),
+                response_cache: ResponseCache::new(self.cache_responses),
+                edge_headers: self.edge_headers,
+                circuit_breaker: self.circuit_breaker.map(circuit::CircuitBreaker::new),
+            }),
+            partition: None,
+            edge_headers: None,
+        })
     }
+}
 
-    /// Get a unified numerical mapping for conditions on trades and quotes
-    /// using the [/v1/meta/conditions/{tick_type}](https://polygon.io/docs/get_v1_meta_conditions__ticktype__anchor) API.
-    pub async fn stock_equities_condition_mappings(
-        &self,
-        tick_type: TickType,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesConditionMappingsResponse, reqwest::Error> {
-        let uri = format!(
-            "/v1/meta/conditions/{}",
-            tick_type.to_string().to_lowercase()
-        );
-        self.send_request::<StockEquitiesConditionMappingsResponse>(&uri, query_params)
-            .await
+/// Applies the options of a [`RESTClientBuilder`] to the underlying HTTP
+/// client.
+#[cfg(not(target_arch = "wasm32"))]
+fn configure_client(
+    mut client: reqwest::ClientBuilder,
+    timeout: Option<Duration>,
+    proxy: Option<&ProxyConfig>,
+    no_compression: bool,
+) -> Result<reqwest::ClientBuilder, Error> {
+    if let Some(timeout) = timeout {
+        client = client.timeout(timeout);
     }
 
-    /// Get the most recent trade for a given stock using the
-    /// [/v2/last/trade/{stocks_ticker}](https://polygon.io/docs/get_v2_last_trade__stocksTicker__anchor) API.
-    pub async fn stock_equities_historic_trades(
-        &self,
-        stocks_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesHistoricTradesResponse, reqwest::Error> {
-        let uri = format!("/v2/last/trade/{}", stocks_ticker);
-        self.send_request::<StockEquitiesHistoricTradesResponse>(&uri, query_params)
-            .await
+    if let Some(proxy) = proxy {
+        client = client.no_proxy().proxy(proxy.to_proxy()?);
     }
 
-    /// Get the most recent NBBO quote tick for a given stock using the
-    /// [/v2/last/nbbo/{stocks_ticker}](https://polygon.io/docs/get_v2_last_nbbo__stocksTicker__anchor) API.
-    pub async fn stock_equities_last_quote_for_a_symbol(
-        &self,
-        stocks_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesLastQuoteForASymbolResponse, reqwest::Error> {
-        let uri = format!("/v2/last/nbbo/{}", stocks_ticker);
-        self.send_request::<StockEquitiesLastQuoteForASymbolResponse>(&uri, query_params)
-            .await
+    if no_compression {
+        client = client.no_gzip().no_brotli();
     }
 
-    /// Get the open, close, and afterhours prices of a stock symbol on a
-    /// certain date using the [/v1/open-close/{stocks_ticker}/{date}](https://polygon.io/docs/get_v1_open-close__stocksTicker___date__anchor) API.
-    pub async fn stock_equities_daily_open_close(
-        &self,
-        stocks_ticker: &str,
-        date: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesDailyOpenCloseResponse, reqwest::Error> {
-        let uri = format!("/v1/open-close/{}/{}", stocks_ticker, date);
-        self.send_request::<StockEquitiesDailyOpenCloseResponse>(&uri, query_params)
-            .await
+    Ok(client)
+}
+
+/// Applies the options of a [`RESTClientBuilder`] to the underlying HTTP
+/// client. In the browser, timeouts, proxies, and compression are up to
+/// `fetch`, so there are none to apply.
+#[cfg(target_arch = "wasm32")]
+fn configure_client(
+    client: reqwest::ClientBuilder,
+    _timeout: Option<Duration>,
+    _proxy: Option<&ProxyConfig>,
+    _no_compression: bool,
+) -> Result<reqwest::ClientBuilder, Error> {
+    Ok(client)
+}
+
+impl RESTClient {
+    /// Returns a new REST client.
+    ///
+    /// The `auth_key` parameter optionally provides the API key to use for
+    /// authentication. If `None` is provided, then the API key specified in the
+    /// `POLYGON_AUTH_KEY` environment variable is used.
+    ///
+    /// The `timeout` parameter optionally provides the duration to wait for a
+    /// response to a request.
+    ///
+    /// Use [`RESTClient::try_new()`] to handle a missing API key without
+    /// panicking, or [`RESTClient::builder()`] for additional configuration.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `auth_key` is `None` and the
+    /// `POLYGON_AUTH_KEY` environment variable is not set.
+    #[allow(clippy::panic)]
+    pub fn new(auth_key: Option<&str>, timeout: Option<core::time::Duration>) -> Self {
+        match RESTClient::try_new(auth_key, timeout) {
+            Ok(client) => client,
+            Err(Error::MissingAuthKey) => panic!("POLYGON_AUTH_KEY not set"),
+            Err(e) => panic!("failed to build client: {}", e),
+        }
     }
 
-    /// Get aggregate bars for a stock over a given date range in custom time
-    /// window sizes using the [/v2/aggs/ticker/{stocks_ticker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__stocksTicker__range__multiplier___timespan___from___to__anchor) API.
-    pub async fn stock_equities_aggregates(
-        &self,
-        stocks_ticker: &str,
-        multiplier: u32,
-        timespan: &str,
-        from: &str,
-        to: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesAggregatesResponse, reqwest::Error> {
-        let uri = format!(
-            "/v2/aggs/ticker/{}/range/{}/{}/{}/{}",
-            stocks_ticker, multiplier, timespan, from, to
-        );
-        self.send_request::<StockEquitiesAggregatesResponse>(&uri, query_params)
-            .await
+    /// Returns a new REST client, like [`RESTClient::new()`], or an error
+    /// instead of panicking.
+    ///
+    /// Returns [`Error::MissingAuthKey`] if `auth_key` is `None` and the
+    /// `POLYGON_AUTH_KEY` environment variable is not set.
+    pub fn try_new(
+        auth_key: Option<&str>,
+        timeout: Option<core::time::Duration>,
+    ) -> Result<Self, Error> {
+        let mut builder = RESTClient::builder();
+
+        if let Some(auth_key) = auth_key {
+            builder = builder.auth_key(auth_key);
+        }
+
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        builder.build()
     }
 
-    /// Get the daily open, high, low, and close for the entire stocks and
-    /// equities market using the [/v2/aggs/grouped/locale/{locale}/market/{market}/{date}](https://polygon.io/docs/get_v2_aggs_grouped_locale_us_market_stocks__date__anchor) API.
-    pub async fn stock_equities_grouped_daily(
-        &self,
-        locale: &str,
-        market: &str,
-        date: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesGroupedDailyResponse, reqwest::Error> {
-        let uri = format!(
-            "/v2/aggs/grouped/locale/{}/market/{}/{}",
-            locale, market, date
-        );
-        self.send_request::<StockEquitiesGroupedDailyResponse>(&uri, query_params)
-            .await
+    /// Returns a builder used to configure a new REST client.
+    pub fn builder() -> RESTClientBuilder {
+        RESTClientBuilder::default()
     }
 
-    /// Get the previous day's open, high, low, and close for the specified
-    /// stock ticker using the [/v2/aggs/ticker/{stocks_ticker}/prev](https://polygon.io/docs/get_v2_aggs_ticker__stocksTicker__prev_anchor) API.
-    pub async fn stock_equities_previous_close(
-        &self,
-        stocks_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesPreviousCloseResponse, reqwest::Error> {
-        let uri = format!("/v2/aggs/ticker/{}/prev", stocks_ticker);
-        self.send_request::<StockEquitiesPreviousCloseResponse>(&uri, query_params)
-            .await
+    /// Returns the API key used for requests, which is redacted when
+    /// formatted.
+    pub fn auth_key(&self) -> &ApiKey {
+        &self.inner.auth_key
     }
 
-    /// Get the current minute, day, and previous day's aggregate, as well as
-    /// the last trade and quote for all traded stock symbols using the [/v2/snapshot/locale/{locale}/markets/{market}/tickers](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks_tickers_anchor) API.
-    pub async fn stock_equities_snapshot_all_tickers(
+    /// Returns the API URL used for requests.
+    ///
+    /// The default API URL is <https://api.polygon.io>.
+    pub fn api_url(&self) -> &str {
+        &self.inner.api_url
+    }
+
+    /// Returns the cache of reference data used by the enrichment helpers,
+    /// which is shared with the clones and partitions of this client.
+    #[cfg(any(feature = "reference", feature = "stocks"))]
+    pub fn reference_cache(&self) -> &ReferenceCache {
+        &self.inner.reference_cache
+    }
+
+    /// Returns the cache of responses, which is shared with the clones and
+    /// partitions of this client.
+    pub fn response_cache(&self) -> &ResponseCache {
+        &self.inner.response_cache
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
+    async fn send_request<RespType>(
         &self,
-        locale: &str,
+        uri: &str,
         query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesSnapshotAllTickersResponse, reqwest::Error> {
-        let uri = format!("/v2/snapshot/locale/{}/markets/stocks/tickers", locale);
-        self.send_request::<StockEquitiesSnapshotAllTickersResponse>(&uri, query_params)
+    ) -> Result<RespType, Error>
+    where
+        RespType: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let url = format!("{}{}", self.inner.api_url, uri);
+        self.send_url_with_opt_in_retry::<RespType, _>(&url, query_params)
             .await
     }
 
-    /// Get the current minute, day, and previous day's aggregate, as well as
-    /// the last trade and quote for a single traded stock ticker using the [/v2/snapshot/locale/{locale}/markets/stocks/tickers/{ticker}](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks_tickers__stocksTicker__anchor) API.
-    pub async fn stock_equities_snapshot_single_ticker(
+    /// Sends a request to a path of the API and returns the body of the
+    /// response as it was received, without deserializing it.
+    ///
+    /// This is an escape hatch for storing raw payloads or reading fields
+    /// that the response types do not cover yet. The request is
+    /// authenticated, paced, and retried like those of the typed methods, and
+    /// a response with an error status is still returned as an [`Error`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use polygon_client::rest::RESTClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let body = client
+    ///         .send_raw("/v3/reference/tickers/MSFT", &HashMap::new())
+    ///         .await
+    ///         .expect("failed to query ticker details");
+    ///     std::fs::write("msft.json", &body).expect("failed to write body");
+    /// }
+    /// ```
+    pub async fn send_raw(
         &self,
-        locale: &str,
-        ticker: &str,
+        path: &str,
         query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesSnapshotAllTickersResponse, reqwest::Error> {
-        let uri = format!(
-            "/v2/snapshot/locale/{}/markets/stocks/tickers/{}",
-            locale, ticker
-        );
-        self.send_request::<StockEquitiesSnapshotAllTickersResponse>(&uri, query_params)
-            .await
+    ) -> Result<Bytes, Error> {
+        let url = format!("{}{}", self.inner.api_url, path);
+        let send = || self.retry_transient(&url, || self.send_url_once_raw(&url, query_params));
+        let res = if self.inner.retry_rate_limited {
+            self.retry_rate_limited(send).await?
+        } else {
+            send().await?
+        };
+        Ok(res.body)
     }
 
-    /// Get the current top 20 gainers or losers of the day in the
-    /// stocks/equities markets using the [/v2/snapshot/locale/{locale}/markets/stocks/{direction}](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks__direction__anchor) API.
-    pub async fn stock_equities_snapshot_gainers_losers(
+    /// Sends a request to a path of the API and returns the deserialized
+    /// body of the response along with its status, headers, and latency.
+    ///
+    /// This is the counterpart of the typed methods for callers that track
+    /// latency or inspect headers. Unlike
+    /// [`last_response_meta()`](Self::last_response_meta), the metadata
+    /// belongs to this request even if clones of the client send requests
+    /// concurrently.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use polygon_client::rest::RESTClient;
+    /// use polygon_client::types::StockEquitiesPreviousCloseResponse;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let resp = client
+    ///         .send_with_meta::<StockEquitiesPreviousCloseResponse>(
+    ///             "/v2/aggs/ticker/MSFT/prev",
+    ///             &HashMap::new(),
+    ///         )
+    ///         .await
+    ///         .expect("failed to query previous close");
+    ///     println!("{:?} in {:?}", resp.request_id(), resp.elapsed);
+    /// }
+    /// ```
+    pub async fn send_with_meta<RespType>(
         &self,
-        locale: &str,
-        direction: &str,
+        path: &str,
         query_params: &HashMap<&str, &str>,
-    ) -> Result<StockEquitiesSnapshotGainersLosersResponse, reqwest::Error> {
-        let uri = format!(
-            "/v2/snapshot/locale/{}/markets/stocks/{}",
-            locale, direction
-        );
-        self.send_request::<StockEquitiesSnapshotGainersLosersResponse>(&uri, query_params)
-            .await
+    ) -> Result<ApiResponse<RespType>, Error>
+    where
+        RespType: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let url = format!("{}{}", self.inner.api_url, path);
+        let send = || {
+            self.retry_transient(&url, || {
+                self.send_url_once_with_meta::<RespType, _>(&url, query_params)
+            })
+        };
+        if self.inner.retry_rate_limited {
+            self.retry_rate_limited(send).await
+        } else {
+            send().await
+        }
     }
 
-    //
-    // Forex APIs
-    //
-
-    /// Get aggregate bars for a forex pair over a given date range in custom
-    /// time window sizes using the [/v2/aggs/ticker/{forexTicker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__forexTicker__range__multiplier___timespan___from___to__anchor) API.
-    pub async fn forex_currencies_aggregates(
+    async fn send_url_once<RespType, Q>(
         &self,
-        forex_ticker: &str,
-        multiplier: u32,
-        timespan: &str,
-        from: &str,
-        to: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ForexCurrenciesAggregatesResponse, reqwest::Error> {
-        let uri = format!(
-            "/v2/aggs/ticker/{}/range/{}/{}/{}/{}",
-            forex_ticker, multiplier, timespan, from, to
-        );
-        self.send_request::<ForexCurrenciesAggregatesResponse>(&uri, query_params)
-            .await
+        url: &str,
+        query_params: &Q,
+    ) -> Result<RespType, Error>
+    where
+        RespType: serde::de::DeserializeOwned + Send + 'static,
+        Q: serde::Serialize + ?Sized,
+    {
+        let res = self
+            .send_url_once_with_meta::<RespType, Q>(url, query_params)
+            .await?;
+        Ok(res.body)
     }
 
-    /// Get the daily open, high, low, and close for the entire forex markets
-    /// using the [/v2/aggs/grouped/locale/global/market/fx/{date}](https://polygon.io/docs/get_v2_aggs_grouped_locale_global_market_fx__date__anchor) API.
-    pub async fn forex_currencies_grouped_daily(
+    async fn send_url_once_with_meta<RespType, Q>(
         &self,
-        date: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ForexCurrenciesGroupedDailyResponse, reqwest::Error> {
-        let uri = format!("/v2/aggs/grouped/locale/global/market/fx/{}", date);
-        self.send_request::<ForexCurrenciesGroupedDailyResponse>(&uri, query_params)
-            .await
+        url: &str,
+        query_params: &Q,
+    ) -> Result<ApiResponse<RespType>, Error>
+    where
+        RespType: serde::de::DeserializeOwned + Send + 'static,
+        Q: serde::Serialize + ?Sized,
+    {
+        let res = self.send_url_once_raw(url, query_params).await?;
+        match self.decode_body::<RespType, _>(res.body.clone()).await {
+            Ok(body) => Ok(res.with_body(body)),
+            Err(Error::Decode(e)) => {
+                Err(error_in_body(res.status, &res.body).unwrap_or(Error::Decode(e)))
+            }
+            Err(e) => Err(e),
+        }
     }
 
-    /// Get the previous day's open, high, low, and close for the specified
-    /// forex pair using the [/v2/aggs/ticker/{forex_ticker}/prev](https://polygon.io/docs/get_v2_aggs_ticker__forexTicker__prev_anchor) API.
-    pub async fn forex_currencies_previous_close(
+    /// Sends a request and returns the undecoded body and metadata of a
+    /// successful response.
+    async fn send_url_once_raw<Q>(
         &self,
-        forex_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<ForexCurrenciesPreviousCloseResponse, reqwest::Error> {
-        let uri = format!("/v2/aggs/ticker/{}/prev", forex_ticker);
-        self.send_request::<ForexCurrenciesPreviousCloseResponse>(&uri, query_params)
-            .await
+        url: &str,
+        query_params: &Q,
+    ) -> Result<ApiResponse<Bytes>, Error>
+    where
+        Q: serde::Serialize + ?Sized,
+    {
+        let req = self.build_request(url, query_params)?;
+        // Launchpad users may be entitled to different data, so each has
+        // their own responses.
+        let cache_key = response_cache::cache_key(req.url(), self.edge_headers().map(|h| h.id()));
+        if let Some(res) = self.inner.response_cache.get(&cache_key) {
+            return Ok(res);
+        }
+        let class = EndpointClass::of(req.url().path());
+
+        let (res, permit, started) = self.execute(url, req).await?;
+        let status = res.status().as_u16();
+        let headers = res.headers().clone();
+        let body = res.bytes().await.map_err(|e| self.redact_url(e))?;
+        let elapsed = started.elapsed();
+        drop(permit);
+        #[cfg(feature = "metrics")]
+        telemetry::record_body(url, body.len());
+        let res = ApiResponse {
+            body,
+            status,
+            headers,
+            elapsed,
+        };
+        self.inner.response_cache.insert(cache_key, class, &res);
+        Ok(res)
     }
 
-    //
-    // Crypto APIs
-    //
-
-    /// Get a list of cryptocurrency exchanges which are supported by polygon.io
-    /// using the [/v1/meta/crypto-exchanges](https://polygon.io/docs/get_v1_meta_crypto-exchanges_anchor) API.
-    pub async fn crypto_crypto_exchanges(
-        &self,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<CryptoCryptoExchangesResponse, reqwest::Error> {
-        self.send_request::<CryptoCryptoExchangesResponse>(
-            "/v1/meta/crypto-exchanges",
-            query_params,
-        )
-        .await
+    /// Returns an authenticated request for a URL.
+    fn build_request<Q>(&self, url: &str, query_params: &Q) -> Result<reqwest::Request, Error>
+    where
+        Q: serde::Serialize + ?Sized,
+    {
+        let mut req = self.inner.client.get(url).query(query_params);
+        req = match self.inner.auth_mode {
+            AuthMode::BearerHeader => req.bearer_auth(self.inner.auth_key.expose()),
+            AuthMode::QueryParameter => {
+                req.query(&[(secret::API_KEY_PARAM, self.inner.auth_key.expose())])
+            }
+        };
+        if let Some(encoding) = self.accept_encoding() {
+            req = req.header(ACCEPT_ENCODING, encoding);
+        }
+        if let Some(headers) = self.edge_headers() {
+            req = headers.apply(req);
+        }
+        req.build().map_err(|e| self.redact_url(e).into())
     }
 
-    /// Get the open and close prices of a cryptocurrency symbol on a certain day
-    /// using [/v1/open-close/crypto/{from}/{to}/{date}](https://polygon.io/docs/get_v1_open-close_crypto__from___to___date__anchor) API.
-    pub async fn crypto_daily_open_close(
-        &self,
-        from: &str,
-        to: &str,
-        date: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<CryptoDailyOpenCloseResponse, reqwest::Error> {
-        let uri = format!("/v1/open-close/crypto/{}/{}/{}", from, to, date);
-        self.send_request::<CryptoDailyOpenCloseResponse>(&uri, query_params)
-            .await
+    /// Returns the `Accept-Encoding` set by the client, if any.
+    fn accept_encoding(&self) -> Option<&'static str> {
+        if self.inner.force_identity_encoding {
+            Some(meta::IDENTITY_ENCODING)
+        } else {
+            None
+        }
     }
 
-    /// Get aggregate bars for a cryptocurrency over a given date range in custom
-    /// time window sizes using the [/v2/aggs/ticker/{cryptoTicker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__cryptoTicker__range__multiplier___timespan___from___to__anchor) API.
-    pub async fn crypto_aggregates(
+    /// Sends a request once the request budget allows it and returns the
+    /// response if its status is successful, along with the permit to hold
+    /// while its body is read and the time at which it was sent.
+    async fn execute(
         &self,
-        crypto_ticker: &str,
-        multiplier: u32,
-        timespan: &str,
-        from: &str,
-        to: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<CryptoAggregatesResponse, reqwest::Error> {
-        let uri = format!(
-            "/v2/aggs/ticker/{}/range/{}/{}/{}/{}",
-            crypto_ticker, multiplier, timespan, from, to
-        );
-        self.send_request::<CryptoAggregatesResponse>(&uri, query_params)
-            .await
+        url: &str,
+        req: reqwest::Request,
+    ) -> Result<(reqwest::Response, RequestPermit, Instant), Error> {
+        let _probe = self.check_circuit()?;
+        self.throttle().await;
+        let permit = self.acquire_permit().await;
+        let started = Instant::now();
+        let res = match self.inner.client.execute(req).await {
+            Ok(res) => res,
+            Err(e) => {
+                self.record_circuit(circuit::is_failure(&e));
+                return Err(self.redact_url(e).into());
+            }
+        };
+        self.record_circuit(res.status().is_server_error());
+        #[cfg(feature = "metrics")]
+        telemetry::record_response(url, res.status().as_u16(), started.elapsed());
+        #[cfg(not(feature = "metrics"))]
+        let _ = url;
+        self.inner
+            .responses
+            .record(ResponseMeta::new(&res, self.accept_encoding()));
+
+        if res.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = res
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| retry::parse_retry_after(v, Utc::now()));
+            // The body is only informative, so a body that cannot be read
+            // does not hide the rate limit.
+            let response = match res.bytes().await {
+                Ok(body) => {
+                    #[cfg(feature = "metrics")]
+                    telemetry::record_body(url, body.len());
+                    serde_json::from_slice::<ErrorResponse>(&body).ok()
+                }
+                Err(_) => None,
+            };
+            return Err(Error::RateLimited {
+                retry_after,
+                response,
+            });
+        }
+
+        if !res.status().is_success() {
+            let status = res.status().as_u16();
+            let body = res.bytes().await.map_err(|e| self.redact_url(e))?;
+            drop(permit);
+            #[cfg(feature = "metrics")]
+            telemetry::record_body(url, body.len());
+            return Err(api_error(status, &body));
+        }
+        Ok((res, permit, started))
     }
 
-    /// Get the daily open, high, low, and close for the entire crypto markets
-    /// using the [/v2/aggs/grouped/locale/global/market/crypto/{date}](https://polygon.io/docs/get_v2_aggs_grouped_locale_global_market_crypto__date__anchor) API.
-    pub async fn crypto_grouped_daily(
-        &self,
-        date: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<CryptoGroupedDailyResponse, reqwest::Error> {
-        let uri = format!("/v2/aggs/grouped/locale/global/market/crypto/{}", date);
-        self.send_request::<CryptoGroupedDailyResponse>(&uri, query_params)
-            .await
+    /// Redacts the API key from the URL of a transport error.
+    fn redact_url(&self, mut e: reqwest::Error) -> reqwest::Error {
+        if let Some(url) = e.url_mut() {
+            secret::redact_url(url);
+        }
+        e
     }
 
-    /// Get the previous day's open, high, low, and close for the specified
-    /// cryptocurrency using the [/v2/aggs/ticker/{crypto_ticker}/prev](https://polygon.io/docs/get_v2_aggs_ticker__cryptoTicker__prev_anchor) API.
-    pub async fn crypto_previous_close(
-        &self,
-        crypto_ticker: &str,
-        query_params: &HashMap<&str, &str>,
-    ) -> Result<CryptoPreviousCloseResponse, reqwest::Error> {
-        let uri = format!("/v2/aggs/ticker/{}/prev", crypto_ticker);
-        self.send_request::<CryptoPreviousCloseResponse>(&uri, query_params)
-            .await
+    /// Deserializes a response body, offloading the work to the blocking
+    /// thread pool if the body is larger than the configured threshold.
+    async fn decode_body<RespType, B>(&self, body: B) -> Result<RespType, Error>
+    where
+        RespType: serde::de::DeserializeOwned + Send + 'static,
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        match self.inner.decode_offload_threshold {
+            // There is no blocking thread pool to offload to in the browser.
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(threshold) if body.as_ref().len() > threshold => {
+                let decode = move || decode_json::<RespType>(body.as_ref());
+                match tokio::task::spawn_blocking(decode).await {
+                    Ok(res) => res,
+                    Err(e) => match e.try_into_panic() {
+                        Ok(panic) => std::panic::resume_unwind(panic),
+                        // The runtime is shutting down.
+                        Err(e) => Err(Error::Io(std::io::Error::other(e))),
+                    },
+                }
+            }
+            _ => decode_json::<RespType>(body.as_ref()),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::error::Error;
     use crate::rest::RESTClient;
     use crate::types::*;
     use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
 
-    #[test]
-    fn test_reference_tickers() {
-        let mut query_params = HashMap::new();
-        query_params.insert("ticker", "MSFT");
-        let resp =
-            tokio_test::block_on(RESTClient::new(None, None).reference_tickers(&query_params))
-                .unwrap();
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.count, 1);
-        assert_eq!(resp.results[0].market, "stocks");
-        assert_eq!(resp.results[0].currency_name, "usd");
-    }
+    fn assert_send<T: Send>(_: &T) {}
 
     #[test]
-    fn test_reference_ticker_types() {
-        let query_params = HashMap::new();
-        let resp =
-            tokio_test::block_on(RESTClient::new(None, None).reference_ticker_types(&query_params))
-                .unwrap();
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.results.types["CS"], "Common Stock");
-        assert_eq!(resp.results.index_types["INDEX"], "Index");
+    fn test_clone_shares_state() {
+        let client = RESTClient::builder().auth_key("test").build().unwrap();
+        let clone = client.clone();
+        assert!(Arc::ptr_eq(&client.inner, &clone.inner));
+        assert_eq!(clone.auth_key(), "test");
     }
 
     #[test]
-    fn test_reference_ticker_details() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).reference_ticker_details("MSFT", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.country, "usa");
-        assert_eq!(resp.name, "Microsoft Corporation");
-        assert_eq!(resp.symbol, "MSFT");
+    fn test_try_new() {
+        let client = RESTClient::try_new(Some("test"), None).unwrap();
+        assert_eq!(client.auth_key(), "test");
     }
 
     #[test]
-    fn test_reference_ticker_details_vx() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).reference_ticker_details_vx("MSFT", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.results.ticker, "MSFT");
-        assert_eq!(resp.results.currency_name, "usd");
+    fn test_invalid_config() {
+        let builder = || RESTClient::builder().auth_key("test");
+        let errors = [
+            builder().max_in_flight(0).build().err(),
+            builder().throttle(0, Duration::from_secs(1)).build().err(),
+            builder().throttle(5, Duration::ZERO).build().err(),
+        ];
+        for err in errors {
+            assert!(matches!(err, Some(Error::Config(_))));
+        }
     }
 
     #[test]
-    fn test_reference_ticker_news() {
-        let query_params = HashMap::new();
-        let resp =
-            tokio_test::block_on(RESTClient::new(None, None).reference_ticker_news(&query_params))
-                .unwrap();
-        assert_eq!(resp.status, "OK");
-    }
-
+    fn test_api_url() {
+        let client = RESTClient::builder()
+            .auth_key("test")
+            .api_url("http://localhost:8080/")
+            .build()
+            .unwrap();
+        assert_eq!(client.api_url(), "http://localhost:8080");
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
     #[test]
-    fn test_reference_markets() {
-        let query_params = HashMap::new();
-        let resp =
-            tokio_test::block_on(RESTClient::new(None, None).reference_markets(&query_params))
+    fn test_http_client() {
+        use crate::rest::test_server::{self, Response, TestServer};
+        use reqwest::header::{HeaderMap, HeaderValue};
+
+        tokio_test::block_on(async {
+            let server =
+                TestServer::start(vec![Response::ok(String::from(r#"{"status":"OK"}"#))]).await;
+            let mut headers = HeaderMap::new();
+            headers.insert("X-App", HeaderValue::from_static("watchlist"));
+            let http_client = reqwest::Client::builder()
+                .default_headers(headers)
+                .build()
                 .unwrap();
-        assert_eq!(resp.status, "OK");
-        let bond = resp.results.iter().find(|x| x.market == "BONDS");
-        assert!(bond.is_some());
-        assert_eq!(bond.unwrap().desc, "Bonds");
-    }
-
-    #[test]
-    fn test_reference_locales() {
-        let query_params = HashMap::new();
-        let resp =
-            tokio_test::block_on(RESTClient::new(None, None).reference_locales(&query_params))
+            let client = test_server::builder(&server)
+                .http_client(http_client)
+                .build()
                 .unwrap();
-        assert_eq!(resp.status, "OK");
-        let bond = resp.results.iter().find(|x| x.locale == "US");
-        assert!(bond.is_some());
-        assert_eq!(bond.unwrap().name, "United States of America");
-    }
-
-    #[test]
-    fn test_reference_stock_splits() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).reference_stock_splits("MSFT", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.status, "OK");
-        let bond = resp.results.iter().find(|x| x.ex_date == "1998-02-23");
-        assert!(bond.is_some());
-        assert_eq!(bond.unwrap().ratio, 0.5);
-    }
-
-    #[test]
-    fn test_reference_stock_dividends() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).reference_stock_dividends("MSFT", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.status, "OK");
-        let bond = resp.results.iter().find(|x| x.ex_date == "2021-02-17");
-        assert!(bond.is_some());
-        assert_eq!(bond.unwrap().amount, 0.56);
-    }
-
-    #[test]
-    fn test_reference_stock_financials() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).reference_stock_financials("MSFT", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.status, "OK");
-        let fin = resp.results.iter().find(|x| x.ticker == "MSFT");
-        assert!(fin.is_some());
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).reference_stock_financials("AAPL", &query_params),
-        )
-        .unwrap();
-        let fin = resp.results.iter().find(|x| x.ticker == "AAPL");
-        assert!(fin.is_some());
-    }
-
-    #[test]
-    fn test_reference_stock_financials_vx() {
-        let mut query_params = HashMap::new();
-        query_params.insert("ticker", "MSFT");
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).reference_stock_financials_vx(&query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.count, 1);
-        let result = resp.results.first().unwrap();
-        for v in &result.financials.balance_sheet {
-            println!("{} = true", v.0);
-        }
-        let income_statement = &result.financials.income_statement;
-        assert!(income_statement.contains_key(FAC_REVENUES));
-        assert!(income_statement.get(FAC_REVENUES).unwrap().unit.is_some());
-        assert_eq!(
-            income_statement
-                .get(FAC_REVENUES)
-                .unwrap()
-                .unit
-                .as_ref()
-                .unwrap(),
-            "USD"
-        );
-    }
-
-    #[test]
-    fn test_reference_market_holidays() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).reference_market_holidays(&query_params),
-        )
-        .unwrap();
-        assert_ne!(resp.len(), 0);
-    }
-
-    #[test]
-    fn test_reference_market_status() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).reference_market_status(&query_params),
-        )
-        .unwrap();
-        assert_ne!(resp.exchanges.len(), 0);
-    }
 
-    #[test]
-    fn test_stock_equities_exchanges() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).stock_equities_exchanges(&query_params),
+            client
+                .send_request::<serde_json::Value>("/v1/test", &HashMap::new())
+                .await
+                .unwrap();
+            assert_eq!(
+                server.request_headers("X-App"),
+                vec![Some(String::from("watchlist"))]
+            );
+            assert_eq!(
+                server.request_headers("Authorization"),
+                vec![Some(String::from("Bearer test"))]
+            );
+        });
+    }
+
+    #[cfg(all(
+        feature = "gzip",
+        any(
+            feature = "reference",
+            feature = "stocks",
+            feature = "forex",
+            feature = "crypto",
+            feature = "options",
+            feature = "futures",
+            feature = "indices",
+            feature = "economy",
+            feature = "partners"
         )
-        .unwrap();
-        assert_ne!(resp.len(), 0);
-        let dji = resp
-            .iter()
-            .find(|x| x.code.is_some() && x.code.as_ref().unwrap() == "DJI");
-        assert!(dji.is_some());
-        assert_eq!(dji.unwrap().market, "index");
-    }
-
+    ))]
     #[test]
-    fn test_stock_equities_condition_mappings() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None)
-                .stock_equities_condition_mappings(TickType::Trades, &query_params),
-        )
-        .unwrap();
-        assert_ne!(resp.len(), 0);
-        let regular = resp.iter().find(|x| x.1 == "Regular");
-        assert!(regular.is_some());
-    }
+    fn test_compression() {
+        use crate::rest::test_server::{self, Response, TestServer};
+
+        tokio_test::block_on(async {
+            let ok = || Response::ok(String::from(r#"{"status":"OK"}"#));
+            let server = TestServer::start(vec![ok(), ok()]).await;
+            for compression in [true, false].iter() {
+                let client = test_server::builder(&server)
+                    .compression(*compression)
+                    .build()
+                    .unwrap();
+                client
+                    .send_request::<serde_json::Value>("/v1/test", &HashMap::new())
+                    .await
+                    .unwrap();
+            }
 
-    #[test]
-    fn test_stock_equities_historic_trades() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).stock_equities_historic_trades("MSFT", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.results.T.unwrap(), "MSFT");
+            let accept_encoding = server.request_headers("Accept-Encoding");
+            assert!(accept_encoding[0].as_deref().unwrap().contains("gzip"));
+            assert_eq!(accept_encoding[1], None);
+        });
     }
 
     #[test]
-    fn test_stock_equities_last_quote_for_a_symbol() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None)
-                .stock_equities_last_quote_for_a_symbol("MSFT", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.results.T.unwrap(), "MSFT");
-    }
+    fn test_send_raw() {
+        use crate::error::ErrorKind;
+        use crate::rest::test_server::{self, Response, TestServer};
+
+        tokio_test::block_on(async {
+            let body = r#"{"status":"OK", "results":{"ticker":"MSFT","new_field":1}}"#;
+            let server = TestServer::start(vec![
+                Response::ok(String::from(body)),
+                Response::error(404, r#"{"status":"NOT_FOUND"}"#),
+            ])
+            .await;
+            let client = test_server::client(&server);
 
+            let mut params = HashMap::new();
+            params.insert("date", "2024-01-02");
+            let raw = client
+                .send_raw("/v3/reference/tickers/MSFT", &params)
+                .await
+                .unwrap();
+            assert_eq!(&raw[..], body.as_bytes());
+
+            let err = client
+                .send_raw("/v3/reference/tickers/NOPE", &HashMap::new())
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::NotFound);
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/v3/reference/tickers/MSFT?date=2024-01-02",
+                    "/v3/reference/tickers/NOPE"
+                ]
+            );
+        });
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
     #[test]
-    fn test_stock_equities_daily_open_close() {
-        let query_params = HashMap::new();
-        let resp =
-            tokio_test::block_on(RESTClient::new(None, None).stock_equities_daily_open_close(
-                "MSFT",
-                "2020-10-14",
-                &query_params,
-            ))
-            .unwrap();
-        assert_eq!(resp.symbol, "MSFT");
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.open, 223f64);
-        assert_eq!(resp.high, 224.22);
-        assert_eq!(resp.low, 219.13);
-        assert_eq!(resp.close, 220.86);
-        assert_eq!(resp.volume, 23451713f64);
-        assert_eq!(resp.after_hours, 220.3);
-        assert_eq!(resp.pre_market, 224.03);
-    }
+    fn test_auth_mode() {
+        use crate::rest::test_server::{Response, TestServer};
+        use crate::rest::{AuthMode, RetryPolicy};
+
+        tokio_test::block_on(async {
+            let server =
+                TestServer::start(vec![Response::ok(String::from(r#"{"status":"OK"}"#))]).await;
+            let client = RESTClient::builder()
+                .auth_key("secret")
+                .api_url(&server.url())
+                .auth_mode(AuthMode::QueryParameter)
+                .retry_policy(RetryPolicy::disabled())
+                .build()
+                .unwrap();
 
+            let no_params = HashMap::new();
+            client
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap();
+            assert_eq!(server.requests(), vec!["/v1/test?apiKey=secret"]);
+            assert_eq!(server.request_headers("Authorization"), vec![None]);
+
+            // Once the server has stopped, the error redacts the key from the
+            // URL.
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            let err = client
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            assert!(!err.to_string().contains("secret"));
+            assert!(err.to_string().contains("/v1/test?apiKey=***"));
+        });
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
     #[test]
-    fn test_stock_equities_aggregates() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(RESTClient::new(None, None).stock_equities_aggregates(
-            "MSFT",
-            1,
-            "day",
-            "2020-10-14",
-            "2020-10-14",
-            &query_params,
-        ))
-        .unwrap();
-        assert_eq!(resp.ticker, "MSFT");
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.query_count, 1);
-        assert_eq!(resp.results_count, 1);
-        let result = resp.results.first().unwrap();
-        assert_eq!(result.v, 23451713f64);
-        assert_eq!(result.vw.unwrap(), 221.41);
-        assert_eq!(result.o, 223f64);
-        assert_eq!(result.c, 220.86);
-        assert_eq!(result.h, 224.22);
-        assert_eq!(result.l, 219.13);
-        assert_eq!(result.t.unwrap(), 1602648000000);
-        assert_eq!(result.n.unwrap(), 244243f64);
-    }
+    fn test_api_error() {
+        use crate::error::{Error, ErrorKind};
+        use crate::rest::test_server::{self, Response, TestServer};
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::error(
+                    401,
+                    r#"{"status":"ERROR","request_id":"abc","error":"Unknown API Key"}"#,
+                ),
+                Response::error(502, "<html>Bad Gateway</html>"),
+            ])
+            .await;
+            let client = test_server::builder(&server)
+                .retry_policy(crate::rest::RetryPolicy::disabled())
+                .build()
+                .unwrap();
 
+            let no_params = HashMap::new();
+            let err = client
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Auth);
+            assert_eq!(err.status(), Some(401));
+            assert_eq!(
+                err.to_string(),
+                "API error: HTTP 401 ERROR: Unknown API Key (request abc)"
+            );
+
+            let err = client
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                Error::Api {
+                    status: 502,
+                    response: None,
+                    ..
+                }
+            ));
+            assert_eq!(err.kind(), ErrorKind::Transport);
+        });
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
     #[test]
-    fn test_stock_equities_grouped_daily() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(RESTClient::new(None, None).stock_equities_grouped_daily(
-            "us",
-            "stocks",
-            "2020-10-14",
-            &query_params,
-        ))
-        .unwrap();
-        assert_eq!(resp.status, "OK");
-        let msft = resp
-            .results
-            .iter()
-            .find(|x| x.T.is_some() && x.T.as_ref().unwrap() == "MSFT");
-        assert!(msft.is_some());
-        assert!(msft.unwrap().vw.is_some());
-        assert_eq!(msft.unwrap().vw.unwrap(), 221.41);
-        assert_eq!(msft.unwrap().o, 223f64);
-        assert_eq!(msft.unwrap().h, 224.22);
-        assert_eq!(msft.unwrap().l, 219.13);
-    }
+    fn test_error_response() {
+        use crate::error::Error;
+        use crate::rest::test_server::{self, Response, TestServer};
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::error(
+                    403,
+                    r#"{"status":"NOT_AUTHORIZED","request_id":"abc","message":"You are not entitled to this data."}"#,
+                ),
+                Response::ok(String::from(
+                    r#"{"status":"ERROR","request_id":"def","error":"Unknown API Key"}"#,
+                )),
+                Response::rate_limited(Some(0)),
+            ])
+            .await;
+            let client = test_server::builder(&server)
+                .rate_limit_policy(crate::rest::RateLimitPolicy::disabled())
+                .build()
+                .unwrap();
 
+            let no_params = HashMap::new();
+            let err = client
+                .send_request::<ReferenceTickersResponse>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            let response = err.error_response().unwrap();
+            assert_eq!(response.status.as_deref(), Some("NOT_AUTHORIZED"));
+            assert_eq!(response.request_id.as_deref(), Some("abc"));
+            assert_eq!(
+                response.message.as_deref(),
+                Some("You are not entitled to this data.")
+            );
+            assert_eq!(response.error, None);
+
+            let err = client
+                .send_request::<ReferenceTickersResponse>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Api { status: 200, .. }));
+            assert_eq!(
+                err.error_response().unwrap().reason(),
+                Some("Unknown API Key")
+            );
+
+            let err = client
+                .send_request::<ReferenceTickersResponse>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "rate limited: exceeded maximum requests, retry after 0s"
+            );
+            assert_eq!(
+                err.error_response().unwrap().reason(),
+                Some("exceeded maximum requests")
+            );
+        });
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
     #[test]
-    fn test_stock_equities_previous_close() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).stock_equities_previous_close("MSFT", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.ticker, "MSFT");
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.results_count, 1);
-        let result = resp.results.first();
-        assert!(result.is_some());
-        assert!(result.unwrap().T.is_some());
-        assert_eq!(result.unwrap().T.as_ref().unwrap(), "MSFT");
+    fn test_timeout() {
+        use std::time::Duration;
+
+        use crate::error::Error;
+
+        tokio_test::block_on(async {
+            // Accept connections without ever answering them.
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move {
+                let mut connections = Vec::new();
+                while let Ok((stream, _)) = listener.accept().await {
+                    connections.push(stream);
+                }
+            });
+
+            let client = RESTClient::builder()
+                .auth_key("test")
+                .api_url(&url)
+                .timeout(Duration::from_millis(50))
+                .retry_policy(crate::rest::RetryPolicy::disabled())
+                .build()
+                .unwrap();
+            let err = client
+                .send_request::<serde_json::Value>("/v1/test", &HashMap::new())
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Timeout(_)));
+            assert!(err.reqwest().unwrap().is_timeout());
+        });
     }
 
+    #[cfg(feature = "stocks")]
     #[test]
-    fn test_stock_equities_snapshot_all_tickers() {
-        let query_params = HashMap::new();
-        let _resp = tokio_test::block_on(
-            RESTClient::new(None, None).stock_equities_snapshot_all_tickers("us", &query_params),
-        )
-        .unwrap();
-    }
+    fn test_decode_offload() {
+        use crate::error::Error;
+        use crate::rest::test_server::{self, Response, TestServer};
+        use crate::ticker::StockTicker;
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(include_str!("../fixtures/previous_close.json").into()),
+                Response::ok(String::from(r#"{"status":"OK","results":"bad"}"#)),
+            ])
+            .await;
 
-    #[test]
-    fn test_stock_equities_snapshot_gainers_losers() {
-        let query_params = HashMap::new();
-        let _resp = tokio_test::block_on(
-            RESTClient::new(None, None).stock_equities_snapshot_gainers_losers(
-                "us",
-                "gainers",
-                &query_params,
-            ),
-        )
-        .unwrap();
-    }
+            let client = test_server::builder(&server)
+                .decode_offload_threshold(0)
+                .build()
+                .unwrap();
 
-    #[test]
-    fn test_crypto_crypto_exchanges() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).crypto_crypto_exchanges(&query_params),
-        )
-        .unwrap();
-        assert_ne!(resp.len(), 0);
-        let coinbase = resp.iter().find(|x| x.name == "Coinbase");
-        assert!(coinbase.is_some());
-    }
+            let query_params = HashMap::new();
+            let resp = client
+                .stock_equities_previous_close(StockTicker::new("MSFT").unwrap(), &query_params)
+                .await
+                .unwrap();
+            assert_eq!(resp.results[0].c, 294.85);
 
-    #[test]
-    fn test_forex_currencies_aggregates() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(RESTClient::new(None, None).forex_currencies_aggregates(
-            "C:EURUSD",
-            1,
-            "day",
-            "2020-10-14",
-            "2020-10-14",
-            &query_params,
-        ))
-        .unwrap();
-        assert_eq!(resp.ticker, "C:EURUSD");
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.query_count, 1);
-        assert_eq!(resp.results_count, 1);
-        let result = resp.results.first().unwrap();
-        assert_eq!(result.v, 211796f64);
-        assert_eq!(result.vw.unwrap(), 1.1748);
-        assert_eq!(result.o, 1.17439);
-        assert_eq!(result.c, 1.17496);
-        assert_eq!(result.h, 1.1771);
-        assert_eq!(result.l, 1.17198);
-        assert_eq!(result.t.unwrap(), 1602633600000);
-        assert_eq!(result.n.unwrap(), 211796f64);
+            let err = client
+                .stock_equities_previous_close(StockTicker::new("MSFT").unwrap(), &query_params)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Decode(_)));
+        });
     }
 
     #[test]
-    fn test_forex_currencies_grouped_daily() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).forex_currencies_grouped_daily("2020-10-14", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.status, "OK");
-        let msft = resp
-            .results
-            .iter()
-            .find(|x| x.T.is_some() && x.T.as_ref().unwrap() == "C:EURMUR");
-        assert!(msft.is_some());
-        assert!(msft.unwrap().vw.is_some());
-        assert_eq!(msft.unwrap().vw.unwrap(), 45.2081);
-        assert_eq!(msft.unwrap().o, 45.37);
-        assert_eq!(msft.unwrap().h, 45.59);
-        assert_eq!(msft.unwrap().l, 44.83);
-    }
+    fn test_futures_and_streams_are_send() {
+        #[cfg(feature = "stocks")]
+        use crate::ticker::StockTicker;
 
-    #[test]
-    fn test_forex_currencies_previous_close() {
+        let client = RESTClient::builder().auth_key("test").build().unwrap();
         let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).forex_currencies_previous_close("C:EURUSD", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.ticker, "C:EURUSD");
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.results_count, 1);
-        let result = resp.results.first();
-        assert!(result.is_some());
-        assert!(result.unwrap().T.is_some());
-        assert_eq!(result.unwrap().T.as_ref().unwrap(), "C:EURUSD");
-    }
 
-    #[test]
-    fn test_crypto_daily_open_close() {
-        let mut query_params = HashMap::new();
-        query_params.insert("adjusted", "true");
-        let resp = tokio_test::block_on(RESTClient::new(None, None).crypto_daily_open_close(
-            "BTC",
-            "USD",
-            "2020-10-14",
+        #[cfg(feature = "stocks")]
+        assert_send(
+            &client.stock_equities_previous_close(StockTicker::new("MSFT").unwrap(), &query_params),
+        );
+        #[cfg(all(feature = "reference", feature = "stocks"))]
+        assert_send(&client.ticker_overview(StockTicker::new("MSFT").unwrap()));
+        assert_send(
+            &client.paginate::<ReferenceTickersResponse>("/v3/reference/tickers", &query_params),
+        );
+        #[cfg(feature = "reference")]
+        assert_send(&client.reference_tickers_stream(&query_params));
+        #[cfg(feature = "reference")]
+        assert_send(&client.reference_ticker_news_stream(&query_params));
+        #[cfg(feature = "stocks")]
+        assert_send(&client.stock_trades_stream(StockTicker::new("MSFT").unwrap(), 0..1));
+        #[cfg(feature = "stocks")]
+        assert_send(&client.stock_quotes_stream(StockTicker::new("MSFT").unwrap(), 0..1));
+        assert_send(&client.collect_all::<ReferenceTickersResponse>(
+            "/v3/reference/tickers",
             &query_params,
-        ))
-        .unwrap();
-        assert_eq!(resp.symbol, "BTC-USD");
-        assert!(resp.is_utc);
-        assert_eq!(resp.open, 11443f64);
-        assert_eq!(resp.close, 11427.7);
-    }
-
-    #[test]
-    fn test_crypto_aggregates() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(RESTClient::new(None, None).crypto_aggregates(
-            "X:BTCUSD",
-            1,
-            "day",
-            "2020-10-14",
-            "2020-10-14",
+            crate::rest::CollectOptions::new(),
+        ));
+        assert_send(&client.stream_array::<StockEquitiesAggregates>(
+            "/v2/aggs/grouped/locale/us/market/stocks/2024-01-02",
             &query_params,
-        ))
-        .unwrap();
-        assert_eq!(resp.ticker, "X:BTCUSD");
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.query_count, 1);
-        assert_eq!(resp.results_count, 1);
-        let result = resp.results.first().unwrap();
-        assert_eq!(result.vw.unwrap(), 11405.5019);
-        assert_eq!(result.o, 11443f64);
-        assert_eq!(result.c, 11427.7);
-        assert_eq!(result.h, 11564f64);
-        assert_eq!(result.l, 11284.27);
-        assert_eq!(result.t.unwrap(), 1602633600000);
-        assert_eq!(result.n.unwrap(), 142439f64);
-    }
-
-    #[test]
-    fn test_crypto_grouped_daily() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).crypto_grouped_daily("2020-10-14", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.status, "OK");
-        let msft = resp
-            .results
-            .iter()
-            .find(|x| x.T.is_some() && x.T.as_ref().unwrap() == "X:LTCUSD");
-        assert!(msft.is_some());
-        assert!(msft.unwrap().vw.is_some());
-        assert_eq!(msft.unwrap().vw.unwrap(), 50.1376);
-        assert_eq!(msft.unwrap().o, 49.981);
-        assert_eq!(msft.unwrap().h, 51.095);
-        assert_eq!(msft.unwrap().l, 49.2427);
-    }
-
-    #[test]
-    fn test_crypto_previous_close() {
-        let query_params = HashMap::new();
-        let resp = tokio_test::block_on(
-            RESTClient::new(None, None).crypto_previous_close("X:BTCUSD", &query_params),
-        )
-        .unwrap();
-        assert_eq!(resp.ticker, "X:BTCUSD");
-        assert_eq!(resp.status, "OK");
-        assert_eq!(resp.results_count, 1);
-        let result = resp.results.first();
-        assert!(result.is_some());
-        assert!(result.unwrap().T.is_some());
-        assert_eq!(result.unwrap().T.as_ref().unwrap(), "X:BTCUSD");
+            "results",
+        ));
     }
 }
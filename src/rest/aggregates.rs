@@ -0,0 +1,384 @@
+//! A single entry point for the aggregate bars of any asset class.
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use chrono::{Duration, NaiveDate};
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+use crate::error::Error;
+use crate::rest::{RESTClient, AGGREGATES_MAX_LIMIT};
+use crate::ticker::Ticker;
+use crate::types::*;
+
+/// Returns the number of days of bars of the given size that fit in a single
+/// response of at most [`AGGREGATES_MAX_LIMIT`] bars, counting every minute
+/// of the day as crypto trades around the clock, or `None` if every bar of
+/// any range fits.
+fn days_per_chunk(multiplier: u32, timespan: Timespan) -> Option<i64> {
+    let bars_per_day = match timespan {
+        Timespan::Second => 86_400,
+        Timespan::Minute => 1_440,
+        Timespan::Hour => 24,
+        _ => return None,
+    };
+    let bars_per_day = (bars_per_day / i64::from(multiplier.max(1))).max(1);
+    Some((i64::from(AGGREGATES_MAX_LIMIT) / bars_per_day).max(1))
+}
+
+/// Splits `range` into consecutive ranges of at most `days` days.
+fn split_range(range: &RangeInclusive<NaiveDate>, days: i64) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut chunks = Vec::new();
+    let mut from = *range.start();
+    while from <= *range.end() {
+        let to = from
+            .checked_add_signed(Duration::days(days - 1))
+            .map_or(*range.end(), |to| to.min(*range.end()));
+        chunks.push((from, to));
+        from = match to.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    chunks
+}
+
+impl RESTClient {
+    /// Get aggregate bars for a ticker in any asset class over a given date
+    /// range in custom time window sizes using the [/v2/aggs/ticker/{ticker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__stocksTicker__range__multiplier___timespan___from___to__anchor) API.
+    ///
+    /// The asset class is that of `ticker`, which is any of the validated
+    /// ticker types, such as [`CryptoTicker`](crate::ticker::CryptoTicker)
+    /// for `X:BTCUSD`.
+    ///
+    /// Bars of every asset class are returned as the same [`Bar`] type, so
+    /// code that handles several asset classes does not need to branch on
+    /// the response type.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use polygon_client::rest::RESTClient;
+    /// use polygon_client::ticker::{CryptoTicker, StockTicker, Ticker};
+    /// use polygon_client::types::Timespan;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let query_params = HashMap::new();
+    ///     let tickers: [Ticker; 2] = [
+    ///         StockTicker::new("MSFT").unwrap().into(),
+    ///         CryptoTicker::new("BTCUSD").unwrap().into(),
+    ///     ];
+    ///     for ticker in &tickers {
+    ///         let resp = client
+    ///             .aggregates(ticker, 1, Timespan::Day, "2023-01-09", "2023-01-13", &query_params)
+    ///             .await
+    ///             .expect("failed to query aggregates");
+    ///         println!("{}: {} bars", resp.ticker, resp.results.len());
+    ///     }
+    /// }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn aggregates(
+        &self,
+        ticker: impl Into<Ticker>,
+        multiplier: u32,
+        timespan: Timespan,
+        from: &str,
+        to: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<AggregatesResponse, Error> {
+        let ticker: Ticker = ticker.into();
+        self.send_aggregates(
+            ticker.as_str(),
+            multiplier,
+            timespan,
+            from,
+            to,
+            query_params,
+        )
+        .await
+    }
+
+    /// Get every aggregate bar of a ticker in any asset class over a date
+    /// range that may hold more bars than a single response, such as years
+    /// of minute bars, as one series ordered oldest first.
+    ///
+    /// The range is split into chunks whose bars fit in a response of
+    /// [`AGGREGATES_MAX_LIMIT`] bars, of which up to `concurrency` are
+    /// requested at once. The pages of a chunk that still holds more bars,
+    /// such as one of second bars, are followed as with
+    /// [`RESTClient::paginate()`]. Bars with the same timestamp returned by
+    /// two chunks are only kept once, and bars without a timestamp are listed
+    /// first. The ticker is handled as with [`RESTClient::aggregates()`].
+    ///
+    /// A long backfill is best run on a [`RESTClient::partition()`] so that
+    /// its requests cannot starve other requests of the client.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use chrono::NaiveDate;
+    /// use polygon_client::rest::RESTClient;
+    /// use polygon_client::ticker::StockTicker;
+    /// use polygon_client::types::Timespan;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let backfill = client.partition(4).expect("invalid partition");
+    ///     let ticker = StockTicker::new("MSFT").unwrap();
+    ///     let from = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+    ///     let to = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+    ///     let bars = backfill
+    ///         .aggregates_chunked(ticker, 1, Timespan::Minute, from..=to, &HashMap::new(), 4)
+    ///         .await
+    ///         .expect("failed to download aggregates");
+    ///     println!("{} bars", bars.len());
+    /// }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn aggregates_chunked(
+        &self,
+        ticker: impl Into<Ticker>,
+        multiplier: u32,
+        timespan: Timespan,
+        range: RangeInclusive<NaiveDate>,
+        query_params: &HashMap<&str, &str>,
+        concurrency: usize,
+    ) -> Result<Vec<Bar>, Error> {
+        let ticker: Ticker = ticker.into();
+        let chunks = match days_per_chunk(multiplier, timespan) {
+            Some(days) => split_range(&range, days),
+            None => vec![(*range.start(), *range.end())],
+        };
+
+        let mut query_params = query_params.clone();
+        let limit = AGGREGATES_MAX_LIMIT.to_string();
+        query_params.entry("limit").or_insert(&limit);
+        let query_params = &query_params;
+
+        let chunks: Vec<Vec<Bar>> = stream::iter(chunks)
+            .map(|(from, to)| {
+                let uri = format!(
+                    "/v2/aggs/ticker/{}/range/{}/{}/{}/{}",
+                    ticker,
+                    multiplier,
+                    timespan,
+                    from.format("%Y-%m-%d"),
+                    to.format("%Y-%m-%d")
+                );
+                self.paginate_limited::<AggregatesResponse>(
+                    &uri,
+                    query_params,
+                    AGGREGATES_MAX_LIMIT,
+                )
+                .try_collect()
+            })
+            .buffered(concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        let mut bars: Vec<Bar> = chunks.into_iter().flatten().collect();
+        bars.sort_by_key(|bar| bar.t);
+        // Bars without a timestamp cannot be matched across chunks, so they
+        // are all kept.
+        bars.dedup_by(|a, b| a.t.is_some() && a.t == b.t);
+        Ok(bars)
+    }
+
+    /// Requests the aggregate bars of a ticker that already has the prefix of
+    /// its asset class, if any.
+    pub(crate) async fn send_aggregates(
+        &self,
+        ticker: &str,
+        multiplier: u32,
+        timespan: Timespan,
+        from: &str,
+        to: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<AggregatesResponse, Error> {
+        let uri = format!(
+            "/v2/aggs/ticker/{}/range/{}/{}/{}/{}",
+            ticker, multiplier, timespan, from, to
+        );
+        self.send_limited_request::<AggregatesResponse>(&uri, query_params, AGGREGATES_MAX_LIMIT)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::ticker::{CryptoTicker, ForexTicker, StockTicker, Ticker};
+    use crate::types::*;
+    use std::collections::HashMap;
+
+    fn bars(ticker: &str) -> String {
+        format!(
+            r#"{{"ticker":"{}","adjusted":true,"queryCount":1,"resultsCount":1,"status":"OK",
+                "request_id":"1","results":[{{"o":1.0,"h":2.0,"l":0.5,"c":1.5,"v":10.0,"t":1673240400000}}]}}"#,
+            ticker
+        )
+    }
+
+    #[test]
+    fn test_aggregates() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(bars("X:BTCUSD")),
+                Response::ok(bars("C:EURUSD")),
+                Response::ok(bars("MSFT")),
+            ])
+            .await;
+            let client = test_server::client(&server);
+            let query_params = HashMap::new();
+
+            let crypto = client
+                .aggregates(
+                    CryptoTicker::new("BTCUSD").unwrap(),
+                    1,
+                    Timespan::Day,
+                    "2023-01-09",
+                    "2023-01-13",
+                    &query_params,
+                )
+                .await
+                .unwrap();
+            assert_eq!(crypto.results[0].c, 1.5);
+
+            let forex = client
+                .aggregates(
+                    ForexTicker::new("C:EURUSD").unwrap(),
+                    1,
+                    Timespan::Day,
+                    "2023-01-09",
+                    "2023-01-13",
+                    &query_params,
+                )
+                .await
+                .unwrap();
+            assert_eq!(forex.ticker, "C:EURUSD");
+            assert_eq!(forex.results[0].t, Some(1673240400000));
+            // Bars of every asset class share the same type.
+            assert_eq!(forex.results[0], crypto.results[0]);
+
+            client
+                .aggregates(
+                    "msft".parse::<Ticker>().unwrap(),
+                    5,
+                    Timespan::Minute,
+                    "2023-01-09",
+                    "2023-01-13",
+                    &query_params,
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/v2/aggs/ticker/X:BTCUSD/range/1/day/2023-01-09/2023-01-13",
+                    "/v2/aggs/ticker/C:EURUSD/range/1/day/2023-01-09/2023-01-13",
+                    "/v2/aggs/ticker/MSFT/range/5/minute/2023-01-09/2023-01-13",
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_timespan() {
+        assert_eq!(Timespan::Quarter.to_string(), "quarter");
+        assert_eq!(
+            serde_json::to_string(&Timespan::Minute).unwrap(),
+            r#""minute""#
+        );
+        assert_eq!(
+            serde_json::from_str::<Timespan>(r#""week""#).unwrap(),
+            Timespan::Week
+        );
+    }
+
+    #[test]
+    fn test_split_range() {
+        use chrono::NaiveDate;
+
+        use crate::rest::aggregates::{days_per_chunk, split_range};
+
+        let date = |m, d| NaiveDate::from_ymd_opt(2023, m, d).unwrap();
+        assert_eq!(days_per_chunk(1, Timespan::Minute), Some(34));
+        assert_eq!(days_per_chunk(1, Timespan::Second), Some(1));
+        assert_eq!(days_per_chunk(1, Timespan::Day), None);
+        assert_eq!(
+            split_range(&(date(1, 1)..=date(2, 9)), 34),
+            vec![(date(1, 1), date(2, 3)), (date(2, 4), date(2, 9))]
+        );
+        assert_eq!(
+            split_range(&(date(1, 1)..=date(1, 1)), 34),
+            vec![(date(1, 1), date(1, 1))]
+        );
+        let last = NaiveDate::MAX.pred_opt().unwrap();
+        assert_eq!(
+            split_range(&(last..=NaiveDate::MAX), 34),
+            vec![(last, NaiveDate::MAX)]
+        );
+    }
+
+    #[test]
+    fn test_aggregates_chunked() {
+        use chrono::NaiveDate;
+
+        tokio_test::block_on(async {
+            let page = |t: &[Option<u64>]| {
+                let results: Vec<String> = t
+                    .iter()
+                    .map(|t| match t {
+                        Some(t) => {
+                            format!(r#"{{"o":1.0,"h":2.0,"l":0.5,"c":1.5,"v":10.0,"t":{}}}"#, t)
+                        }
+                        None => String::from(r#"{"o":1.0,"h":2.0,"l":0.5,"c":1.5,"v":10.0}"#),
+                    })
+                    .collect();
+                format!(
+                    r#"{{"ticker":"MSFT","adjusted":true,"queryCount":1,"resultsCount":1,"status":"OK",
+                        "request_id":"1","results":[{}]}}"#,
+                    results.join(",")
+                )
+            };
+            let server = TestServer::start(vec![
+                Response::ok(page(&[Some(1), Some(2), Some(3), None])),
+                Response::ok(page(&[Some(3), None, Some(4)])),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let from = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+            let to = NaiveDate::from_ymd_opt(2023, 2, 9).unwrap();
+            let bars = client
+                .aggregates_chunked(
+                    StockTicker::new("MSFT").unwrap(),
+                    1,
+                    Timespan::Minute,
+                    from..=to,
+                    &HashMap::new(),
+                    1,
+                )
+                .await
+                .unwrap();
+            let t: Vec<Option<u64>> = bars.iter().map(|bar| bar.t).collect();
+            assert_eq!(t, vec![None, None, Some(1), Some(2), Some(3), Some(4)]);
+
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/v2/aggs/ticker/MSFT/range/1/minute/2023-01-01/2023-02-03?limit=50000",
+                    "/v2/aggs/ticker/MSFT/range/1/minute/2023-02-04/2023-02-09?limit=50000",
+                ]
+            );
+        });
+    }
+}
@@ -0,0 +1,501 @@
+//! Analytics helpers that combine endpoints with the calendar and adjustment
+//! utilities.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use chrono::{Duration, NaiveDate};
+use futures::TryStreamExt;
+
+use crate::adjust::{adjust_bars, bar_date, Adjustment};
+use crate::calendar;
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::runtime::{SystemTime, UNIX_EPOCH};
+use crate::ticker::StockTicker;
+use crate::types::*;
+
+/// The maximum number of bars requested per page of aggregates.
+const AGGREGATES_PAGE_LIMIT: &str = "50000";
+
+impl RESTClient {
+    /// Get a continuous series of daily bars for a stock over a range of
+    /// dates, adjusted for corporate actions.
+    ///
+    /// Unadjusted bars are downloaded for the full range, following every page
+    /// of the [/v2/aggs/ticker/{stocksTicker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__stocksTicker__range__multiplier___timespan___from___to__anchor)
+    /// API, and adjusted locally using the splits and dividends of the stock
+    /// so that the adjustment is consistent for the whole series.
+    ///
+    /// The series is validated against the trading calendar: bars for days on
+    /// which the market is closed are dropped and duplicate bars are removed.
+    /// Trading days between the first and last bar that have no bar, such as
+    /// days on which the stock did not trade, are filled in using the previous
+    /// close and are marked as [`filled`](DailyBar::filled).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use chrono::NaiveDate;
+    ///
+    /// use polygon_client::adjust::Adjustment;
+    /// use polygon_client::rest::RESTClient;
+    /// use polygon_client::ticker::StockTicker;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let aapl = StockTicker::new("AAPL").unwrap();
+    ///     let from = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+    ///     let to = NaiveDate::from_ymd_opt(2020, 12, 31).unwrap();
+    ///     let series = client
+    ///         .daily_series(aapl, from..=to, Adjustment::SplitsAndDividends)
+    ///         .await
+    ///         .expect("failed to get daily series");
+    ///     for bar in series.bars {
+    ///         println!("{}: {}", bar.date, bar.close);
+    ///     }
+    /// }
+    /// ```
+    pub async fn daily_series(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        range: RangeInclusive<NaiveDate>,
+        adjustment: Adjustment,
+    ) -> Result<DailySeries, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let (from, to) = (*range.start(), *range.end());
+        let uri = format!(
+            "/v2/aggs/ticker/{}/range/1/day/{}/{}",
+            stocks_ticker,
+            from.format("%Y-%m-%d"),
+            to.format("%Y-%m-%d")
+        );
+        let mut query_params = HashMap::new();
+        query_params.insert("adjusted", "false");
+        query_params.insert("sort", Order::Asc.as_str());
+        query_params.insert("limit", AGGREGATES_PAGE_LIMIT);
+
+        let no_params = HashMap::new();
+        let (mut bars, splits, dividends) = futures::try_join!(
+            self.paginate::<StockEquitiesAggregatesResponse>(&uri, &query_params)
+                .try_collect::<Vec<_>>(),
+            async {
+                if adjustment.includes_splits() {
+                    self.reference_stock_splits(&stocks_ticker, &no_params)
+                        .await
+                        .map(|r| r.results)
+                } else {
+                    Ok(Vec::new())
+                }
+            },
+            async {
+                if adjustment.includes_dividends() {
+                    self.reference_stock_dividends(&stocks_ticker, &no_params)
+                        .await
+                        .map(|r| r.results)
+                } else {
+                    Ok(Vec::new())
+                }
+            },
+        )?;
+
+        bars.sort_by_key(|bar| bar.t);
+        adjust_bars(&mut bars, &splits, &dividends, adjustment);
+
+        Ok(DailySeries {
+            ticker: stocks_ticker.into(),
+            adjustment,
+            bars: continuous_series(&bars, from, to),
+        })
+    }
+}
+
+impl RESTClient {
+    /// Get the top gainers and losers of the day in the stocks/equities
+    /// markets, each with up to `headlines` of its most recent news articles.
+    ///
+    /// The gainers and losers are taken from the
+    /// [/v2/snapshot/locale/{locale}/markets/stocks/{direction}](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks__direction__anchor)
+    /// API, and the headlines of every ticker are then requested concurrently
+    /// from the [/v2/reference/news](https://polygon.io/docs/get_v2_reference_news_anchor)
+    /// API. If any of the requests fails, the first error is returned.
+    pub async fn movers_report(
+        &self,
+        locale: &str,
+        headlines: usize,
+    ) -> Result<MoversReport, Error> {
+        let no_params = HashMap::new();
+        let (gainers, losers) = futures::try_join!(
+            self.stock_equities_snapshot_gainers_losers(locale, "gainers", &no_params),
+            self.stock_equities_snapshot_gainers_losers(locale, "losers", &no_params),
+        )?;
+
+        let (gainers, losers) = futures::try_join!(
+            self.with_headlines(gainers.tickers, headlines),
+            self.with_headlines(losers.tickers, headlines),
+        )?;
+
+        Ok(MoversReport { gainers, losers })
+    }
+
+    async fn with_headlines(
+        &self,
+        snapshots: Vec<StockEquitiesTickerSnapshot>,
+        headlines: usize,
+    ) -> Result<Vec<Mover>, Error> {
+        let limit = headlines.to_string();
+
+        futures::future::try_join_all(snapshots.into_iter().map(|snapshot| {
+            let limit = limit.as_str();
+            async move {
+                if headlines == 0 {
+                    return Ok(Mover {
+                        snapshot,
+                        headlines: Vec::new(),
+                    });
+                }
+
+                let mut news_params = HashMap::new();
+                news_params.insert("ticker", snapshot.ticker.as_str());
+                news_params.insert("order", Order::Desc.as_str());
+                news_params.insert("sort", Sort::PublishedUtc.as_str());
+                news_params.insert("limit", limit);
+                let news = self.reference_ticker_news(&news_params).await?;
+
+                Ok(Mover {
+                    snapshot,
+                    headlines: news.results,
+                })
+            }
+        }))
+        .await
+    }
+
+    /// Get the trailing dividend yield of each of the given stocks and return
+    /// those yielding at least `min_yield_percent`, highest yield first.
+    ///
+    /// The yield is the sum of the dividends with an ex-dividend date in the
+    /// past year divided by the previous day's close. Stocks without a
+    /// previous close are left out. The dividends and previous close of every
+    /// stock are requested concurrently; if any of the requests fails, the
+    /// first error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use polygon_client::rest::RESTClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let yields = client
+    ///         .dividend_screener(&["MSFT", "T", "VZ"], 3.0)
+    ///         .await
+    ///         .expect("failed to screen dividends");
+    ///     for y in yields {
+    ///         println!("{}: {:.2}%", y.ticker, y.yield_percent);
+    ///     }
+    /// }
+    /// ```
+    pub async fn dividend_screener<S: AsRef<str>>(
+        &self,
+        stocks_tickers: &[S],
+        min_yield_percent: f64,
+    ) -> Result<Vec<DividendYield>, Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        match calendar::eastern_date(now) {
+            Some(today) => {
+                self.dividend_screener_as_of(stocks_tickers, min_yield_percent, today)
+                    .await
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn dividend_screener_as_of<S: AsRef<str>>(
+        &self,
+        stocks_tickers: &[S],
+        min_yield_percent: f64,
+        today: NaiveDate,
+    ) -> Result<Vec<DividendYield>, Error> {
+        let one_year_ago = today - Duration::days(365);
+
+        let yields = futures::future::try_join_all(stocks_tickers.iter().map(|ticker| {
+            let ticker = ticker.as_ref();
+            async move {
+                let stocks_ticker = StockTicker::new(ticker)?;
+                let no_params = HashMap::new();
+                let (dividends, previous_close) = futures::try_join!(
+                    self.reference_stock_dividends(&stocks_ticker, &no_params),
+                    self.stock_equities_previous_close(&stocks_ticker, &no_params),
+                )?;
+
+                let close = match previous_close.results.first() {
+                    Some(bar) if bar.c > 0.0 => bar.c,
+                    _ => return Ok::<_, Error>(None),
+                };
+
+                let trailing: Vec<f64> = dividends
+                    .results
+                    .iter()
+                    .filter(|d| {
+                        NaiveDate::parse_from_str(&d.ex_date, "%Y-%m-%d")
+                            .is_ok_and(|date| date > one_year_ago && date <= today)
+                    })
+                    .map(|d| d.amount)
+                    .collect();
+                let trailing_dividends: f64 = trailing.iter().sum();
+
+                Ok(Some(DividendYield {
+                    ticker: String::from(ticker),
+                    close,
+                    trailing_dividends,
+                    dividend_count: trailing.len(),
+                    yield_percent: trailing_dividends / close * 100.0,
+                }))
+            }
+        }))
+        .await?;
+
+        let mut yields: Vec<_> = yields
+            .into_iter()
+            .flatten()
+            .filter(|y| y.yield_percent >= min_yield_percent)
+            .collect();
+        yields.sort_by(|a, b| {
+            b.yield_percent
+                .partial_cmp(&a.yield_percent)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.ticker.cmp(&b.ticker))
+        });
+        Ok(yields)
+    }
+}
+
+/// Converts bars sorted by time into one bar per trading day between the first
+/// and last bar within `from` and `to`, filling in missing days.
+fn continuous_series(
+    bars: &[StockEquitiesAggregates],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<DailyBar> {
+    let mut by_date: Vec<(NaiveDate, &StockEquitiesAggregates)> = bars
+        .iter()
+        .filter_map(|bar| bar_date(bar).map(|date| (date, bar)))
+        .filter(|(date, _)| *date >= from && *date <= to && calendar::is_trading_day(*date))
+        .collect();
+    by_date.dedup_by_key(|(date, _)| *date);
+
+    let (first, last) = match (by_date.first(), by_date.last()) {
+        (Some(first), Some(last)) => (first.0, last.0),
+        _ => return Vec::new(),
+    };
+
+    let mut series: Vec<DailyBar> = Vec::with_capacity(by_date.len());
+    let mut next = by_date.iter().peekable();
+
+    for date in calendar::trading_days(first, last) {
+        match next.peek() {
+            Some((bar_date, bar)) if *bar_date == date => {
+                series.push(DailyBar {
+                    date,
+                    open: bar.o,
+                    high: bar.h,
+                    low: bar.l,
+                    close: bar.c,
+                    volume: bar.v,
+                    vwap: bar.vw,
+                    filled: false,
+                });
+                next.next();
+            }
+            _ => {
+                let close = series.last().map(|b| b.close).unwrap_or_default();
+                series.push(DailyBar {
+                    date,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 0.0,
+                    vwap: None,
+                    filled: true,
+                });
+            }
+        }
+    }
+
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adjust::Adjustment;
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::ticker::StockTicker;
+    use chrono::NaiveDate;
+
+    fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn aggregates_page(bars: &[(NaiveDate, f64)]) -> String {
+        let results = bars
+            .iter()
+            .map(|(date, close)| {
+                let t = date
+                    .and_hms_opt(4, 0, 0)
+                    .unwrap()
+                    .and_utc()
+                    .timestamp_millis();
+                format!(
+                    r#"{{"o":{c},"h":{c},"l":{c},"c":{c},"v":100,"vw":{c},"t":{t}}}"#,
+                    c = close,
+                    t = t
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            r#"{{"ticker":"AAPL","adjusted":false,"queryCount":{n},"resultsCount":{n},"status":"OK","request_id":"1","results":[{r}]}}"#,
+            n = bars.len(),
+            r = results
+        )
+    }
+
+    #[test]
+    fn test_daily_series() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(aggregates_page(&[
+                    (ymd(2020, 8, 27), 400.0),
+                    (ymd(2020, 8, 28), 500.0),
+                    (ymd(2020, 8, 29), 500.0),
+                    (ymd(2020, 9, 1), 100.0),
+                    (ymd(2020, 9, 1), 100.0),
+                ]))
+                .on_path("/v2/aggs/ticker/AAPL/range/1/day/2020-08-01/2020-09-30"),
+                Response::ok(String::from(
+                    r#"{"status":"OK","count":1,"results":[{"ticker":"AAPL","exDate":"2020-08-31","paymentDate":"2020-08-28","ratio":0.25,"tofactor":4,"forfactor":1}]}"#,
+                ))
+                .on_path("/v2/reference/splits/AAPL"),
+            ])
+            .await;
+
+            let client = test_server::client(&server);
+
+            let series = client
+                .daily_series(
+                    StockTicker::new("AAPL").unwrap(),
+                    ymd(2020, 8, 1)..=ymd(2020, 9, 30),
+                    Adjustment::Splits,
+                )
+                .await
+                .unwrap();
+
+            let dates: Vec<_> = series.bars.iter().map(|b| b.date).collect();
+            assert_eq!(
+                dates,
+                vec![
+                    ymd(2020, 8, 27),
+                    ymd(2020, 8, 28),
+                    ymd(2020, 8, 31),
+                    ymd(2020, 9, 1)
+                ]
+            );
+
+            let closes: Vec<_> = series.bars.iter().map(|b| b.close).collect();
+            assert_eq!(closes, vec![100.0, 125.0, 125.0, 100.0]);
+            assert!(series.bars[2].filled);
+            assert_eq!(series.bars[2].volume, 0.0);
+            assert_eq!(series.bars[1].volume, 400.0);
+            assert!(server.requests()[0].contains("adjusted=false"));
+        });
+    }
+
+    #[test]
+    fn test_movers_report() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(include_str!("../../fixtures/ticker_snapshot.json").into())
+                    .on_path("/v2/snapshot/locale/us/markets/stocks/gainers"),
+                Response::ok(String::from(r#"{"status":"OK","tickers":[]}"#))
+                    .on_path("/v2/snapshot/locale/us/markets/stocks/losers"),
+                Response::ok(include_str!("../../fixtures/news.json").into())
+                    .on_path("/v2/reference/news"),
+            ])
+            .await;
+
+            let client = test_server::client(&server);
+
+            let report = client.movers_report("us", 3).await.unwrap();
+            assert!(report.losers.is_empty());
+            assert_eq!(report.gainers.len(), 1);
+            assert_eq!(report.gainers[0].snapshot.ticker, "MSFT");
+            assert_eq!(
+                report.gainers[0].headlines[0].title,
+                "Microsoft Announces Quarterly Dividend"
+            );
+
+            let news = server
+                .requests()
+                .into_iter()
+                .find(|r| r.starts_with("/v2/reference/news"))
+                .unwrap();
+            assert!(news.contains("ticker=MSFT"));
+            assert!(news.contains("limit=3"));
+        });
+    }
+
+    #[test]
+    fn test_dividend_screener() {
+        let dividends = |ticker: &str, amount: f64| {
+            let results = ["2023-02-15", "2023-05-17", "2023-08-16", "2023-11-15", "2022-11-16"]
+                .iter()
+                .map(|date| {
+                    format!(
+                        r#"{{"ticker":"{}","exDate":"{}","paymentDate":"{}","recordDate":"{}","amount":{}}}"#,
+                        ticker, date, date, date, amount
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            format!(r#"{{"status":"OK","count":5,"results":[{}]}}"#, results)
+        };
+        let previous_close = |ticker: &str, close: f64| {
+            format!(
+                r#"{{"ticker":"{}","adjusted":true,"queryCount":1,"resultsCount":1,"status":"OK","request_id":"1","count":1,
+                    "results":[{{"T":"{}","o":{c},"h":{c},"l":{c},"c":{c},"v":1,"t":1704315600000}}]}}"#,
+                ticker,
+                ticker,
+                c = close
+            )
+        };
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(dividends("MSFT", 0.68)).on_path("/v2/reference/dividends/MSFT"),
+                Response::ok(previous_close("MSFT", 370.0)).on_path("/v2/aggs/ticker/MSFT/prev"),
+                Response::ok(dividends("T", 0.2775)).on_path("/v2/reference/dividends/T"),
+                Response::ok(previous_close("T", 16.65)).on_path("/v2/aggs/ticker/T/prev"),
+            ])
+            .await;
+
+            let client = test_server::client(&server);
+
+            let yields = client
+                .dividend_screener_as_of(&["MSFT", "T"], 1.0, ymd(2024, 1, 3))
+                .await
+                .unwrap();
+            assert_eq!(yields.len(), 1);
+            assert_eq!(yields[0].ticker, "T");
+            assert_eq!(yields[0].dividend_count, 4);
+            assert!((yields[0].trailing_dividends - 1.11).abs() < 1e-9);
+            assert!((yields[0].yield_percent - 6.6667).abs() < 1e-4);
+        });
+    }
+}
@@ -0,0 +1,181 @@
+//! Point-in-time lookups of historical quotes and trades.
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::ticker::StockTicker;
+use crate::types::*;
+
+/// The number of requests a batched lookup keeps in flight at once.
+const AS_OF_CONCURRENCY: usize = 8;
+
+/// The number of trades requested by a trade lookup. Trades that share a SIP
+/// timestamp are returned in no particular order, so several are requested
+/// and the tie is broken by sequence number.
+const TRADE_LOOKUP_LIMIT: &str = "50";
+
+impl RESTClient {
+    /// Get the NBBO quote that prevailed for a stock at `timestamp`, given in
+    /// Unix nanoseconds, using the [/v3/quotes/{stocks_ticker}](https://polygon.io/docs/stocks/get_v3_quotes__stockticker) API.
+    ///
+    /// This is the latest quote whose SIP timestamp is at or before
+    /// `timestamp`, found with a single request sorted newest first. Returns
+    /// `None` if there is no such quote, such as before the ticker was
+    /// listed. Rate limited requests are retried according to the client's
+    /// [`RateLimitPolicy`](crate::rest::RateLimitPolicy).
+    pub async fn quote_as_of(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        timestamp: u64,
+    ) -> Result<Option<StockEquitiesQuoteV3>, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let url = format!("{}/v3/quotes/{}", self.inner.api_url, stocks_ticker);
+        let query = [
+            ("timestamp.lte", timestamp.to_string()),
+            ("order", Order::Desc.to_string()),
+            ("sort", Sort::Timestamp.to_string()),
+            ("limit", String::from("1")),
+        ];
+
+        let resp = self
+            .send_url_with_rate_limit::<StockEquitiesQuotesResponseV3, _>(&url, &query)
+            .await?;
+        Ok(resp.results.into_iter().next())
+    }
+
+    /// Get the last trade of a stock at or before `timestamp`, given in Unix
+    /// nanoseconds, using the [/v3/trades/{stocks_ticker}](https://polygon.io/docs/stocks/get_v3_trades__stockticker) API.
+    ///
+    /// A trade whose SIP timestamp equals `timestamp` is included. When
+    /// several trades share the latest timestamp, the one with the highest
+    /// sequence number is returned. Returns `None` if there is no such trade.
+    pub async fn trade_as_of(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        timestamp: u64,
+    ) -> Result<Option<StockEquitiesTradeV3>, Error> {
+        self.last_trade(&stocks_ticker.into(), "timestamp.lte", timestamp)
+            .await
+    }
+
+    /// Get the last trade of a stock strictly before `timestamp`, given in
+    /// Unix nanoseconds.
+    ///
+    /// This is the same as [`RESTClient::trade_as_of()`] except that trades
+    /// at exactly `timestamp` are excluded, which is useful for finding the
+    /// trade that preceded a known trade.
+    pub async fn trade_before(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        timestamp: u64,
+    ) -> Result<Option<StockEquitiesTradeV3>, Error> {
+        self.last_trade(&stocks_ticker.into(), "timestamp.lt", timestamp)
+            .await
+    }
+
+    async fn last_trade(
+        &self,
+        stocks_ticker: &StockTicker,
+        bound: &str,
+        timestamp: u64,
+    ) -> Result<Option<StockEquitiesTradeV3>, Error> {
+        let url = format!("{}/v3/trades/{}", self.inner.api_url, stocks_ticker);
+        let query = [
+            (bound, timestamp.to_string()),
+            ("order", Order::Desc.to_string()),
+            ("sort", Sort::Timestamp.to_string()),
+            ("limit", String::from(TRADE_LOOKUP_LIMIT)),
+        ];
+
+        let resp = self
+            .send_url_with_rate_limit::<StockEquitiesTradesResponseV3, _>(&url, &query)
+            .await?;
+        Ok(resp
+            .results
+            .into_iter()
+            .max_by_key(|t| (t.sip_timestamp, t.sequence_number)))
+    }
+
+    /// Get the prevailing NBBO quote for each `(ticker, timestamp)` pair, as
+    /// with [`RESTClient::quote_as_of()`].
+    ///
+    /// Several requests are sent concurrently, and the quotes are returned in
+    /// the same order as `requests`. The first error fails the whole batch.
+    pub async fn quotes_as_of(
+        &self,
+        requests: &[(StockTicker, u64)],
+    ) -> Result<Vec<Option<StockEquitiesQuoteV3>>, Error> {
+        stream::iter(requests)
+            .map(|(ticker, timestamp)| self.quote_as_of(ticker, *timestamp))
+            .buffered(AS_OF_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::ticker::StockTicker;
+
+    const QUOTE: &str = r#"{"status":"OK","request_id":"1","results":[{"ask_exchange":11,"ask_price":300.5,"ask_size":2,
+        "bid_exchange":12,"bid_price":300.25,"bid_size":3,"participant_timestamp":1633712400123456000,
+        "sequence_number":42,"sip_timestamp":1633712400123456789,"tape":3}],
+        "next_url":"https://api.polygon.io/v3/quotes/MSFT?cursor=abc"}"#;
+
+    #[test]
+    fn test_trade_as_of() {
+        tokio_test::block_on(async {
+            // Two trades share the latest timestamp and are returned out of
+            // sequence order.
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"status":"OK","request_id":"1","results":[
+                    {"exchange":4,"id":"1","price":300.1,"sequence_number":7,"sip_timestamp":1633712400000000000,"size":100},
+                    {"exchange":4,"id":"2","price":300.2,"sequence_number":9,"sip_timestamp":1633712400000000000,"size":100},
+                    {"exchange":4,"id":"3","price":300.0,"sequence_number":5,"sip_timestamp":1633712399000000000,"size":100}]}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            let trade = client
+                .trade_before(StockTicker::new("MSFT").unwrap(), 1633712400000000001)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(trade.id, "2");
+            assert_eq!(
+                server.requests(),
+                vec!["/v3/trades/MSFT?timestamp.lt=1633712400000000001&order=desc&sort=timestamp&limit=50"]
+            );
+        });
+    }
+
+    #[test]
+    fn test_quote_as_of() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(QUOTE)).on_path("/v3/quotes/MSFT"),
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"2","results":[]}"#,
+                ))
+                .on_path("/v3/quotes/NEW"),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let requests = [
+                (StockTicker::new("MSFT").unwrap(), 1633712400200000000),
+                (StockTicker::new("NEW").unwrap(), 1633712400200000000),
+            ];
+            let quotes = client.quotes_as_of(&requests).await.unwrap();
+            let quote = quotes[0].as_ref().unwrap();
+            assert_eq!(quote.bid_price, 300.25);
+            assert_eq!(quote.sip_timestamp, 1633712400123456789);
+            assert!(quotes[1].is_none());
+
+            assert!(server.requests().contains(&String::from(
+                "/v3/quotes/MSFT?timestamp.lte=1633712400200000000&order=desc&sort=timestamp&limit=1"
+            )));
+        });
+    }
+}
@@ -0,0 +1,357 @@
+//! Sharing the request budget of a client between subsystems.
+//!
+//! Every request sent by a [`RESTClient`], including the requests sent by
+//! helpers such as [`RESTClient::paginate()`] and the as-of lookups, goes
+//! through the same rate limit policy, the same
+//! [`throttle`](crate::rest::RESTClientBuilder::throttle), and, if one is
+//! configured with
+//! [`RESTClientBuilder::max_in_flight()`](crate::rest::RESTClientBuilder::max_in_flight),
+//! the same limit on requests in flight. A background job that issues many
+//! requests, such as a backfill of missed bars or a snapshot poller, can
+//! therefore use up the budget of the whole client. Giving it a client
+//! returned by [`RESTClient::partition()`] or
+//! [`RESTClient::partition_builder()`] caps its share of the budget so that
+//! other callers are not starved.
+use std::iter;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::error::Error;
+use crate::rest::throttle::Throttle;
+use crate::rest::{RESTClient, RateLimitPolicy, RetryPolicy};
+
+/// A share of the request budget of a client.
+pub(crate) struct Partition {
+    permits: Option<Arc<Semaphore>>,
+    pub(crate) throttle: Option<Throttle>,
+    retry_policy: Option<RetryPolicy>,
+    rate_limit_policy: Option<RateLimitPolicy>,
+    parent: Option<Arc<Partition>>,
+}
+
+/// The permits held by a request while it is in flight.
+pub(crate) struct RequestPermit {
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+/// A builder used to configure a partition of the request budget of a
+/// [`RESTClient`], returned by [`RESTClient::partition_builder()`].
+///
+/// Each limit applies on top of the limits of the parent client, so a
+/// partition can only ever use less of the budget. Limits that are not set
+/// are those of the parent client.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use polygon_client::rest::{RESTClient, RetryPolicy};
+///
+/// let client = RESTClient::builder()
+///     .auth_key("my-api-key")
+///     .max_in_flight(8)
+///     .throttle(100, Duration::from_secs(60))
+///     .build()
+///     .expect("failed to build client");
+///
+/// // The poller can use 2 of the 8 requests in flight and 20 of the 100
+/// // requests per minute, and does not retry failed requests.
+/// let poller = client
+///     .partition_builder()
+///     .max_in_flight(2)
+///     .throttle(20, Duration::from_secs(60))
+///     .retry_policy(RetryPolicy::disabled())
+///     .build()
+///     .expect("invalid partition");
+/// ```
+#[derive(Clone)]
+pub struct PartitionBuilder {
+    client: RESTClient,
+    max_in_flight: Option<usize>,
+    throttle: Option<(u32, Duration)>,
+    retry_policy: Option<RetryPolicy>,
+    rate_limit_policy: Option<RateLimitPolicy>,
+}
+
+impl PartitionBuilder {
+    /// Limits the number of requests that the partition has in flight at
+    /// once.
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    /// Paces requests so that the partition sends at most `requests`
+    /// requests per `window`, retries included.
+    pub fn throttle(mut self, requests: u32, window: Duration) -> Self {
+        self.throttle = Some((requests, window));
+        self
+    }
+
+    /// Sets the policy used when a request of the partition fails with a
+    /// transient error.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Sets the policy used when the server rate limits a request of the
+    /// partition.
+    pub fn rate_limit_policy(mut self, policy: RateLimitPolicy) -> Self {
+        self.rate_limit_policy = Some(policy);
+        self
+    }
+
+    /// Builds a client that sends its requests through the partition.
+    ///
+    /// Returns [`Error::Config`] if `max_in_flight` is zero, or if the
+    /// throttle allows no requests or has an empty window.
+    pub fn build(self) -> Result<RESTClient, Error> {
+        if self.max_in_flight == Some(0) {
+            return Err(Error::Config(String::from(
+                "max_in_flight must be greater than zero",
+            )));
+        }
+        if let Some((requests, window)) = self.throttle {
+            if requests == 0 || window.is_zero() {
+                return Err(Error::Config(String::from(
+                    "throttle must allow at least one request per non-empty window",
+                )));
+            }
+        }
+
+        Ok(RESTClient {
+            partition: Some(Arc::new(Partition {
+                permits: self.max_in_flight.map(|n| Arc::new(Semaphore::new(n))),
+                throttle: self
+                    .throttle
+                    .map(|(requests, window)| Throttle::new(requests, window)),
+                retry_policy: self.retry_policy,
+                rate_limit_policy: self.rate_limit_policy,
+                parent: self.client.partition.clone(),
+            })),
+            ..self.client
+        })
+    }
+}
+
+impl RESTClient {
+    /// Returns a client that shares the connection pool, configuration, and
+    /// request budget of this client, but has at most `max_in_flight`
+    /// requests in flight at once.
+    ///
+    /// Requests sent through the returned client count against both its own
+    /// limit and the limits of this client, so a partition of a partition is
+    /// bounded by both. Use [`RESTClient::partition_builder()`] to also cap
+    /// the share of the throttle and the retries of the partition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use polygon_client::rest::RESTClient;
+    ///
+    /// let client = RESTClient::builder()
+    ///     .auth_key("my-api-key")
+    ///     .max_in_flight(8)
+    ///     .build()
+    ///     .expect("failed to build client");
+    ///
+    /// // The backfill job can use at most 2 of the 8 requests in flight.
+    /// let backfill_client = client.partition(2).expect("invalid partition");
+    /// ```
+    ///
+    /// Returns [`Error::Config`] if `max_in_flight` is zero.
+    pub fn partition(&self, max_in_flight: usize) -> Result<RESTClient, Error> {
+        self.partition_builder()
+            .max_in_flight(max_in_flight)
+            .build()
+    }
+
+    /// Returns a builder of a client that shares the connection pool,
+    /// configuration, and request budget of this client, but with its own
+    /// limits on top of those of this client.
+    pub fn partition_builder(&self) -> PartitionBuilder {
+        PartitionBuilder {
+            client: self.clone(),
+            max_in_flight: None,
+            throttle: None,
+            retry_policy: None,
+            rate_limit_policy: None,
+        }
+    }
+
+    /// Returns the partitions of the client, from the innermost outwards.
+    pub(crate) fn partitions(&self) -> impl Iterator<Item = &Partition> {
+        iter::successors(self.partition.as_deref(), |p| p.parent.as_deref())
+    }
+
+    /// Returns the retry policy of the innermost partition that sets one, or
+    /// else that of the client.
+    pub(crate) fn retry_policy(&self) -> &RetryPolicy {
+        self.partitions()
+            .find_map(|p| p.retry_policy.as_ref())
+            .unwrap_or(&self.inner.retry_policy)
+    }
+
+    /// Returns the rate limit policy of the innermost partition that sets
+    /// one, or else that of the client.
+    pub(crate) fn rate_limit_policy(&self) -> RateLimitPolicy {
+        self.partitions()
+            .find_map(|p| p.rate_limit_policy)
+            .unwrap_or(self.inner.rate_limit_policy)
+    }
+
+    /// Waits until the request budget allows another request to be sent.
+    ///
+    /// Permits are acquired from the innermost partition outwards and from
+    /// the client last, the same order for every request, so requests waiting
+    /// on different partitions cannot deadlock.
+    pub(crate) async fn acquire_permit(&self) -> RequestPermit {
+        let mut permits = Vec::new();
+
+        for partition in self.partitions() {
+            if let Some(semaphore) = &partition.permits {
+                permits.extend(acquire(semaphore).await);
+            }
+        }
+        if let Some(max_in_flight) = &self.inner.max_in_flight {
+            permits.extend(acquire(max_in_flight).await);
+        }
+
+        RequestPermit { _permits: permits }
+    }
+}
+
+async fn acquire(semaphore: &Arc<Semaphore>) -> Option<OwnedSemaphorePermit> {
+    // The semaphores are never closed, but if one were there would be no
+    // permits left to wait for.
+    semaphore.clone().acquire_owned().await.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use crate::error::Error;
+    use crate::rest::RESTClient;
+
+    #[test]
+    fn test_partition() {
+        tokio_test::block_on(async {
+            let client = RESTClient::builder()
+                .auth_key("test")
+                .max_in_flight(2)
+                .build()
+                .unwrap();
+            let background = client.partition(1).unwrap();
+            let wait = Duration::from_millis(50);
+
+            let _first = background.acquire_permit().await;
+            // The partition is exhausted but the client still has a permit.
+            assert!(tokio::time::timeout(wait, background.acquire_permit())
+                .await
+                .is_err());
+            let second = tokio::time::timeout(wait, client.acquire_permit())
+                .await
+                .unwrap();
+
+            // The client is exhausted, which also blocks other partitions.
+            let other = client.partition(1).unwrap();
+            assert!(tokio::time::timeout(wait, other.acquire_permit())
+                .await
+                .is_err());
+            drop(second);
+            assert!(tokio::time::timeout(wait, other.acquire_permit())
+                .await
+                .is_ok());
+            assert!(client.partition(0).is_err());
+        });
+    }
+
+    #[test]
+    fn test_partition_throttle() {
+        tokio_test::block_on(async {
+            let client = RESTClient::builder()
+                .auth_key("test")
+                .throttle(10, Duration::from_millis(100))
+                .build()
+                .unwrap();
+            let background = client
+                .partition_builder()
+                .throttle(1, Duration::from_millis(100))
+                .build()
+                .unwrap();
+
+            // The partition runs out of requests long before the client.
+            let start = Instant::now();
+            background.throttle().await;
+            client.throttle().await;
+            assert!(start.elapsed() < Duration::from_millis(50));
+            background.throttle().await;
+            assert!(start.elapsed() >= Duration::from_millis(90));
+
+            let invalid = client
+                .partition_builder()
+                .throttle(0, Duration::from_secs(1));
+            assert!(matches!(invalid.build(), Err(Error::Config(_))));
+        });
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
+    #[test]
+    fn test_partition_retry_policy() {
+        use std::collections::HashMap;
+
+        use crate::rest::test_server::{self, Response, TestServer};
+        use crate::rest::RetryPolicy;
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::error(503, ""),
+                Response::ok(String::from(r#"{"status":"OK"}"#)),
+            ])
+            .await;
+            let client = test_server::builder(&server)
+                .retry_policy(RetryPolicy::new(3, Duration::ZERO))
+                .build()
+                .unwrap();
+            let background = client
+                .partition_builder()
+                .retry_policy(RetryPolicy::disabled())
+                .build()
+                .unwrap();
+
+            // The partition does not retry, while the client does.
+            let no_params = HashMap::new();
+            let err = background
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            assert_eq!(err.status(), Some(503));
+            let resp = client
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap();
+            assert_eq!(resp["status"], "OK");
+
+            let nested = background.partition(1).unwrap();
+            assert_eq!(nested.retry_policy(), &RetryPolicy::disabled());
+            assert_eq!(client.retry_policy().max_attempts, 3);
+        });
+    }
+}
@@ -0,0 +1,261 @@
+//! Failing fast while the API is degraded.
+//!
+//! When the API returns server errors or stops answering, every request a
+//! busy application sends, and every retry of it, waits for its own failure
+//! and adds to the load on the API. A client built with
+//! [`RESTClientBuilder::circuit_breaker()`](crate::rest::RESTClientBuilder::circuit_breaker)
+//! counts the consecutive failures of its requests and, once they reach the
+//! threshold of its [`CircuitBreakerPolicy`], opens the circuit: requests
+//! then fail right away with [`Error::CircuitOpen`] until the cooldown has
+//! passed. The next request after the cooldown is sent as a probe, and closes
+//! the circuit if it succeeds or opens it again if it fails. Other requests
+//! keep failing fast while the probe is in flight.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::runtime::{self, Instant};
+
+/// The policy of a circuit breaker that fails requests fast after repeated
+/// failures of the API.
+///
+/// A failure is a response with a `5xx` status code, a timeout, or a
+/// connection that could not be established. Any other response, even an
+/// unsuccessful one such as `404 Not Found`, shows that the API is up and
+/// resets the count.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use polygon_client::rest::{CircuitBreakerPolicy, RESTClient};
+///
+/// let client = RESTClient::builder()
+///     .auth_key("my-api-key")
+///     .circuit_breaker(CircuitBreakerPolicy::new(5, Duration::from_secs(30)))
+///     .build()
+///     .expect("failed to build client");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitBreakerPolicy {
+    /// The number of consecutive failures that opens the circuit.
+    pub failure_threshold: u32,
+    /// How long requests fail fast once the circuit is open.
+    pub cooldown: Duration,
+}
+
+impl CircuitBreakerPolicy {
+    /// Returns a new circuit breaker policy.
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreakerPolicy {
+            failure_threshold,
+            cooldown,
+        }
+    }
+}
+
+/// The state of the circuit of a client and its clones.
+pub(crate) struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    state: Mutex<CircuitState>,
+    /// Set while the request probing the API after the cooldown is in flight.
+    probing: AtomicBool,
+}
+
+/// The right of a request to probe the API after the cooldown, released when
+/// the request completes or is dropped.
+#[derive(Debug)]
+pub(crate) struct Probe<'a> {
+    probing: &'a AtomicBool,
+}
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        self.probing.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Default)]
+struct CircuitState {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(policy: CircuitBreakerPolicy) -> Self {
+        CircuitBreaker {
+            policy,
+            state: Mutex::new(CircuitState::default()),
+            probing: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns an error if the circuit is open at `now`, or the probe to hold
+    /// while the request is in flight if the cooldown has passed.
+    fn check(&self, now: Instant) -> Result<Option<Probe<'_>>, Error> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match state.open_until {
+            Some(until) if until > now => Err(Error::CircuitOpen {
+                retry_after: until - now,
+            }),
+            Some(_) => {
+                let acquired = self
+                    .probing
+                    .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok();
+                if acquired {
+                    Ok(Some(Probe {
+                        probing: &self.probing,
+                    }))
+                } else {
+                    Err(Error::CircuitOpen {
+                        retry_after: Duration::ZERO,
+                    })
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Records the outcome of a request that completed at `now`.
+    fn record(&self, failed: bool, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if failed {
+            state.failures = state.failures.saturating_add(1);
+            if state.failures >= self.policy.failure_threshold {
+                state.open_until = Some(now + self.policy.cooldown);
+            }
+        } else {
+            *state = CircuitState::default();
+        }
+    }
+}
+
+/// Returns `true` if a request that failed with `err` counts against the
+/// circuit.
+pub(crate) fn is_failure(err: &reqwest::Error) -> bool {
+    err.is_timeout() || runtime::is_connect(err)
+}
+
+impl RESTClient {
+    /// Returns an error if the client's circuit is open, or the probe to
+    /// hold while the request is in flight if its cooldown has passed.
+    pub(crate) fn check_circuit(&self) -> Result<Option<Probe<'_>>, Error> {
+        match &self.inner.circuit_breaker {
+            Some(breaker) => breaker.check(Instant::now()),
+            None => Ok(None),
+        }
+    }
+
+    /// Records whether a request failed in a way that counts against the
+    /// client's circuit.
+    pub(crate) fn record_circuit(&self, failed: bool) {
+        if let Some(breaker) = &self.inner.circuit_breaker {
+            breaker.record(failed, Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::error::Error;
+    use crate::rest::circuit::{CircuitBreaker, CircuitBreakerPolicy};
+    use crate::runtime::Instant;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(CircuitBreakerPolicy::new(2, Duration::from_secs(30)));
+        let start = Instant::now();
+
+        breaker.record(true, start);
+        breaker.record(false, start);
+        breaker.record(true, start);
+        assert!(matches!(breaker.check(start), Ok(None)));
+
+        breaker.record(true, start);
+        let later = start + Duration::from_secs(10);
+        match breaker.check(later) {
+            Err(Error::CircuitOpen { retry_after }) => {
+                assert_eq!(retry_after, Duration::from_secs(20))
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // After the cooldown, a single failure opens the circuit again.
+        let after_cooldown = start + Duration::from_secs(30);
+        let probe = breaker.check(after_cooldown);
+        assert!(matches!(probe, Ok(Some(_))));
+        breaker.record(true, after_cooldown);
+        drop(probe);
+        assert!(breaker.check(after_cooldown).is_err());
+    }
+
+    #[test]
+    fn test_circuit_breaker_admits_one_probe() {
+        let breaker = CircuitBreaker::new(CircuitBreakerPolicy::new(1, Duration::from_secs(30)));
+        let start = Instant::now();
+        breaker.record(true, start);
+
+        let after_cooldown = start + Duration::from_secs(30);
+        let probe = breaker.check(after_cooldown).unwrap();
+        assert!(probe.is_some());
+        // Other requests fail fast while the probe is in flight.
+        assert!(matches!(
+            breaker.check(after_cooldown),
+            Err(Error::CircuitOpen { retry_after }) if retry_after == Duration::ZERO
+        ));
+
+        // A probe dropped before it completed lets the next request probe.
+        drop(probe);
+        let probe = breaker.check(after_cooldown).unwrap();
+        assert!(probe.is_some());
+        breaker.record(false, after_cooldown);
+        drop(probe);
+        assert!(matches!(breaker.check(after_cooldown), Ok(None)));
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
+    #[test]
+    fn test_circuit_opens_on_server_errors() {
+        use std::collections::HashMap;
+
+        use crate::rest::test_server::{self, Response, TestServer};
+        use crate::rest::RetryPolicy;
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::error(503, "<html>Service Unavailable</html>"),
+                Response::error(500, "<html>Internal Server Error</html>"),
+            ])
+            .await;
+            let client = test_server::builder(&server)
+                .retry_policy(RetryPolicy::new(5, Duration::from_millis(1)))
+                .circuit_breaker(CircuitBreakerPolicy::new(2, Duration::from_secs(60)))
+                .build()
+                .unwrap();
+
+            let err = client
+                .send_request::<serde_json::Value>("/v1/test", &HashMap::new())
+                .await
+                .unwrap_err();
+            // The third attempt fails fast instead of being retried.
+            assert!(matches!(err, Error::CircuitOpen { .. }));
+            assert_eq!(server.requests().len(), 2);
+        });
+    }
+}
@@ -0,0 +1,44 @@
+//! Handling of failed requests by the helpers that combine several endpoints.
+#[cfg(all(feature = "reference", feature = "stocks"))]
+use std::sync::Arc;
+
+#[cfg(all(feature = "reference", feature = "stocks"))]
+use crate::error::Error;
+#[cfg(all(feature = "reference", feature = "stocks"))]
+use crate::types::PartError;
+
+/// What a helper that combines several endpoints, such as
+/// [`RESTClient::ticker_overview()`](crate::rest::RESTClient::ticker_overview),
+/// does when one of its requests fails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompositePolicy {
+    /// Return the error of the first part that failed.
+    #[default]
+    FailFast,
+    /// Return the parts that succeeded, leaving out those that failed and
+    /// listing their errors in the result.
+    Partial,
+}
+
+/// Collects the result of one part of a composite helper, returning `None`
+/// and recording the error if the part failed under
+/// [`CompositePolicy::Partial`].
+#[cfg(all(feature = "reference", feature = "stocks"))]
+pub(crate) fn collect_part<T>(
+    policy: CompositePolicy,
+    part: &'static str,
+    result: Result<T, Error>,
+    errors: &mut Vec<PartError>,
+) -> Result<Option<T>, Error> {
+    match (result, policy) {
+        (Ok(v), _) => Ok(Some(v)),
+        (Err(e), CompositePolicy::FailFast) => Err(e),
+        (Err(e), CompositePolicy::Partial) => {
+            errors.push(PartError {
+                part,
+                error: Arc::new(e),
+            });
+            Ok(None)
+        }
+    }
+}
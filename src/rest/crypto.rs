@@ -0,0 +1,309 @@
+//! Crypto endpoints such as exchanges and aggregates.
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rest::{RESTClient, TRADES_MAX_LIMIT};
+use crate::ticker::CryptoTicker;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get a list of cryptocurrency exchanges which are supported by polygon.io
+    /// using the [/v1/meta/crypto-exchanges](https://polygon.io/docs/get_v1_meta_crypto-exchanges_anchor) API.
+    pub async fn crypto_crypto_exchanges(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<CryptoCryptoExchangesResponse, Error> {
+        self.send_request::<CryptoCryptoExchangesResponse>(
+            "/v1/meta/crypto-exchanges",
+            query_params,
+        )
+        .await
+    }
+
+    /// Get the open and close prices of a cryptocurrency symbol on a certain day
+    /// using [/v1/open-close/crypto/{from}/{to}/{date}](https://polygon.io/docs/get_v1_open-close_crypto__from___to___date__anchor) API.
+    pub async fn crypto_daily_open_close(
+        &self,
+        from: &str,
+        to: &str,
+        date: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<CryptoDailyOpenCloseResponse, Error> {
+        let uri = format!("/v1/open-close/crypto/{}/{}/{}", from, to, date);
+        self.send_request::<CryptoDailyOpenCloseResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get aggregate bars for a cryptocurrency over a given date range in custom
+    /// time window sizes using the [/v2/aggs/ticker/{cryptoTicker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__cryptoTicker__range__multiplier___timespan___from___to__anchor) API.
+    pub async fn crypto_aggregates(
+        &self,
+        crypto_ticker: impl Into<CryptoTicker>,
+        multiplier: u32,
+        timespan: Timespan,
+        from: &str,
+        to: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<CryptoAggregatesResponse, Error> {
+        let crypto_ticker: CryptoTicker = crypto_ticker.into();
+        let resp = self
+            .send_aggregates(
+                crypto_ticker.as_str(),
+                multiplier,
+                timespan,
+                from,
+                to,
+                query_params,
+            )
+            .await?;
+        Ok(resp.into())
+    }
+
+    /// Get trades for a cryptocurrency using the
+    /// [/v3/trades/{crypto_ticker}](https://polygon.io/docs/crypto/get_v3_trades__cryptoticker) API.
+    ///
+    /// A single page of trades is returned. Pass [`CryptoTradesResponseV3`] to
+    /// [`RESTClient::paginate()`] to stream every page.
+    pub async fn crypto_trades(
+        &self,
+        crypto_ticker: impl Into<CryptoTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<CryptoTradesResponseV3, Error> {
+        let crypto_ticker: CryptoTicker = crypto_ticker.into();
+        let uri = format!("/v3/trades/{}", crypto_ticker);
+        self.send_limited_request::<CryptoTradesResponseV3>(&uri, query_params, TRADES_MAX_LIMIT)
+            .await
+    }
+
+    /// Get the daily open, high, low, and close for the entire crypto markets
+    /// using the [/v2/aggs/grouped/locale/global/market/crypto/{date}](https://polygon.io/docs/get_v2_aggs_grouped_locale_global_market_crypto__date__anchor) API.
+    pub async fn crypto_grouped_daily(
+        &self,
+        date: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<CryptoGroupedDailyResponse, Error> {
+        let uri = format!("/v2/aggs/grouped/locale/global/market/crypto/{}", date);
+        self.send_request::<CryptoGroupedDailyResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the previous day's open, high, low, and close for the specified
+    /// cryptocurrency using the [/v2/aggs/ticker/{crypto_ticker}/prev](https://polygon.io/docs/get_v2_aggs_ticker__cryptoTicker__prev_anchor) API.
+    pub async fn crypto_previous_close(
+        &self,
+        crypto_ticker: impl Into<CryptoTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<CryptoPreviousCloseResponse, Error> {
+        let crypto_ticker: CryptoTicker = crypto_ticker.into();
+        let uri = format!("/v2/aggs/ticker/{}/prev", crypto_ticker);
+        self.send_request::<CryptoPreviousCloseResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the last trade for a crypto pair using the
+    /// [/v1/last/crypto/{from}/{to}](https://polygon.io/docs/crypto/get_v1_last_crypto__from___to) API.
+    pub async fn crypto_last_trade(
+        &self,
+        from: &str,
+        to: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<CryptoLastTradeResponse, Error> {
+        let uri = format!("/v1/last/crypto/{}/{}", from, to);
+        self.send_request::<CryptoLastTradeResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the current level 2 book of a crypto pair using the
+    /// [/v2/snapshot/locale/global/markets/crypto/tickers/{ticker}/book](https://polygon.io/docs/crypto/get_v2_snapshot_locale_global_markets_crypto_tickers__ticker__book) API.
+    ///
+    /// The book can be converted into a [`DepthBook`](crate::depth::DepthBook).
+    pub async fn crypto_l2_book(
+        &self,
+        crypto_ticker: impl Into<CryptoTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<CryptoL2BookResponse, Error> {
+        let crypto_ticker: CryptoTicker = crypto_ticker.into();
+        let uri = format!(
+            "/v2/snapshot/locale/global/markets/crypto/tickers/{}/book",
+            crypto_ticker
+        );
+        self.send_request::<CryptoL2BookResponse>(&uri, query_params)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::RESTClient;
+    use crate::ticker::CryptoTicker;
+    use crate::types::{CryptoTradesResponseV3, Timespan};
+    use futures::TryStreamExt;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_crypto_crypto_exchanges() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).crypto_crypto_exchanges(&query_params),
+        )
+        .unwrap();
+        assert_ne!(resp.len(), 0);
+        let coinbase = resp.iter().find(|x| x.name == "Coinbase");
+        assert!(coinbase.is_some());
+    }
+
+    #[test]
+    fn test_crypto_daily_open_close() {
+        let mut query_params = HashMap::new();
+        query_params.insert("adjusted", "true");
+        let resp = tokio_test::block_on(RESTClient::new(None, None).crypto_daily_open_close(
+            "BTC",
+            "USD",
+            "2020-10-14",
+            &query_params,
+        ))
+        .unwrap();
+        assert_eq!(resp.symbol, "BTC-USD");
+        assert!(resp.is_utc);
+        assert_eq!(resp.open, 11443f64);
+        assert_eq!(resp.close, 11427.7);
+    }
+
+    #[test]
+    fn test_crypto_aggregates() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(RESTClient::new(None, None).crypto_aggregates(
+            CryptoTicker::new("X:BTCUSD").unwrap(),
+            1,
+            Timespan::Day,
+            "2020-10-14",
+            "2020-10-14",
+            &query_params,
+        ))
+        .unwrap();
+        assert_eq!(resp.ticker, "X:BTCUSD");
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.query_count, 1);
+        assert_eq!(resp.results_count, 1);
+        let result = resp.results.first().unwrap();
+        assert_eq!(result.vw.unwrap(), 11405.5019);
+        assert_eq!(result.o, 11443f64);
+        assert_eq!(result.c, 11427.7);
+        assert_eq!(result.h, 11564f64);
+        assert_eq!(result.l, 11284.27);
+        assert_eq!(result.t.unwrap(), 1602633600000);
+        assert_eq!(result.n.unwrap(), 142439f64);
+    }
+
+    #[test]
+    fn test_crypto_grouped_daily() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).crypto_grouped_daily("2020-10-14", &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        let msft = resp
+            .results
+            .iter()
+            .find(|x| x.T.is_some() && x.T.as_ref().unwrap() == "X:LTCUSD");
+        assert!(msft.is_some());
+        assert!(msft.unwrap().vw.is_some());
+        assert_eq!(msft.unwrap().vw.unwrap(), 50.1376);
+        assert_eq!(msft.unwrap().o, 49.981);
+        assert_eq!(msft.unwrap().h, 51.095);
+        assert_eq!(msft.unwrap().l, 49.2427);
+    }
+
+    #[test]
+    fn test_crypto_previous_close() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .crypto_previous_close(CryptoTicker::new("X:BTCUSD").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.ticker, "X:BTCUSD");
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results_count, 1);
+        let result = resp.results.first();
+        assert!(result.is_some());
+        assert!(result.unwrap().T.is_some());
+        assert_eq!(result.unwrap().T.as_ref().unwrap(), "X:BTCUSD");
+    }
+
+    #[test]
+    fn test_crypto_last_trade() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(RESTClient::new(None, None).crypto_last_trade(
+            "BTC",
+            "USD",
+            &query_params,
+        ))
+        .unwrap();
+        assert_eq!(resp.symbol, "BTC-USD");
+    }
+
+    #[test]
+    fn test_crypto_l2_book() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .crypto_l2_book(CryptoTicker::new("X:BTCUSD").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.data.ticker, "X:BTCUSD");
+    }
+
+    #[test]
+    fn test_crypto_trades_pagination() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"1","results":[
+                        {"conditions":[1],"exchange":1,"id":"191450340","participant_timestamp":1625097600103000000,"price":35060,"size":1.0434526}],
+                        "next_url":"https://api.polygon.io/v3/trades/X:BTC-USD?cursor=abc"}"#,
+                )),
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"2","results":[
+                        {"exchange":2,"id":"191450341","participant_timestamp":1625097600368000000,"price":35059.99,"size":0.0058883}]}"#,
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("limit", "1");
+            let trades: Vec<_> = client
+                .paginate::<CryptoTradesResponseV3>("/v3/trades/X:BTC-USD", &query_params)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(trades.len(), 2);
+            assert_eq!(trades[0].conditions, vec![1]);
+            assert_eq!(trades[1].price, 35059.99);
+            assert!(trades[1].conditions.is_empty());
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/v3/trades/X:BTC-USD?limit=1",
+                    "/v3/trades/X:BTC-USD?cursor=abc"
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_crypto_trades() {
+        let mut query_params = HashMap::new();
+        query_params.insert("limit", "10");
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .crypto_trades(CryptoTicker::new("X:BTC-USD").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results.len(), 10);
+    }
+}
@@ -0,0 +1,154 @@
+//! Health checks of the streamed data path against the last trades.
+use std::time::Duration;
+
+use futures::future::join_all;
+use futures::{stream, Stream};
+
+use crate::diagnostics::{QualityReport, StreamMonitor};
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::runtime;
+
+impl RESTClient {
+    /// Compare the last streamed trade of each symbol tracked by `monitor`
+    /// with its last trade from [`RESTClient::last_price()`].
+    ///
+    /// One request is sent per tracked symbol, concurrently, so only a few
+    /// symbols should be tracked. A failed request does not fail the report;
+    /// its error is recorded in [`QualitySample::rest_error`] instead.
+    ///
+    /// [`QualitySample::rest_error`]: crate::diagnostics::QualitySample::rest_error
+    pub async fn sample_data_quality(
+        &self,
+        monitor: &StreamMonitor,
+    ) -> Result<QualityReport, Error> {
+        let prices = join_all(monitor.symbols().iter().map(|s| self.last_price(s))).await;
+        let rest_trades = monitor
+            .symbols()
+            .iter()
+            .zip(&prices)
+            .map(|(symbol, price)| {
+                let trade = price.as_ref().ok().map(|p| (p.price(), p.timestamp()));
+                (symbol, trade)
+            })
+            .collect();
+
+        let mut report = QualityReport::new(monitor, rest_trades);
+        for (sample, price) in report.samples.iter_mut().zip(prices) {
+            sample.rest_error = price.err().map(|e| e.to_string());
+        }
+        Ok(report)
+    }
+
+    /// Returns a stream that yields a [`QualityReport`] of the symbols tracked
+    /// by `monitor` every `interval`, starting immediately.
+    ///
+    /// The stream never ends. A failed sample is yielded as an error and does
+    /// not stop later samples, so the stream can be consumed by a background
+    /// task that reports the health of the data path for as long as the
+    /// application runs. An `interval` shorter than a millisecond samples
+    /// every millisecond. Call it on a [`RESTClient::partition()`] to keep the
+    /// checks from using the budget of other requests.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use futures::StreamExt;
+    /// use polygon_client::diagnostics::{QualityThresholds, StreamMonitor};
+    /// use polygon_client::rest::RESTClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let poller = client.partition(1).expect("invalid partition");
+    ///     // Record each streamed trade with `monitor.record(&trade)`.
+    ///     let monitor = StreamMonitor::new(&["MSFT", "AAPL"]);
+    ///
+    ///     let checks = poller.data_quality_checks(monitor, Duration::from_secs(60));
+    ///     let mut checks = Box::pin(checks);
+    ///     while let Some(report) = checks.next().await {
+    ///         let report = report.unwrap();
+    ///         for sample in report.unhealthy(QualityThresholds::default()) {
+    ///             println!("{}: {:?}", sample.symbol, sample);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn data_quality_checks(
+        &self,
+        monitor: StreamMonitor,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<QualityReport, Error>> + '_ {
+        let ticks = runtime::interval(interval.max(Duration::from_millis(1)));
+        stream::unfold((ticks, monitor), move |(mut ticks, monitor)| async move {
+            ticks.tick().await;
+            let report = self.sample_data_quality(&monitor).await;
+            Some((report, (ticks, monitor)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+
+    use crate::diagnostics::StreamMonitor;
+    use crate::events::TradeEvent;
+    use crate::rest::test_server::{self, Response, TestServer};
+
+    #[test]
+    fn test_data_quality_checks() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(include_str!("../../fixtures/last_trade.json").into())
+                    .on_path("/v2/last/trade/MSFT"),
+                Response::ok(String::from(
+                    r#"{"last":{"conditions":[1],"exchange":4,"price":16835.42,"size":0.006909,"timestamp":1605560885027},"request_id":"1","status":"success","symbol":"BTC-USD"}"#,
+                ))
+                .on_path("/v1/last/crypto/BTC/USD"),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let monitor = StreamMonitor::new(&["MSFT", "X:BTCUSD"]);
+            monitor.record_at(
+                &TradeEvent::new("MSFT", 294.9, 100.0, 1633728000000),
+                1633728000100,
+            );
+
+            let mut checks =
+                Box::pin(client.data_quality_checks(monitor, Duration::from_millis(1)));
+            let report = checks.next().await.unwrap().unwrap();
+            assert_eq!(report.samples.len(), 2);
+            assert_eq!(report.samples[0].divergence(), Some(0.0));
+            assert_eq!(report.samples[0].staleness_millis(), Some(123));
+            assert_eq!(report.samples[0].latency_millis(), Some(100));
+            assert_eq!(report.missing(), vec!["X:BTCUSD"]);
+            assert_eq!(report.samples[1].rest_price, Some(16835.42));
+        });
+    }
+
+    #[test]
+    fn test_sample_data_quality_records_errors() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(include_str!("../../fixtures/last_trade.json").into())
+                    .on_path("/v2/last/trade/MSFT"),
+                Response::error(404, r#"{"status":"NOT_FOUND"}"#).on_path("/v2/last/trade/AAPL"),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let monitor = StreamMonitor::new(&["MSFT", "AAPL"]);
+            let report = client.sample_data_quality(&monitor).await.unwrap();
+            assert!(report.samples[0].rest_price.is_some());
+            assert!(report.samples[0].rest_error.is_none());
+            assert_eq!(report.samples[1].rest_price, None);
+            assert!(report.samples[1].rest_error.is_some());
+        });
+    }
+}
@@ -0,0 +1,104 @@
+//! Economy endpoints such as inflation and treasury yields.
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get monthly inflation measures such as the CPI and PCE price index
+    /// using the [/fed/v1/inflation](https://polygon.io/docs/rest/economy/inflation) API.
+    pub async fn economy_inflation(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<EconomyInflationResponse, Error> {
+        self.send_request::<EconomyInflationResponse>("/fed/v1/inflation", query_params)
+            .await
+    }
+
+    /// Get market and model based inflation expectations using the
+    /// [/fed/v1/inflation-expectations](https://polygon.io/docs/rest/economy/inflation-expectations) API.
+    pub async fn economy_inflation_expectations(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<EconomyInflationExpectationsResponse, Error> {
+        self.send_request::<EconomyInflationExpectationsResponse>(
+            "/fed/v1/inflation-expectations",
+            query_params,
+        )
+        .await
+    }
+
+    /// Get daily treasury yields at constant maturities using the
+    /// [/fed/v1/treasury-yields](https://polygon.io/docs/rest/economy/treasury-yields) API.
+    pub async fn economy_treasury_yields(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<EconomyTreasuryYieldsResponse, Error> {
+        self.send_request::<EconomyTreasuryYieldsResponse>("/fed/v1/treasury-yields", query_params)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::RESTClient;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_economy_inflation() {
+        let mut query_params = HashMap::new();
+        query_params.insert("date", "2024-01-01");
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).economy_inflation(&query_params))
+                .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(
+            resp.results[0].date,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_economy_responses() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"1","results":[{"date":"2024-01-01","cpi":308.417,
+                        "cpi_core":314.438,"cpi_year_over_year":3.09,"pce":121.949,"pce_core":122.3}]}"#,
+                )),
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"2","results":[{"date":"2024-01-01","market_5_year":2.17,
+                        "market_10_year":2.28,"forward_years_5_to_10":2.39,"model_1_year":2.9}]}"#,
+                )),
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"3","results":[{"date":"2024-01-02","yield_1_month":5.55,
+                        "yield_10_year":3.95,"yield_30_year":4.1}],
+                        "next_url":"https://api.polygon.io/fed/v1/treasury-yields?cursor=abc"}"#,
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+            let query_params = HashMap::new();
+
+            let inflation = client.economy_inflation(&query_params).await.unwrap();
+            assert_eq!(inflation.results[0].cpi_year_over_year, Some(3.09));
+            assert_eq!(inflation.results[0].pce_spending, None);
+
+            let expectations = client
+                .economy_inflation_expectations(&query_params)
+                .await
+                .unwrap();
+            assert_eq!(expectations.results[0].forward_years_5_to_10, Some(2.39));
+
+            let yields = client.economy_treasury_yields(&query_params).await.unwrap();
+            let first = &yields.results[0];
+            assert_eq!(first.date, NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+            assert_eq!(first.yield_10_year, Some(3.95));
+            assert!(yields.next_url.is_some());
+        });
+    }
+}
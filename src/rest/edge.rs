@@ -0,0 +1,155 @@
+//! Headers identifying the end user of a Launchpad request.
+//!
+//! [Launchpad](https://polygon.io/launchpad) lets a reseller query the API on
+//! behalf of its own users, and requires every request to say which user it
+//! is for through the `X-Polygon-Edge-*` headers. A client sends the
+//! [`EdgeHeaders`] given to
+//! [`RESTClientBuilder::edge_headers()`](crate::rest::RESTClientBuilder::edge_headers)
+//! with every request, and [`RESTClient::with_edge_headers()`] returns a
+//! handle that sends the headers of another user while sharing everything
+//! else with the client.
+use std::sync::Arc;
+
+use crate::rest::RESTClient;
+
+const EDGE_ID: &str = "X-Polygon-Edge-ID";
+const EDGE_IP_ADDRESS: &str = "X-Polygon-Edge-IP-Address";
+const EDGE_USER_AGENT: &str = "X-Polygon-Edge-User-Agent";
+
+/// The end user on whose behalf Launchpad requests are sent.
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::rest::{EdgeHeaders, RESTClient};
+///
+/// let client = RESTClient::builder()
+///     .auth_key("my-api-key")
+///     .build()
+///     .expect("failed to build client");
+///
+/// let user_client = client.with_edge_headers(
+///     EdgeHeaders::new("user-1234", "203.0.113.7").user_agent("Mozilla/5.0"),
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdgeHeaders {
+    id: String,
+    ip_address: String,
+    user_agent: Option<String>,
+}
+
+impl EdgeHeaders {
+    /// Returns the headers of the user with the given ID, as known to the
+    /// reseller, and IP address.
+    pub fn new(id: &str, ip_address: &str) -> Self {
+        EdgeHeaders {
+            id: String::from(id),
+            ip_address: String::from(ip_address),
+            user_agent: None,
+        }
+    }
+
+    /// Sets the user agent of the user's application, such as the
+    /// `User-Agent` of their browser.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(String::from(user_agent));
+        self
+    }
+
+    /// Returns the ID of the user.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub(crate) fn apply(&self, mut req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        req = req
+            .header(EDGE_ID, &self.id)
+            .header(EDGE_IP_ADDRESS, &self.ip_address);
+        if let Some(user_agent) = &self.user_agent {
+            req = req.header(EDGE_USER_AGENT, user_agent);
+        }
+        req
+    }
+}
+
+impl RESTClient {
+    /// Returns a client that shares the connection pool, configuration, and
+    /// request budget of this client, but sends the given Launchpad headers
+    /// instead of those set with
+    /// [`RESTClientBuilder::edge_headers()`](crate::rest::RESTClientBuilder::edge_headers).
+    pub fn with_edge_headers(&self, headers: EdgeHeaders) -> RESTClient {
+        RESTClient {
+            edge_headers: Some(Arc::new(headers)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the Launchpad headers sent with the requests of this client.
+    pub(crate) fn edge_headers(&self) -> Option<&EdgeHeaders> {
+        self.edge_headers
+            .as_deref()
+            .or(self.inner.edge_headers.as_ref())
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    )
+))]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::EdgeHeaders;
+
+    #[test]
+    fn test_edge_headers() {
+        tokio_test::block_on(async {
+            let ok = || Response::ok(String::from(r#"{"status":"OK"}"#));
+            let server = TestServer::start(vec![ok(), ok(), ok()]).await;
+            let client = test_server::builder(&server)
+                .edge_headers(EdgeHeaders::new("user-1", "203.0.113.1"))
+                .build()
+                .unwrap();
+            let other = client.with_edge_headers(
+                EdgeHeaders::new("user-2", "203.0.113.2").user_agent("Mozilla/5.0"),
+            );
+
+            let no_params = HashMap::new();
+            for c in [&client, &other, &client].iter() {
+                c.send_request::<serde_json::Value>("/v1/test", &no_params)
+                    .await
+                    .unwrap();
+            }
+
+            let ids: Vec<_> = server.request_headers("X-Polygon-Edge-ID");
+            assert_eq!(
+                ids,
+                vec![
+                    Some(String::from("user-1")),
+                    Some(String::from("user-2")),
+                    Some(String::from("user-1"))
+                ]
+            );
+            assert_eq!(
+                server.request_headers("X-Polygon-Edge-IP-Address")[1].as_deref(),
+                Some("203.0.113.2")
+            );
+            assert_eq!(
+                server.request_headers("X-Polygon-Edge-User-Agent"),
+                vec![None, Some(String::from("Mozilla/5.0")), None]
+            );
+        });
+    }
+}
@@ -0,0 +1,225 @@
+//! Forex endpoints such as currency aggregates.
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rest::{RESTClient, QUOTES_MAX_LIMIT};
+use crate::ticker::ForexTicker;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get aggregate bars for a forex pair over a given date range in custom
+    /// time window sizes using the [/v2/aggs/ticker/{forexTicker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__forexTicker__range__multiplier___timespan___from___to__anchor) API.
+    pub async fn forex_currencies_aggregates(
+        &self,
+        forex_ticker: impl Into<ForexTicker>,
+        multiplier: u32,
+        timespan: Timespan,
+        from: &str,
+        to: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ForexCurrenciesAggregatesResponse, Error> {
+        let forex_ticker: ForexTicker = forex_ticker.into();
+        let resp = self
+            .send_aggregates(
+                forex_ticker.as_str(),
+                multiplier,
+                timespan,
+                from,
+                to,
+                query_params,
+            )
+            .await?;
+        Ok(resp.into())
+    }
+
+    /// Get the daily open, high, low, and close for the entire forex markets
+    /// using the [/v2/aggs/grouped/locale/global/market/fx/{date}](https://polygon.io/docs/get_v2_aggs_grouped_locale_global_market_fx__date__anchor) API.
+    pub async fn forex_currencies_grouped_daily(
+        &self,
+        date: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ForexCurrenciesGroupedDailyResponse, Error> {
+        let uri = format!("/v2/aggs/grouped/locale/global/market/fx/{}", date);
+        self.send_request::<ForexCurrenciesGroupedDailyResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the previous day's open, high, low, and close for the specified
+    /// forex pair using the [/v2/aggs/ticker/{forex_ticker}/prev](https://polygon.io/docs/get_v2_aggs_ticker__forexTicker__prev_anchor) API.
+    pub async fn forex_currencies_previous_close(
+        &self,
+        forex_ticker: impl Into<ForexTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ForexCurrenciesPreviousCloseResponse, Error> {
+        let forex_ticker: ForexTicker = forex_ticker.into();
+        let uri = format!("/v2/aggs/ticker/{}/prev", forex_ticker);
+        self.send_request::<ForexCurrenciesPreviousCloseResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get BBO quotes for a forex pair using the
+    /// [/v3/quotes/{fx_ticker}](https://polygon.io/docs/forex/get_v3_quotes__fxticker) API.
+    ///
+    /// A single page of quotes is returned. Pass [`ForexQuotesResponseV3`] to
+    /// [`RESTClient::paginate()`] to stream every page.
+    pub async fn forex_quotes(
+        &self,
+        fx_ticker: impl Into<ForexTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ForexQuotesResponseV3, Error> {
+        let fx_ticker: ForexTicker = fx_ticker.into();
+        let uri = format!("/v3/quotes/{}", fx_ticker);
+        self.send_limited_request::<ForexQuotesResponseV3>(&uri, query_params, QUOTES_MAX_LIMIT)
+            .await
+    }
+
+    /// Get the last quote for a forex pair using the
+    /// [/v1/last_quote/currencies/{from}/{to}](https://polygon.io/docs/forex/get_v1_last_quote_currencies__from___to) API.
+    pub async fn forex_last_quote(
+        &self,
+        from: &str,
+        to: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ForexLastQuoteResponse, Error> {
+        let uri = format!("/v1/last_quote/currencies/{}/{}", from, to);
+        self.send_request::<ForexLastQuoteResponse>(&uri, query_params)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::RESTClient;
+    use crate::ticker::ForexTicker;
+    use crate::types::{ForexQuotesResponseV3, Timespan};
+    use futures::TryStreamExt;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_forex_currencies_aggregates() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(RESTClient::new(None, None).forex_currencies_aggregates(
+            ForexTicker::new("C:EURUSD").unwrap(),
+            1,
+            Timespan::Day,
+            "2020-10-14",
+            "2020-10-14",
+            &query_params,
+        ))
+        .unwrap();
+        assert_eq!(resp.ticker, "C:EURUSD");
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.query_count, 1);
+        assert_eq!(resp.results_count, 1);
+        let result = resp.results.first().unwrap();
+        assert_eq!(result.v, 211796f64);
+        assert_eq!(result.vw.unwrap(), 1.1748);
+        assert_eq!(result.o, 1.17439);
+        assert_eq!(result.c, 1.17496);
+        assert_eq!(result.h, 1.1771);
+        assert_eq!(result.l, 1.17198);
+        assert_eq!(result.t.unwrap(), 1602633600000);
+        assert_eq!(result.n.unwrap(), 211796f64);
+    }
+
+    #[test]
+    fn test_forex_currencies_grouped_daily() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).forex_currencies_grouped_daily("2020-10-14", &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        let msft = resp
+            .results
+            .iter()
+            .find(|x| x.T.is_some() && x.T.as_ref().unwrap() == "C:EURMUR");
+        assert!(msft.is_some());
+        assert!(msft.unwrap().vw.is_some());
+        assert_eq!(msft.unwrap().vw.unwrap(), 45.2081);
+        assert_eq!(msft.unwrap().o, 45.37);
+        assert_eq!(msft.unwrap().h, 45.59);
+        assert_eq!(msft.unwrap().l, 44.83);
+    }
+
+    #[test]
+    fn test_forex_currencies_previous_close() {
+        let query_params = HashMap::new();
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).forex_currencies_previous_close(
+                ForexTicker::new("C:EURUSD").unwrap(),
+                &query_params,
+            ))
+            .unwrap();
+        assert_eq!(resp.ticker, "C:EURUSD");
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results_count, 1);
+        let result = resp.results.first();
+        assert!(result.is_some());
+        assert!(result.unwrap().T.is_some());
+        assert_eq!(result.unwrap().T.as_ref().unwrap(), "C:EURUSD");
+    }
+
+    #[test]
+    fn test_forex_last_quote() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(RESTClient::new(None, None).forex_last_quote(
+            "EUR",
+            "USD",
+            &query_params,
+        ))
+        .unwrap();
+        assert_eq!(resp.symbol, "EUR/USD");
+    }
+
+    #[test]
+    fn test_forex_quotes() {
+        let mut query_params = HashMap::new();
+        query_params.insert("limit", "10");
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .forex_quotes(ForexTicker::new("C:EUR-USD").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results.len(), 10);
+    }
+
+    #[test]
+    fn test_forex_quotes_pagination() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"1","results":[
+                        {"ask_exchange":48,"ask_price":1.18565,"bid_exchange":48,"bid_price":1.18558,"participant_timestamp":1625097600000000000}],
+                        "next_url":"https://api.polygon.io/v3/quotes/C:EUR-USD?cursor=abc"}"#,
+                )),
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"2","results":[
+                        {"ask_price":1.18566,"bid_price":1.18559,"participant_timestamp":1625097600001000000}]}"#,
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("limit", "1");
+            let quotes: Vec<_> = client
+                .paginate::<ForexQuotesResponseV3>("/v3/quotes/C:EUR-USD", &query_params)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(quotes.len(), 2);
+            assert_eq!(quotes[0].ask_exchange, Some(48));
+            assert_eq!(quotes[1].bid_price, 1.18559);
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/v3/quotes/C:EUR-USD?limit=1",
+                    "/v3/quotes/C:EUR-USD?cursor=abc"
+                ]
+            );
+        });
+    }
+}
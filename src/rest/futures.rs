@@ -0,0 +1,245 @@
+//! Futures endpoints such as products, contracts, trades, and quotes.
+//!
+//! The futures APIs are in beta and their responses may change.
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get the futures products, such as the E-mini S&P 500, using the
+    /// [/futures/vX/products](https://polygon.io/docs/rest/futures/contracts/products) API.
+    ///
+    /// Use [`RESTClient::paginate()`] with [`FuturesProductsResponse`] to
+    /// list every product.
+    pub async fn futures_products(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<FuturesProductsResponse, Error> {
+        self.send_request::<FuturesProductsResponse>("/futures/vX/products", query_params)
+            .await
+    }
+
+    /// Get the futures contracts, such as the contracts of a product for each
+    /// delivery month, using the
+    /// [/futures/vX/contracts](https://polygon.io/docs/rest/futures/contracts/all-contracts) API.
+    ///
+    /// Use [`RESTClient::paginate()`] with [`FuturesContractsResponse`] to
+    /// list every contract.
+    pub async fn futures_contracts(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<FuturesContractsResponse, Error> {
+        self.send_request::<FuturesContractsResponse>("/futures/vX/contracts", query_params)
+            .await
+    }
+
+    /// Get the trades of a futures contract using the
+    /// [/futures/vX/trades/{ticker}](https://polygon.io/docs/rest/futures/trades-quotes/trades) API.
+    ///
+    /// Use [`RESTClient::paginate()`] with [`FuturesTradesResponse`] to
+    /// list every trade in a time range.
+    pub async fn futures_trades(
+        &self,
+        ticker: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<FuturesTradesResponse, Error> {
+        let uri = format!("/futures/vX/trades/{}", ticker);
+        self.send_request::<FuturesTradesResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the top of book quotes of a futures contract using the
+    /// [/futures/vX/quotes/{ticker}](https://polygon.io/docs/rest/futures/trades-quotes/quotes) API.
+    ///
+    /// Use [`RESTClient::paginate()`] with [`FuturesQuotesResponse`] to
+    /// list every quote in a time range.
+    pub async fn futures_quotes(
+        &self,
+        ticker: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<FuturesQuotesResponse, Error> {
+        let uri = format!("/futures/vX/quotes/{}", ticker);
+        self.send_request::<FuturesQuotesResponse>(&uri, query_params)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use futures::TryStreamExt;
+
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::RESTClient;
+    use crate::types::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_futures_products() {
+        let mut query_params = HashMap::new();
+        query_params.insert("product_code", "ES");
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).futures_products(&query_params))
+                .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results[0].product_code, "ES");
+    }
+
+    #[test]
+    fn test_futures_products_response() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"status":"OK","request_id":"1","results":[{"product_code":"CL",
+                    "name":"Crude Oil","trading_venue":"XNYM","type":"single",
+                    "asset_class":"commodity","sector":"energy","sub_sector":"crude_oil",
+                    "trade_currency_code":"USD","settlement_currency_code":"USD",
+                    "settlement_method":"physical","unit":"barrels","unit_quantity":1000}],
+                    "next_url":"https://api.polygon.io/futures/vX/products?cursor=abc"}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("sector", "energy");
+            let resp = client.futures_products(&query_params).await.unwrap();
+            let product = &resp.results[0];
+            assert_eq!(product.product_code, "CL");
+            assert_eq!(product.trading_venue.as_deref(), Some("XNYM"));
+            assert_eq!(product.product_type.as_deref(), Some("single"));
+            assert_eq!(product.settlement_method.as_deref(), Some("physical"));
+            assert_eq!(product.unit_quantity, Some(1000.0));
+            assert_eq!(product.last_updated, None);
+            assert!(resp.next_url.is_some());
+            assert_eq!(
+                server.requests(),
+                vec!["/futures/vX/products?sector=energy"]
+            );
+        });
+    }
+
+    #[test]
+    fn test_futures_contracts_pagination() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"1","results":[{"ticker":"ESZ4",
+                        "product_code":"ES","trading_venue":"XCME","type":"single","active":true,
+                        "first_trade_date":"2023-09-15","last_trade_date":"2024-12-20",
+                        "settlement_date":"2024-12-20","days_to_maturity":30}],
+                        "next_url":"https://api.polygon.io/futures/vX/contracts?cursor=abc"}"#,
+                )),
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"2","results":[{"ticker":"ESH5",
+                        "product_code":"ES","type":"single","active":true}]}"#,
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("product_code", "ES");
+            let contracts: Vec<_> = client
+                .paginate::<FuturesContractsResponse>("/futures/vX/contracts", &query_params)
+                .try_collect()
+                .await
+                .unwrap();
+
+            assert_eq!(contracts.len(), 2);
+            assert_eq!(contracts[0].contract_month(), Some(12));
+            assert_eq!(
+                contracts[0].last_trade_date,
+                NaiveDate::from_ymd_opt(2024, 12, 20)
+            );
+            assert_eq!(contracts[0].days_to_maturity, Some(30));
+            assert_eq!(contracts[1].contract_month(), Some(3));
+            assert_eq!(contracts[1].settlement_date, None);
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/futures/vX/contracts?product_code=ES",
+                    "/futures/vX/contracts?cursor=abc",
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_futures_trades_pagination() {
+        let first_page = r#"{"status":"OK","request_id":"1","results":[{"ticker":"ESZ4",
+            "price":6001.25,"size":2,"timestamp":1732000000000000000,
+            "session_end_date":"2024-11-19"}],
+            "next_url":"https://api.polygon.io/futures/vX/trades/ESZ4?cursor=abc"}"#;
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(first_page)),
+                Response::ok(String::from(first_page)),
+                Response::ok(String::from(
+                    r#"{"status":"OK","request_id":"2","results":[{"ticker":"ESZ4",
+                        "price":6001.5,"size":1,"timestamp":1732000000500000000}]}"#,
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("limit", "1");
+            let first = client.futures_trades("ESZ4", &query_params).await.unwrap();
+            assert_eq!(first.results[0].price, 6001.25);
+            assert_eq!(
+                first.results[0].session_end_date,
+                NaiveDate::from_ymd_opt(2024, 11, 19)
+            );
+            assert!(first.next_url.is_some());
+
+            let rest: Vec<_> = client
+                .paginate::<FuturesTradesResponse>("/futures/vX/trades/ESZ4", &query_params)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(rest.len(), 2);
+            assert_eq!(rest[1].size, 1.0);
+            assert_eq!(rest[1].session_end_date, None);
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/futures/vX/trades/ESZ4?limit=1",
+                    "/futures/vX/trades/ESZ4?limit=1",
+                    "/futures/vX/trades/ESZ4?cursor=abc",
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_futures_quotes() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"status":"OK","request_id":"1","results":[{"ticker":"ESZ4",
+                    "bid_price":6001.0,"bid_size":12,"bid_timestamp":1732000000000000000,
+                    "ask_price":6001.25,"ask_size":8,"ask_timestamp":1732000000100000000,
+                    "timestamp":1732000000100000000,"session_end_date":"2024-11-19"},
+                    {"ticker":"ESZ4","bid_price":6001.0,"bid_size":10,
+                    "timestamp":1732000000200000000}]}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("timestamp.gte", "2024-11-19");
+            let resp = client.futures_quotes("ESZ4", &query_params).await.unwrap();
+            assert_eq!(resp.results.len(), 2);
+            assert_eq!(resp.results[0].ask_size, Some(8.0));
+            assert_eq!(resp.results[0].spread(), Some(0.25));
+            assert_eq!(resp.results[1].ask_price, None);
+            assert_eq!(resp.results[1].spread(), None);
+            assert!(resp.next_url.is_none());
+            assert_eq!(
+                server.requests(),
+                vec!["/futures/vX/quotes/ESZ4?timestamp.gte=2024-11-19"]
+            );
+        });
+    }
+}
@@ -0,0 +1,90 @@
+//! Index endpoints.
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::ticker::IndexTicker;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get the current value of indices such as `I:SPX` using the
+    /// [/v3/snapshot/indices](https://polygon.io/docs/indices/get_v3_snapshot_indices) API.
+    ///
+    /// Unlike stocks, whose snapshots are requested per market with
+    /// [`RESTClient::stock_equities_snapshot_all_tickers()`], indices have a
+    /// snapshot of their own. If `tickers` is empty, every index is returned
+    /// one page at a time.
+    pub async fn indices_snapshot(
+        &self,
+        tickers: &[IndexTicker],
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<IndicesSnapshotResponse, Error> {
+        let tickers: Vec<&str> = tickers.iter().map(IndexTicker::as_str).collect();
+        let tickers = tickers.join(",");
+        let mut query_params = query_params.clone();
+        if !tickers.is_empty() {
+            query_params.insert("ticker.any_of", &tickers);
+        }
+        self.send_request::<IndicesSnapshotResponse>("/v3/snapshot/indices", &query_params)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::RESTClient;
+    use crate::ticker::IndexTicker;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_indices_snapshot() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .indices_snapshot(&[IndexTicker::new("I:SPX").unwrap()], &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results[0].ticker, "I:SPX");
+    }
+
+    #[test]
+    fn test_indices_snapshot_response() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"status":"OK","request_id":"1","results":[{"ticker":"I:SPX",
+                    "name":"S&P 500","value":3822.39,"market_status":"closed",
+                    "type":"indices","timeframe":"REAL-TIME",
+                    "last_updated":1679597116344223500,"session":{"change":-50.01,
+                    "change_percent":-1.45,"close":3822.39,"high":3834.41,
+                    "low":3801.11,"open":3827.38,"previous_close":3872.4}},
+                    {"ticker":"I:NOPE","error":"NOT_FOUND","message":"Ticker not found."}]}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            let resp = client
+                .indices_snapshot(
+                    &[
+                        IndexTicker::new("I:SPX").unwrap(),
+                        IndexTicker::new("I:NOPE").unwrap(),
+                    ],
+                    &HashMap::new(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(resp.results[0].value, Some(3822.39));
+            assert_eq!(
+                resp.results[0].session.as_ref().unwrap().change,
+                Some(-50.01)
+            );
+            assert_eq!(resp.results[1].value, None);
+            assert_eq!(resp.results[1].error.as_deref(), Some("NOT_FOUND"));
+            assert_eq!(
+                server.requests(),
+                vec!["/v3/snapshot/indices?ticker.any_of=I%3ASPX%2CI%3ANOPE"]
+            );
+        });
+    }
+}
@@ -0,0 +1,152 @@
+//! A single entry point for the most recent price of any ticker.
+use std::collections::HashMap;
+
+use crate::error::{DecodeError, Error};
+use crate::rest::RESTClient;
+use crate::ticker::{self, TickerError};
+use crate::types::*;
+
+/// The number of nanoseconds in a millisecond.
+const NANOS_PER_MILLI: u64 = 1_000_000;
+
+impl RESTClient {
+    /// Get the most recent price of a ticker in any asset class.
+    ///
+    /// The asset class is taken from the ticker's prefix, and the cheapest
+    /// endpoint that provides a price for that asset class is used:
+    ///
+    /// * stocks and options use the last trade from [/v2/last/trade/{ticker}](https://polygon.io/docs/get_v2_last_trade__stocksTicker__anchor)
+    /// * crypto, such as `X:BTCUSD`, uses the last trade from [/v1/last/crypto/{from}/{to}](https://polygon.io/docs/crypto/get_v1_last_crypto__from___to)
+    /// * forex, such as `C:EURUSD`, uses the midpoint of the last quote from [/v1/last_quote/currencies/{from}/{to}](https://polygon.io/docs/forex/get_v1_last_quote_currencies__from___to)
+    ///
+    /// Timestamps are converted to Unix milliseconds for every asset class.
+    /// Returns [`Error::InvalidTicker`] for indices, which have no last trade,
+    /// and for crypto and forex tickers that are not valid pairs, and
+    /// [`Error::Decode`] for a last trade without a price or timestamp.
+    pub async fn last_price(&self, ticker: &str) -> Result<LastPrice, Error> {
+        let no_params = HashMap::new();
+
+        match ticker::asset_class(ticker) {
+            class @ (AssetClass::Stocks | AssetClass::Options) => {
+                let trade = self.send_last_trade(ticker, &no_params).await?.results;
+                let price = LastTradePrice {
+                    ticker: String::from(ticker),
+                    price: trade.price.ok_or_else(|| missing_field("results.p"))?,
+                    size: trade.size,
+                    exchange: trade.exchange,
+                    timestamp: trade
+                        .sip_timestamp
+                        .ok_or_else(|| missing_field("results.t"))?
+                        / NANOS_PER_MILLI,
+                };
+                match class {
+                    AssetClass::Options => Ok(LastPrice::Option(price)),
+                    _ => Ok(LastPrice::Stock(price)),
+                }
+            }
+            AssetClass::Crypto => {
+                let (from, to) = ticker::split_pair(ticker)?;
+                let trade = self.crypto_last_trade(from, to, &no_params).await?.last;
+                Ok(LastPrice::Crypto(LastTradePrice {
+                    ticker: String::from(ticker),
+                    price: trade.price,
+                    size: Some(trade.size),
+                    exchange: trade.exchange,
+                    timestamp: trade.timestamp,
+                }))
+            }
+            AssetClass::Forex => {
+                let (from, to) = ticker::split_pair(ticker)?;
+                let quote = self.forex_last_quote(from, to, &no_params).await?.last;
+                Ok(LastPrice::Forex(LastQuotePrice {
+                    ticker: String::from(ticker),
+                    bid: quote.bid,
+                    ask: quote.ask,
+                    exchange: quote.exchange,
+                    timestamp: quote.timestamp,
+                }))
+            }
+            class => Err(TickerError::UnsupportedAssetClass(class).into()),
+        }
+    }
+}
+
+/// Returns the error of a response without the field at `path`.
+fn missing_field(path: &str) -> Error {
+    let source =
+        <serde_json::Error as serde::de::Error>::custom(format!("missing field `{}`", path));
+    Error::Decode(DecodeError::new(String::from(path), source, &[]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::types::*;
+
+    #[test]
+    fn test_last_price() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(include_str!("../../fixtures/last_trade.json").into())
+                    .on_path("/v2/last/trade/MSFT"),
+                Response::ok(String::from(
+                    r#"{"last":{"conditions":[1],"exchange":4,"price":16835.42,"size":0.006909,"timestamp":1605560885027},"request_id":"1","status":"success","symbol":"BTC-USD"}"#,
+                ))
+                .on_path("/v1/last/crypto/BTC/USD"),
+                Response::ok(String::from(
+                    r#"{"last":{"ask":1.1802,"bid":1.18,"exchange":48,"timestamp":1605555313000},"request_id":"1","status":"success","symbol":"EUR/USD"}"#,
+                ))
+                .on_path("/v1/last_quote/currencies/EUR/USD"),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let stock = client.last_price("MSFT").await.unwrap();
+            assert!(matches!(stock, LastPrice::Stock(_)));
+            assert_eq!(stock.price(), 294.9);
+            assert_eq!(stock.timestamp(), 1633728000123);
+
+            let crypto = client.last_price("X:BTCUSD").await.unwrap();
+            assert_eq!(crypto.asset_class(), AssetClass::Crypto);
+            assert_eq!(crypto.price(), 16835.42);
+            assert_eq!(crypto.timestamp(), 1605560885027);
+
+            let forex = client.last_price("C:EURUSD").await.unwrap();
+            assert_eq!(forex.asset_class(), AssetClass::Forex);
+            assert!((forex.price() - 1.1801).abs() < 1e-9);
+            assert_eq!(forex.ticker(), "C:EURUSD");
+        });
+    }
+
+    #[test]
+    fn test_last_price_without_price() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"request_id":"1","status":"OK","results":{"T":"MSFT","s":25,"t":1633728000123000000}}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            let err = client.last_price("MSFT").await.unwrap_err();
+            match err {
+                Error::Decode(e) => assert_eq!(e.path, "results.p"),
+                e => panic!("unexpected error: {:?}", e),
+            }
+        });
+    }
+
+    #[test]
+    fn test_last_price_rejects_indices() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(Vec::new()).await;
+            let client = test_server::client(&server);
+
+            assert!(matches!(
+                client.last_price("I:SPX").await,
+                Err(Error::InvalidTicker(_))
+            ));
+            assert!(server.requests().is_empty());
+        });
+    }
+}
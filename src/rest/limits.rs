@@ -0,0 +1,238 @@
+//! Validation of the `limit` query parameter.
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rest::{Paginated, RESTClient};
+use crate::types::*;
+
+/// The maximum `limit` of the v3 tickers endpoint.
+pub const REFERENCE_TICKERS_MAX_LIMIT: u32 = 1000;
+/// The maximum `limit` of the v2 ticker news endpoint.
+pub const REFERENCE_TICKER_NEWS_MAX_LIMIT: u32 = 1000;
+/// The maximum `limit` of the v3 dividends endpoint.
+pub const REFERENCE_STOCK_DIVIDENDS_MAX_LIMIT: u32 = 1000;
+/// The maximum `limit` of the vX stock financials endpoint.
+pub const REFERENCE_STOCK_FINANCIALS_VX_MAX_LIMIT: u32 = 100;
+/// The maximum `limit` of the aggregates endpoints of every asset class.
+pub const AGGREGATES_MAX_LIMIT: u32 = 50000;
+/// The maximum `limit` of the v3 trades endpoint.
+pub const TRADES_MAX_LIMIT: u32 = 50000;
+/// The maximum `limit` of the v3 quotes endpoint.
+pub const QUOTES_MAX_LIMIT: u32 = 50000;
+/// The maximum `limit` of the v3 options chain snapshot endpoint.
+pub const OPTIONS_CHAIN_SNAPSHOT_MAX_LIMIT: u32 = 250;
+
+/// Query parameters with owned values.
+type Query<'a> = Vec<(&'a str, String)>;
+
+/// What to do when the `limit` query parameter exceeds the maximum supported
+/// by an endpoint.
+///
+/// A `limit` that is not a positive number is always rejected with
+/// [`Error::InvalidLimit`].
+///
+/// The server may also apply a lower limit than the one requested. When a
+/// page has fewer results than the requested `limit` but more pages remain,
+/// the number of results is recorded in the `applied_limit` field of the
+/// response, which is `None` if the requested limit was honored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LimitPolicy {
+    /// Return [`Error::InvalidLimit`] without sending the request.
+    #[default]
+    Reject,
+    /// Send the request with the endpoint's maximum instead.
+    Clamp,
+}
+
+/// A paginated response that reports when the server returned fewer results
+/// than the requested `limit`.
+pub(crate) trait LimitedResponse: Paginated {
+    /// Returns the number of results in the page.
+    fn result_count(&self) -> usize;
+
+    /// Records the limit the server applied instead of the requested one.
+    fn set_applied_limit(&mut self, limit: u32);
+}
+
+macro_rules! impl_limited_response {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl LimitedResponse for $t {
+                fn result_count(&self) -> usize {
+                    self.results.len()
+                }
+
+                fn set_applied_limit(&mut self, limit: u32) {
+                    self.applied_limit = Some(limit);
+                }
+            }
+        )+
+    };
+}
+
+impl_limited_response!(
+    ReferenceTickersResponseV3,
+    ReferenceTickerNewsResponseV2,
+    ReferenceStockFinancialsVXResponse,
+    ReferenceStockDividendsResponseV3,
+    StockEquitiesAggregatesResponse,
+    StockEquitiesTradesResponseV3,
+    StockEquitiesQuotesResponseV3,
+    ForexQuotesResponseV3,
+    CryptoTradesResponseV3,
+    OptionsChainSnapshotResponse,
+);
+
+impl<T> LimitedResponse for SlimResponse<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn result_count(&self) -> usize {
+        self.results.len()
+    }
+
+    fn set_applied_limit(&mut self, limit: u32) {
+        self.applied_limit = Some(limit);
+    }
+}
+
+impl RESTClient {
+    /// Returns the query parameters with `limit` checked against `max`
+    /// according to the client's [`LimitPolicy`], along with the limit that
+    /// will be requested, if any.
+    pub(crate) fn limit_query<'a>(
+        &self,
+        query_params: &HashMap<&'a str, &'a str>,
+        max: u32,
+    ) -> Result<(Query<'a>, Option<u32>), Error> {
+        let mut query: Query = query_params
+            .iter()
+            .map(|(k, v)| (*k, v.to_string()))
+            .collect();
+
+        let param = match query.iter_mut().find(|(k, _)| *k == "limit") {
+            Some((_, v)) => v,
+            None => return Ok((query, None)),
+        };
+
+        let limit = match param.trim().parse::<u32>() {
+            Ok(limit) if limit > 0 => limit,
+            _ => {
+                return Err(Error::InvalidLimit {
+                    limit: param.clone(),
+                    max,
+                })
+            }
+        };
+
+        let limit = match self.inner.limit_policy {
+            _ if limit <= max => limit,
+            LimitPolicy::Clamp => max,
+            LimitPolicy::Reject => {
+                return Err(Error::InvalidLimit {
+                    limit: param.clone(),
+                    max,
+                })
+            }
+        };
+        *param = limit.to_string();
+
+        Ok((query, Some(limit)))
+    }
+
+    /// Sends a request to an endpoint whose `limit` is at most `max`.
+    ///
+    /// If a page has fewer results than the requested limit but the server
+    /// still returned a next page, then the server applied a lower limit than
+    /// requested and the number of results is recorded in the response.
+    pub(crate) async fn send_limited_request<RespType>(
+        &self,
+        uri: &str,
+        query_params: &HashMap<&str, &str>,
+        max: u32,
+    ) -> Result<RespType, Error>
+    where
+        RespType: LimitedResponse + Send + 'static,
+    {
+        let (query, limit) = self.limit_query(query_params, max)?;
+        let url = format!("{}{}", self.inner.api_url, uri);
+        let mut resp = self
+            .send_url_with_opt_in_retry::<RespType, _>(&url, &query)
+            .await?;
+
+        if let Some(limit) = limit {
+            let count = resp.result_count();
+            if count < limit as usize && resp.next_url().is_some() {
+                resp.set_applied_limit(count as u32);
+            }
+        }
+
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::error::Error;
+    use crate::rest::{LimitPolicy, RESTClient};
+
+    #[test]
+    fn test_limit_query() {
+        let client = RESTClient::builder().auth_key("test").build().unwrap();
+        let mut query_params = HashMap::new();
+        query_params.insert("limit", "2000");
+
+        assert!(matches!(
+            client.limit_query(&query_params, 1000),
+            Err(Error::InvalidLimit { max: 1000, .. })
+        ));
+        query_params.insert("limit", "0");
+        assert!(client.limit_query(&query_params, 1000).is_err());
+        query_params.insert("limit", "ten");
+        assert!(client.limit_query(&query_params, 1000).is_err());
+
+        let client = RESTClient::builder()
+            .auth_key("test")
+            .limit_policy(LimitPolicy::Clamp)
+            .build()
+            .unwrap();
+        query_params.insert("limit", "2000");
+        let (query, limit) = client.limit_query(&query_params, 1000).unwrap();
+        assert_eq!(query, vec![("limit", String::from("1000"))]);
+        assert_eq!(limit, Some(1000));
+    }
+
+    #[cfg(feature = "reference")]
+    #[test]
+    fn test_detects_server_cap() {
+        use crate::rest::test_server::{self, Response, TestServer};
+
+        tokio_test::block_on(async {
+            let page = |next_url: &str| {
+                Response::ok(format!(
+                    r#"{{"results":[{{"ticker":"A","name":"Agilent","market":"stocks","locale":"us","primary_exchange":"XNYS","active":true,
+                        "currency_name":"usd","last_updated_utc":"2021-10-01T00:00:00Z"}}],
+                        "status":"OK","request_id":"1","count":1{}}}"#,
+                    next_url
+                ))
+            };
+            let server = TestServer::start(vec![
+                page(r#","next_url":"https://api.polygon.io/v3/reference/tickers?cursor=abc""#),
+                page(""),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("limit", "10");
+            let resp = client.reference_tickers(&query_params).await.unwrap();
+            assert_eq!(resp.applied_limit, Some(1));
+
+            // The last page may have fewer results than the limit.
+            let resp = client.reference_tickers(&query_params).await.unwrap();
+            assert_eq!(resp.applied_limit, None);
+        });
+    }
+}
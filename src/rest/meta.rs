@@ -0,0 +1,287 @@
+//! Metadata of the responses received by a client.
+//!
+//! Compressed responses are a common casualty of corporate proxies, which may
+//! strip the `Accept-Encoding` header of a request or compress a response that
+//! was not asked to be. The client records the `Content-Encoding` of every
+//! response so that such interference can be diagnosed, and
+//! [`RESTClientBuilder::force_identity_encoding()`](crate::rest::RESTClientBuilder::force_identity_encoding)
+//! asks the server and any proxy in between not to compress responses at
+//! all. A response that the client decompresses itself, with the `gzip` or
+//! `brotli` features, loses its `Content-Encoding` and is recorded as
+//! uncompressed.
+//!
+//! The metadata also holds the `X-Request-Id` of each response, which
+//! polygon.io support asks for when investigating a request, and the
+//! rate-limit headers sent by the server, from which callers can pace their
+//! requests.
+//!
+//! The metadata recorded by the client is that of the most recent response,
+//! which is ambiguous when a client is shared by concurrent tasks. An
+//! [`ApiResponse`] instead ties the status, headers, and latency of a
+//! response to its body.
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, CONTENT_ENCODING, CONTENT_LENGTH};
+
+use crate::rest::RESTClient;
+
+/// The `Content-Encoding` counted for responses that were not compressed.
+pub const IDENTITY_ENCODING: &str = "identity";
+
+const REQUEST_ID: &str = "x-request-id";
+const RATE_LIMIT: &str = "x-ratelimit-limit";
+const RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+const RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+
+/// Metadata of a single response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ResponseMeta {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The `Content-Encoding` of the body as it was received, such as `gzip`,
+    /// or `None` if the body was not compressed.
+    pub content_encoding: Option<String>,
+    /// The `Accept-Encoding` sent with the request, or `None` if the header
+    /// was not set by the client.
+    pub accept_encoding: Option<String>,
+    /// The length of the body as it was received, if the server sent a
+    /// `Content-Length`.
+    pub content_length: Option<u64>,
+    /// The `X-Request-Id` of the response.
+    pub request_id: Option<String>,
+    /// The number of requests allowed in the current window, from the
+    /// `X-RateLimit-Limit` header.
+    pub rate_limit: Option<u64>,
+    /// The number of requests left in the current window, from the
+    /// `X-RateLimit-Remaining` header.
+    pub rate_limit_remaining: Option<u64>,
+    /// When the current window ends, from the `X-RateLimit-Reset` header,
+    /// which is in Unix seconds.
+    pub rate_limit_reset: Option<u64>,
+}
+
+impl ResponseMeta {
+    pub(crate) fn new(res: &reqwest::Response, accept_encoding: Option<&str>) -> Self {
+        let header = |name: &str| {
+            res.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+
+        ResponseMeta {
+            status: res.status().as_u16(),
+            content_encoding: header(CONTENT_ENCODING.as_str()),
+            accept_encoding: accept_encoding.map(String::from),
+            content_length: header(CONTENT_LENGTH.as_str()).and_then(|v| v.parse().ok()),
+            request_id: header(REQUEST_ID),
+            rate_limit: header(RATE_LIMIT).and_then(|v| v.parse().ok()),
+            rate_limit_remaining: header(RATE_LIMIT_REMAINING).and_then(|v| v.parse().ok()),
+            rate_limit_reset: header(RATE_LIMIT_RESET).and_then(|v| v.parse().ok()),
+        }
+    }
+
+    /// Returns the encoding of the body, which is [`IDENTITY_ENCODING`] if it
+    /// was not compressed.
+    pub fn encoding(&self) -> &str {
+        self.content_encoding
+            .as_deref()
+            .unwrap_or(IDENTITY_ENCODING)
+    }
+}
+
+/// A deserialized response body along with the HTTP metadata of the
+/// response, as returned by
+/// [`RESTClient::send_with_meta()`](crate::rest::RESTClient::send_with_meta).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ApiResponse<T> {
+    /// The deserialized body.
+    pub body: T,
+    /// The HTTP status code.
+    pub status: u16,
+    /// The headers of the response.
+    pub headers: HeaderMap,
+    /// The time from sending the request to receiving the whole body. If the
+    /// request was retried, this only covers the last attempt, and it is zero
+    /// for a response taken from the
+    /// [`ResponseCache`](crate::rest::ResponseCache).
+    pub elapsed: Duration,
+}
+
+impl<T> ApiResponse<T> {
+    /// Returns the value of a header, if it is present and is valid text.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// Returns the `X-Request-Id` of the response.
+    pub fn request_id(&self) -> Option<&str> {
+        self.header(REQUEST_ID)
+    }
+
+    pub(crate) fn with_body<U>(self, body: U) -> ApiResponse<U> {
+        ApiResponse {
+            body,
+            status: self.status,
+            headers: self.headers,
+            elapsed: self.elapsed,
+        }
+    }
+}
+
+/// The metadata recorded for the responses received by a client and its
+/// clones.
+#[derive(Default)]
+pub(crate) struct ResponseLog {
+    last: Mutex<Option<ResponseMeta>>,
+    encodings: Mutex<HashMap<String, u64>>,
+}
+
+impl ResponseLog {
+    pub(crate) fn record(&self, meta: ResponseMeta) {
+        let mut encodings = self
+            .encodings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *encodings.entry(String::from(meta.encoding())).or_default() += 1;
+        drop(encodings);
+
+        let mut last = self.last.lock().unwrap_or_else(PoisonError::into_inner);
+        *last = Some(meta);
+    }
+}
+
+impl RESTClient {
+    /// Returns the metadata of the most recent response received by this
+    /// client or any of its clones.
+    pub fn last_response_meta(&self) -> Option<ResponseMeta> {
+        let last = self
+            .inner
+            .responses
+            .last
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        last.clone()
+    }
+
+    /// Returns the number of responses received by this client and its
+    /// clones with each `Content-Encoding`, with uncompressed responses
+    /// counted as [`IDENTITY_ENCODING`].
+    pub fn content_encoding_counts(&self) -> HashMap<String, u64> {
+        let encodings = self
+            .inner
+            .responses
+            .encodings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        encodings.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::rest::test_server::{self, Response, TestServer};
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
+    #[test]
+    fn test_response_meta() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                // Only the header matters, so the body is left uncompressed.
+                // The encoding is one that the client does not decompress
+                // itself, even with the `gzip` or `brotli` features.
+                Response::ok(String::from(r#"{"status":"OK"}"#)).header("Content-Encoding", "zstd"),
+                Response::ok(String::from(r#"{"status":"OK"}"#))
+                    .header("X-Request-Id", "abc123")
+                    .header("X-RateLimit-Limit", "5")
+                    .header("X-RateLimit-Remaining", "3"),
+            ])
+            .await;
+            let client = test_server::builder(&server)
+                .force_identity_encoding(true)
+                .build()
+                .unwrap();
+            assert_eq!(client.last_response_meta(), None);
+
+            let no_params = HashMap::new();
+            for _ in 0..2 {
+                client
+                    .send_request::<serde_json::Value>("/v1/test", &no_params)
+                    .await
+                    .unwrap();
+            }
+
+            let meta = client.last_response_meta().unwrap();
+            assert_eq!(meta.status, 200);
+            assert_eq!(meta.content_encoding, None);
+            assert_eq!(meta.encoding(), "identity");
+            assert_eq!(meta.accept_encoding.as_deref(), Some("identity"));
+            assert_eq!(meta.content_length, Some(15));
+            assert_eq!(meta.request_id.as_deref(), Some("abc123"));
+            assert_eq!(meta.rate_limit, Some(5));
+            assert_eq!(meta.rate_limit_remaining, Some(3));
+            assert_eq!(meta.rate_limit_reset, None);
+
+            let counts = client.content_encoding_counts();
+            assert_eq!(counts.get("zstd"), Some(&1));
+            assert_eq!(counts.get("identity"), Some(&1));
+            assert_eq!(
+                server.request_headers("Accept-Encoding"),
+                vec![Some(String::from("identity")); 2]
+            );
+        });
+    }
+
+    #[test]
+    fn test_send_with_meta() {
+        use crate::error::Error;
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(r#"{"status":"OK","count":1}"#))
+                    .header("X-Request-Id", "abc123"),
+                Response::ok(String::from(r#"{"status":"OK","count":"bad"}"#)),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let no_params = HashMap::new();
+            let resp = client
+                .send_with_meta::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap();
+            assert_eq!(resp.body["count"], 1);
+            assert_eq!(resp.status, 200);
+            assert_eq!(resp.request_id(), Some("abc123"));
+            assert_eq!(resp.header("Content-Length"), Some("25"));
+            assert!(resp.elapsed > std::time::Duration::ZERO);
+
+            #[derive(Debug, serde::Deserialize)]
+            struct Count {
+                #[allow(dead_code)]
+                count: u32,
+            }
+            let err = client
+                .send_with_meta::<Count>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Decode(_)));
+        });
+    }
+}
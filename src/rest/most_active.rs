@@ -0,0 +1,113 @@
+//! The most actively traded stocks, ranked from the full-market snapshot.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get the `n` most actively traded stocks of the day by volume.
+    ///
+    /// Polygon provides the top gainers and losers but not the most active
+    /// tickers, so they are ranked from the snapshot of every ticker returned
+    /// by the [/v2/snapshot/locale/{locale}/markets/stocks/tickers](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks_tickers_anchor)
+    /// API. See [`most_active_by()`](RESTClient::most_active_by) to rank them
+    /// by dollar volume instead.
+    pub async fn most_active(
+        &self,
+        locale: &str,
+        n: usize,
+    ) -> Result<Vec<StockEquitiesTickerSnapshot>, Error> {
+        self.most_active_by(locale, n, ActivityMeasure::Volume)
+            .await
+    }
+
+    /// Get the `n` most actively traded stocks of the day, ranked by
+    /// `measure`.
+    ///
+    /// Tickers that have not traded today are left out, so fewer than `n`
+    /// snapshots are returned before the first trades of the day. Tickers
+    /// that are equally active are sorted by ticker.
+    pub async fn most_active_by(
+        &self,
+        locale: &str,
+        n: usize,
+        measure: ActivityMeasure,
+    ) -> Result<Vec<StockEquitiesTickerSnapshot>, Error> {
+        let resp = self
+            .stock_equities_snapshot_all_tickers(locale, &HashMap::new())
+            .await?;
+        Ok(rank(resp.tickers, n, measure))
+    }
+}
+
+fn activity(snapshot: &StockEquitiesTickerSnapshot, measure: ActivityMeasure) -> f64 {
+    let day = &snapshot.day;
+    match measure {
+        ActivityMeasure::Volume => day.v,
+        ActivityMeasure::DollarVolume => day.v * day.vw.unwrap_or(day.c),
+    }
+}
+
+fn rank(
+    snapshots: Vec<StockEquitiesTickerSnapshot>,
+    n: usize,
+    measure: ActivityMeasure,
+) -> Vec<StockEquitiesTickerSnapshot> {
+    let mut ranked: Vec<_> = snapshots
+        .into_iter()
+        .map(|s| (activity(&s, measure), s))
+        .filter(|(activity, _)| *activity > 0.0)
+        .collect();
+    ranked.sort_by(|(a, x), (b, y)| {
+        b.partial_cmp(a)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| x.ticker.cmp(&y.ticker))
+    });
+    ranked.truncate(n);
+    ranked.into_iter().map(|(_, s)| s).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::most_active::rank;
+    use crate::rest::RESTClient;
+    use crate::types::*;
+
+    fn snapshot(ticker: &str, v: f64, vw: Option<f64>) -> StockEquitiesTickerSnapshot {
+        let resp: StockEquitiesSnapshotAllTickersResponse =
+            serde_json::from_str(include_str!("../../fixtures/ticker_snapshot.json")).unwrap();
+        let mut snapshot = resp.tickers[0].clone();
+        snapshot.ticker = String::from(ticker);
+        snapshot.day.c = 10.0;
+        snapshot.day.v = v;
+        snapshot.day.vw = vw;
+        snapshot
+    }
+
+    #[test]
+    fn test_most_active() {
+        let resp = tokio_test::block_on(RESTClient::new(None, None).most_active("us", 10)).unwrap();
+        assert!(resp.len() <= 10);
+        assert!(resp.windows(2).all(|w| w[0].day.v >= w[1].day.v));
+    }
+
+    #[test]
+    fn test_rank() {
+        let snapshots = vec![
+            snapshot("PENNY", 5000.0, Some(0.5)),
+            snapshot("MSFT", 1000.0, Some(300.0)),
+            snapshot("AAPL", 1000.0, None),
+            snapshot("HALT", 0.0, None),
+        ];
+
+        let by_volume = rank(snapshots.clone(), 10, ActivityMeasure::Volume);
+        let tickers: Vec<_> = by_volume.iter().map(|s| s.ticker.as_str()).collect();
+        assert_eq!(tickers, vec!["PENNY", "AAPL", "MSFT"]);
+
+        let by_value = rank(snapshots, 2, ActivityMeasure::DollarVolume);
+        let tickers: Vec<_> = by_value.iter().map(|s| s.ticker.as_str()).collect();
+        assert_eq!(tickers, vec!["MSFT", "AAPL"]);
+    }
+}
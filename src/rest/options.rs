@@ -0,0 +1,589 @@
+//! Options endpoints such as the options chain snapshot.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+use crate::error::Error;
+use crate::rest::{RESTClient, OPTIONS_CHAIN_SNAPSHOT_MAX_LIMIT};
+use crate::ticker::StockTicker;
+use crate::types::*;
+
+/// The maximum number of contracts returned per page of the options chain
+/// snapshot.
+const OPTIONS_CHAIN_PAGE_LIMIT: &str = "250";
+
+/// The maximum number of contracts returned per page of the options contracts
+/// reference API.
+const OPTIONS_CONTRACTS_PAGE_LIMIT: &str = "1000";
+
+/// The number of daily bars an as-of chain keeps in flight at once.
+const AS_OF_CHAIN_CONCURRENCY: usize = 8;
+
+/// A contract that can be placed in an [`OptionsChain`].
+trait ChainContract {
+    fn expiration_date(&self) -> &str;
+    fn strike_price(&self) -> f64;
+    fn contract_type(&self) -> OptionsContractType;
+}
+
+impl ChainContract for OptionsContractSnapshot {
+    fn expiration_date(&self) -> &str {
+        &self.details.expiration_date
+    }
+
+    fn strike_price(&self) -> f64 {
+        self.details.strike_price
+    }
+
+    fn contract_type(&self) -> OptionsContractType {
+        self.details.contract_type
+    }
+}
+
+impl ChainContract for OptionsContract {
+    fn expiration_date(&self) -> &str {
+        &self.expiration_date
+    }
+
+    fn strike_price(&self) -> f64 {
+        self.strike_price
+    }
+
+    fn contract_type(&self) -> OptionsContractType {
+        self.contract_type
+    }
+}
+
+impl ChainContract for OptionsContractAsOf {
+    fn expiration_date(&self) -> &str {
+        &self.details.expiration_date
+    }
+
+    fn strike_price(&self) -> f64 {
+        self.details.strike_price
+    }
+
+    fn contract_type(&self) -> OptionsContractType {
+        self.details.contract_type
+    }
+}
+
+/// Client-side filters applied to an options chain by
+/// [`RESTClient::options_chain()`].
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::rest::OptionsChainFilter;
+/// use polygon_client::types::OptionsContractType;
+///
+/// // Calls expiring in December 2025 with a strike within 10% of the
+/// // underlying price.
+/// let filter = OptionsChainFilter::new()
+///     .contract_type(OptionsContractType::Call)
+///     .expiration_range("2025-12-01", "2025-12-31")
+///     .moneyness(0.1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OptionsChainFilter {
+    contract_type: Option<OptionsContractType>,
+    expiration_from: Option<String>,
+    expiration_to: Option<String>,
+    moneyness: Option<f64>,
+}
+
+impl OptionsChainFilter {
+    /// Returns a filter that matches every contract.
+    pub fn new() -> Self {
+        OptionsChainFilter::default()
+    }
+
+    /// Only include contracts of the given type.
+    pub fn contract_type(mut self, contract_type: OptionsContractType) -> Self {
+        self.contract_type = Some(contract_type);
+        self
+    }
+
+    /// Only include contracts that expire between `from` and `to`, inclusive.
+    /// Dates are formatted as `YYYY-MM-DD`.
+    pub fn expiration_range(mut self, from: &str, to: &str) -> Self {
+        self.expiration_from = Some(String::from(from));
+        self.expiration_to = Some(String::from(to));
+        self
+    }
+
+    /// Only include contracts with a strike price within `window` of the
+    /// underlying price, expressed as a fraction of the underlying price. For
+    /// example, a window of `0.1` includes strikes within 10% of the
+    /// underlying price.
+    ///
+    /// This filter is not applied if the snapshot does not include the price
+    /// of the underlying asset.
+    pub fn moneyness(mut self, window: f64) -> Self {
+        self.moneyness = Some(window);
+        self
+    }
+
+    /// Returns the query parameters that let the server apply as much of the
+    /// filter as possible, reducing the number of pages requested.
+    fn query_params(&self, limit: &str) -> Vec<(&'static str, String)> {
+        let mut params = vec![("limit", String::from(limit))];
+
+        if let Some(contract_type) = self.contract_type {
+            params.push(("contract_type", contract_type.to_string()));
+        }
+        if let Some(from) = &self.expiration_from {
+            params.push(("expiration_date.gte", from.clone()));
+        }
+        if let Some(to) = &self.expiration_to {
+            params.push(("expiration_date.lte", to.clone()));
+        }
+
+        params
+    }
+
+    fn matches<C: ChainContract>(&self, contract: &C, underlying_price: Option<f64>) -> bool {
+        if let Some(contract_type) = self.contract_type {
+            if contract.contract_type() != contract_type {
+                return false;
+            }
+        }
+
+        if let Some(from) = &self.expiration_from {
+            if contract.expiration_date() < from.as_str() {
+                return false;
+            }
+        }
+
+        if let Some(to) = &self.expiration_to {
+            if contract.expiration_date() > to.as_str() {
+                return false;
+            }
+        }
+
+        if let (Some(window), Some(price)) = (self.moneyness, underlying_price) {
+            if (contract.strike_price() - price).abs() > price * window {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl RESTClient {
+    /// Get a snapshot of all options contracts for an underlying ticker using
+    /// the [/v3/snapshot/options/{underlyingAsset}](https://polygon.io/docs/options/get_v3_snapshot_options__underlyingasset) API.
+    ///
+    /// Only the first page of contracts is returned. Use
+    /// [`RESTClient::options_chain()`] to collect the full chain.
+    pub async fn options_chain_snapshot(
+        &self,
+        underlying_asset: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<OptionsChainSnapshotResponse, Error> {
+        let underlying_asset: StockTicker = underlying_asset.into();
+        let uri = format!("/v3/snapshot/options/{}", underlying_asset);
+        self.send_limited_request::<OptionsChainSnapshotResponse>(
+            &uri,
+            query_params,
+            OPTIONS_CHAIN_SNAPSHOT_MAX_LIMIT,
+        )
+        .await
+    }
+
+    /// Get the full options chain for an underlying ticker, grouped by
+    /// expiration date and then by strike price in ascending order.
+    ///
+    /// Every page of the [/v3/snapshot/options/{underlyingAsset}](https://polygon.io/docs/options/get_v3_snapshot_options__underlyingasset)
+    /// API is requested and only the contracts that match `filter` are kept.
+    pub async fn options_chain(
+        &self,
+        underlying_asset: impl Into<StockTicker>,
+        filter: &OptionsChainFilter,
+    ) -> Result<OptionsChain, Error> {
+        let underlying_asset: StockTicker = underlying_asset.into();
+        let uri = format!("/v3/snapshot/options/{}", underlying_asset);
+        let params = filter.query_params(OPTIONS_CHAIN_PAGE_LIMIT);
+        let query_params = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+
+        let contracts: Vec<OptionsContractSnapshot> = self
+            .paginate::<OptionsChainSnapshotResponse>(&uri, &query_params)
+            .try_collect()
+            .await?;
+
+        let underlying_price = contracts
+            .iter()
+            .find_map(|c| c.underlying_asset.as_ref().and_then(|u| u.price));
+
+        let contracts = contracts
+            .into_iter()
+            .filter(|c| filter.matches(c, underlying_price))
+            .collect();
+
+        Ok(OptionsChain {
+            underlying_ticker: String::from(underlying_asset),
+            underlying_price,
+            expirations: group_chain(contracts),
+        })
+    }
+
+    /// Get the options contracts of an underlying ticker, or of the whole
+    /// market, using the [/v3/reference/options/contracts](https://polygon.io/docs/options/get_v3_reference_options_contracts) API.
+    ///
+    /// The `as_of` query parameter lists the contracts as they were on a past
+    /// date. Use [`RESTClient::paginate()`] with [`OptionsContractsResponse`]
+    /// to list every contract.
+    pub async fn reference_options_contracts(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<OptionsContractsResponse, Error> {
+        self.send_request::<OptionsContractsResponse>(
+            "/v3/reference/options/contracts",
+            query_params,
+        )
+        .await
+    }
+
+    /// Reconstruct the options chain of an underlying ticker as it was on a
+    /// past `date`, grouped the same way as [`RESTClient::options_chain()`].
+    ///
+    /// The contracts listed on `date` that had not yet expired are taken from
+    /// the [/v3/reference/options/contracts](https://polygon.io/docs/options/get_v3_reference_options_contracts)
+    /// API with its `as_of` parameter, and only those that match `filter` are
+    /// kept. The daily bar of each remaining contract on `date` is then
+    /// requested, and its close serves as an approximate settlement price.
+    /// The underlying price used by the moneyness filter is the close of the
+    /// underlying on `date`.
+    ///
+    /// One request is sent per contract, so a narrow filter keeps the number
+    /// of requests down. Several requests are sent concurrently and rate
+    /// limited requests are retried according to the client's
+    /// [`RateLimitPolicy`](crate::rest::RateLimitPolicy). Any other error
+    /// fails the whole chain.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use chrono::NaiveDate;
+    /// use polygon_client::rest::{OptionsChainFilter, RESTClient};
+    /// use polygon_client::ticker::StockTicker;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let spy = StockTicker::new("SPY").unwrap();
+    ///     let date = NaiveDate::from_ymd_opt(2023, 3, 1).unwrap();
+    ///     let filter = OptionsChainFilter::new()
+    ///         .expiration_range("2023-03-01", "2023-03-31")
+    ///         .moneyness(0.05);
+    ///     let chain = client.options_chain_as_of(spy, date, &filter).await.unwrap();
+    ///     for expiration in chain.expirations {
+    ///         for strike in expiration.strikes {
+    ///             let call = strike.call.and_then(|c| c.settlement_price());
+    ///             println!("{} {}: {:?}", expiration.expiration_date, strike.strike_price, call);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn options_chain_as_of(
+        &self,
+        underlying_asset: impl Into<StockTicker>,
+        date: NaiveDate,
+        filter: &OptionsChainFilter,
+    ) -> Result<OptionsChain<OptionsContractAsOf>, Error> {
+        let underlying_asset: StockTicker = underlying_asset.into();
+        let date = date.format("%Y-%m-%d").to_string();
+        let underlying_price = self
+            .daily_bar(underlying_asset.as_str(), &date)
+            .await?
+            .map(|bar| bar.c);
+
+        let mut params = filter.query_params(OPTIONS_CONTRACTS_PAGE_LIMIT);
+        params.push(("underlying_ticker", underlying_asset.to_string()));
+        params.push(("as_of", date.clone()));
+        let query_params = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+
+        let contracts: Vec<OptionsContract> = self
+            .paginate::<OptionsContractsResponse>("/v3/reference/options/contracts", &query_params)
+            .try_collect()
+            .await?;
+
+        let date = date.as_str();
+        let contracts = contracts
+            .into_iter()
+            .filter(|c| c.expiration_date.as_str() >= date && filter.matches(c, underlying_price));
+        let contracts = stream::iter(contracts)
+            .map(|details| async move {
+                let bar = self.daily_bar(&details.ticker, date).await?;
+                Ok::<_, Error>(OptionsContractAsOf { details, bar })
+            })
+            .buffered(AS_OF_CHAIN_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        Ok(OptionsChain {
+            underlying_ticker: String::from(underlying_asset),
+            underlying_price,
+            expirations: group_chain(contracts),
+        })
+    }
+
+    /// Returns the daily bar of a ticker on a date formatted as `YYYY-MM-DD`,
+    /// or `None` if it did not trade that day.
+    async fn daily_bar(&self, ticker: &str, date: &str) -> Result<Option<Bar>, Error> {
+        let url = format!(
+            "{}/v2/aggs/ticker/{}/range/1/day/{}/{}",
+            self.inner.api_url, ticker, date, date
+        );
+        let no_params: [(&str, &str); 0] = [];
+        let resp = self
+            .send_url_with_rate_limit::<AggregatesResponse, _>(&url, &no_params)
+            .await?;
+        Ok(resp.results.into_iter().next())
+    }
+}
+
+/// Groups contracts by expiration date and then by strike price, both in
+/// ascending order.
+fn group_chain<C: ChainContract>(mut contracts: Vec<C>) -> Vec<OptionsChainExpiration<C>> {
+    contracts.sort_by(|a, b| {
+        a.expiration_date().cmp(b.expiration_date()).then_with(|| {
+            a.strike_price()
+                .partial_cmp(&b.strike_price())
+                .unwrap_or(Ordering::Equal)
+        })
+    });
+
+    let mut expirations: Vec<OptionsChainExpiration<C>> = Vec::new();
+    for contract in contracts {
+        if expirations
+            .last()
+            .is_none_or(|e| e.expiration_date != contract.expiration_date())
+        {
+            expirations.push(OptionsChainExpiration {
+                expiration_date: String::from(contract.expiration_date()),
+                strikes: Vec::new(),
+            });
+        }
+
+        let strikes = match expirations.last_mut() {
+            Some(expiration) => &mut expiration.strikes,
+            None => continue,
+        };
+        if strikes
+            .last()
+            .is_none_or(|s| s.strike_price != contract.strike_price())
+        {
+            strikes.push(OptionsChainStrike {
+                strike_price: contract.strike_price(),
+                call: None,
+                put: None,
+            });
+        }
+
+        let strike = match strikes.last_mut() {
+            Some(strike) => strike,
+            None => continue,
+        };
+        match contract.contract_type() {
+            OptionsContractType::Call => strike.call = Some(contract),
+            OptionsContractType::Put => strike.put = Some(contract),
+            OptionsContractType::Other => {}
+        }
+    }
+
+    expirations
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::{OptionsChainFilter, RESTClient};
+    use crate::ticker::StockTicker;
+    use crate::types::*;
+    use std::collections::HashMap;
+
+    fn contract(contract_type: &str, expiration_date: &str, strike_price: f64) -> String {
+        format!(
+            r#"{{"details":{{"contract_type":"{}","exercise_style":"american","expiration_date":"{}","shares_per_contract":100,"strike_price":{},"ticker":"O:AAPL"}},"underlying_asset":{{"price":100.0,"ticker":"AAPL"}}}}"#,
+            contract_type, expiration_date, strike_price
+        )
+    }
+
+    fn chain_page(contracts: &[String], next_url: Option<&str>) -> String {
+        let next_url = match next_url {
+            Some(u) => format!(r#","next_url":"{}""#, u),
+            None => String::new(),
+        };
+        format!(
+            r#"{{"request_id":"1","status":"OK","results":[{}]{}}}"#,
+            contracts.join(","),
+            next_url
+        )
+    }
+
+    #[test]
+    fn test_options_chain_snapshot() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .options_chain_snapshot(StockTicker::new("AAPL").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+    }
+
+    #[test]
+    fn test_options_chain_groups_and_filters() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(chain_page(
+                    &[
+                        contract("put", "2025-12-19", 105.0),
+                        contract("call", "2026-01-16", 100.0),
+                        contract("call", "2025-12-19", 150.0),
+                    ],
+                    Some("https://api.polygon.io/v3/snapshot/options/AAPL?cursor=abc"),
+                )),
+                Response::ok(chain_page(
+                    &[
+                        contract("call", "2025-12-19", 105.0),
+                        contract("call", "2025-12-19", 95.0),
+                    ],
+                    None,
+                )),
+            ])
+            .await;
+
+            let client = test_server::client(&server);
+
+            let filter = OptionsChainFilter::new()
+                .expiration_range("2025-12-01", "2025-12-31")
+                .moneyness(0.1);
+            let chain = client
+                .options_chain(StockTicker::new("AAPL").unwrap(), &filter)
+                .await
+                .unwrap();
+
+            assert_eq!(chain.underlying_price, Some(100.0));
+            assert_eq!(chain.expirations.len(), 1);
+
+            let expiration = &chain.expirations[0];
+            assert_eq!(expiration.expiration_date, "2025-12-19");
+            let strikes: Vec<_> = expiration.strikes.iter().map(|s| s.strike_price).collect();
+            assert_eq!(strikes, vec![95.0, 105.0]);
+            assert!(expiration.strikes[0].put.is_none());
+            assert!(expiration.strikes[1].call.is_some());
+            assert!(expiration.strikes[1].put.is_some());
+
+            let requests = server.requests();
+            assert_eq!(requests.len(), 2);
+            assert!(requests[0].contains("expiration_date.gte=2025-12-01"));
+            assert!(requests[0].contains("expiration_date.lte=2025-12-31"));
+        });
+    }
+
+    #[test]
+    fn test_options_chain_filters_contract_type() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(chain_page(
+                &[
+                    contract("put", "2025-12-19", 100.0),
+                    contract("call", "2025-12-19", 100.0),
+                ],
+                None,
+            ))])
+            .await;
+
+            let client = test_server::client(&server);
+
+            let filter = OptionsChainFilter::new().contract_type(OptionsContractType::Put);
+            let chain = client
+                .options_chain(StockTicker::new("AAPL").unwrap(), &filter)
+                .await
+                .unwrap();
+
+            let strike = &chain.expirations[0].strikes[0];
+            assert!(strike.put.is_some());
+            assert!(strike.call.is_none());
+            assert!(server.requests()[0].contains("contract_type=put"));
+        });
+    }
+    fn daily_bar(ticker: &str, close: Option<f64>) -> String {
+        let results = match close {
+            Some(c) => format!(
+                r#"[{{"c":{},"h":{},"l":{},"o":{},"t":1677646800000,"v":10}}]"#,
+                c, c, c, c
+            ),
+            None => String::from("[]"),
+        };
+        format!(
+            r#"{{"ticker":"{}","adjusted":true,"queryCount":1,"request_id":"1","resultsCount":1,"status":"OK","results":{}}}"#,
+            ticker, results
+        )
+    }
+
+    fn reference_contract(
+        ticker: &str,
+        contract_type: &str,
+        expiration_date: &str,
+        strike_price: f64,
+    ) -> String {
+        format!(
+            r#"{{"ticker":"{}","underlying_ticker":"SPY","contract_type":"{}","exercise_style":"american","expiration_date":"{}","strike_price":{},"shares_per_contract":100}}"#,
+            ticker, contract_type, expiration_date, strike_price
+        )
+    }
+
+    #[test]
+    fn test_options_chain_as_of() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(daily_bar("SPY", Some(400.0)))
+                    .on_path("/v2/aggs/ticker/SPY/range/1/day/2023-03-01/2023-03-01"),
+                Response::ok(chain_page(
+                    &[
+                        reference_contract("O:SPY230302P00400000", "put", "2023-03-02", 400.0),
+                        reference_contract("O:SPY230302C00400000", "call", "2023-03-02", 400.0),
+                        reference_contract("O:SPY230302C00500000", "call", "2023-03-02", 500.0),
+                    ],
+                    None,
+                ))
+                .on_path("/v3/reference/options/contracts"),
+                Response::ok(daily_bar("O:SPY230302P00400000", None))
+                    .on_path("/v2/aggs/ticker/O:SPY230302P00400000/"),
+                Response::ok(daily_bar("O:SPY230302C00400000", Some(3.5)))
+                    .on_path("/v2/aggs/ticker/O:SPY230302C00400000/"),
+            ])
+            .await;
+
+            let client = test_server::client(&server);
+
+            let date = chrono::NaiveDate::from_ymd_opt(2023, 3, 1).unwrap();
+            let filter = OptionsChainFilter::new().moneyness(0.1);
+            let chain = client
+                .options_chain_as_of(StockTicker::new("SPY").unwrap(), date, &filter)
+                .await
+                .unwrap();
+
+            assert_eq!(chain.underlying_price, Some(400.0));
+            assert_eq!(chain.expirations.len(), 1);
+
+            let strikes = &chain.expirations[0].strikes;
+            assert_eq!(strikes.len(), 1);
+            assert_eq!(strikes[0].strike_price, 400.0);
+            let call = strikes[0].call.as_ref().unwrap();
+            assert_eq!(call.settlement_price(), Some(3.5));
+            let put = strikes[0].put.as_ref().unwrap();
+            assert_eq!(put.settlement_price(), None);
+
+            let requests = server.requests();
+            assert_eq!(requests.len(), 4);
+            assert!(requests[1].contains("underlying_ticker=SPY"));
+            assert!(requests[1].contains("as_of=2023-03-01"));
+        });
+    }
+}
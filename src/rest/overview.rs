@@ -0,0 +1,159 @@
+//! Composite helpers that combine several endpoints into a single call.
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rest::composite::collect_part;
+use crate::rest::RESTClient;
+use crate::ticker::StockTicker;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get an overview of a stock ticker suitable for a quote page.
+    ///
+    /// The ticker details, previous day's bar, most recent trade, and most
+    /// recent news article are requested concurrently. The ticker details
+    /// are memoized in the client's
+    /// [`ReferenceCache`](crate::rest::ReferenceCache).
+    ///
+    /// If any of the requests fails, the error of the first failed part in
+    /// that order is returned, unless the client uses
+    /// [`CompositePolicy::Partial`](crate::rest::CompositePolicy::Partial),
+    /// in which case the failed parts are left out and their errors are
+    /// listed in [`TickerOverview::errors`].
+    pub async fn ticker_overview(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+    ) -> Result<TickerOverview, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let no_params = HashMap::new();
+        let mut news_params = HashMap::new();
+        news_params.insert("ticker", stocks_ticker.as_str());
+        news_params.insert("order", Order::Desc.as_str());
+        news_params.insert("sort", Sort::PublishedUtc.as_str());
+        news_params.insert("limit", "1");
+
+        let (details, previous_close, last_trade, news) = futures::join!(
+            self.cached_ticker_details(&stocks_ticker),
+            self.stock_equities_previous_close(&stocks_ticker, &no_params),
+            self.stock_equities_historic_trades(&stocks_ticker, &no_params),
+            self.reference_ticker_news(&news_params),
+        );
+
+        let policy = self.inner.composite_policy;
+        let mut errors = Vec::new();
+        let details = collect_part(policy, "details", details, &mut errors)?;
+        let previous_close = collect_part(policy, "previous_close", previous_close, &mut errors)?;
+        let last_trade = collect_part(policy, "last_trade", last_trade, &mut errors)?;
+        let news = collect_part(policy, "latest_news", news, &mut errors)?;
+
+        Ok(TickerOverview {
+            details,
+            previous_close: previous_close.and_then(|r| r.results.into_iter().next()),
+            last_trade: last_trade.map(|r| r.results),
+            latest_news: news.and_then(|r| r.results.into_iter().next()),
+            errors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::{CompositePolicy, RESTClient};
+    use crate::ticker::StockTicker;
+
+    #[test]
+    fn test_ticker_overview() {
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).ticker_overview(StockTicker::new("MSFT").unwrap()),
+        )
+        .unwrap();
+        assert_eq!(resp.details.unwrap().ticker, "MSFT");
+        assert_eq!(resp.last_trade.unwrap().ticker.unwrap(), "MSFT");
+        assert!(resp.previous_close.is_some());
+    }
+
+    #[test]
+    fn test_ticker_overview_combines_responses() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(include_str!("../../fixtures/ticker_details_v3.json").into())
+                    .on_path("/v3/reference/tickers/MSFT"),
+                Response::ok(include_str!("../../fixtures/previous_close.json").into())
+                    .on_path("/v2/aggs/ticker/MSFT/prev"),
+                Response::ok(include_str!("../../fixtures/last_trade.json").into())
+                    .on_path("/v2/last/trade/MSFT"),
+                Response::ok(include_str!("../../fixtures/news.json").into())
+                    .on_path("/v2/reference/news"),
+            ])
+            .await;
+
+            let client = test_server::client(&server);
+
+            let overview = client
+                .ticker_overview(StockTicker::new("MSFT").unwrap())
+                .await
+                .unwrap();
+            assert_eq!(overview.details.unwrap().name, "Microsoft Corp");
+            assert_eq!(overview.previous_close.unwrap().c, 294.85);
+            assert_eq!(overview.last_trade.unwrap().price.unwrap(), 294.9);
+            assert_eq!(
+                overview.latest_news.unwrap().title,
+                "Microsoft Announces Quarterly Dividend"
+            );
+        });
+    }
+
+    #[test]
+    fn test_ticker_overview_fails_if_any_request_fails() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(include_str!("../../fixtures/ticker_details_v3.json").into())
+                    .on_path("/v3/reference/tickers/MSFT"),
+                Response::ok(include_str!("../../fixtures/previous_close.json").into())
+                    .on_path("/v2/aggs/ticker/MSFT/prev"),
+                Response::ok(include_str!("../../fixtures/news.json").into())
+                    .on_path("/v2/reference/news"),
+            ])
+            .await;
+
+            let client = test_server::client(&server);
+
+            assert!(client
+                .ticker_overview(StockTicker::new("MSFT").unwrap())
+                .await
+                .is_err());
+        });
+    }
+
+    #[test]
+    fn test_ticker_overview_partial() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(include_str!("../../fixtures/ticker_details_v3.json").into())
+                    .on_path("/v3/reference/tickers/MSFT"),
+                Response::ok(include_str!("../../fixtures/previous_close.json").into())
+                    .on_path("/v2/aggs/ticker/MSFT/prev"),
+                Response::ok(include_str!("../../fixtures/news.json").into())
+                    .on_path("/v2/reference/news"),
+            ])
+            .await;
+
+            let client = test_server::builder(&server)
+                .composite_policy(CompositePolicy::Partial)
+                .build()
+                .unwrap();
+
+            let overview = client
+                .ticker_overview(StockTicker::new("MSFT").unwrap())
+                .await
+                .unwrap();
+            assert_eq!(overview.details.unwrap().name, "Microsoft Corp");
+            assert_eq!(overview.previous_close.unwrap().c, 294.85);
+            assert!(overview.last_trade.is_none());
+            assert!(overview.latest_news.is_some());
+            assert_eq!(overview.errors.len(), 1);
+            assert_eq!(overview.errors[0].part, "last_trade");
+        });
+    }
+}
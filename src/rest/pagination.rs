@@ -0,0 +1,798 @@
+//! Support for walking paginated responses.
+use std::collections::HashMap;
+
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::types::*;
+
+/// A response that contains a single page of results and, optionally, the URL
+/// of the next page.
+pub trait Paginated: serde::de::DeserializeOwned {
+    /// The type of each result in the page.
+    type Item;
+
+    /// Returns the URL of the next page, if any.
+    fn next_url(&self) -> Option<&str>;
+
+    /// Consumes the page and returns its results.
+    fn into_results(self) -> Vec<Self::Item>;
+
+    /// Returns the cursor of the next page, if any.
+    fn cursor(&self) -> Option<PageCursor> {
+        self.next_url().and_then(PageCursor::from_next_url)
+    }
+}
+
+/// The position of the next page of a paginated endpoint, taken from the
+/// `cursor` query parameter of a `next_url`.
+///
+/// The cursor holds the query of the listing along with the position in it,
+/// so a long backfill can persist the cursor of each page, such as to a file
+/// or a database, and pick up where it left off after a restart with
+/// [`RESTClient::paginate_from_cursor()`]. It serializes as a plain string.
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::rest::PageCursor;
+///
+/// let cursor = PageCursor::from_next_url(
+///     "https://api.polygon.io/v3/reference/tickers?cursor=YWN0aXZlPXRydWU",
+/// )
+/// .unwrap();
+/// assert_eq!(cursor.as_str(), "YWN0aXZlPXRydWU");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PageCursor(String);
+
+impl PageCursor {
+    /// Returns a cursor with the given value, such as one that was persisted
+    /// with [`PageCursor::as_str()`].
+    pub fn new(cursor: &str) -> Self {
+        PageCursor(String::from(cursor))
+    }
+
+    /// Returns the cursor of a `next_url`, or `None` if the URL has no
+    /// `cursor` query parameter.
+    pub fn from_next_url(next_url: &str) -> Option<Self> {
+        let url = match Url::parse(next_url) {
+            Ok(url) => url,
+            Err(_) => Url::parse("https://api.polygon.io")
+                .ok()?
+                .join(next_url)
+                .ok()?,
+        };
+        url.query_pairs()
+            .find(|(name, _)| name == "cursor")
+            .map(|(_, value)| PageCursor(value.into_owned()))
+    }
+
+    /// Returns the value of the cursor.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Paginated for ReferenceTickersResponseV3 {
+    type Item = ReferenceTickersResponseTickerV3;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for ReferenceTickerNewsResponseV2 {
+    type Item = ReferenceTickerNewsResultsV2;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for ReferenceStockFinancialsVXResponse {
+    type Item = ReferenceStockFinancialsVXResult;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for ReferenceStockDividendsResponseV3 {
+    type Item = ReferenceStockDividendsResultV3;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for StockEquitiesAggregatesResponse {
+    type Item = StockEquitiesAggregates;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for StockEquitiesTradesResponseV3 {
+    type Item = StockEquitiesTradeV3;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for StockEquitiesQuotesResponseV3 {
+    type Item = StockEquitiesQuoteV3;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for ForexQuotesResponseV3 {
+    type Item = ForexQuoteV3;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for CryptoTradesResponseV3 {
+    type Item = CryptoTradeV3;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl<T> Paginated for SlimResponse<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    type Item = T;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for EconomyInflationResponse {
+    type Item = EconomyInflation;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for EconomyInflationExpectationsResponse {
+    type Item = EconomyInflationExpectations;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for EconomyTreasuryYieldsResponse {
+    type Item = EconomyTreasuryYields;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for FuturesProductsResponse {
+    type Item = FuturesProduct;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for FuturesContractsResponse {
+    type Item = FuturesContract;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for FuturesTradesResponse {
+    type Item = FuturesTrade;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for FuturesQuotesResponse {
+    type Item = FuturesQuote;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for IndicesSnapshotResponse {
+    type Item = IndexSnapshot;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for BenzingaGuidanceResponse {
+    type Item = BenzingaGuidance;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for OptionsContractsResponse {
+    type Item = OptionsContract;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+impl Paginated for OptionsChainSnapshotResponse {
+    type Item = OptionsContractSnapshot;
+
+    fn next_url(&self) -> Option<&str> {
+        self.next_url.as_deref()
+    }
+
+    fn into_results(self) -> Vec<Self::Item> {
+        self.results
+    }
+}
+
+/// Caps on how much [`RESTClient::collect_all()`] fetches. By default, every
+/// page is fetched.
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::rest::CollectOptions;
+///
+/// let options = CollectOptions::new().max_pages(10).max_items(5000);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CollectOptions {
+    max_pages: Option<usize>,
+    max_items: Option<usize>,
+}
+
+impl CollectOptions {
+    /// Returns options that fetch every page.
+    pub fn new() -> Self {
+        CollectOptions::default()
+    }
+
+    /// Stops after `max_pages` pages.
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    /// Stops once `max_items` results have been fetched, and returns no more
+    /// than that.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+}
+
+struct PageRequest {
+    url: String,
+    query_params: Vec<(String, String)>,
+}
+
+impl RESTClient {
+    /// Returns a stream of results from a paginated endpoint.
+    ///
+    /// The first page is requested from `uri` with `query_params`. Each
+    /// subsequent page is requested from the `next_url` of the previous page
+    /// until no `next_url` is returned.
+    ///
+    /// If the server rate limits a page request, the stream waits and requests
+    /// the same page again according to the client's
+    /// [`RateLimitPolicy`](crate::rest::RateLimitPolicy), so a long crawl
+    /// resumes where it left off instead of failing. Any other error ends the
+    /// stream after it is yielded.
+    pub fn paginate<'a, R>(
+        &'a self,
+        uri: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> impl Stream<Item = Result<R::Item, Error>> + 'a
+    where
+        R: Paginated + Send + 'static,
+        R::Item: 'a,
+    {
+        let first = PageRequest {
+            url: format!("{}{}", self.inner.api_url, uri),
+            query_params: query_params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+        self.paginate_from::<R>(Ok(first))
+    }
+
+    /// Returns a stream of results from a paginated endpoint starting at the
+    /// page of `cursor`, such as one persisted by an earlier crawl of the same
+    /// endpoint, and following the `next_url` of each page from there.
+    ///
+    /// Rate limited pages are requested again as with
+    /// [`RESTClient::paginate()`].
+    pub fn paginate_from_cursor<'a, R>(
+        &'a self,
+        uri: &str,
+        cursor: &PageCursor,
+    ) -> impl Stream<Item = Result<R::Item, Error>> + 'a
+    where
+        R: Paginated + Send + 'static,
+        R::Item: 'a,
+    {
+        let first = PageRequest {
+            url: format!("{}{}", self.inner.api_url, uri),
+            query_params: vec![(String::from("cursor"), cursor.0.clone())],
+        };
+        self.paginate_from::<R>(Ok(first))
+    }
+
+    /// Returns a stream of results from a paginated endpoint whose `limit` is
+    /// at most `max`, like [`RESTClient::paginate()`].
+    ///
+    /// The `limit` in `query_params` is checked against `max` according to
+    /// the client's [`LimitPolicy`](crate::rest::LimitPolicy). If it is
+    /// rejected, the stream yields the error and ends.
+    pub(crate) fn paginate_limited<'a, R>(
+        &'a self,
+        uri: &str,
+        query_params: &HashMap<&str, &str>,
+        max: u32,
+    ) -> impl Stream<Item = Result<R::Item, Error>> + 'a
+    where
+        R: Paginated + Send + 'static,
+        R::Item: 'a,
+    {
+        let first = self
+            .limit_query(query_params, max)
+            .map(|(query, _)| PageRequest {
+                url: format!("{}{}", self.inner.api_url, uri),
+                query_params: query.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+            });
+        self.paginate_from::<R>(first)
+    }
+
+    /// Returns a stream of results from the page requested by `first` and
+    /// those that follow it.
+    fn paginate_from<'a, R>(
+        &'a self,
+        first: Result<PageRequest, Error>,
+    ) -> impl Stream<Item = Result<R::Item, Error>> + 'a
+    where
+        R: Paginated + Send + 'static,
+        R::Item: 'a,
+    {
+        self.pages_from::<R>(first)
+            .map_ok(|page| stream::iter(page.into_results().into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Returns a stream of the page requested by `first` and those that
+    /// follow it.
+    fn pages_from<'a, R>(
+        &'a self,
+        first: Result<PageRequest, Error>,
+    ) -> impl Stream<Item = Result<R, Error>> + 'a
+    where
+        R: Paginated + Send + 'static,
+    {
+        stream::try_unfold(Some(first), move |req| async move {
+            let req = match req {
+                Some(req) => req?,
+                None => return Ok::<_, Error>(None),
+            };
+
+            let page = self
+                .send_url_with_rate_limit::<R, _>(&req.url, &req.query_params)
+                .await?;
+
+            let next = page.next_url().map(|next_url| {
+                Ok(PageRequest {
+                    url: self.rebase_url(next_url),
+                    query_params: Vec::new(),
+                })
+            });
+
+            Ok(Some((page, next)))
+        })
+    }
+
+    /// Fetches the pages of a paginated endpoint, like
+    /// [`RESTClient::paginate()`], and returns their results in a single
+    /// `Vec`.
+    ///
+    /// Pages are requested one at a time, so they go through the client's
+    /// [`throttle()`](crate::rest::RESTClientBuilder::throttle) like any other
+    /// request, and rate limited pages are requested again according to its
+    /// [`RateLimitPolicy`](crate::rest::RateLimitPolicy). No more pages are
+    /// requested once one of the caps of `options` is reached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use polygon_client::rest::{CollectOptions, RESTClient};
+    /// use polygon_client::types::ReferenceTickersResponse;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let mut query_params = HashMap::new();
+    ///     query_params.insert("market", "stocks");
+    ///     let tickers = client
+    ///         .collect_all::<ReferenceTickersResponse>(
+    ///             "/v3/reference/tickers",
+    ///             &query_params,
+    ///             CollectOptions::new().max_items(5000),
+    ///         )
+    ///         .await
+    ///         .expect("failed to list tickers");
+    ///     println!("{} tickers", tickers.len());
+    /// }
+    /// ```
+    pub async fn collect_all<R>(
+        &self,
+        uri: &str,
+        query_params: &HashMap<&str, &str>,
+        options: CollectOptions,
+    ) -> Result<Vec<R::Item>, Error>
+    where
+        R: Paginated + Send + 'static,
+    {
+        let first = PageRequest {
+            url: format!("{}{}", self.inner.api_url, uri),
+            query_params: query_params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+        let max_pages = options.max_pages.unwrap_or(usize::MAX);
+        let max_items = options.max_items.unwrap_or(usize::MAX);
+        let pages = self.pages_from::<R>(Ok(first));
+        futures::pin_mut!(pages);
+
+        let mut results = Vec::new();
+        let mut fetched = 0;
+        while fetched < max_pages && results.len() < max_items {
+            match pages.try_next().await? {
+                Some(page) => results.extend(page.into_results()),
+                None => break,
+            }
+            fetched += 1;
+        }
+        results.truncate(max_items);
+        Ok(results)
+    }
+
+    /// Rewrites a `next_url` returned by the server so that it uses the
+    /// configured API URL.
+    fn rebase_url(&self, next_url: &str) -> String {
+        match Url::parse(next_url) {
+            Ok(url) => match url.query() {
+                Some(query) => format!("{}{}?{}", self.inner.api_url, url.path(), query),
+                None => format!("{}{}", self.inner.api_url, url.path()),
+            },
+            Err(_) => format!("{}{}", self.inner.api_url, next_url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::RateLimitPolicy;
+    use crate::types::*;
+    use futures::{StreamExt, TryStreamExt};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn tickers_page(tickers: &[&str], next_url: Option<&str>) -> String {
+        let results = tickers
+            .iter()
+            .map(|t| {
+                format!(
+                    r#"{{"ticker":"{}","name":"{}","market":"stocks","locale":"us","primary_exchange":"XNAS","active":true,"currency_name":"usd","last_updated_utc":"2021-01-01"}}"#,
+                    t, t
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let next_url = match next_url {
+            Some(u) => format!(r#","next_url":"{}""#, u),
+            None => String::new(),
+        };
+        format!(
+            r#"{{"results":[{}],"status":"OK","request_id":"1","count":{}{}}}"#,
+            results,
+            tickers.len(),
+            next_url
+        )
+    }
+
+    #[test]
+    fn test_paginate_resumes_after_rate_limit() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(tickers_page(
+                    &["AAPL", "MSFT"],
+                    Some("https://api.polygon.io/v3/reference/tickers?cursor=abc"),
+                )),
+                Response::rate_limited(Some(0)),
+                Response::ok(tickers_page(&["TSLA"], None)),
+            ])
+            .await;
+
+            let client = test_server::builder(&server)
+                .rate_limit_policy(RateLimitPolicy::new(1, Duration::from_millis(1)))
+                .build()
+                .unwrap();
+
+            let mut query_params = HashMap::new();
+            query_params.insert("market", "stocks");
+            let tickers: Vec<_> = client
+                .paginate::<ReferenceTickersResponse>("/v3/reference/tickers", &query_params)
+                .try_collect()
+                .await
+                .unwrap();
+
+            let tickers: Vec<_> = tickers.iter().map(|t| t.ticker.as_str()).collect();
+            assert_eq!(tickers, vec!["AAPL", "MSFT", "TSLA"]);
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/v3/reference/tickers?market=stocks",
+                    "/v3/reference/tickers?cursor=abc",
+                    "/v3/reference/tickers?cursor=abc",
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_paginate_gives_up_after_max_retries() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(tickers_page(
+                    &["AAPL"],
+                    Some("https://api.polygon.io/v3/reference/tickers?cursor=abc"),
+                )),
+                Response::rate_limited(None),
+                Response::rate_limited(None),
+            ])
+            .await;
+
+            let client = test_server::builder(&server)
+                .rate_limit_policy(RateLimitPolicy::new(1, Duration::from_millis(1)))
+                .build()
+                .unwrap();
+
+            let results: Vec<_> = client
+                .paginate::<ReferenceTickersResponse>("/v3/reference/tickers", &HashMap::new())
+                .collect::<Vec<_>>()
+                .await;
+
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].as_ref().unwrap().ticker, "AAPL");
+            assert!(matches!(
+                results[1],
+                Err(crate::error::Error::RateLimited {
+                    retry_after: None,
+                    ..
+                })
+            ));
+        });
+    }
+
+    #[test]
+    fn test_page_cursor() {
+        use crate::rest::{PageCursor, Paginated};
+
+        let page: ReferenceTickersResponse = serde_json::from_str(&tickers_page(
+            &["AAPL"],
+            Some("https://api.polygon.io/v3/reference/tickers?cursor=YWJj%3D&limit=1"),
+        ))
+        .unwrap();
+        let cursor = page.cursor().unwrap();
+        assert_eq!(cursor.as_str(), "YWJj=");
+        assert_eq!(
+            PageCursor::from_next_url("/v3/reference/tickers?cursor=YWJj%3D"),
+            Some(cursor.clone())
+        );
+        assert_eq!(
+            PageCursor::from_next_url("https://api.polygon.io/v3/reference/tickers"),
+            None
+        );
+
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(json, r#""YWJj=""#);
+        assert_eq!(serde_json::from_str::<PageCursor>(&json).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_paginate_from_cursor() {
+        use crate::rest::PageCursor;
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(tickers_page(
+                    &["MSFT"],
+                    Some("https://api.polygon.io/v3/reference/tickers?cursor=def"),
+                )),
+                Response::ok(tickers_page(&["TSLA"], None)),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let tickers: Vec<_> = client
+                .paginate_from_cursor::<ReferenceTickersResponse>(
+                    "/v3/reference/tickers",
+                    &PageCursor::new("abc="),
+                )
+                .map_ok(|t| t.ticker)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(tickers, vec!["MSFT", "TSLA"]);
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/v3/reference/tickers?cursor=abc%3D",
+                    "/v3/reference/tickers?cursor=def"
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_collect_all() {
+        use crate::rest::CollectOptions;
+
+        tokio_test::block_on(async {
+            let pages = || {
+                vec![
+                    Response::ok(tickers_page(
+                        &["AAPL", "MSFT"],
+                        Some("https://api.polygon.io/v3/reference/tickers?cursor=abc"),
+                    )),
+                    Response::ok(tickers_page(
+                        &["NVDA", "TSLA"],
+                        Some("https://api.polygon.io/v3/reference/tickers?cursor=def"),
+                    )),
+                    Response::ok(tickers_page(&["ZM"], None)),
+                ]
+            };
+            let collect = |server: &TestServer, options: CollectOptions| {
+                let client = test_server::client(server);
+                async move {
+                    client
+                        .collect_all::<ReferenceTickersResponse>(
+                            "/v3/reference/tickers",
+                            &HashMap::new(),
+                            options,
+                        )
+                        .await
+                        .unwrap()
+                        .into_iter()
+                        .map(|t| t.ticker)
+                        .collect::<Vec<_>>()
+                }
+            };
+
+            let server = TestServer::start(pages()).await;
+            let all = collect(&server, CollectOptions::new()).await;
+            assert_eq!(all, vec!["AAPL", "MSFT", "NVDA", "TSLA", "ZM"]);
+            assert_eq!(server.requests().len(), 3);
+
+            let server = TestServer::start(pages()).await;
+            let first_pages = collect(&server, CollectOptions::new().max_pages(2)).await;
+            assert_eq!(first_pages, vec!["AAPL", "MSFT", "NVDA", "TSLA"]);
+            assert_eq!(server.requests().len(), 2);
+
+            let server = TestServer::start(pages()).await;
+            let first_items = collect(&server, CollectOptions::new().max_items(3)).await;
+            assert_eq!(first_items, vec!["AAPL", "MSFT", "NVDA"]);
+            assert_eq!(server.requests().len(), 2);
+        });
+    }
+}
@@ -0,0 +1,77 @@
+//! Partner endpoints such as Benzinga.
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get the EPS and revenue guidance issued by companies using the
+    /// [/benzinga/v1/guidance](https://polygon.io/docs/rest/partners/benzinga/guidance) API.
+    ///
+    /// Filter by company with the `ticker` query parameter, and use
+    /// [`RESTClient::paginate()`] with [`BenzingaGuidanceResponse`] to list
+    /// every record.
+    pub async fn benzinga_guidance(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<BenzingaGuidanceResponse, Error> {
+        self.send_request::<BenzingaGuidanceResponse>("/benzinga/v1/guidance", query_params)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::RESTClient;
+    use crate::types::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_benzinga_guidance() {
+        let mut query_params = HashMap::new();
+        query_params.insert("ticker", "AAPL");
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).benzinga_guidance(&query_params))
+                .unwrap();
+        assert_eq!(resp.status, "OK");
+    }
+
+    #[test]
+    fn test_benzinga_guidance_response() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"status":"OK","request_id":"1","results":[{"benzinga_id":"1",
+                    "ticker":"AAPL","company_name":"Apple","date":"2024-05-02",
+                    "time":"16:30:00","fiscal_period":"Q3","fiscal_year":2024,
+                    "currency":"USD","min_eps_guidance":1.3,"max_eps_guidance":1.4,
+                    "min_revenue_guidance":8.1e10,"estimated_revenue_guidance":8.3e10,
+                    "positioning":"primary","release_type":"official","importance":4},
+                    {"benzinga_id":"2","ticker":"AAPL","date":"2024-02-01",
+                    "time":"08:00:00","estimated_eps_guidance":2.1}]}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("ticker", "AAPL");
+            let resp = client.benzinga_guidance(&query_params).await.unwrap();
+
+            let guidance = &resp.results[0];
+            assert_eq!(guidance.date, NaiveDate::from_ymd_opt(2024, 5, 2).unwrap());
+            assert_eq!(guidance.fiscal_period.as_deref(), Some("Q3"));
+            assert_eq!(guidance.eps_range(), Some((1.3, 1.4)));
+            assert_eq!(guidance.revenue_range(), Some((8.1e10, 8.3e10)));
+            assert_eq!(guidance.timing(), Some(ReleaseTiming::AfterMarket));
+
+            let guidance = &resp.results[1];
+            assert_eq!(guidance.eps_range(), Some((2.1, 2.1)));
+            assert_eq!(guidance.revenue_range(), None);
+            assert_eq!(guidance.timing(), Some(ReleaseTiming::PreMarket));
+            assert_eq!(server.requests(), vec!["/benzinga/v1/guidance?ticker=AAPL"]);
+        });
+    }
+}
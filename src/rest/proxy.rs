@@ -0,0 +1,196 @@
+//! Explicit proxy configuration.
+//!
+//! By default, the client picks up a proxy from the `HTTP_PROXY`,
+//! `HTTPS_PROXY`, and `NO_PROXY` environment variables, as well as from the
+//! system configuration on some platforms. Corporate environments often need
+//! a proxy that is set by the application instead, which is done by passing a
+//! [`ProxyConfig`] to
+//! [`RESTClientBuilder::proxy()`](crate::rest::RESTClientBuilder::proxy). A
+//! client with an explicit proxy ignores the environment altogether.
+use std::fmt;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::Error;
+
+/// The proxy through which a client sends its requests.
+///
+/// The password of the proxy is left out of the `Debug` output.
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::rest::{ProxyConfig, RESTClient};
+///
+/// let proxy = ProxyConfig::new("http://proxy.corp.example:3128")
+///     .basic_auth("svc-market-data", "hunter2")
+///     .no_proxy(&["localhost", "10.0.0.0/8", ".corp.example"]);
+///
+/// let client = RESTClient::builder()
+///     .auth_key("my-api-key")
+///     .proxy(proxy)
+///     .build()
+///     .expect("failed to build client");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ProxyConfig {
+    url: String,
+    basic_auth: Option<(String, String)>,
+    no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Returns a configuration that sends every request through the proxy at
+    /// `url`, such as `http://proxy.corp.example:3128`.
+    pub fn new(url: &str) -> Self {
+        ProxyConfig {
+            url: String::from(url),
+            ..Default::default()
+        }
+    }
+
+    /// Authenticates with the proxy using HTTP basic authentication.
+    pub fn basic_auth(mut self, username: &str, password: &str) -> Self {
+        self.basic_auth = Some((String::from(username), String::from(password)));
+        self
+    }
+
+    /// Sends requests to `hosts` directly instead of through the proxy.
+    ///
+    /// Each host is a domain name, where a leading `.` also matches its
+    /// subdomains, an IP address, or an IP network such as `10.0.0.0/8`, as
+    /// in the `NO_PROXY` environment variable.
+    pub fn no_proxy<S: AsRef<str>>(mut self, hosts: &[S]) -> Self {
+        self.no_proxy
+            .extend(hosts.iter().map(|h| String::from(h.as_ref())));
+        self
+    }
+
+    /// Returns the URL of the proxy.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the hosts that are not sent through the proxy.
+    pub fn no_proxy_hosts(&self) -> &[String] {
+        &self.no_proxy
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn to_proxy(&self) -> Result<reqwest::Proxy, Error> {
+        let mut proxy = reqwest::Proxy::all(&self.url)?;
+        if let Some((username, password)) = &self.basic_auth {
+            proxy = proxy.basic_auth(username, password);
+        }
+        if !self.no_proxy.is_empty() {
+            proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&self.no_proxy.join(",")));
+        }
+        Ok(proxy)
+    }
+}
+
+impl fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("url", &self.url)
+            .field(
+                "basic_auth",
+                &self
+                    .basic_auth
+                    .as_ref()
+                    .map(|(username, _)| (username, "***")),
+            )
+            .field("no_proxy", &self.no_proxy)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::ProxyConfig;
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
+    #[test]
+    fn test_proxy() {
+        use std::collections::HashMap;
+
+        use crate::rest::test_server::{Response, TestServer};
+        use crate::rest::RESTClient;
+
+        tokio_test::block_on(async {
+            let proxy =
+                TestServer::start(vec![Response::ok(String::from(r#"{"status":"OK"}"#))]).await;
+            let client = RESTClient::builder()
+                .auth_key("test")
+                .api_url("http://polygon.test")
+                .proxy(ProxyConfig::new(&proxy.url()).basic_auth("user", "pass"))
+                .build()
+                .unwrap();
+
+            client
+                .send_request::<serde_json::Value>("/v1/test", &HashMap::new())
+                .await
+                .unwrap();
+            assert_eq!(proxy.requests(), vec!["http://polygon.test/v1/test"]);
+            assert_eq!(
+                proxy.request_headers("Proxy-Authorization"),
+                vec![Some(String::from("Basic dXNlcjpwYXNz"))]
+            );
+        });
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
+    #[test]
+    fn test_no_proxy() {
+        use std::collections::HashMap;
+
+        use crate::rest::test_server::{self, Response, TestServer};
+        use crate::rest::RetryPolicy;
+
+        tokio_test::block_on(async {
+            let server =
+                TestServer::start(vec![Response::ok(String::from(r#"{"status":"OK"}"#))]).await;
+            // Nothing listens on the discard port, so requests sent through
+            // the proxy fail.
+            let proxy = ProxyConfig::new("http://127.0.0.1:9").no_proxy(&["127.0.0.1"]);
+            let client = test_server::builder(&server)
+                .proxy(proxy)
+                .retry_policy(RetryPolicy::disabled())
+                .build()
+                .unwrap();
+
+            client
+                .send_request::<serde_json::Value>("/v1/test", &HashMap::new())
+                .await
+                .unwrap();
+            assert_eq!(server.requests(), vec!["/v1/test"]);
+        });
+    }
+
+    #[test]
+    fn test_debug_hides_password() {
+        let proxy = ProxyConfig::new("http://proxy:3128").basic_auth("user", "secret");
+        let debug = format!("{:?}", proxy);
+        assert!(debug.contains("user"));
+        assert!(!debug.contains("secret"));
+    }
+}
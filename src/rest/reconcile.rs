@@ -0,0 +1,71 @@
+//! Reconciliation of streamed trades against official daily bars.
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::reconcile::{DailyStats, Reconciliation, Tolerance};
+use crate::rest::RESTClient;
+use crate::ticker::StockTicker;
+use crate::types::Timespan;
+
+impl RESTClient {
+    /// Compare daily statistics accumulated from streamed trades with the
+    /// official unadjusted daily bar from the [/v2/aggs/ticker/{stocks_ticker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__stocksticker__range__multiplier___timespan___from___to__anchor) API.
+    ///
+    /// The returned [`Reconciliation`] has no official bar if the API has
+    /// none for the day, such as when it has not been published yet.
+    pub async fn reconcile_daily(
+        &self,
+        stats: &DailyStats,
+        tolerance: Tolerance,
+    ) -> Result<Reconciliation, Error> {
+        let date = stats.date.format("%Y-%m-%d").to_string();
+        let mut query_params = HashMap::new();
+        query_params.insert("adjusted", "false");
+
+        let ticker = StockTicker::new(&stats.symbol)?;
+        let resp = self
+            .stock_equities_aggregates(ticker, 1, Timespan::Day, &date, &date, &query_params)
+            .await?;
+        let official = resp.results.into_iter().next();
+
+        Ok(Reconciliation::new(stats.clone(), official, tolerance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::events::TradeEvent;
+    use crate::reconcile::{DailyStatsAccumulator, Field, Tolerance};
+    use crate::rest::test_server::{self, Response, TestServer};
+
+    #[test]
+    fn test_reconcile_daily() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"ticker":"MSFT","status":"OK","queryCount":1,"resultsCount":1,"adjusted":false,
+                    "results":[{"v":300,"vw":101,"o":100,"c":102,"h":102,"l":100,"t":1704258000000,"n":3}],
+                    "request_id":"1"}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            // 2024-01-03 at 9:30 and 16:00 Eastern time.
+            let mut acc = DailyStatsAccumulator::new();
+            acc.push(&TradeEvent::new("MSFT", 100.0, 100.0, 1704292200000));
+            acc.push(&TradeEvent::new("MSFT", 102.0, 100.0, 1704315600000));
+            let stats = acc.stats().remove(0);
+
+            let report = client
+                .reconcile_daily(&stats, Tolerance::default())
+                .await
+                .unwrap();
+            assert!(report.official.is_some());
+            let fields: Vec<_> = report.discrepancies.iter().map(|d| d.field).collect();
+            assert_eq!(fields, vec![Field::Volume, Field::Trades]);
+            assert_eq!(
+                server.requests(),
+                vec!["/v2/aggs/ticker/MSFT/range/1/day/2024-01-03/2024-01-03?adjusted=false"]
+            );
+        });
+    }
+}
@@ -0,0 +1,898 @@
+//! Reference data endpoints such as tickers, news, splits, and dividends.
+use std::collections::HashMap;
+
+use futures::Stream;
+
+use crate::error::Error;
+use crate::rest::{
+    RESTClient, REFERENCE_STOCK_DIVIDENDS_MAX_LIMIT, REFERENCE_STOCK_FINANCIALS_VX_MAX_LIMIT,
+    REFERENCE_TICKERS_MAX_LIMIT, REFERENCE_TICKER_NEWS_MAX_LIMIT,
+};
+use crate::ticker::StockTicker;
+use crate::types::*;
+
+/// Typed filters for [`RESTClient::reference_tickers_with_options()`].
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::rest::ReferenceTickersOptions;
+/// use polygon_client::types::{AssetClass, TickerType};
+///
+/// let options = ReferenceTickersOptions::new()
+///     .market(AssetClass::Otc)
+///     .ticker_type(TickerType::Etf);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ReferenceTickersOptions {
+    market: Option<AssetClass>,
+    ticker_type: Option<TickerType>,
+    active: Option<bool>,
+}
+
+impl ReferenceTickersOptions {
+    /// Returns options that do not filter the tickers.
+    pub fn new() -> Self {
+        ReferenceTickersOptions::default()
+    }
+
+    /// Only list tickers of the given market.
+    pub fn market(mut self, market: AssetClass) -> Self {
+        self.market = Some(market);
+        self
+    }
+
+    /// Only list tickers of the given type.
+    pub fn ticker_type(mut self, ticker_type: TickerType) -> Self {
+        self.ticker_type = Some(ticker_type);
+        self
+    }
+
+    /// Only list tickers that are actively traded, or that are delisted.
+    pub fn active(mut self, active: bool) -> Self {
+        self.active = Some(active);
+        self
+    }
+
+    fn query_params(&self) -> HashMap<&'static str, &str> {
+        let mut params = HashMap::new();
+        if let Some(market) = &self.market {
+            params.insert("market", market.as_str());
+        }
+        if let Some(ticker_type) = &self.ticker_type {
+            params.insert("type", ticker_type.as_str());
+        }
+        if let Some(active) = self.active {
+            params.insert("active", if active { "true" } else { "false" });
+        }
+        params
+    }
+}
+
+impl RESTClient {
+    /// Query all ticker symbols supported by polygon.io using the
+    /// [/v3/reference/tickers](https://polygon.io/docs/get_v3_reference_tickers_anchor)
+    /// API.
+    pub async fn reference_tickers(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceTickersResponse, Error> {
+        self.send_limited_request::<ReferenceTickersResponse>(
+            "/v3/reference/tickers",
+            query_params,
+            REFERENCE_TICKERS_MAX_LIMIT,
+        )
+        .await
+    }
+
+    /// Stream every ticker symbol that matches `query_params` using the
+    /// [/v3/reference/tickers](https://polygon.io/docs/get_v3_reference_tickers_anchor)
+    /// API, following the `next_url` of each page until the last one.
+    ///
+    /// Rate limited pages are requested again as with
+    /// [`RESTClient::paginate()`].
+    pub fn reference_tickers_stream<'a>(
+        &'a self,
+        query_params: &HashMap<&str, &str>,
+    ) -> impl Stream<Item = Result<ReferenceTickersResponseTickerV3, Error>> + 'a {
+        self.paginate_limited::<ReferenceTickersResponse>(
+            "/v3/reference/tickers",
+            query_params,
+            REFERENCE_TICKERS_MAX_LIMIT,
+        )
+    }
+
+    /// Query the ticker symbols that match typed `options`, such as those of
+    /// a market, using the
+    /// [/v3/reference/tickers](https://polygon.io/docs/get_v3_reference_tickers_anchor)
+    /// API.
+    pub async fn reference_tickers_with_options(
+        &self,
+        options: &ReferenceTickersOptions,
+    ) -> Result<ReferenceTickersResponse, Error> {
+        self.reference_tickers(&options.query_params()).await
+    }
+
+    /// Stream every ticker symbol that matches typed `options` as with
+    /// [`RESTClient::reference_tickers_stream()`].
+    pub fn reference_tickers_stream_with_options<'a>(
+        &'a self,
+        options: &ReferenceTickersOptions,
+    ) -> impl Stream<Item = Result<ReferenceTickersResponseTickerV3, Error>> + 'a {
+        self.reference_tickers_stream(&options.query_params())
+    }
+
+    /// Get a mapping of ticker types to their descriptive names using the
+    /// [/v2/reference/types](https://polygon.io/docs/get_v2_reference_types_anchor)
+    /// API.
+    pub async fn reference_ticker_types(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceTickerTypesResponse, Error> {
+        self.send_request::<ReferenceTickerTypesResponse>("/v2/reference/types", query_params)
+            .await
+    }
+
+    /// Get details for a ticker symbol's company/entity using the
+    /// [/v1/meta/symbols/{stocks_ticker}/company](https://polygon.io/docs/get_v1_meta_symbols__stocksTicker__company_anchor)
+    /// API.
+    pub async fn reference_ticker_details(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceTickerDetailsResponse, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v1/meta/symbols/{}/company", stocks_ticker);
+        self.send_request::<ReferenceTickerDetailsResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get details for a ticker symbol's company/entity using the
+    /// [/vX/reference/tickers/{stocks_ticker}](https://polygon.io/docs/get_vX_reference_tickers__ticker__anchor)
+    /// API.
+    pub async fn reference_ticker_details_vx(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceTickerDetailsResponseVX, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/vX/reference/tickers/{}", stocks_ticker);
+        self.send_request::<ReferenceTickerDetailsResponseVX>(&uri, query_params)
+            .await
+    }
+
+    /// Get details for a ticker symbol's company/entity, including its
+    /// branding, industry classification, and share counts, using the
+    /// [/v3/reference/tickers/{ticker}](https://polygon.io/docs/stocks/get_v3_reference_tickers__ticker)
+    /// API.
+    pub async fn reference_ticker_details_v3(
+        &self,
+        ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceTickerDetailsResponseV3, Error> {
+        let ticker: StockTicker = ticker.into();
+        let uri = format!("/v3/reference/tickers/{}", ticker);
+        self.send_request::<ReferenceTickerDetailsResponseV3>(&uri, query_params)
+            .await
+    }
+
+    /// Get the most recent news articles related to a stock ticker symbol using
+    /// the [/v2/reference/news](https://polygon.io/docs/get_v2_reference_news_anchor) API.
+    pub async fn reference_ticker_news(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceTickerNewsResponse, Error> {
+        self.send_limited_request::<ReferenceTickerNewsResponse>(
+            "/v2/reference/news",
+            query_params,
+            REFERENCE_TICKER_NEWS_MAX_LIMIT,
+        )
+        .await
+    }
+
+    /// Stream every news article that matches `query_params` using the
+    /// [/v2/reference/news](https://polygon.io/docs/get_v2_reference_news_anchor)
+    /// API, following the `next_url` of each page until the last one.
+    ///
+    /// Together with the `published_utc.gte` query parameter, this backfills
+    /// the news history of a ticker.
+    pub fn reference_ticker_news_stream<'a>(
+        &'a self,
+        query_params: &HashMap<&str, &str>,
+    ) -> impl Stream<Item = Result<ReferenceTickerNewsResultsV2, Error>> + 'a {
+        self.paginate_limited::<ReferenceTickerNewsResponse>(
+            "/v2/reference/news",
+            query_params,
+            REFERENCE_TICKER_NEWS_MAX_LIMIT,
+        )
+    }
+
+    /// Get a list of markets that are currently supported by polygon.io using
+    /// the [/v2/reference/markets](https://polygon.io/docs/get_v2_reference_markets_anchor) API.
+    pub async fn reference_markets(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceMarketsResponse, Error> {
+        self.send_request::<ReferenceMarketsResponse>("/v2/reference/markets", query_params)
+            .await
+    }
+
+    /// Get a list of locales currently supported by polygon.io using the
+    /// [/v2/reference/locales](https://polygon.io/docs/get_v2_reference_locales_anchor) API.
+    pub async fn reference_locales(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceLocalesResponse, Error> {
+        self.send_request::<ReferenceLocalesResponse>("/v2/reference/locales", query_params)
+            .await
+    }
+
+    /// Get a list of historical stock splits for a ticker symbol using the
+    /// [/v2/reference/splits/{stocks_ticker}](https://polygon.io/docs/get_v2_reference_splits__stocksTicker__anchor) API.
+    pub async fn reference_stock_splits(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceStockSplitsResponse, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v2/reference/splits/{}", stocks_ticker);
+        self.send_request::<ReferenceStockSplitsResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get a list of historical dividends for a stock using the
+    /// [/v2/reference/dividends/{stocks_ticker}](https://polygon.io/docs/get_v2_reference_dividends__stocksTicker__anchor) API.
+    pub async fn reference_stock_dividends(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceStockDividendsResponse, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v2/reference/dividends/{}", stocks_ticker);
+        self.send_request::<ReferenceStockDividendsResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the dividends that match `query_params`, such as those of a
+    /// `ticker`, using the
+    /// [/v3/reference/dividends](https://polygon.io/docs/stocks/get_v3_reference_dividends)
+    /// API.
+    pub async fn reference_stock_dividends_v3(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceStockDividendsResponseV3, Error> {
+        self.send_limited_request::<ReferenceStockDividendsResponseV3>(
+            "/v3/reference/dividends",
+            query_params,
+            REFERENCE_STOCK_DIVIDENDS_MAX_LIMIT,
+        )
+        .await
+    }
+
+    /// Stream every dividend that matches `query_params` using the
+    /// [/v3/reference/dividends](https://polygon.io/docs/stocks/get_v3_reference_dividends)
+    /// API, following the `next_url` of each page until the last one.
+    ///
+    /// Pages are only requested as the stream is polled, so decades of
+    /// history can be walked without holding it all in memory.
+    pub fn reference_stock_dividends_stream<'a>(
+        &'a self,
+        query_params: &HashMap<&str, &str>,
+    ) -> impl Stream<Item = Result<ReferenceStockDividendsResultV3, Error>> + 'a {
+        self.paginate_limited::<ReferenceStockDividendsResponseV3>(
+            "/v3/reference/dividends",
+            query_params,
+            REFERENCE_STOCK_DIVIDENDS_MAX_LIMIT,
+        )
+    }
+
+    /// Get historical financial data for a stock ticker using the
+    /// [/v2/reference/financials/{stocks_ticker}](https://polygon.io/docs/get_v2_reference_financials__stocksTicker__anchor) API.
+    pub async fn reference_stock_financials(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceStockFinancialsResponse, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v2/reference/financials/{}", stocks_ticker);
+        self.send_request::<ReferenceStockFinancialsResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get historical financial data for a stock ticker using the
+    /// [/vX/reference/financials](https://polygon.io/docs/get_vX_reference_financials_anchor) API.
+    ///
+    /// The endpoint has no stable path yet, but its results follow the
+    /// current schema, including the `timeframe` and `filing_date` of each
+    /// filing.
+    pub async fn reference_stock_financials_vx(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceStockFinancialsVXResponse, Error> {
+        self.send_limited_request::<ReferenceStockFinancialsVXResponse>(
+            "/vX/reference/financials",
+            query_params,
+            REFERENCE_STOCK_FINANCIALS_VX_MAX_LIMIT,
+        )
+        .await
+    }
+
+    /// Get upcoming market holidays and their open/close items using the
+    /// [/v1/marketstatus/upcoming](https://polygon.io/docs/get_v1_marketstatus_upcoming_anchor) API.
+    pub async fn reference_market_holidays(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceMarketStatusUpcomingResponse, Error> {
+        self.send_request::<ReferenceMarketStatusUpcomingResponse>(
+            "/v1/marketstatus/upcoming",
+            query_params,
+        )
+        .await
+    }
+
+    /// Get the current trading status of the exchanges and overall financial
+    /// markets using the [/v1/marketstatus/now](https://polygon.io/docs/get_v1_marketstatus_now_anchor) API.
+    pub async fn reference_market_status(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<ReferenceMarketStatusNowResponse, Error> {
+        self.send_request::<ReferenceMarketStatusNowResponse>("/v1/marketstatus/now", query_params)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::RESTClient;
+    use crate::ticker::StockTicker;
+    use crate::types::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_reference_tickers() {
+        let mut query_params = HashMap::new();
+        query_params.insert("ticker", "MSFT");
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).reference_tickers(&query_params))
+                .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.count, 1);
+        assert_eq!(resp.results[0].market, AssetClass::Stocks);
+        assert_eq!(resp.results[0].currency_name, "usd");
+    }
+
+    #[test]
+    fn test_reference_tickers_stream() {
+        use crate::rest::test_server::{self, Response, TestServer};
+        use futures::TryStreamExt;
+
+        let ticker = |t: &str| {
+            format!(
+                r#"{{"ticker":"{}","name":"{}","market":"stocks","locale":"us","primary_exchange":"XNAS","active":true,"currency_name":"usd","last_updated_utc":"2021-01-01"}}"#,
+                t, t
+            )
+        };
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(format!(
+                    r#"{{"results":[{},{}],"status":"OK","request_id":"1","count":2,"next_url":"https://api.polygon.io/v3/reference/tickers?cursor=abc"}}"#,
+                    ticker("AAPL"),
+                    ticker("MSFT")
+                )),
+                Response::ok(format!(
+                    r#"{{"results":[{}],"status":"OK","request_id":"2","count":1}}"#,
+                    ticker("TSLA")
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("limit", "2");
+            let tickers: Vec<_> = client
+                .reference_tickers_stream(&query_params)
+                .map_ok(|t| t.ticker)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(tickers, vec!["AAPL", "MSFT", "TSLA"]);
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/v3/reference/tickers?limit=2",
+                    "/v3/reference/tickers?cursor=abc"
+                ]
+            );
+
+            query_params.insert("limit", "0");
+            let err = client
+                .reference_tickers_stream(&query_params)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap_err();
+            assert!(matches!(err, crate::error::Error::InvalidLimit { .. }));
+        });
+    }
+
+    #[test]
+    fn test_reference_tickers_with_options() {
+        use super::ReferenceTickersOptions;
+        use crate::rest::test_server::{self, Response, TestServer};
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"results":[],"status":"OK","request_id":"1","count":0}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            let options = ReferenceTickersOptions::new()
+                .market(AssetClass::Otc)
+                .ticker_type(TickerType::Etf);
+            let resp = client
+                .reference_tickers_with_options(&options)
+                .await
+                .unwrap();
+            assert_eq!(resp.count, 0);
+            let request = &server.requests()[0];
+            assert!(request.contains("market=otc"));
+            assert!(request.contains("type=ETF"));
+        });
+    }
+
+    #[test]
+    fn test_reference_ticker_types() {
+        let query_params = HashMap::new();
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).reference_ticker_types(&query_params))
+                .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results.types["CS"], "Common Stock");
+        assert_eq!(resp.results.index_types["INDEX"], "Index");
+    }
+
+    #[test]
+    fn test_reference_ticker_details() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .reference_ticker_details(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.country, "usa");
+        assert_eq!(resp.name, "Microsoft Corporation");
+        assert_eq!(resp.symbol, "MSFT");
+    }
+
+    #[test]
+    fn test_reference_ticker_details_vx() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .reference_ticker_details_vx(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results.ticker, "MSFT");
+        assert_eq!(resp.results.currency_name, "usd");
+    }
+
+    #[test]
+    fn test_reference_ticker_details_vx_decode() {
+        let resp: ReferenceTickerDetailsResponseVX =
+            serde_json::from_str(include_str!("../../fixtures/ticker_details_vx.json")).unwrap();
+        let details = resp.results;
+        assert_eq!(details.outstanding_shares, Some(7507980000));
+        assert!((details.market_cap_billions() - 2204.7433398).abs() < 1e-6);
+        assert_eq!(details.sic_code_number(), None);
+        assert_eq!(details.round_lot, None);
+        assert_eq!(details.composite_figi.as_deref(), Some("BBG000BPH459"));
+        assert_eq!(details.share_class_figi.as_deref(), Some("BBG001S5TD05"));
+
+        let json = include_str!("../../fixtures/ticker_details_vx.json")
+            .replace("7507980000", "7507980000.4");
+        let resp: ReferenceTickerDetailsResponseVX = serde_json::from_str(&json).unwrap();
+        assert_eq!(resp.results.outstanding_shares, Some(7507980000));
+
+        // Keys with the old misspelling are still accepted.
+        let json = include_str!("../../fixtures/ticker_details_vx.json")
+            .replace("composite_figi", "composite_fiji");
+        let resp: ReferenceTickerDetailsResponseVX = serde_json::from_str(&json).unwrap();
+        #[allow(deprecated)]
+        let figi = resp.results.composite_fiji();
+        assert_eq!(figi, Some("BBG000BPH459"));
+    }
+
+    #[test]
+    fn test_reference_ticker_details_v3() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .reference_ticker_details_v3(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results.ticker, "MSFT");
+        assert_eq!(resp.results.sic_code.unwrap(), "7372");
+        assert!(resp.results.branding.unwrap().logo_url.is_some());
+    }
+
+    #[test]
+    fn test_reference_ticker_details_v3_decode() {
+        let resp: ReferenceTickerDetailsResponseV3 =
+            serde_json::from_str(include_str!("../../fixtures/ticker_details_v3.json")).unwrap();
+        let details = resp.results;
+        assert_eq!(details.weighted_shares_outstanding, Some(7432306000));
+        assert_eq!(details.sic_code_number(), Some(7372));
+        assert!((details.market_cap_billions().unwrap() - 2753.155431).abs() < 1e-6);
+        assert!((details.implied_share_price().unwrap() - 370.431).abs() < 1e-3);
+        assert_eq!(details.composite_figi.unwrap(), "BBG000BPH459");
+        assert_eq!(details.total_employees, Some(221000));
+        assert_eq!(
+            details.list_date,
+            Some(chrono::NaiveDate::from_ymd_opt(1986, 3, 13).unwrap())
+        );
+        assert_eq!(details.address.unwrap().postal_code.unwrap(), "98052-6399");
+        assert!(details
+            .branding
+            .unwrap()
+            .icon_url
+            .unwrap()
+            .ends_with("icon.jpeg"));
+    }
+
+    #[test]
+    fn test_reference_ticker_news() {
+        let query_params = HashMap::new();
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).reference_ticker_news(&query_params))
+                .unwrap();
+        assert_eq!(resp.status, "OK");
+    }
+
+    #[test]
+    fn test_reference_ticker_news_insights() {
+        let resp: ReferenceTickerNewsResponseV2 =
+            serde_json::from_str(include_str!("../../fixtures/news.json")).unwrap();
+        let insight = resp.results[0].insight("MSFT").unwrap();
+        assert_eq!(insight.sentiment, Sentiment::Positive);
+        assert!(resp.results[0].insight("AAPL").is_none());
+
+        let insight: NewsInsight =
+            serde_json::from_str(r#"{"ticker":"MSFT","sentiment":"mixed"}"#).unwrap();
+        assert_eq!(insight.sentiment, Sentiment::Other);
+        assert_eq!(insight.sentiment_reasoning, "");
+    }
+
+    #[test]
+    fn test_reference_ticker_news_stream() {
+        use crate::rest::test_server::{self, Response, TestServer};
+        use futures::TryStreamExt;
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(include_str!("../../fixtures/news.json").into()),
+                Response::ok(String::from(
+                    r#"{"results":[],"status":"OK","request_id":"2","count":0}"#,
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("ticker", "MSFT");
+            let titles: Vec<_> = client
+                .reference_ticker_news_stream(&query_params)
+                .map_ok(|n| n.title)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(titles, vec!["Microsoft Announces Quarterly Dividend"]);
+            assert_eq!(
+                server.requests(),
+                vec![
+                    "/v2/reference/news?ticker=MSFT",
+                    "/v2/reference/news?cursor=YWN0aXZlPXRydWUmZGF0ZT0yMDIxLTA0LTI2"
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_reference_markets() {
+        let query_params = HashMap::new();
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).reference_markets(&query_params))
+                .unwrap();
+        assert_eq!(resp.status, "OK");
+        let bond = resp.results.iter().find(|x| x.market == "BONDS");
+        assert!(bond.is_some());
+        assert_eq!(bond.unwrap().desc, "Bonds");
+    }
+
+    #[test]
+    fn test_reference_locales() {
+        let query_params = HashMap::new();
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).reference_locales(&query_params))
+                .unwrap();
+        assert_eq!(resp.status, "OK");
+        let bond = resp.results.iter().find(|x| x.locale == "US");
+        assert!(bond.is_some());
+        assert_eq!(bond.unwrap().name, "United States of America");
+    }
+
+    #[test]
+    fn test_reference_stock_splits() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .reference_stock_splits(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        let bond = resp.results.iter().find(|x| x.ex_date == "1998-02-23");
+        assert!(bond.is_some());
+        assert_eq!(bond.unwrap().ratio, 0.5);
+    }
+
+    #[test]
+    fn test_reference_stock_dividends() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .reference_stock_dividends(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        let bond = resp.results.iter().find(|x| x.ex_date == "2021-02-17");
+        assert!(bond.is_some());
+        assert_eq!(bond.unwrap().amount, 0.56);
+    }
+
+    #[test]
+    fn test_ticker_type_and_market() {
+        let ticker: ReferenceTickersResponseTickerV3 = serde_json::from_str(
+            r#"{"ticker":"SPY","name":"SPDR S&P 500 ETF Trust","market":"stocks","locale":"us","primary_exchange":"ARCX","type":"ETF","active":true,"currency_name":"usd","last_updated_utc":"2021-10-01"}"#,
+        )
+        .unwrap();
+        assert_eq!(ticker.market, AssetClass::Stocks);
+        assert_eq!(ticker.ticker_type, Some(TickerType::Etf));
+
+        let ticker: ReferenceTickersResponseTickerV3 = serde_json::from_str(
+            r#"{"ticker":"XYZ","name":"XYZ","market":"bonds","locale":"us","primary_exchange":"XNYS","type":"NEWTYPE","active":true,"currency_name":"usd","last_updated_utc":"2021-10-01"}"#,
+        )
+        .unwrap();
+        assert_eq!(ticker.market, AssetClass::Other(String::from("bonds")));
+        assert_eq!(ticker.market.ticker_prefix(), None);
+        assert_eq!(ticker.ticker_type.unwrap().as_str(), "NEWTYPE");
+
+        assert_eq!(AssetClass::Forex.to_string(), "fx");
+        assert_eq!(
+            serde_json::from_str::<AssetClass>(r#""otc""#).unwrap(),
+            AssetClass::Otc
+        );
+        assert_eq!(
+            serde_json::to_string(&TickerType::CommonStock).unwrap(),
+            r#""CS""#
+        );
+        assert_eq!(
+            serde_json::from_str::<AssetClass>(r#""fx""#).unwrap(),
+            AssetClass::Forex
+        );
+    }
+
+    #[test]
+    fn test_data_types_compare() {
+        use std::collections::HashSet;
+
+        let ticker = |name: &str| -> ReferenceTickersResponseTickerV3 {
+            serde_json::from_str(&format!(
+                r#"{{"ticker":"MSFT","name":"{}","market":"stocks","locale":"us","primary_exchange":"XNAS","active":true,"currency_name":"usd","last_updated_utc":"2021-10-01"}}"#,
+                name
+            ))
+            .unwrap()
+        };
+        let tickers: HashSet<_> = vec![
+            ticker("Microsoft Corp"),
+            ticker("Microsoft Corp"),
+            ticker("Microsoft Corporation"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(tickers.len(), 2);
+
+        let details = || -> ReferenceTickerDetailsResponseVX {
+            serde_json::from_str(include_str!("../../fixtures/ticker_details_vx.json")).unwrap()
+        };
+        assert_eq!(details(), details());
+    }
+
+    #[test]
+    #[cfg(feature = "chrono-accessors")]
+    fn test_chrono_fields() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        let dividend: ReferenceStockDividendsResultV3 = serde_json::from_str(
+            r#"{"cash_amount":0.68,"declaration_date":"2021-09-14","dividend_type":"CD","ex_dividend_date":"2021-11-17","frequency":4,"pay_date":"","ticker":"MSFT"}"#,
+        )
+        .unwrap();
+        assert_eq!(dividend.ex_dividend_date, "2021-11-17");
+        assert_eq!(
+            dividend.ex_dividend_date(),
+            NaiveDate::from_ymd_opt(2021, 11, 17)
+        );
+        assert_eq!(
+            dividend.declaration_date(),
+            NaiveDate::from_ymd_opt(2021, 9, 14)
+        );
+        assert_eq!(dividend.pay_date(), None);
+        assert_eq!(dividend.record_date(), None);
+
+        let ticker: ReferenceTickersResponseTickerV3 = serde_json::from_str(
+            r#"{"ticker":"MSFT","name":"Microsoft Corp","market":"stocks","locale":"us","primary_exchange":"XNAS","active":true,"currency_name":"usd","last_updated_utc":"2021-10-01T12:30:00.000Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ticker.last_updated_utc(),
+            Utc.with_ymd_and_hms(2021, 10, 1, 12, 30, 0).single()
+        );
+
+        let ticker: ReferenceTickersResponseTickerV3 = serde_json::from_str(
+            r#"{"ticker":"MSFT","name":"Microsoft Corp","market":"stocks","locale":"us","primary_exchange":"XNAS","active":true,"currency_name":"usd","last_updated_utc":"2021-10-01"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ticker.last_updated_utc(),
+            Utc.with_ymd_and_hms(2021, 10, 1, 0, 0, 0).single()
+        );
+
+        let dividend: ReferenceStockDividendsResultV3 = serde_json::from_str(
+            r#"{"cash_amount":0.68,"dividend_type":"CD","ex_dividend_date":"2021-11-17 or so","frequency":4,"ticker":"MSFT"}"#,
+        )
+        .unwrap();
+        assert_eq!(dividend.ex_dividend_date(), None);
+    }
+
+    #[test]
+    fn test_reference_stock_dividends_stream() {
+        use crate::rest::test_server::{self, Response, TestServer};
+        use futures::{StreamExt, TryStreamExt};
+
+        let dividend = |ex_dividend_date: &str| {
+            format!(
+                r#"{{"cash_amount":0.68,"currency":"USD","declaration_date":"2021-09-14","dividend_type":"CD","ex_dividend_date":"{}","frequency":4,"pay_date":"2021-12-09","record_date":"2021-11-18","ticker":"MSFT"}}"#,
+                ex_dividend_date
+            )
+        };
+        tokio_test::block_on(async {
+            let first_page = format!(
+                r#"{{"results":[{},{}],"status":"OK","request_id":"1","next_url":"https://api.polygon.io/v3/reference/dividends?cursor=abc"}}"#,
+                dividend("2021-11-17"),
+                dividend("2021-08-18")
+            );
+            let server = TestServer::start(vec![
+                Response::ok(first_page.clone()),
+                Response::ok(first_page),
+                Response::ok(format!(
+                    r#"{{"results":[{}],"status":"OK","request_id":"2"}}"#,
+                    dividend("2021-05-19")
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let mut query_params = HashMap::new();
+            query_params.insert("ticker", "MSFT");
+
+            // Only the pages that are consumed are requested.
+            let first: Vec<_> = client
+                .reference_stock_dividends_stream(&query_params)
+                .take(1)
+                .collect()
+                .await;
+            assert_eq!(first.len(), 1);
+            assert_eq!(server.requests().len(), 1);
+
+            let dates: Vec<_> = client
+                .reference_stock_dividends_stream(&query_params)
+                .map_ok(|d| d.ex_dividend_date.to_string())
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(dates, vec!["2021-11-17", "2021-08-18", "2021-05-19"]);
+            assert_eq!(
+                server.requests()[1..],
+                [
+                    "/v3/reference/dividends?ticker=MSFT",
+                    "/v3/reference/dividends?cursor=abc"
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_reference_stock_financials() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .reference_stock_financials(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        let fin = resp.results.iter().find(|x| x.ticker == "MSFT");
+        assert!(fin.is_some());
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .reference_stock_financials(StockTicker::new("AAPL").unwrap(), &query_params),
+        )
+        .unwrap();
+        let fin = resp.results.iter().find(|x| x.ticker == "AAPL");
+        assert!(fin.is_some());
+    }
+
+    #[test]
+    fn test_reference_stock_financials_vx_last_page() {
+        let resp: ReferenceStockFinancialsVXResponse = serde_json::from_str(include_str!(
+            "../../fixtures/stock_financials_vx_last_page.json"
+        ))
+        .unwrap();
+        assert_eq!(resp.next_url, None);
+        let result = &resp.results[0];
+        assert_eq!(result.timeframe.as_deref(), Some("annual"));
+        assert_eq!(result.filing_date.as_deref(), Some("2023-11-03"));
+        assert_eq!(
+            result.acceptance_datetime.as_deref(),
+            Some("2023-11-02T22:08:27Z")
+        );
+        assert_eq!(result.sic_code.as_deref(), Some("3571"));
+    }
+
+    #[test]
+    fn test_reference_stock_financials_vx() {
+        let mut query_params = HashMap::new();
+        query_params.insert("ticker", "MSFT");
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).reference_stock_financials_vx(&query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.count, 1);
+        let result = resp.results.first().unwrap();
+        for v in &result.financials.balance_sheet {
+            println!("{} = true", v.0);
+        }
+        let income_statement = &result.financials.income_statement;
+        assert!(income_statement.contains_key(FAC_REVENUES));
+        assert!(income_statement.get(FAC_REVENUES).unwrap().unit.is_some());
+        assert_eq!(
+            income_statement
+                .get(FAC_REVENUES)
+                .unwrap()
+                .unit
+                .as_ref()
+                .unwrap(),
+            "USD"
+        );
+    }
+
+    #[test]
+    fn test_reference_market_holidays() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).reference_market_holidays(&query_params),
+        )
+        .unwrap();
+        assert_ne!(resp.len(), 0);
+    }
+
+    #[test]
+    fn test_reference_market_status() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).reference_market_status(&query_params),
+        )
+        .unwrap();
+        assert_ne!(resp.exchanges.len(), 0);
+    }
+}
@@ -0,0 +1,379 @@
+//! Memoization of the reference data used by the enrichment helpers.
+//!
+//! Reference data such as the list of exchanges, the condition mappings, and
+//! the details of a ticker rarely change, yet helpers that enrich other data
+//! with it would otherwise request it again on every call. Every client keeps
+//! a [`ReferenceCache`], shared with its clones and partitions, through which
+//! these helpers look up reference data. Entries expire after a time to live
+//! set per [`ReferenceKind`] and can be invalidated by hand, such as after a
+//! ticker changes its name.
+//!
+//! Like the [`ResponseCache`](crate::rest::ResponseCache), entries looked up
+//! through a client with [`EdgeHeaders`](crate::rest::EdgeHeaders) are kept
+//! per Launchpad user, so that one user is never given data fetched for
+//! another.
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::runtime::Instant;
+#[cfg(feature = "reference")]
+use crate::ticker::StockTicker;
+use crate::types::*;
+
+/// A kind of reference data kept in a [`ReferenceCache`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ReferenceKind {
+    /// The stock exchanges. Kept for a day by default.
+    Exchanges,
+    /// The condition mappings of each tick type. Kept for a day by default.
+    Conditions,
+    /// The details of each ticker. Kept for an hour by default.
+    TickerDetails,
+}
+
+impl ReferenceKind {
+    fn default_ttl(self) -> Duration {
+        match self {
+            ReferenceKind::Exchanges | ReferenceKind::Conditions => Duration::from_secs(86_400),
+            ReferenceKind::TickerDetails => Duration::from_secs(3_600),
+        }
+    }
+}
+
+struct Entry {
+    value: Arc<dyn Any + Send + Sync>,
+    inserted: Instant,
+}
+
+/// The kind and key of an entry and the Launchpad user it was fetched for.
+type EntryKey = (ReferenceKind, String, Option<String>);
+
+/// A cache of reference data with a time to live for each
+/// [`ReferenceKind`].
+///
+/// Returned by [`RESTClient::reference_cache()`]. Concurrent lookups of an
+/// entry that is missing may each send a request; the last response to
+/// arrive is kept.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use polygon_client::rest::{RESTClient, ReferenceKind};
+///
+/// let client = RESTClient::builder()
+///     .auth_key("my-api-key")
+///     .build()
+///     .expect("failed to build client");
+///
+/// let cache = client.reference_cache();
+/// cache.set_ttl(ReferenceKind::TickerDetails, Duration::from_secs(300));
+/// // Fetch the details of FB again the next time they are needed.
+/// cache.invalidate(ReferenceKind::TickerDetails, "FB");
+/// ```
+#[derive(Default)]
+pub struct ReferenceCache {
+    ttls: Mutex<HashMap<ReferenceKind, Duration>>,
+    entries: Mutex<HashMap<EntryKey, Entry>>,
+}
+
+impl ReferenceCache {
+    /// Returns the time to live of entries of a kind.
+    pub fn ttl(&self, kind: ReferenceKind) -> Duration {
+        let ttls = self.ttls.lock().unwrap_or_else(PoisonError::into_inner);
+        ttls.get(&kind)
+            .copied()
+            .unwrap_or_else(|| kind.default_ttl())
+    }
+
+    /// Sets the time to live of entries of a kind. A time to live of zero
+    /// disables caching of that kind.
+    pub fn set_ttl(&self, kind: ReferenceKind, ttl: Duration) {
+        let mut ttls = self.ttls.lock().unwrap_or_else(PoisonError::into_inner);
+        ttls.insert(kind, ttl);
+    }
+
+    /// Removes the entries of a kind with the given key, such as a ticker or
+    /// a tick type, for every user. Entries of kinds without a key, such as
+    /// the exchanges, use an empty key.
+    pub fn invalidate(&self, kind: ReferenceKind, key: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|(k, entry_key, _), _| *k != kind || entry_key != key);
+    }
+
+    /// Removes every entry of a kind.
+    pub fn invalidate_kind(&self, kind: ReferenceKind) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|(k, _, _), _| *k != kind);
+    }
+
+    /// Removes every entry.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.clear();
+    }
+
+    /// Returns the number of entries, including those that have expired but
+    /// have not been looked up since.
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.len()
+    }
+
+    /// Returns `true` if the cache has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get<T: Clone + 'static>(&self, cache_key: &EntryKey) -> Option<T> {
+        let ttl = self.ttl(cache_key.0);
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let entry = entries.get(cache_key)?;
+        if entry.inserted.elapsed() >= ttl {
+            entries.remove(cache_key);
+            return None;
+        }
+        entry.value.downcast_ref::<T>().cloned()
+    }
+
+    fn insert<T: Send + Sync + 'static>(&self, cache_key: EntryKey, value: T) {
+        if self.ttl(cache_key.0).is_zero() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.insert(
+            cache_key,
+            Entry {
+                value: Arc::new(value),
+                inserted: Instant::now(),
+            },
+        );
+    }
+
+    /// Returns the entry of a kind with the given key fetched for `user` if
+    /// it has not expired, or else calls `fetch` and keeps its result if it
+    /// succeeds.
+    pub(crate) async fn get_or_fetch<T, E, F, Fut>(
+        &self,
+        kind: ReferenceKind,
+        key: &str,
+        user: Option<&str>,
+        fetch: F,
+    ) -> Result<T, E>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let cache_key = (kind, String::from(key), user.map(String::from));
+        if let Some(value) = self.get::<T>(&cache_key) {
+            return Ok(value);
+        }
+
+        let value = fetch().await?;
+        self.insert(cache_key, value.clone());
+        Ok(value)
+    }
+}
+
+impl RESTClient {
+    /// Returns the Launchpad user that reference data is looked up for.
+    fn reference_user(&self) -> Option<&str> {
+        self.edge_headers().map(|h| h.id())
+    }
+
+    /// Get the stock exchanges from the
+    /// [/v1/meta/exchanges](https://polygon.io/docs/get_v1_meta_exchanges_anchor) API,
+    /// memoized in the [`ReferenceCache`].
+    #[cfg(feature = "stocks")]
+    pub async fn cached_exchanges(&self) -> Result<StockEquitiesExchangesResponse, Error> {
+        self.reference_cache()
+            .get_or_fetch(
+                ReferenceKind::Exchanges,
+                "",
+                self.reference_user(),
+                || async { self.stock_equities_exchanges(&HashMap::new()).await },
+            )
+            .await
+    }
+
+    /// Get the condition mappings of a tick type from the
+    /// [/v1/meta/conditions/{tick_type}](https://polygon.io/docs/get_v1_meta_conditions__ticktype__anchor) API,
+    /// memoized in the [`ReferenceCache`] with the tick type, such as
+    /// `trades`, as the key.
+    #[cfg(feature = "stocks")]
+    pub async fn cached_condition_mappings(
+        &self,
+        tick_type: TickType,
+    ) -> Result<StockEquitiesConditionMappingsResponse, Error> {
+        let key = tick_type.to_string().to_lowercase();
+        self.reference_cache()
+            .get_or_fetch(
+                ReferenceKind::Conditions,
+                &key,
+                self.reference_user(),
+                || async {
+                    self.stock_equities_condition_mappings(tick_type, &HashMap::new())
+                        .await
+                },
+            )
+            .await
+    }
+
+    /// Get the details of a ticker from the
+    /// [/v3/reference/tickers/{ticker}](https://polygon.io/docs/stocks/get_v3_reference_tickers__ticker) API,
+    /// memoized in the [`ReferenceCache`] with the ticker as the key.
+    #[cfg(feature = "reference")]
+    pub async fn cached_ticker_details(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+    ) -> Result<ReferenceTickerDetailsResultsV3, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        self.reference_cache()
+            .get_or_fetch(
+                ReferenceKind::TickerDetails,
+                stocks_ticker.as_str(),
+                self.reference_user(),
+                || async {
+                    self.reference_ticker_details_v3(&stocks_ticker, &HashMap::new())
+                        .await
+                        .map(|r| r.results)
+                },
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::rest::reference_cache::{ReferenceCache, ReferenceKind};
+    #[cfg(feature = "reference")]
+    use crate::rest::{
+        test_server::{self, Response, TestServer},
+        EdgeHeaders,
+    };
+    #[cfg(feature = "reference")]
+    use crate::ticker::StockTicker;
+
+    #[test]
+    fn test_get_or_fetch() {
+        tokio_test::block_on(async {
+            let cache = ReferenceCache::default();
+            let fetch = |v: u32| async move { Ok::<_, ()>(v) };
+
+            let first = cache
+                .get_or_fetch(ReferenceKind::TickerDetails, "MSFT", None, || fetch(1))
+                .await;
+            let second = cache
+                .get_or_fetch(ReferenceKind::TickerDetails, "MSFT", None, || fetch(2))
+                .await;
+            let other = cache
+                .get_or_fetch(ReferenceKind::TickerDetails, "AAPL", None, || fetch(3))
+                .await;
+            assert_eq!((first, second, other), (Ok(1), Ok(1), Ok(3)));
+
+            // Errors are not cached.
+            let failed = cache
+                .get_or_fetch(ReferenceKind::Exchanges, "", None, || async {
+                    Err::<u32, _>(())
+                })
+                .await;
+            assert_eq!(failed, Err(()));
+            assert_eq!(cache.len(), 2);
+
+            cache.invalidate(ReferenceKind::TickerDetails, "MSFT");
+            let refetched = cache
+                .get_or_fetch(ReferenceKind::TickerDetails, "MSFT", None, || fetch(4))
+                .await;
+            assert_eq!(refetched, Ok(4));
+
+            cache.invalidate_kind(ReferenceKind::TickerDetails);
+            assert!(cache.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_ttl() {
+        tokio_test::block_on(async {
+            let cache = ReferenceCache::default();
+            assert_eq!(
+                cache.ttl(ReferenceKind::Exchanges),
+                Duration::from_secs(86_400)
+            );
+
+            cache.set_ttl(ReferenceKind::Conditions, Duration::from_millis(10));
+            let fetch = |v: u32| async move { Ok::<_, ()>(v) };
+            let first = cache
+                .get_or_fetch(ReferenceKind::Conditions, "trades", None, || fetch(1))
+                .await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let expired = cache
+                .get_or_fetch(ReferenceKind::Conditions, "trades", None, || fetch(2))
+                .await;
+            assert_eq!((first, expired), (Ok(1), Ok(2)));
+
+            cache.set_ttl(ReferenceKind::Conditions, Duration::ZERO);
+            cache.clear();
+            let _ = cache
+                .get_or_fetch(ReferenceKind::Conditions, "trades", None, || fetch(3))
+                .await;
+            assert!(cache.is_empty());
+        });
+    }
+
+    #[cfg(feature = "reference")]
+    #[test]
+    fn test_cached_ticker_details_shared_by_clones() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(
+                include_str!("../../fixtures/ticker_details_v3.json").into(),
+            )])
+            .await;
+            let client = test_server::client(&server);
+            let msft = StockTicker::new("MSFT").unwrap();
+
+            let first = client.cached_ticker_details(&msft).await.unwrap();
+            let second = client.clone().cached_ticker_details(&msft).await.unwrap();
+            assert_eq!(first.name, "Microsoft Corp");
+            assert_eq!(second.name, first.name);
+            assert_eq!(server.requests(), vec!["/v3/reference/tickers/MSFT"]);
+            assert_eq!(client.reference_cache().len(), 1);
+        });
+    }
+
+    #[cfg(feature = "reference")]
+    #[test]
+    fn test_cached_ticker_details_per_edge_user() {
+        tokio_test::block_on(async {
+            let details =
+                || Response::ok(include_str!("../../fixtures/ticker_details_v3.json").into());
+            let server = TestServer::start(vec![details(), details()]).await;
+            let client = test_server::client(&server);
+            let user_1 = client.with_edge_headers(EdgeHeaders::new("user-1", "203.0.113.1"));
+            let user_2 = client.with_edge_headers(EdgeHeaders::new("user-2", "203.0.113.2"));
+            let msft = StockTicker::new("MSFT").unwrap();
+
+            user_1.cached_ticker_details(&msft).await.unwrap();
+            user_2.cached_ticker_details(&msft).await.unwrap();
+            user_1.cached_ticker_details(&msft).await.unwrap();
+            assert_eq!(server.requests().len(), 2);
+            assert_eq!(client.reference_cache().len(), 2);
+
+            client
+                .reference_cache()
+                .invalidate(ReferenceKind::TickerDetails, "MSFT");
+            assert!(client.reference_cache().is_empty());
+        });
+    }
+}
@@ -0,0 +1,319 @@
+//! Caching of raw responses by request URL.
+//!
+//! Long-running services often ask for the same data again and again, such
+//! as the details of the tickers they follow or a snapshot polled by several
+//! tasks. A client built with
+//! [`RESTClientBuilder::cache_responses()`](crate::rest::RESTClientBuilder::cache_responses)
+//! keeps the body of every successful response in a [`ResponseCache`],
+//! shared with its clones and partitions, and answers later requests for the
+//! same path and query parameters from it until the entry expires. Entries
+//! expire after a time to live set per [`EndpointClass`], so that reference
+//! data can be kept for hours while snapshots are kept for seconds.
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use bytes::Bytes;
+use url::Url;
+
+use crate::rest::ApiResponse;
+use crate::runtime::Instant;
+
+/// A class of endpoints sharing a time to live in a [`ResponseCache`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EndpointClass {
+    /// Reference data such as tickers, exchanges, and dividends, under
+    /// `/v1/meta`, `/v2/reference`, `/v3/reference`, and `/vX/reference`.
+    /// Kept for 6 hours by default.
+    Reference,
+    /// Snapshots of the market, under `/v2/snapshot` and `/v3/snapshot`.
+    /// Kept for 5 seconds by default.
+    Snapshot,
+    /// Aggregate bars, under `/v2/aggs`. Not kept by default.
+    Aggregates,
+    /// Every other endpoint, such as trades, quotes, and last prices. Not
+    /// kept by default.
+    Other,
+}
+
+impl EndpointClass {
+    /// Returns the class of the endpoint at a path, such as
+    /// `/v3/reference/tickers`.
+    pub fn of(path: &str) -> Self {
+        let mut segments = path.trim_start_matches('/').split('/');
+        let version = segments.next().unwrap_or_default();
+        match (version, segments.next().unwrap_or_default()) {
+            ("v1", "meta") | (_, "reference") => EndpointClass::Reference,
+            (_, "snapshot") => EndpointClass::Snapshot,
+            ("v2", "aggs") => EndpointClass::Aggregates,
+            _ => EndpointClass::Other,
+        }
+    }
+
+    fn default_ttl(self) -> Duration {
+        match self {
+            EndpointClass::Reference => Duration::from_secs(6 * 3_600),
+            EndpointClass::Snapshot => Duration::from_secs(5),
+            EndpointClass::Aggregates | EndpointClass::Other => Duration::ZERO,
+        }
+    }
+}
+
+struct Entry {
+    response: ApiResponse<Bytes>,
+    class: EndpointClass,
+    expires: Instant,
+}
+
+/// A cache of raw responses with a time to live for each [`EndpointClass`].
+///
+/// Returned by [`RESTClient::response_cache()`](crate::rest::RESTClient::response_cache).
+/// Every time to live is zero, so that nothing is cached, unless the client
+/// was built with
+/// [`RESTClientBuilder::cache_responses()`](crate::rest::RESTClientBuilder::cache_responses).
+/// Only successful responses are kept, and concurrent requests for an entry
+/// that is missing may each be sent.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use polygon_client::rest::{EndpointClass, RESTClient};
+///
+/// let client = RESTClient::builder()
+///     .auth_key("my-api-key")
+///     .cache_responses(true)
+///     .build()
+///     .expect("failed to build client");
+///
+/// let cache = client.response_cache();
+/// cache.set_ttl(EndpointClass::Aggregates, Duration::from_secs(60));
+/// ```
+pub struct ResponseCache {
+    ttls: Mutex<HashMap<EndpointClass, Duration>>,
+    entries: Mutex<HashMap<CacheKey, Entry>>,
+}
+
+impl ResponseCache {
+    /// Returns an empty cache that uses the default time to live of each
+    /// class if `enabled`, or else keeps nothing.
+    pub(crate) fn new(enabled: bool) -> Self {
+        let ttls = if enabled {
+            HashMap::new()
+        } else {
+            [
+                EndpointClass::Reference,
+                EndpointClass::Snapshot,
+                EndpointClass::Aggregates,
+                EndpointClass::Other,
+            ]
+            .iter()
+            .map(|&class| (class, Duration::ZERO))
+            .collect()
+        };
+
+        ResponseCache {
+            ttls: Mutex::new(ttls),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the time to live of responses of a class.
+    pub fn ttl(&self, class: EndpointClass) -> Duration {
+        let ttls = self.ttls.lock().unwrap_or_else(PoisonError::into_inner);
+        ttls.get(&class)
+            .copied()
+            .unwrap_or_else(|| class.default_ttl())
+    }
+
+    /// Sets the time to live of responses of a class. A time to live of zero
+    /// disables caching of that class.
+    pub fn set_ttl(&self, class: EndpointClass, ttl: Duration) {
+        let mut ttls = self.ttls.lock().unwrap_or_else(PoisonError::into_inner);
+        ttls.insert(class, ttl);
+    }
+
+    /// Removes every response of a class.
+    pub fn invalidate_class(&self, class: EndpointClass) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|_, e| e.class != class);
+    }
+
+    /// Removes every response.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.clear();
+    }
+
+    /// Returns the number of responses, including those that have expired
+    /// but have not been removed since.
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.len()
+    }
+
+    /// Returns `true` if the cache has no responses.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the response kept for a request if it has not expired.
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<ApiResponse<Bytes>> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let entry = entries.get(key)?;
+        if entry.expires <= Instant::now() {
+            entries.remove(key);
+            return None;
+        }
+        let mut response = entry.response.clone();
+        response.elapsed = Duration::ZERO;
+        Some(response)
+    }
+
+    /// Keeps the response to a request if its class is cached.
+    pub(crate) fn insert(
+        &self,
+        key: CacheKey,
+        class: EndpointClass,
+        response: &ApiResponse<Bytes>,
+    ) {
+        let ttl = self.ttl(class);
+        if ttl.is_zero() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        // Expired entries are otherwise only removed when looked up again.
+        entries.retain(|_, e| e.expires > now);
+        entries.insert(
+            key,
+            Entry {
+                response: response.clone(),
+                class,
+                expires: now + ttl,
+            },
+        );
+    }
+}
+
+/// The URL of a request and the Launchpad user it was sent for.
+pub(crate) type CacheKey = (String, Option<String>);
+
+/// Returns the key of a request, which does not depend on the order of the
+/// query parameters.
+pub(crate) fn cache_key(url: &Url, edge_id: Option<&str>) -> CacheKey {
+    let mut query: Vec<_> = url.query_pairs().collect();
+    query.sort();
+
+    let mut key = url.clone();
+    key.query_pairs_mut().clear().extend_pairs(query);
+    (key.into(), edge_id.map(String::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::rest::response_cache::EndpointClass;
+    use crate::rest::RESTClient;
+
+    #[test]
+    fn test_endpoint_class() {
+        assert_eq!(
+            EndpointClass::of("/v3/reference/tickers"),
+            EndpointClass::Reference
+        );
+        assert_eq!(
+            EndpointClass::of("/vX/reference/tickers/MSFT"),
+            EndpointClass::Reference
+        );
+        assert_eq!(
+            EndpointClass::of("/v1/meta/exchanges"),
+            EndpointClass::Reference
+        );
+        assert_eq!(
+            EndpointClass::of("/v2/snapshot/locale/us/markets/stocks/tickers"),
+            EndpointClass::Snapshot
+        );
+        assert_eq!(
+            EndpointClass::of("/v2/aggs/ticker/MSFT/prev"),
+            EndpointClass::Aggregates
+        );
+        assert_eq!(EndpointClass::of("/v3/trades/MSFT"), EndpointClass::Other);
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
+    #[test]
+    fn test_response_cache() {
+        use std::collections::HashMap;
+
+        use crate::rest::test_server::{self, Response, TestServer};
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(r#"{"status":"OK","count":1}"#)),
+                Response::ok(String::from(r#"{"status":"OK","count":2}"#)),
+                Response::ok(String::from(r#"{"status":"OK","count":3}"#)),
+                Response::ok(String::from(r#"{"status":"OK","count":4}"#)),
+            ])
+            .await;
+            let client = test_server::builder(&server)
+                .cache_responses(true)
+                .build()
+                .unwrap();
+
+            let count = |path: &'static str, params: Vec<(&'static str, &'static str)>| {
+                let client = client.clone();
+                async move {
+                    let params: HashMap<_, _> = params.into_iter().collect();
+                    let resp = client
+                        .send_request::<serde_json::Value>(path, &params)
+                        .await
+                        .unwrap();
+                    resp["count"].as_u64().unwrap()
+                }
+            };
+
+            let tickers = "/v3/reference/tickers";
+            let ab = vec![("a", "1"), ("b", "2")];
+            let ba = vec![("b", "2"), ("a", "1")];
+            assert_eq!(count(tickers, ab.clone()).await, 1);
+            // The order of the query parameters does not matter.
+            assert_eq!(count(tickers, ba).await, 1);
+            assert_eq!(count(tickers, vec![("a", "1")]).await, 2);
+            // Trades are not cached by default.
+            assert_eq!(count("/v3/trades/MSFT", Vec::new()).await, 3);
+            assert_eq!(client.response_cache().len(), 2);
+
+            client
+                .response_cache()
+                .invalidate_class(EndpointClass::Reference);
+            assert_eq!(count(tickers, ab).await, 4);
+            assert_eq!(server.requests().len(), 4);
+        });
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let client = RESTClient::builder().auth_key("test").build().unwrap();
+        let cache = client.response_cache();
+        assert_eq!(cache.ttl(EndpointClass::Reference), Duration::ZERO);
+
+        cache.set_ttl(EndpointClass::Snapshot, Duration::from_secs(1));
+        assert_eq!(cache.ttl(EndpointClass::Snapshot), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,448 @@
+//! Policies for retrying requests.
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::runtime;
+
+/// The policy used when the server rate limits a request with an HTTP 429
+/// response.
+///
+/// Rate limited requests are retried up to `max_retries` times. The delay
+/// before each retry is taken from the `Retry-After` header of the response,
+/// given either in seconds or as an HTTP date, or `default_delay` if the
+/// header is not present.
+///
+/// The policy always applies to requests made on behalf of paginated streams
+/// and helpers that send several requests. It applies to every other request
+/// only if the client was built with
+/// [`RESTClientBuilder::retry_rate_limited()`](crate::rest::RESTClientBuilder::retry_rate_limited).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimitPolicy {
+    /// The maximum number of times a rate limited request is retried.
+    pub max_retries: u32,
+    /// The delay used when the response does not include a `Retry-After`
+    /// header.
+    pub default_delay: Duration,
+}
+
+impl RateLimitPolicy {
+    /// Returns a new rate limit policy.
+    pub fn new(max_retries: u32, default_delay: Duration) -> Self {
+        RateLimitPolicy {
+            max_retries,
+            default_delay,
+        }
+    }
+
+    /// Returns a policy that never retries rate limited requests.
+    pub fn disabled() -> Self {
+        RateLimitPolicy::new(0, Duration::from_secs(0))
+    }
+}
+
+impl Default for RateLimitPolicy {
+    /// Retries up to 5 times, waiting 15 seconds between attempts when the
+    /// server does not provide a `Retry-After` header.
+    fn default() -> Self {
+        RateLimitPolicy::new(5, Duration::from_secs(15))
+    }
+}
+
+/// The policy used when a request fails with a transient error, such as a
+/// `503 Service Unavailable` response or a dropped connection.
+///
+/// A request is attempted up to `max_attempts` times in total. The delay
+/// before each retry starts at `base_delay` and doubles with every retry up to
+/// `max_delay`, and is then shortened by a random fraction of up to `jitter`
+/// so that clients that failed together do not retry together.
+///
+/// Rate limited requests are handled by the [`RateLimitPolicy`] instead.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use polygon_client::rest::{RESTClient, RetryPolicy};
+///
+/// let policy = RetryPolicy {
+///     retryable_statuses: vec![502, 503],
+///     ..RetryPolicy::new(4, Duration::from_millis(250))
+/// };
+/// let client = RESTClient::builder()
+///     .auth_key("my-api-key")
+///     .retry_policy(policy)
+///     .build()
+///     .expect("failed to build client");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of times a request is sent, including the first.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub base_delay: Duration,
+    /// The longest delay between two attempts.
+    pub max_delay: Duration,
+    /// The largest fraction of each delay, between 0 and 1, that is randomly
+    /// taken off.
+    pub jitter: f64,
+    /// The HTTP status codes of the responses that are retried.
+    pub retryable_statuses: Vec<u16>,
+    /// Whether requests that could not connect to the server, or that timed
+    /// out, are retried. Other transport errors, such as a request that could
+    /// not be built, are never retried.
+    pub retry_connection_errors: bool,
+}
+
+impl RetryPolicy {
+    /// Returns a policy that sends a request up to `max_attempts` times,
+    /// starting with a delay of `base_delay`. The delay is capped at 30
+    /// seconds and up to half of it is jitter. Responses with a status of
+    /// 500, 502, 503, or 504 and connection errors are retried.
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        RetryPolicy {
+            max_attempts,
+            base_delay,
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+            retryable_statuses: vec![500, 502, 503, 504],
+            retry_connection_errors: true,
+        }
+    }
+
+    /// Returns a policy that never retries failed requests.
+    pub fn disabled() -> Self {
+        RetryPolicy::new(1, Duration::from_secs(0))
+    }
+
+    /// Returns `true` if a request that failed with `err` may be retried.
+    pub fn is_retryable(&self, err: &Error) -> bool {
+        match err {
+            Error::Api { status, .. } => self.retryable_statuses.contains(status),
+            Error::Timeout(_) => self.retry_connection_errors,
+            Error::Transport(e) => {
+                self.retry_connection_errors && (runtime::is_connect(e) || e.is_timeout())
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the delay before the given retry, starting at 1, where
+    /// `random` is a number between 0 and 1 that picks the jitter.
+    fn delay(&self, retry: u32, random: f64) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        delay.mul_f64(1.0 - self.jitter.clamp(0.0, 1.0) * random)
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Sends a request up to 3 times, waiting half a second before the first
+    /// retry and a second before the second.
+    fn default() -> Self {
+        RetryPolicy::new(3, Duration::from_millis(500))
+    }
+}
+
+/// Returns a random number between 0 and 1.
+fn random_fraction() -> f64 {
+    // Each `RandomState` is seeded randomly, which is plenty for jitter.
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Returns the delay given by a `Retry-After` header, which is either a
+/// number of seconds or an HTTP date.
+pub(crate) fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means the request can be retried right away.
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+impl RESTClient {
+    /// Sends a request, retrying it according to the client's
+    /// [`RateLimitPolicy`] if it is rate limited and the client was built with
+    /// [`RESTClientBuilder::retry_rate_limited()`](crate::rest::RESTClientBuilder::retry_rate_limited).
+    pub(crate) async fn send_url_with_opt_in_retry<RespType, Q>(
+        &self,
+        url: &str,
+        query_params: &Q,
+    ) -> Result<RespType, Error>
+    where
+        RespType: serde::de::DeserializeOwned + Send + 'static,
+        Q: serde::Serialize + ?Sized,
+    {
+        if self.inner.retry_rate_limited {
+            self.send_url_with_rate_limit::<RespType, Q>(url, query_params)
+                .await
+        } else {
+            self.send_url::<RespType, Q>(url, query_params).await
+        }
+    }
+
+    /// Sends a request, retrying according to the client's [`RetryPolicy`]
+    /// if it fails with a transient error.
+    pub(crate) async fn send_url<RespType, Q>(
+        &self,
+        url: &str,
+        query_params: &Q,
+    ) -> Result<RespType, Error>
+    where
+        RespType: serde::de::DeserializeOwned + Send + 'static,
+        Q: serde::Serialize + ?Sized,
+    {
+        self.retry_transient(url, || self.send_url_once::<RespType, Q>(url, query_params))
+            .await
+    }
+
+    /// Sends a request, retrying according to the client's
+    /// [`RateLimitPolicy`] if the server rate limits it.
+    pub(crate) async fn send_url_with_rate_limit<RespType, Q>(
+        &self,
+        url: &str,
+        query_params: &Q,
+    ) -> Result<RespType, Error>
+    where
+        RespType: serde::de::DeserializeOwned + Send + 'static,
+        Q: serde::Serialize + ?Sized,
+    {
+        self.retry_rate_limited(|| self.send_url::<RespType, Q>(url, query_params))
+            .await
+    }
+
+    /// Calls `send` until it succeeds, fails with an error that the client's
+    /// [`RetryPolicy`] does not retry, or runs out of attempts.
+    pub(crate) async fn retry_transient<T, F, Fut>(
+        &self,
+        url: &str,
+        mut send: F,
+    ) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let policy = self.retry_policy();
+        let mut attempt = 1;
+
+        loop {
+            let res = send().await;
+            #[cfg(feature = "metrics")]
+            if let Err(e) = &res {
+                crate::telemetry::record_error(url, e);
+            }
+            #[cfg(not(feature = "metrics"))]
+            let _ = url;
+            match res {
+                Err(e) if attempt < policy.max_attempts && policy.is_retryable(&e) => {
+                    runtime::sleep(policy.delay(attempt, random_fraction())).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Calls `send` until it is no longer rate limited or the client's
+    /// [`RateLimitPolicy`] runs out of retries.
+    pub(crate) async fn retry_rate_limited<T, F, Fut>(&self, mut send: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let policy = self.rate_limit_policy();
+        let mut retries = 0;
+
+        loop {
+            match send().await {
+                Err(Error::RateLimited { retry_after, .. }) if retries < policy.max_retries => {
+                    retries += 1;
+                    runtime::sleep(retry_after.unwrap_or(policy.default_delay)).await;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::error::Error;
+    use crate::rest::retry::parse_retry_after;
+    use crate::rest::RetryPolicy;
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+        assert_eq!(parse_retry_after(" 3 ", now), Some(Duration::from_secs(3)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let policy = RetryPolicy {
+            max_delay: Duration::from_secs(3),
+            ..RetryPolicy::new(5, Duration::from_secs(1))
+        };
+        assert_eq!(policy.delay(1, 0.0), Duration::from_secs(1));
+        assert_eq!(policy.delay(2, 0.0), Duration::from_secs(2));
+        assert_eq!(policy.delay(3, 0.0), Duration::from_secs(3));
+        assert_eq!(policy.delay(2, 0.5), Duration::from_millis(1500));
+        assert!((0.0..1.0).contains(&super::random_fraction()));
+    }
+
+    #[test]
+    fn test_retry_connection_errors_only() {
+        use tokio::io::AsyncWriteExt;
+        use tokio::net::TcpListener;
+
+        tokio_test::block_on(async {
+            let policy = RetryPolicy::new(3, Duration::ZERO);
+
+            // Nothing listens on a port once its listener is dropped.
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let closed = format!("http://{}", listener.local_addr().unwrap());
+            drop(listener);
+            let err = reqwest::get(&closed).await.unwrap_err();
+            assert!(policy.is_retryable(&Error::Transport(err)));
+
+            // A response whose body is cut short was received by the server,
+            // so it is not sent again.
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\n{}")
+                    .await;
+            });
+            let err = reqwest::get(&url).await.unwrap().bytes().await.unwrap_err();
+            assert!(!policy.is_retryable(&Error::Transport(err)));
+        });
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
+    #[test]
+    fn test_retry_transient_errors() {
+        use std::collections::HashMap;
+
+        use crate::rest::test_server::{self, Response, TestServer};
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::error(503, r#"{"status":"ERROR","error":"try again"}"#),
+                Response::error(502, ""),
+                Response::ok(String::from(r#"{"status":"OK"}"#)),
+                Response::error(400, r#"{"status":"ERROR","error":"bad request"}"#),
+            ])
+            .await;
+            let client = test_server::builder(&server)
+                .retry_policy(RetryPolicy::new(3, Duration::from_millis(1)))
+                .build()
+                .unwrap();
+
+            let no_params = HashMap::new();
+            let resp = client
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap();
+            assert_eq!(resp["status"], "OK");
+
+            // Client errors are not retried.
+            let err = client
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            assert_eq!(err.status(), Some(400));
+            assert_eq!(server.requests().len(), 4);
+        });
+    }
+
+    #[cfg(any(
+        feature = "reference",
+        feature = "stocks",
+        feature = "forex",
+        feature = "crypto",
+        feature = "options",
+        feature = "futures",
+        feature = "indices",
+        feature = "economy",
+        feature = "partners"
+    ))]
+    #[test]
+    fn test_retry_rate_limited_is_opt_in() {
+        use std::collections::HashMap;
+
+        use crate::rest::test_server::{self, Response, TestServer};
+        use crate::rest::RateLimitPolicy;
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::rate_limited(Some(0)),
+                Response::rate_limited(None),
+                Response::ok(String::from(r#"{"status":"OK"}"#)),
+            ])
+            .await;
+            let policy = RateLimitPolicy::new(1, Duration::from_millis(1));
+            let no_params = HashMap::new();
+
+            let client = test_server::builder(&server)
+                .rate_limit_policy(policy)
+                .build()
+                .unwrap();
+            let err = client
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::RateLimited { .. }));
+
+            let client = test_server::builder(&server)
+                .rate_limit_policy(policy)
+                .retry_rate_limited(true)
+                .build()
+                .unwrap();
+            let resp = client
+                .send_request::<serde_json::Value>("/v1/test", &no_params)
+                .await
+                .unwrap();
+            assert_eq!(resp["status"], "OK");
+            assert_eq!(server.requests().len(), 3);
+        });
+    }
+}
@@ -0,0 +1,175 @@
+//! Projections of large stock responses onto slim structs.
+//!
+//! Each method requests the same endpoint as its full counterpart but decodes
+//! the results into a caller-chosen type, such as [`ClosesOnly`] or
+//! [`TradePricesOnly`], so fields that are not needed are skipped while
+//! parsing. Any type implementing [`serde::Deserialize`] can be used to
+//! select a different set of fields.
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+
+use crate::error::Error;
+use crate::rest::{RESTClient, AGGREGATES_MAX_LIMIT, TRADES_MAX_LIMIT};
+use crate::ticker::StockTicker;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get the daily bars of the entire stocks and equities market, decoded
+    /// into `T`, using the [/v2/aggs/grouped/locale/{locale}/market/{market}/{date}](https://polygon.io/docs/get_v2_aggs_grouped_locale_us_market_stocks__date__anchor) API.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use polygon_client::rest::RESTClient;
+    /// use polygon_client::types::{ClosesOnly, StocksMarket};
+    ///
+    /// # async fn run() -> Result<(), polygon_client::Error> {
+    /// let client = RESTClient::try_new(None, None).expect("failed to create client");
+    /// let closes = client
+    ///     .stock_equities_grouped_daily_as::<ClosesOnly>("us", StocksMarket::Stocks, "2024-01-03", &HashMap::new())
+    ///     .await?;
+    /// for close in &closes.results {
+    ///     println!("{:?} {}", close.ticker, close.c);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stock_equities_grouped_daily_as<T>(
+        &self,
+        locale: &str,
+        market: StocksMarket,
+        date: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<SlimResponse<T>, Error>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let uri = format!(
+            "/v2/aggs/grouped/locale/{}/market/{}/{}",
+            locale, market, date
+        );
+        self.send_request::<SlimResponse<T>>(&uri, query_params)
+            .await
+    }
+
+    /// Get aggregate bars for a stock, decoded into `T`, using the
+    /// [/v2/aggs/ticker/{stocks_ticker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__stocksTicker__range__multiplier___timespan___from___to__anchor) API.
+    pub async fn stock_equities_aggregates_as<T>(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        multiplier: u32,
+        timespan: Timespan,
+        from: &str,
+        to: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<SlimResponse<T>, Error>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!(
+            "/v2/aggs/ticker/{}/range/{}/{}/{}/{}",
+            stocks_ticker, multiplier, timespan, from, to
+        );
+        self.send_limited_request::<SlimResponse<T>>(&uri, query_params, AGGREGATES_MAX_LIMIT)
+            .await
+    }
+
+    /// Get trades for a stock, decoded into `T`, using the
+    /// [/v3/trades/{stocks_ticker}](https://polygon.io/docs/stocks/get_v3_trades__stockticker) API.
+    pub async fn stock_equities_trades_v3_as<T>(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<SlimResponse<T>, Error>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v3/trades/{}", stocks_ticker);
+        self.send_limited_request::<SlimResponse<T>>(&uri, query_params, TRADES_MAX_LIMIT)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use futures::TryStreamExt;
+
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::RESTClient;
+    use crate::types::*;
+
+    #[test]
+    fn test_stock_equities_grouped_daily_closes() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).stock_equities_grouped_daily_as::<ClosesOnly>(
+                "us",
+                StocksMarket::Stocks,
+                "2024-01-03",
+                &query_params,
+            ),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert!(resp
+            .results
+            .iter()
+            .any(|r| r.ticker.as_deref() == Some("MSFT")));
+    }
+
+    #[test]
+    fn test_slim_responses() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(
+                    r#"{"adjusted":true,"queryCount":2,"resultsCount":2,"status":"OK","results":[
+                        {"T":"MSFT","v":1.0,"vw":1.0,"o":1.0,"c":370.6,"h":1.0,"l":1.0,"t":1704315600000,"n":1},
+                        {"T":"AAPL","v":1.0,"vw":1.0,"o":1.0,"c":184.25,"h":1.0,"l":1.0,"t":1704315600000,"n":1}]}"#,
+                )),
+                Response::ok(String::from(
+                    r#"{"request_id":"1","status":"OK","results":[{"conditions":[12],"exchange":11,"id":"1",
+                        "price":370.5,"sequence_number":1,"sip_timestamp":1704292200000000000,"size":100,"tape":3}],
+                        "next_url":"/v3/trades/MSFT?cursor=abc"}"#,
+                )),
+                Response::ok(String::from(
+                    r#"{"request_id":"2","status":"OK","results":[{"conditions":[],"exchange":4,"id":"2",
+                        "price":370.6,"sequence_number":2,"sip_timestamp":1704292200000000001,"size":10,"tape":3}]}"#,
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+            let query_params = HashMap::new();
+
+            let closes = client
+                .stock_equities_grouped_daily_as::<ClosesOnly>(
+                    "us",
+                    StocksMarket::Stocks,
+                    "2024-01-03",
+                    &query_params,
+                )
+                .await
+                .unwrap();
+            let tickers: Vec<_> = closes
+                .results
+                .iter()
+                .map(|r| (r.ticker.as_deref().unwrap(), r.c))
+                .collect();
+            assert_eq!(tickers, vec![("MSFT", 370.6), ("AAPL", 184.25)]);
+
+            let trades: Vec<TradePricesOnly> = client
+                .paginate::<SlimResponse<TradePricesOnly>>("/v3/trades/MSFT", &query_params)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(trades.len(), 2);
+            assert_eq!((trades[1].price, trades[1].size), (370.6, 10.0));
+        });
+    }
+}
@@ -0,0 +1,759 @@
+//! Stock equities endpoints such as trades, quotes, and aggregates.
+use std::collections::HashMap;
+use std::ops::Range;
+
+use futures::stream::Stream;
+
+use crate::error::Error;
+use crate::rest::{RESTClient, QUOTES_MAX_LIMIT, TRADES_MAX_LIMIT};
+use crate::ticker::StockTicker;
+use crate::types::*;
+
+/// Options for the grouped daily bars returned by
+/// [`RESTClient::stock_equities_grouped_daily_with_options()`].
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::rest::GroupedDailyOptions;
+///
+/// let options = GroupedDailyOptions::new().adjusted(false).include_otc(true);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct GroupedDailyOptions {
+    adjusted: Option<bool>,
+    include_otc: bool,
+}
+
+impl GroupedDailyOptions {
+    /// Returns the server's default options, which adjust for splits and
+    /// leave out OTC tickers.
+    pub fn new() -> Self {
+        GroupedDailyOptions::default()
+    }
+
+    /// Whether the bars are adjusted for splits.
+    pub fn adjusted(mut self, adjusted: bool) -> Self {
+        self.adjusted = Some(adjusted);
+        self
+    }
+
+    /// Whether to include OTC tickers, whose bars have
+    /// [`otc`](StockEquitiesAggregates::otc) set.
+    pub fn include_otc(mut self, include_otc: bool) -> Self {
+        self.include_otc = include_otc;
+        self
+    }
+
+    fn query_params(&self) -> HashMap<&'static str, &'static str> {
+        let mut params = HashMap::new();
+        if let Some(adjusted) = self.adjusted {
+            params.insert("adjusted", if adjusted { "true" } else { "false" });
+        }
+        if self.include_otc {
+            params.insert("include_otc", "true");
+        }
+        params
+    }
+}
+
+/// Returns the query of a v3 ticks endpoint for the ticks whose SIP
+/// timestamp is in `range`, oldest first, in pages of `limit`.
+fn tick_range_query(range: &Range<u64>, limit: u32) -> [(&'static str, String); 5] {
+    [
+        ("timestamp.gte", range.start.to_string()),
+        ("timestamp.lt", range.end.to_string()),
+        ("order", Order::Asc.to_string()),
+        ("sort", Sort::Timestamp.to_string()),
+        ("limit", limit.to_string()),
+    ]
+}
+
+impl RESTClient {
+    /// Get a list of stock exchanges which are supported by polygon.io using
+    /// the [/v1/meta/exchanges](https://polygon.io/docs/get_v1_meta_exchanges_anchor) API.
+    pub async fn stock_equities_exchanges(
+        &self,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesExchangesResponse, Error> {
+        self.send_request::<StockEquitiesExchangesResponse>("/v1/meta/exchanges", query_params)
+            .await
+    }
+
+    /// Get a unified numerical mapping for conditions on trades and quotes
+    /// using the [/v1/meta/conditions/{tick_type}](https://polygon.io/docs/get_v1_meta_conditions__ticktype__anchor) API.
+    pub async fn stock_equities_condition_mappings(
+        &self,
+        tick_type: TickType,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesConditionMappingsResponse, Error> {
+        let uri = format!(
+            "/v1/meta/conditions/{}",
+            tick_type.to_string().to_lowercase()
+        );
+        self.send_request::<StockEquitiesConditionMappingsResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the most recent trade for a given stock using the
+    /// [/v2/last/trade/{stocks_ticker}](https://polygon.io/docs/get_v2_last_trade__stocksTicker__anchor) API.
+    pub async fn stock_equities_historic_trades(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesHistoricTradesResponse, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        self.send_last_trade(stocks_ticker.as_str(), query_params)
+            .await
+    }
+
+    /// Requests the most recent trade of a stock or option contract ticker.
+    pub(crate) async fn send_last_trade(
+        &self,
+        ticker: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesHistoricTradesResponse, Error> {
+        let uri = format!("/v2/last/trade/{}", ticker);
+        self.send_request::<StockEquitiesHistoricTradesResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the most recent NBBO quote tick for a given stock using the
+    /// [/v2/last/nbbo/{stocks_ticker}](https://polygon.io/docs/get_v2_last_nbbo__stocksTicker__anchor) API.
+    pub async fn stock_equities_last_quote_for_a_symbol(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesLastQuoteForASymbolResponse, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v2/last/nbbo/{}", stocks_ticker);
+        self.send_request::<StockEquitiesLastQuoteForASymbolResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get trades for a stock using the
+    /// [/v3/trades/{stocks_ticker}](https://polygon.io/docs/stocks/get_v3_trades__stockticker) API.
+    pub async fn stock_equities_trades_v3(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesTradesResponseV3, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v3/trades/{}", stocks_ticker);
+        self.send_limited_request::<StockEquitiesTradesResponseV3>(
+            &uri,
+            query_params,
+            TRADES_MAX_LIMIT,
+        )
+        .await
+    }
+
+    /// Stream the trades of a stock whose SIP timestamp is in `range`, given
+    /// in Unix nanoseconds, oldest first, using the
+    /// [/v3/trades/{stocks_ticker}](https://polygon.io/docs/stocks/get_v3_trades__stockticker) API.
+    ///
+    /// Trades are requested in pages of [`TRADES_MAX_LIMIT`], following the
+    /// `next_url` of each page until the last one, so a full day of ticks can
+    /// be consumed without handling cursors. Rate limited pages are requested
+    /// again as with [`RESTClient::paginate()`].
+    pub fn stock_trades_stream<'a>(
+        &'a self,
+        stocks_ticker: impl Into<StockTicker>,
+        range: Range<u64>,
+    ) -> impl Stream<Item = Result<StockEquitiesTradeV3, Error>> + 'a {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v3/trades/{}", stocks_ticker);
+        let query = tick_range_query(&range, TRADES_MAX_LIMIT);
+        let query_params = query.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.paginate::<StockEquitiesTradesResponseV3>(&uri, &query_params)
+    }
+
+    /// Get NBBO quotes for a stock using the
+    /// [/v3/quotes/{stocks_ticker}](https://polygon.io/docs/stocks/get_v3_quotes__stockticker) API.
+    pub async fn stock_equities_quotes_v3(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesQuotesResponseV3, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v3/quotes/{}", stocks_ticker);
+        self.send_limited_request::<StockEquitiesQuotesResponseV3>(
+            &uri,
+            query_params,
+            QUOTES_MAX_LIMIT,
+        )
+        .await
+    }
+
+    /// Stream the NBBO quotes of a stock whose SIP timestamp is in `range`,
+    /// given in Unix nanoseconds, oldest first, using the
+    /// [/v3/quotes/{stocks_ticker}](https://polygon.io/docs/stocks/get_v3_quotes__stockticker) API.
+    ///
+    /// Like [`RESTClient::stock_trades_stream()`], quotes are requested in
+    /// pages of [`QUOTES_MAX_LIMIT`] and the `next_url` of each page is
+    /// followed until the last one.
+    pub fn stock_quotes_stream<'a>(
+        &'a self,
+        stocks_ticker: impl Into<StockTicker>,
+        range: Range<u64>,
+    ) -> impl Stream<Item = Result<StockEquitiesQuoteV3, Error>> + 'a {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v3/quotes/{}", stocks_ticker);
+        let query = tick_range_query(&range, QUOTES_MAX_LIMIT);
+        let query_params = query.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.paginate::<StockEquitiesQuotesResponseV3>(&uri, &query_params)
+    }
+
+    /// Get the open, close, and afterhours prices of a stock symbol on a
+    /// certain date using the [/v1/open-close/{stocks_ticker}/{date}](https://polygon.io/docs/get_v1_open-close__stocksTicker___date__anchor) API.
+    pub async fn stock_equities_daily_open_close(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        date: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesDailyOpenCloseResponse, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v1/open-close/{}/{}", stocks_ticker, date);
+        self.send_request::<StockEquitiesDailyOpenCloseResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get aggregate bars for a stock over a given date range in custom time
+    /// window sizes using the [/v2/aggs/ticker/{stocks_ticker}/range/{multiplier}/{timespan}/{from}/{to}](https://polygon.io/docs/get_v2_aggs_ticker__stocksTicker__range__multiplier___timespan___from___to__anchor) API.
+    pub async fn stock_equities_aggregates(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        multiplier: u32,
+        timespan: Timespan,
+        from: &str,
+        to: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesAggregatesResponse, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        self.send_aggregates(
+            stocks_ticker.as_str(),
+            multiplier,
+            timespan,
+            from,
+            to,
+            query_params,
+        )
+        .await
+    }
+
+    /// Get the daily open, high, low, and close for the entire stocks and
+    /// equities market using the [/v2/aggs/grouped/locale/{locale}/market/{market}/{date}](https://polygon.io/docs/get_v2_aggs_grouped_locale_us_market_stocks__date__anchor) API.
+    pub async fn stock_equities_grouped_daily(
+        &self,
+        locale: &str,
+        market: StocksMarket,
+        date: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesGroupedDailyResponse, Error> {
+        let uri = format!(
+            "/v2/aggs/grouped/locale/{}/market/{}/{}",
+            locale, market, date
+        );
+        self.send_request::<StockEquitiesGroupedDailyResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get a stream of the daily open, high, low, and close of each ticker in
+    /// the entire stocks and equities market, decoded as the response of the
+    /// [/v2/aggs/grouped/locale/{locale}/market/{market}/{date}](https://polygon.io/docs/get_v2_aggs_grouped_locale_us_market_stocks__date__anchor) API
+    /// is received. See [`RESTClient::stream_array()`].
+    pub fn stock_equities_grouped_daily_stream<'a>(
+        &'a self,
+        locale: &str,
+        market: StocksMarket,
+        date: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> impl Stream<Item = Result<StockEquitiesAggregates, Error>> + 'a {
+        let uri = format!(
+            "/v2/aggs/grouped/locale/{}/market/{}/{}",
+            locale, market, date
+        );
+        self.stream_array(&uri, query_params, "results")
+    }
+
+    /// Get the daily open, high, low, and close for the entire stocks and
+    /// equities market with typed options, such as whether to include OTC
+    /// tickers, using the [/v2/aggs/grouped/locale/{locale}/market/{market}/{date}](https://polygon.io/docs/get_v2_aggs_grouped_locale_us_market_stocks__date__anchor) API.
+    pub async fn stock_equities_grouped_daily_with_options(
+        &self,
+        locale: &str,
+        market: StocksMarket,
+        date: &str,
+        options: &GroupedDailyOptions,
+    ) -> Result<StockEquitiesGroupedDailyResponse, Error> {
+        self.stock_equities_grouped_daily(locale, market, date, &options.query_params())
+            .await
+    }
+
+    /// Get the previous day's open, high, low, and close for the specified
+    /// stock ticker using the [/v2/aggs/ticker/{stocks_ticker}/prev](https://polygon.io/docs/get_v2_aggs_ticker__stocksTicker__prev_anchor) API.
+    pub async fn stock_equities_previous_close(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesPreviousCloseResponse, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let uri = format!("/v2/aggs/ticker/{}/prev", stocks_ticker);
+        self.send_request::<StockEquitiesPreviousCloseResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the current minute, day, and previous day's aggregate, as well as
+    /// the last trade and quote for all traded stock symbols using the [/v2/snapshot/locale/{locale}/markets/stocks/tickers](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks_tickers_anchor) API.
+    pub async fn stock_equities_snapshot_all_tickers(
+        &self,
+        locale: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesSnapshotAllTickersResponse, Error> {
+        self.stock_equities_snapshot_all_tickers_market(locale, StocksMarket::Stocks, query_params)
+            .await
+    }
+
+    /// Get the snapshots of all traded symbols of a market, such as OTC,
+    /// using the [/v2/snapshot/locale/{locale}/markets/{market}/tickers](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks_tickers_anchor) API.
+    pub async fn stock_equities_snapshot_all_tickers_market(
+        &self,
+        locale: &str,
+        market: StocksMarket,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesSnapshotAllTickersResponse, Error> {
+        let uri = format!("/v2/snapshot/locale/{}/markets/{}/tickers", locale, market);
+        self.send_request::<StockEquitiesSnapshotAllTickersResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get a stream of the snapshots of all traded stock symbols, decoded as
+    /// the response of the [/v2/snapshot/locale/{locale}/markets/{market}/tickers](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks_tickers_anchor) API
+    /// is received. See [`RESTClient::stream_array()`].
+    pub fn stock_equities_snapshot_all_tickers_stream<'a>(
+        &'a self,
+        locale: &str,
+        market: StocksMarket,
+        query_params: &HashMap<&str, &str>,
+    ) -> impl Stream<Item = Result<StockEquitiesTickerSnapshot, Error>> + 'a {
+        let uri = format!("/v2/snapshot/locale/{}/markets/{}/tickers", locale, market);
+        self.stream_array(&uri, query_params, "tickers")
+    }
+
+    /// Get the current minute, day, and previous day's aggregate, as well as
+    /// the last trade and quote for a single traded stock ticker using the [/v2/snapshot/locale/{locale}/markets/stocks/tickers/{ticker}](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks_tickers__stocksTicker__anchor) API.
+    pub async fn stock_equities_snapshot_single_ticker(
+        &self,
+        locale: &str,
+        ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesSnapshotAllTickersResponse, Error> {
+        self.stock_equities_snapshot_single_ticker_market(
+            locale,
+            StocksMarket::Stocks,
+            ticker,
+            query_params,
+        )
+        .await
+    }
+
+    /// Get the snapshot of a single traded ticker of a market, such as OTC,
+    /// using the [/v2/snapshot/locale/{locale}/markets/{market}/tickers/{ticker}](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks_tickers__stocksTicker__anchor) API.
+    pub async fn stock_equities_snapshot_single_ticker_market(
+        &self,
+        locale: &str,
+        market: StocksMarket,
+        ticker: impl Into<StockTicker>,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesSnapshotAllTickersResponse, Error> {
+        let ticker: StockTicker = ticker.into();
+        let uri = format!(
+            "/v2/snapshot/locale/{}/markets/{}/tickers/{}",
+            locale, market, ticker
+        );
+        self.send_request::<StockEquitiesSnapshotAllTickersResponse>(&uri, query_params)
+            .await
+    }
+
+    /// Get the current top 20 gainers or losers of the day in the
+    /// stocks/equities markets using the [/v2/snapshot/locale/{locale}/markets/stocks/{direction}](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks__direction__anchor) API.
+    pub async fn stock_equities_snapshot_gainers_losers(
+        &self,
+        locale: &str,
+        direction: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesSnapshotGainersLosersResponse, Error> {
+        self.stock_equities_snapshot_gainers_losers_market(
+            locale,
+            StocksMarket::Stocks,
+            direction,
+            query_params,
+        )
+        .await
+    }
+
+    /// Get the current top 20 gainers or losers of the day in a market, such
+    /// as OTC, using the [/v2/snapshot/locale/{locale}/markets/{market}/{direction}](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks__direction__anchor) API.
+    pub async fn stock_equities_snapshot_gainers_losers_market(
+        &self,
+        locale: &str,
+        market: StocksMarket,
+        direction: &str,
+        query_params: &HashMap<&str, &str>,
+    ) -> Result<StockEquitiesSnapshotGainersLosersResponse, Error> {
+        let uri = format!(
+            "/v2/snapshot/locale/{}/markets/{}/{}",
+            locale, market, direction
+        );
+        self.send_request::<StockEquitiesSnapshotGainersLosersResponse>(&uri, query_params)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::rest::{GroupedDailyOptions, RESTClient};
+    use crate::ticker::StockTicker;
+    use crate::types::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_stock_equities_exchanges() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).stock_equities_exchanges(&query_params),
+        )
+        .unwrap();
+        assert_ne!(resp.len(), 0);
+        let dji = resp
+            .iter()
+            .find(|x| x.code.is_some() && x.code.as_ref().unwrap() == "DJI");
+        assert!(dji.is_some());
+        assert_eq!(dji.unwrap().market, "index");
+    }
+
+    #[test]
+    fn test_stock_equities_condition_mappings() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .stock_equities_condition_mappings(TickType::Trades, &query_params),
+        )
+        .unwrap();
+        assert_ne!(resp.len(), 0);
+        let regular = resp.iter().find(|x| x.1 == "Regular");
+        assert!(regular.is_some());
+    }
+
+    #[test]
+    fn test_stock_equities_historic_trades() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .stock_equities_historic_trades(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.results.ticker.unwrap(), "MSFT");
+    }
+
+    #[test]
+    fn test_stock_equities_last_quote_for_a_symbol() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None).stock_equities_last_quote_for_a_symbol(
+                StockTicker::new("MSFT").unwrap(),
+                &query_params,
+            ),
+        )
+        .unwrap();
+        assert_eq!(resp.results.ticker.unwrap(), "MSFT");
+    }
+
+    #[test]
+    fn test_stock_equities_trades_v3() {
+        let mut query_params = HashMap::new();
+        query_params.insert("limit", "10");
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .stock_equities_trades_v3(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results.len(), 10);
+    }
+
+    #[test]
+    fn test_stock_trades_stream() {
+        use futures::TryStreamExt;
+
+        let trade = |sequence_number: u64| {
+            format!(
+                r#"{{"conditions":[12],"exchange":11,"id":"{}","price":289.95,"sequence_number":{},"sip_timestamp":1633687200{:09},"size":100,"tape":3}}"#,
+                sequence_number, sequence_number, sequence_number
+            )
+        };
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(format!(
+                    r#"{{"results":[{},{}],"status":"OK","request_id":"1","next_url":"https://api.polygon.io/v3/trades/MSFT?cursor=abc"}}"#,
+                    trade(1),
+                    trade(2)
+                )),
+                Response::ok(format!(
+                    r#"{{"results":[{}],"status":"OK","request_id":"2"}}"#,
+                    trade(3)
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let sequence_numbers: Vec<_> = client
+                .stock_trades_stream(
+                    StockTicker::new("MSFT").unwrap(),
+                    1633687200000000000..1633773600000000000,
+                )
+                .map_ok(|t| t.sequence_number)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(sequence_numbers, vec![1, 2, 3]);
+
+            let requests = server.requests();
+            assert!(requests[0].starts_with("/v3/trades/MSFT?"));
+            for param in [
+                "timestamp.gte=1633687200000000000",
+                "timestamp.lt=1633773600000000000",
+                "order=asc",
+                "sort=timestamp",
+                "limit=50000",
+            ]
+            .iter()
+            {
+                assert!(requests[0].contains(param), "{}", requests[0]);
+            }
+            assert_eq!(requests[1], "/v3/trades/MSFT?cursor=abc");
+        });
+    }
+
+    #[test]
+    fn test_stock_equities_quotes_v3() {
+        let mut query_params = HashMap::new();
+        query_params.insert("limit", "10");
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .stock_equities_quotes_v3(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results.len(), 10);
+    }
+
+    #[test]
+    fn test_stock_quotes_stream() {
+        use futures::TryStreamExt;
+
+        let quote = |sequence_number: u64, bid_price: f64| {
+            format!(
+                r#"{{"ask_exchange":11,"ask_price":290.1,"ask_size":2,"bid_exchange":12,"bid_price":{},"bid_size":3,"sequence_number":{},"sip_timestamp":1633687200{:09},"tape":3}}"#,
+                bid_price, sequence_number, sequence_number
+            )
+        };
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(format!(
+                    r#"{{"results":[{}],"status":"OK","request_id":"1","next_url":"https://api.polygon.io/v3/quotes/MSFT?cursor=abc"}}"#,
+                    quote(1, 289.9)
+                )),
+                Response::ok(format!(
+                    r#"{{"results":[{}],"status":"OK","request_id":"2"}}"#,
+                    quote(2, 290.0)
+                )),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let bids: Vec<_> = client
+                .stock_quotes_stream(
+                    StockTicker::new("MSFT").unwrap(),
+                    1633687200000000000..1633773600000000000,
+                )
+                .map_ok(|q| q.bid_price)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(bids, vec![289.9, 290.0]);
+
+            let requests = server.requests();
+            assert!(requests[0].starts_with("/v3/quotes/MSFT?"));
+            assert!(requests[0].contains("timestamp.lt=1633773600000000000"));
+            assert!(requests[0].contains("limit=50000"));
+            assert_eq!(requests[1], "/v3/quotes/MSFT?cursor=abc");
+        });
+    }
+
+    #[test]
+    fn test_stock_equities_daily_open_close() {
+        let query_params = HashMap::new();
+        let resp =
+            tokio_test::block_on(RESTClient::new(None, None).stock_equities_daily_open_close(
+                StockTicker::new("MSFT").unwrap(),
+                "2020-10-14",
+                &query_params,
+            ))
+            .unwrap();
+        assert_eq!(resp.symbol, "MSFT");
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.open, 223f64);
+        assert_eq!(resp.high, 224.22);
+        assert_eq!(resp.low, 219.13);
+        assert_eq!(resp.close, 220.86);
+        assert_eq!(resp.volume, 23451713f64);
+        assert_eq!(resp.after_hours, 220.3);
+        assert_eq!(resp.pre_market, 224.03);
+    }
+
+    #[test]
+    fn test_stock_equities_aggregates() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(RESTClient::new(None, None).stock_equities_aggregates(
+            StockTicker::new("MSFT").unwrap(),
+            1,
+            Timespan::Day,
+            "2020-10-14",
+            "2020-10-14",
+            &query_params,
+        ))
+        .unwrap();
+        assert_eq!(resp.ticker, "MSFT");
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.query_count, 1);
+        assert_eq!(resp.results_count, 1);
+        let result = resp.results.first().unwrap();
+        assert_eq!(result.v, 23451713f64);
+        assert_eq!(result.vw.unwrap(), 221.41);
+        assert_eq!(result.o, 223f64);
+        assert_eq!(result.c, 220.86);
+        assert_eq!(result.h, 224.22);
+        assert_eq!(result.l, 219.13);
+        assert_eq!(result.t.unwrap(), 1602648000000);
+        assert_eq!(result.n.unwrap(), 244243f64);
+    }
+
+    #[test]
+    fn test_stock_equities_grouped_daily() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(RESTClient::new(None, None).stock_equities_grouped_daily(
+            "us",
+            StocksMarket::Stocks,
+            "2020-10-14",
+            &query_params,
+        ))
+        .unwrap();
+        assert_eq!(resp.status, "OK");
+        let msft = resp
+            .results
+            .iter()
+            .find(|x| x.T.is_some() && x.T.as_ref().unwrap() == "MSFT");
+        assert!(msft.is_some());
+        assert!(msft.unwrap().vw.is_some());
+        assert_eq!(msft.unwrap().vw.unwrap(), 221.41);
+        assert_eq!(msft.unwrap().o, 223f64);
+        assert_eq!(msft.unwrap().h, 224.22);
+        assert_eq!(msft.unwrap().l, 219.13);
+    }
+
+    #[test]
+    fn test_stock_equities_grouped_daily_include_otc() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"adjusted":false,"queryCount":2,"resultsCount":2,"status":"OK","results":[
+                    {"T":"MSFT","v":1.0,"o":1.0,"c":1.0,"h":1.0,"l":1.0,"t":1602705600000},
+                    {"T":"NSRGY","v":1.0,"o":1.0,"c":1.0,"h":1.0,"l":1.0,"t":1602705600000,"otc":true}]}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            let options = GroupedDailyOptions::new().adjusted(false).include_otc(true);
+            let resp = client
+                .stock_equities_grouped_daily_with_options(
+                    "us",
+                    StocksMarket::Stocks,
+                    "2020-10-14",
+                    &options,
+                )
+                .await
+                .unwrap();
+            let otc: Vec<_> = resp.results.iter().map(|r| r.otc).collect();
+            assert_eq!(otc, vec![false, true]);
+
+            let request = &server.requests()[0];
+            assert!(request.starts_with("/v2/aggs/grouped/locale/us/market/stocks/2020-10-14?"));
+            assert!(request.contains("include_otc=true"));
+            assert!(request.contains("adjusted=false"));
+        });
+    }
+
+    #[test]
+    fn test_stock_equities_previous_close() {
+        let query_params = HashMap::new();
+        let resp = tokio_test::block_on(
+            RESTClient::new(None, None)
+                .stock_equities_previous_close(StockTicker::new("MSFT").unwrap(), &query_params),
+        )
+        .unwrap();
+        assert_eq!(resp.ticker, "MSFT");
+        assert_eq!(resp.status, "OK");
+        assert_eq!(resp.results_count, 1);
+        let result = resp.results.first();
+        assert!(result.is_some());
+        assert!(result.unwrap().T.is_some());
+        assert_eq!(result.unwrap().T.as_ref().unwrap(), "MSFT");
+    }
+
+    #[test]
+    fn test_stock_equities_snapshot_all_tickers() {
+        let query_params = HashMap::new();
+        let _resp = tokio_test::block_on(
+            RESTClient::new(None, None).stock_equities_snapshot_all_tickers("us", &query_params),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_stock_equities_snapshot_gainers_losers() {
+        let query_params = HashMap::new();
+        let _resp = tokio_test::block_on(
+            RESTClient::new(None, None).stock_equities_snapshot_gainers_losers(
+                "us",
+                "gainers",
+                &query_params,
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_stock_equities_snapshot_otc() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![Response::ok(String::from(
+                r#"{"status":"OK","count":0,"tickers":[]}"#,
+            ))])
+            .await;
+            let client = test_server::client(&server);
+
+            client
+                .stock_equities_snapshot_all_tickers_market(
+                    "us",
+                    StocksMarket::Otc,
+                    &HashMap::new(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                server.requests(),
+                vec!["/v2/snapshot/locale/us/markets/otc/tickers"]
+            );
+        });
+    }
+}
@@ -0,0 +1,491 @@
+//! Streaming of the elements of large array responses.
+//!
+//! The grouped daily bars and the snapshot of every ticker in a market are
+//! returned as a single response of several megabytes, which is normally
+//! buffered in full and then decoded into a `Vec` of thousands of elements.
+//! [`RESTClient::stream_array()`] instead decodes the elements of the array
+//! one by one as the body arrives, so that they can be processed, or written
+//! elsewhere, while the rest of the body is still being received and without
+//! holding all of them in memory at once.
+use std::collections::{HashMap, VecDeque};
+
+use bytes::Bytes;
+use futures::stream::{self, Stream};
+
+use crate::error::{decode_json, error_in_body, DecodeError, Error};
+use crate::rest::budget::RequestPermit;
+use crate::rest::RESTClient;
+
+/// Splits the elements of an array field of a JSON object out of a body that
+/// is received in chunks.
+///
+/// The body is only scanned for the nesting of objects, arrays, and strings,
+/// so the elements are not validated until they are decoded.
+#[derive(Debug)]
+pub(crate) struct ArrayScanner {
+    field: Vec<u8>,
+    buf: Vec<u8>,
+    pos: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    state: ScanState,
+    /// Where the string being scanned started.
+    string_start: usize,
+    /// The last string that was completed at the top level of the object.
+    last_string: Vec<u8>,
+    /// Whether the top-level value being scanned belongs to the field.
+    in_field: bool,
+    element_start: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScanState {
+    /// Looking for the array field.
+    Seeking,
+    /// Splitting the elements of the array.
+    InArray,
+    /// The end of the array was reached.
+    Done,
+}
+
+impl ArrayScanner {
+    /// Returns a scanner of the elements of the array in the `field` of the
+    /// top-level object.
+    pub(crate) fn new(field: &str) -> Self {
+        ArrayScanner {
+            field: field.as_bytes().to_vec(),
+            buf: Vec::new(),
+            pos: 0,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            state: ScanState::Seeking,
+            string_start: 0,
+            last_string: Vec::new(),
+            in_field: false,
+            element_start: None,
+        }
+    }
+
+    /// Returns whether the array was found.
+    pub(crate) fn found(&self) -> bool {
+        self.state != ScanState::Seeking
+    }
+
+    /// Returns whether the end of the array was reached.
+    pub(crate) fn is_done(&self) -> bool {
+        self.state == ScanState::Done
+    }
+
+    /// Returns the part of the body that was not split into elements, which
+    /// is the whole body if it has no such array.
+    pub(crate) fn body(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Adds the next chunk of the body and returns the elements that it
+    /// completes.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        let mut elements = Vec::new();
+        if self.is_done() {
+            return elements;
+        }
+        self.buf.extend_from_slice(chunk);
+
+        while self.pos < self.buf.len() && !self.is_done() {
+            let b = self.buf[self.pos];
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if b == b'\\' {
+                    self.escaped = true;
+                } else if b == b'"' {
+                    self.in_string = false;
+                    if self.state == ScanState::Seeking && self.depth == 1 {
+                        self.last_string = self.buf[self.string_start + 1..self.pos].to_vec();
+                    }
+                }
+                self.pos += 1;
+                continue;
+            }
+
+            match self.state {
+                ScanState::Seeking => self.seek(b),
+                _ => {
+                    if let Some(element) = self.split(b) {
+                        elements.push(element);
+                    }
+                }
+            }
+            self.pos += 1;
+        }
+
+        // Only the element being scanned is still needed once in the array.
+        if self.found() {
+            let keep = self.element_start.unwrap_or(self.pos);
+            self.buf.drain(..keep);
+            self.pos -= keep;
+            self.element_start = self.element_start.map(|_| 0);
+        }
+        elements
+    }
+
+    fn seek(&mut self, b: u8) {
+        match b {
+            b'"' => {
+                self.in_string = true;
+                self.string_start = self.pos;
+            }
+            b':' if self.depth == 1 => self.in_field = self.last_string == self.field,
+            b',' if self.depth == 1 => self.in_field = false,
+            b'[' if self.depth == 1 && self.in_field => {
+                self.depth += 1;
+                self.state = ScanState::InArray;
+            }
+            b'{' | b'[' => self.depth += 1,
+            b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    fn split(&mut self, b: u8) -> Option<Vec<u8>> {
+        let at_top = self.depth == 2;
+        match b {
+            b'"' => {
+                self.in_string = true;
+                self.start_element(at_top);
+            }
+            b'{' | b'[' => {
+                self.start_element(at_top);
+                self.depth += 1;
+            }
+            b'}' | b']' => {
+                if at_top {
+                    self.state = ScanState::Done;
+                    return self.take_element();
+                }
+                self.depth -= 1;
+            }
+            b',' if at_top => return self.take_element(),
+            b' ' | b'\t' | b'\n' | b'\r' => {}
+            _ => self.start_element(at_top),
+        }
+        None
+    }
+
+    fn start_element(&mut self, at_top: bool) {
+        if at_top && self.element_start.is_none() {
+            self.element_start = Some(self.pos);
+        }
+    }
+
+    fn take_element(&mut self) -> Option<Vec<u8>> {
+        let start = self.element_start.take()?;
+        let mut end = self.pos;
+        while end > start && self.buf[end - 1].is_ascii_whitespace() {
+            end -= 1;
+        }
+        Some(self.buf[start..end].to_vec())
+    }
+}
+
+/// The body of a streamed response, read a chunk at a time.
+#[cfg(not(target_arch = "wasm32"))]
+struct Body(reqwest::Response);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Body {
+    fn new(res: reqwest::Response) -> Self {
+        Body(res)
+    }
+
+    async fn chunk(&mut self) -> reqwest::Result<Option<Bytes>> {
+        self.0.chunk().await
+    }
+}
+
+/// The body of a streamed response. The browser hands it to reqwest only as
+/// a whole, so it is read as a single chunk.
+#[cfg(target_arch = "wasm32")]
+struct Body(Option<reqwest::Response>);
+
+#[cfg(target_arch = "wasm32")]
+impl Body {
+    fn new(res: reqwest::Response) -> Self {
+        Body(Some(res))
+    }
+
+    async fn chunk(&mut self) -> reqwest::Result<Option<Bytes>> {
+        match self.0.take() {
+            Some(res) => res.bytes().await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The state of a response whose array elements are being streamed.
+struct ArrayStream {
+    body: Body,
+    status: u16,
+    _permit: RequestPermit,
+    scanner: ArrayScanner,
+    field: String,
+    elements: VecDeque<Vec<u8>>,
+}
+
+/// Returns the error of a body that ended before the end of the array in
+/// its `field`, of which `rest` is the part after the last complete element.
+fn truncated_array(field: &str, rest: &[u8]) -> Error {
+    let source = <serde_json::Error as serde::de::Error>::custom(format!(
+        "the body ended before the end of the `{}` array",
+        field
+    ));
+    Error::Decode(DecodeError::new(String::from(field), source, rest))
+}
+
+impl RESTClient {
+    /// Sends a request to a path of the API and returns a stream of the
+    /// elements of the array in the `field` of the response, such as
+    /// `results`, decoded as they are received.
+    ///
+    /// The request is retried like those of the typed methods until the
+    /// response starts to arrive. Every other field of the response is
+    /// ignored. A JSON object without the field, which polygon.io returns
+    /// when there are no results, yields no elements, unless it is an error.
+    /// A body that is not a JSON object, such as the page of a proxy, or that
+    /// ends before the end of the array fails with [`Error::Decode`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use futures::TryStreamExt;
+    /// use polygon_client::rest::RESTClient;
+    /// use polygon_client::types::StockEquitiesAggregates;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = RESTClient::try_new(None, None).expect("failed to create client");
+    ///     let volume = client
+    ///         .stream_array::<StockEquitiesAggregates>(
+    ///             "/v2/aggs/grouped/locale/us/market/stocks/2024-01-02",
+    ///             &HashMap::new(),
+    ///             "results",
+    ///         )
+    ///         .try_fold(0.0, |volume, bar| async move { Ok(volume + bar.v) })
+    ///         .await
+    ///         .expect("failed to stream grouped daily bars");
+    ///     println!("total volume: {}", volume);
+    /// }
+    /// ```
+    pub fn stream_array<'a, T>(
+        &'a self,
+        path: &str,
+        query_params: &HashMap<&str, &str>,
+        field: &str,
+    ) -> impl Stream<Item = Result<T, Error>> + 'a
+    where
+        T: serde::de::DeserializeOwned + 'a,
+    {
+        let url = format!("{}{}", self.inner.api_url, path);
+        let query_params: Vec<_> = query_params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let field = String::from(field);
+
+        enum State {
+            Start(String, Vec<(String, String)>, String),
+            Streaming(Box<ArrayStream>),
+        }
+
+        stream::try_unfold(
+            Some(State::Start(url, query_params, field)),
+            move |state| async move {
+                let mut s = match state {
+                    Some(State::Start(url, query_params, field)) => {
+                        let (res, permit) = self.start_stream(&url, &query_params).await?;
+                        Box::new(ArrayStream {
+                            status: res.status().as_u16(),
+                            body: Body::new(res),
+                            _permit: permit,
+                            scanner: ArrayScanner::new(&field),
+                            field,
+                            elements: VecDeque::new(),
+                        })
+                    }
+                    Some(State::Streaming(s)) => s,
+                    None => return Ok(None),
+                };
+
+                loop {
+                    if let Some(element) = s.elements.pop_front() {
+                        let item = decode_json::<T>(&element)?;
+                        return Ok(Some((item, Some(State::Streaming(s)))));
+                    }
+                    if s.scanner.is_done() {
+                        return Ok(None);
+                    }
+                    match s.body.chunk().await.map_err(|e| self.redact_url(e))? {
+                        Some(chunk) => {
+                            let elements = s.scanner.push(&chunk);
+                            s.elements.extend(elements);
+                        }
+                        None if s.scanner.found() => {
+                            return Err(truncated_array(&s.field, s.scanner.body()));
+                        }
+                        None => {
+                            if let Some(e) = error_in_body(s.status, s.scanner.body()) {
+                                return Err(e);
+                            }
+                            decode_json::<serde_json::Map<String, serde_json::Value>>(
+                                s.scanner.body(),
+                            )?;
+                            return Ok(None);
+                        }
+                    }
+                }
+            },
+        )
+    }
+
+    /// Sends the request of a stream, retrying it like those of the typed
+    /// methods, and returns the response before its body is read.
+    async fn start_stream(
+        &self,
+        url: &str,
+        query_params: &[(String, String)],
+    ) -> Result<(reqwest::Response, RequestPermit), Error> {
+        let send = || {
+            self.retry_transient(url, || async move {
+                let req = self.build_request(url, query_params)?;
+                let (res, permit, _) = self.execute(url, req).await?;
+                Ok((res, permit))
+            })
+        };
+        if self.inner.retry_rate_limited {
+            self.retry_rate_limited(send).await
+        } else {
+            send().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use futures::TryStreamExt;
+
+    use crate::error::{Error, ErrorKind};
+    use crate::rest::streaming::ArrayScanner;
+    use crate::rest::test_server::{self, Response, TestServer};
+
+    fn scan(body: &str, field: &str, chunk_len: usize) -> Vec<String> {
+        let mut scanner = ArrayScanner::new(field);
+        let mut elements = Vec::new();
+        for chunk in body.as_bytes().chunks(chunk_len) {
+            elements.extend(scanner.push(chunk));
+        }
+        elements
+            .into_iter()
+            .map(|e| String::from_utf8(e).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_array_scanner() {
+        let body = r#"{"status":"OK","note":"results","nested":{"results":[9]},
+            "results": [ {"T":"A]\"}","c":1.5}, {"T":"B","x":[1,{"y":2}]} ,3,"s"],
+            "count":4}"#;
+        let expected = vec![
+            r#"{"T":"A]\"}","c":1.5}"#,
+            r#"{"T":"B","x":[1,{"y":2}]}"#,
+            "3",
+            r#""s""#,
+        ];
+        for chunk_len in [1, 2, 7, body.len()].iter() {
+            assert_eq!(scan(body, "results", *chunk_len), expected);
+        }
+
+        assert!(scan(r#"{"results":[]}"#, "results", 1).is_empty());
+        assert!(scan(r#"{"status":"OK","count":0}"#, "results", 3).is_empty());
+        assert_eq!(
+            scan(r#"{"tickers":[{"a":1}]}"#, "tickers", 4),
+            vec![r#"{"a":1}"#]
+        );
+    }
+
+    #[test]
+    fn test_stream_array() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Bar {
+            #[serde(rename = "T")]
+            ticker: String,
+            c: f64,
+        }
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(vec![
+                Response::ok(String::from(
+                    r#"{"status":"OK","results":[{"T":"MSFT","c":1.5},{"T":"AAPL","c":2.5}]}"#,
+                )),
+                Response::ok(String::from(
+                    r#"{"status":"ERROR","request_id":"abc","error":"Unknown API Key"}"#,
+                )),
+                Response::error(404, r#"{"status":"NOT_FOUND"}"#),
+                Response::ok(String::from(
+                    r#"{"status":"OK","results":[{"T":"MSFT","c":1.5},{"T":"AA"#,
+                )),
+                Response::ok(String::from("<html>502 Bad Gateway</html>")),
+                Response::ok(String::from(r#"{"status":"OK","resultsCount":0}"#)),
+            ])
+            .await;
+            let client = test_server::client(&server);
+
+            let no_params = HashMap::new();
+            let bars: Vec<Bar> = client
+                .stream_array("/v1/test", &no_params, "results")
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(bars.len(), 2);
+            assert_eq!((bars[1].ticker.as_str(), bars[1].c), ("AAPL", 2.5));
+
+            let err = client
+                .stream_array::<Bar>("/v1/test", &no_params, "results")
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Api { status: 200, .. }));
+
+            let err = client
+                .stream_array::<Bar>("/v1/test", &no_params, "results")
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::NotFound);
+
+            let mut bars = Box::pin(client.stream_array::<Bar>("/v1/test", &no_params, "results"));
+            assert_eq!(bars.try_next().await.unwrap().unwrap().ticker, "MSFT");
+            let err = bars.try_next().await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Decode);
+
+            let err = client
+                .stream_array::<Bar>("/v1/test", &no_params, "results")
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Decode);
+
+            let bars: Vec<Bar> = client
+                .stream_array("/v1/test", &no_params, "results")
+                .try_collect()
+                .await
+                .unwrap();
+            assert!(bars.is_empty());
+        });
+    }
+}
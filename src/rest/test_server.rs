@@ -0,0 +1,189 @@
+//! A minimal HTTP server that replays scripted responses in tests.
+//!
+//! Which helpers the tests use depends on the enabled endpoint groups.
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::rest::{RESTClient, RESTClientBuilder};
+
+/// A scripted response returned by the [`TestServer`].
+pub(crate) struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+    path: Option<String>,
+}
+
+impl Response {
+    /// Returns a `200 OK` response with a JSON body.
+    pub(crate) fn ok(body: String) -> Self {
+        Response {
+            status: 200,
+            headers: Vec::new(),
+            body,
+            path: None,
+        }
+    }
+
+    /// Returns a `429 Too Many Requests` response with an optional
+    /// `Retry-After` header.
+    pub(crate) fn rate_limited(retry_after: Option<u64>) -> Self {
+        let headers = match retry_after {
+            Some(secs) => vec![(String::from("Retry-After"), secs.to_string())],
+            None => Vec::new(),
+        };
+
+        Response {
+            status: 429,
+            headers,
+            body: String::from(r#"{"status":"ERROR","error":"exceeded maximum requests"}"#),
+            path: None,
+        }
+    }
+
+    /// Returns a response with the given status code and JSON body.
+    pub(crate) fn error(status: u16, body: &str) -> Self {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: String::from(body),
+            path: None,
+        }
+    }
+
+    /// Adds a header to the response.
+    pub(crate) fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((String::from(name), String::from(value)));
+        self
+    }
+
+    /// Restricts the response to requests whose path starts with `path`.
+    ///
+    /// This allows responses to be matched to concurrent requests that may
+    /// arrive in any order.
+    pub(crate) fn on_path(mut self, path: &str) -> Self {
+        self.path = Some(String::from(path));
+        self
+    }
+}
+
+/// A server that answers each incoming request with the first remaining
+/// scripted response that matches it and records the path and headers of
+/// every request it receives.
+pub(crate) struct TestServer {
+    url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+    heads: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestServer {
+    /// Starts a server on a random local port that replays `responses` in
+    /// order.
+    pub(crate) async fn start(mut responses: Vec<Response>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let heads = Arc::new(Mutex::new(Vec::new()));
+        let recorded_heads = heads.clone();
+
+        tokio::spawn(async move {
+            while !responses.is_empty() {
+                let (mut stream, _) = match listener.accept().await {
+                    Ok(v) => v,
+                    Err(_) => return,
+                };
+
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let head = String::from_utf8_lossy(&head);
+                let path = String::from(head.split_whitespace().nth(1).unwrap_or_default());
+                recorded.lock().unwrap().push(path.clone());
+                recorded_heads.lock().unwrap().push(head.to_string());
+
+                let index = responses
+                    .iter()
+                    .position(|r| r.path.as_ref().is_none_or(|p| path.starts_with(p)));
+                let response = match index {
+                    Some(index) => responses.remove(index),
+                    None => Response {
+                        status: 404,
+                        headers: Vec::new(),
+                        body: String::from(r#"{"status":"NOT_FOUND"}"#),
+                        path: None,
+                    },
+                };
+
+                let mut out = format!(
+                    "HTTP/1.1 {} TEST\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    response.status,
+                    response.body.len()
+                );
+                for (name, value) in &response.headers {
+                    out.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                out.push_str("\r\n");
+                out.push_str(&response.body);
+
+                let _ = stream.write_all(out.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            }
+        });
+
+        TestServer {
+            url,
+            requests,
+            heads,
+        }
+    }
+
+    /// Returns the base URL of the server.
+    pub(crate) fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Returns the path and query of each request received so far.
+    pub(crate) fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the value of a header of each request received so far, or
+    /// `None` for requests without it. Header names are matched regardless of
+    /// case.
+    pub(crate) fn request_headers(&self, name: &str) -> Vec<Option<String>> {
+        let prefix = format!("{}:", name.to_lowercase());
+        self.heads
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|head| {
+                head.lines()
+                    .find(|line| line.to_lowercase().starts_with(&prefix))
+                    .map(|line| String::from(line[prefix.len()..].trim()))
+            })
+            .collect()
+    }
+}
+
+/// Returns a builder of a client that sends its requests to `server`.
+pub(crate) fn builder(server: &TestServer) -> RESTClientBuilder {
+    RESTClient::builder()
+        .auth_key("test")
+        .api_url(&server.url())
+}
+
+/// Returns a client that sends its requests to `server`.
+pub(crate) fn client(server: &TestServer) -> RESTClient {
+    builder(server).build().unwrap()
+}
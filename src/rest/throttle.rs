@@ -0,0 +1,132 @@
+//! Client-side pacing of requests.
+//!
+//! polygon.io limits the number of requests that a key may send per minute,
+//! with free keys allowed only 5. Rather than sending requests until the
+//! server answers `429 Too Many Requests`, a client built with
+//! [`RESTClientBuilder::throttle()`](crate::rest::RESTClientBuilder::throttle)
+//! delays each request until the budget of its window allows it. The budget
+//! is a token bucket shared by the client and its clones, so requests may be
+//! sent in a burst up to the size of the window and are then spread evenly
+//! over it. A partition built with
+//! [`PartitionBuilder::throttle()`](crate::rest::PartitionBuilder::throttle)
+//! has a bucket of its own, and its requests wait for both buckets.
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::rest::RESTClient;
+use crate::runtime::{self, Instant};
+
+/// A token bucket holding up to `capacity` requests, refilled at a constant
+/// rate.
+pub(crate) struct Throttle {
+    capacity: f64,
+    interval: Duration,
+    state: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// The number of requests that may be sent right away, which is negative
+    /// when requests are already waiting for the bucket to refill.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Throttle {
+    /// Returns a bucket that allows `requests` requests per `window`, which
+    /// starts out full.
+    pub(crate) fn new(requests: u32, window: Duration) -> Self {
+        Throttle {
+            capacity: f64::from(requests),
+            interval: window / requests,
+            state: Mutex::new(Bucket {
+                tokens: f64::from(requests),
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a request from the bucket and returns how long to wait before
+    /// sending it.
+    ///
+    /// A request that has to wait still takes its token right away, so
+    /// requests are sent in the order in which they reserved their tokens.
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut bucket = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let refill = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens =
+            (bucket.tokens + refill.as_secs_f64() / self.interval.as_secs_f64()).min(self.capacity);
+        bucket.refilled_at = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            self.interval.mul_f64(-bucket.tokens)
+        }
+    }
+}
+
+impl RESTClient {
+    /// Waits until the throttles of the client and its partitions, if any,
+    /// allow another request to be sent.
+    pub(crate) async fn throttle(&self) {
+        let now = Instant::now();
+        let wait = self
+            .partitions()
+            .filter_map(|p| p.throttle.as_ref())
+            .chain(&self.inner.throttle)
+            .map(|throttle| throttle.reserve(now))
+            .max()
+            .unwrap_or_default();
+        if !wait.is_zero() {
+            runtime::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use crate::rest::throttle::Throttle;
+    use crate::rest::RESTClient;
+
+    #[test]
+    fn test_reserve() {
+        let throttle = Throttle::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert_eq!(throttle.reserve(start), Duration::ZERO);
+        assert_eq!(throttle.reserve(start), Duration::ZERO);
+        assert_eq!(throttle.reserve(start), Duration::from_secs(30));
+        assert_eq!(throttle.reserve(start), Duration::from_secs(60));
+
+        // The bucket refills one request every 30 seconds, but never holds
+        // more than 2.
+        let later = start + Duration::from_secs(300);
+        assert_eq!(throttle.reserve(later), Duration::ZERO);
+        assert_eq!(throttle.reserve(later), Duration::ZERO);
+        assert_eq!(throttle.reserve(later), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_throttle_is_shared_by_clones() {
+        tokio_test::block_on(async {
+            let client = RESTClient::builder()
+                .auth_key("test")
+                .throttle(1, Duration::from_millis(100))
+                .build()
+                .unwrap();
+            let clone = client.clone();
+
+            let start = Instant::now();
+            client.throttle().await;
+            assert!(start.elapsed() < Duration::from_millis(50));
+            clone.throttle().await;
+            assert!(start.elapsed() >= Duration::from_millis(90));
+        });
+    }
+}
@@ -0,0 +1,182 @@
+//! Reconciliation of today's bar between the snapshot and aggregates
+//! endpoints.
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::calendar;
+use crate::error::Error;
+use crate::rest::RESTClient;
+use crate::runtime::{SystemTime, UNIX_EPOCH};
+use crate::ticker::StockTicker;
+use crate::types::*;
+
+impl RESTClient {
+    /// Get today's bar for a stock, reconciling the ticker snapshot with the
+    /// aggregates endpoint.
+    ///
+    /// The `day` bar of the [/v2/snapshot/locale/us/markets/stocks/tickers](https://polygon.io/docs/get_v2_snapshot_locale_us_markets_stocks_tickers_anchor)
+    /// API is updated as trades happen, while the daily bar of the
+    /// [/v2/aggs/ticker/{stocks_ticker}/range/1/day/{date}/{date}](https://polygon.io/docs/get_v2_aggs_ticker__stocksTicker__range__multiplier___timespan___from___to__anchor)
+    /// API can lag behind it during the day, so the two often disagree. Both
+    /// are requested concurrently and the bar with the larger volume, which
+    /// has seen more of the day's trades, is returned. When both have the
+    /// same volume, the aggregates bar is preferred since it includes the
+    /// start time and number of transactions. The result is flagged as
+    /// [`stale`](TodayBar::stale) when the other source is missing the bar or
+    /// has a different volume.
+    ///
+    /// Today is the current date in US Eastern time. A snapshot that was last
+    /// updated on an earlier day, such as before the first trade of the
+    /// morning, is ignored. Returns `None` if neither source has a bar for
+    /// today, such as on a weekend.
+    pub async fn today_bar(
+        &self,
+        stocks_ticker: impl Into<StockTicker>,
+    ) -> Result<Option<TodayBar>, Error> {
+        let stocks_ticker: StockTicker = stocks_ticker.into();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        match calendar::eastern_date(now) {
+            Some(date) => self.day_bar(&stocks_ticker, date).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn day_bar(
+        &self,
+        stocks_ticker: &StockTicker,
+        date: NaiveDate,
+    ) -> Result<Option<TodayBar>, Error> {
+        let mut snapshot_params = HashMap::new();
+        snapshot_params.insert("tickers", stocks_ticker.as_str());
+        let mut aggregates_params = HashMap::new();
+        aggregates_params.insert("adjusted", "false");
+        let day = date.format("%Y-%m-%d").to_string();
+
+        let (snapshot, aggregates) = futures::try_join!(
+            self.stock_equities_snapshot_all_tickers("us", &snapshot_params),
+            self.stock_equities_aggregates(
+                stocks_ticker,
+                1,
+                Timespan::Day,
+                &day,
+                &day,
+                &aggregates_params,
+            ),
+        )?;
+
+        // The snapshot's `updated` time is in Unix nanoseconds.
+        let snapshot = snapshot
+            .tickers
+            .into_iter()
+            .find(|t| t.ticker == stocks_ticker.as_str())
+            .filter(|t| calendar::eastern_date(t.updated / 1_000_000) == Some(date))
+            .map(|t| t.day)
+            .filter(|bar| bar.v > 0.0);
+        let aggregate = aggregates.results.into_iter().next();
+
+        let (bar, source, stale) = match (&snapshot, &aggregate) {
+            (Some(s), Some(a)) if s.v > a.v => (s.clone(), TodayBarSource::Snapshot, true),
+            (Some(s), Some(a)) => (a.clone(), TodayBarSource::Aggregates, s.v != a.v),
+            (Some(s), None) => (s.clone(), TodayBarSource::Snapshot, true),
+            (None, Some(a)) => (a.clone(), TodayBarSource::Aggregates, true),
+            (None, None) => return Ok(None),
+        };
+
+        Ok(Some(TodayBar {
+            date,
+            bar,
+            source,
+            stale,
+            snapshot,
+            aggregate,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ticker::StockTicker;
+    use chrono::NaiveDate;
+
+    use crate::rest::test_server::{self, Response, TestServer};
+    use crate::types::TodayBarSource;
+
+    fn snapshot(volume: u64, updated: u64) -> Response {
+        Response::ok(format!(
+            r#"{{"status":"OK","count":1,"tickers":[{{"ticker":"MSFT",
+                "day":{{"o":370,"h":372,"l":369,"c":371.5,"v":{},"vw":370.9}},
+                "lastQuote":{{"P":371.6,"S":2,"p":371.5,"s":3,"t":1704301200000000000}},
+                "lastTrade":{{"p":371.5,"s":100,"t":1704301200000000000,"x":4}},
+                "prevDay":{{"o":368,"h":371,"l":367,"c":370,"v":20000000,"vw":369.5}},
+                "todaysChange":1.5,"todaysChangePerc":0.4,"updated":{}}}]}}"#,
+            volume, updated
+        ))
+        .on_path("/v2/snapshot")
+    }
+
+    fn aggregates(volume: u64) -> Response {
+        Response::ok(format!(
+            r#"{{"ticker":"MSFT","status":"OK","queryCount":1,"resultsCount":1,"adjusted":false,
+                "results":[{{"v":{},"vw":370.8,"o":370,"c":371,"h":372,"l":369,"t":1704258000000,"n":1200}}],
+                "request_id":"1"}}"#,
+            volume
+        ))
+        .on_path("/v2/aggs")
+    }
+
+    #[test]
+    fn test_today_bar() {
+        tokio_test::block_on(async {
+            // 2024-01-03 at 12:00 Eastern time.
+            let updated = 1704301200000000000;
+            let server = TestServer::start(vec![
+                snapshot(150000, updated),
+                aggregates(120000),
+                snapshot(120000, updated),
+                aggregates(120000),
+                // A snapshot from the previous day is ignored.
+                snapshot(900000, updated - 86_400_000_000_000),
+                aggregates(120000),
+            ])
+            .await;
+            let client = test_server::client(&server);
+            let date = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+
+            let today = client
+                .day_bar(&StockTicker::new("MSFT").unwrap(), date)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(today.source, TodayBarSource::Snapshot);
+            assert!(today.stale);
+            assert_eq!(today.bar.c, 371.5);
+            assert_eq!(today.aggregate.unwrap().v, 120000.0);
+
+            let today = client
+                .day_bar(&StockTicker::new("MSFT").unwrap(), date)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(today.source, TodayBarSource::Aggregates);
+            assert!(!today.stale);
+            assert_eq!(today.bar.n, Some(1200.0));
+
+            let today = client
+                .day_bar(&StockTicker::new("MSFT").unwrap(), date)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(today.source, TodayBarSource::Aggregates);
+            assert!(today.stale);
+            assert!(today.snapshot.is_none());
+
+            assert!(server.requests().contains(&String::from(
+                "/v2/aggs/ticker/MSFT/range/1/day/2024-01-03/2024-01-03?adjusted=false"
+            )));
+        });
+    }
+}
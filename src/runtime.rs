@@ -0,0 +1,70 @@
+//! Timers and clocks that work both natively and on `wasm32`.
+//!
+//! Natively these come from tokio and the standard library. On
+//! `wasm32-unknown-unknown` the standard library has no clock and tokio no
+//! timer, so they are backed by the browser instead.
+// Not every combination of features uses every item.
+#![allow(dead_code, unused_imports)]
+
+#[cfg(target_arch = "wasm32")]
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use tokio::time::{interval, sleep, Instant, Interval};
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Waits until `duration` has elapsed.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await
+}
+
+/// Ticks at a fixed period, starting immediately, like
+/// [`tokio::time::Interval`].
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct Interval {
+    period: Duration,
+    next: Instant,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Interval {
+    /// Waits until the next tick.
+    pub(crate) async fn tick(&mut self) -> Instant {
+        let now = Instant::now();
+        if self.next > now {
+            sleep(self.next - now).await;
+        }
+        let tick = self.next;
+        self.next += self.period;
+        tick
+    }
+}
+
+/// Returns an interval whose first tick completes immediately. Unlike
+/// tokio's, a zero `period` ticks every millisecond instead of panicking.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn interval(period: Duration) -> Interval {
+    Interval {
+        period: period.max(Duration::from_millis(1)),
+        next: Instant::now(),
+    }
+}
+
+/// Returns `true` if a request failed because no connection could be made
+/// to the server. The browser does not tell such failures apart from other
+/// transport errors, so on `wasm32` this is never the case.
+#[cfg(feature = "rest")]
+pub(crate) fn is_connect(err: &reqwest::Error) -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    return err.is_connect();
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = err;
+        false
+    }
+}
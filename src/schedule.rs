@@ -0,0 +1,184 @@
+//! Periodic polling aligned to wall-clock boundaries.
+//!
+//! Polling a snapshot endpoint with a fixed interval drifts: each poll starts
+//! a little later than the last by however long the previous one took, so the
+//! polled series stops lining up with the minute bars it is compared with. A
+//! [`Schedule`] instead fires on multiples of its period since the Unix
+//! epoch, such as every minute at `:00` seconds or every five minutes at
+//! `:00`, `:05`, and so on, and reports the boundary each tick belongs to.
+//!
+//! An [`offset`](Schedule::offset) moves every tick after its boundary, such
+//! as to give the last bar time to be published, and a
+//! [`jitter`](Schedule::jitter) adds a random delay so that many clients on
+//! the same schedule do not poll at the same instant. Neither changes the
+//! boundary that is reported.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::collections::HashMap;
+//! use std::time::Duration;
+//!
+//! use polygon_client::rest::RESTClient;
+//! use polygon_client::schedule::Schedule;
+//!
+//! # async fn run() -> Result<(), polygon_client::Error> {
+//! let client = RESTClient::try_new(None, None).expect("failed to create client");
+//! // Poll through a partition so that slow polls cannot pile up and use the
+//! // budget of other requests.
+//! let poller = client.partition(1)?;
+//! let mut schedule = Schedule::every_minute()
+//!     .offset(Duration::from_secs(2))
+//!     .jitter(Duration::from_millis(500));
+//!
+//! loop {
+//!     let minute = schedule.tick().await;
+//!     let snapshot = poller
+//!         .stock_equities_snapshot_all_tickers("us", &HashMap::new())
+//!         .await?;
+//!     println!("{}: {} tickers", minute, snapshot.tickers.len());
+//! }
+//! # }
+//! ```
+use std::time::Duration;
+
+use crate::runtime::{self, SystemTime, UNIX_EPOCH};
+
+/// Fires on multiples of a period since the Unix epoch.
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    period_ms: u64,
+    offset_ms: u64,
+    jitter_ms: u64,
+    last: Option<u64>,
+    rng: u64,
+}
+
+impl Schedule {
+    /// Returns a schedule that fires every `period`, rounded down to whole
+    /// milliseconds. Periods that divide a day, such as one minute or five
+    /// minutes, fire at the same times of day in every time zone with a
+    /// whole-hour offset from UTC, including US Eastern time. A period
+    /// shorter than a millisecond fires every millisecond.
+    pub fn every(period: Duration) -> Self {
+        let period_ms = (period.as_millis() as u64).max(1);
+
+        let seed = now_ms().wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ period_ms;
+        Schedule {
+            period_ms,
+            offset_ms: 0,
+            jitter_ms: 0,
+            last: None,
+            rng: seed | 1,
+        }
+    }
+
+    /// Returns a schedule that fires at the start of every minute.
+    pub fn every_minute() -> Self {
+        Schedule::every(Duration::from_secs(60))
+    }
+
+    /// Returns a schedule that fires at the start of every five minutes.
+    pub fn every_five_minutes() -> Self {
+        Schedule::every(Duration::from_secs(300))
+    }
+
+    /// Fires each tick `offset` after its boundary.
+    pub fn offset(mut self, offset: Duration) -> Self {
+        self.offset_ms = offset.as_millis() as u64;
+        self
+    }
+
+    /// Delays each tick by a random amount of time less than `jitter`.
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter_ms = jitter.as_millis() as u64;
+        self
+    }
+
+    /// Returns the first boundary, in Unix milliseconds, whose tick is not
+    /// before `now_ms` and that has not already been returned by
+    /// [`tick()`](Schedule::tick).
+    pub fn next_boundary(&self, now_ms: u64) -> u64 {
+        let since = now_ms.saturating_sub(self.offset_ms);
+        let boundary = since.div_ceil(self.period_ms) * self.period_ms;
+        match self.last {
+            Some(last) if boundary <= last => last + self.period_ms,
+            _ => boundary,
+        }
+    }
+
+    /// Waits until the next tick and returns its boundary in Unix
+    /// milliseconds.
+    ///
+    /// Boundaries whose tick was missed, such as while the previous poll was
+    /// still running, are skipped rather than fired late one after another.
+    pub async fn tick(&mut self) -> u64 {
+        let boundary = self.next_boundary(now_ms());
+        let fire_at = boundary + self.offset_ms + self.next_jitter();
+
+        let wait = fire_at.saturating_sub(now_ms());
+        if wait > 0 {
+            runtime::sleep(Duration::from_millis(wait)).await;
+        }
+
+        self.last = Some(boundary);
+        boundary
+    }
+
+    fn next_jitter(&mut self) -> u64 {
+        if self.jitter_ms == 0 {
+            return 0;
+        }
+
+        // xorshift64 is plenty to spread clients apart.
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng % self.jitter_ms
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::schedule::{now_ms, Schedule};
+
+    #[test]
+    fn test_next_boundary() {
+        let schedule = Schedule::every_five_minutes();
+        // 2024-01-03 at 10:02:30 and 10:05:00 Eastern time.
+        assert_eq!(schedule.next_boundary(1704294150000), 1704294300000);
+        assert_eq!(schedule.next_boundary(1704294300000), 1704294300000);
+
+        let mut schedule = Schedule::every_minute().offset(Duration::from_secs(2));
+        // A tick 1s after the boundary is still ahead, 3s after it is missed.
+        assert_eq!(schedule.next_boundary(1704294301000), 1704294300000);
+        assert_eq!(schedule.next_boundary(1704294303000), 1704294360000);
+
+        schedule.last = Some(1704294300000);
+        assert_eq!(schedule.next_boundary(1704294299000), 1704294360000);
+    }
+
+    #[test]
+    fn test_tick() {
+        tokio_test::block_on(async {
+            let mut schedule =
+                Schedule::every(Duration::from_millis(20)).jitter(Duration::from_millis(5));
+
+            let first = schedule.tick().await;
+            let second = schedule.tick().await;
+            assert_eq!(first % 20, 0);
+            assert_eq!(second % 20, 0);
+            assert!(second > first);
+            assert!(now_ms() >= second);
+        });
+    }
+}
@@ -0,0 +1,133 @@
+//! Keeping the API key out of logs.
+//!
+//! The API key is held in an [`ApiKey`], whose `Debug` and `Display` output
+//! leaves out the key, so that clients, builders, and their configuration can
+//! be logged as usual. Its value is only given out by [`ApiKey::expose()`].
+//! Request URLs that carry the key as the `apiKey` query parameter are
+//! redacted the same way before they appear in an error.
+use std::fmt;
+
+#[cfg(feature = "rest")]
+use url::Url;
+
+/// What is shown instead of a secret.
+const REDACTED: &str = "***";
+
+/// The name of the query parameter that carries the API key.
+#[cfg(feature = "rest")]
+pub(crate) const API_KEY_PARAM: &str = "apiKey";
+
+/// A polygon.io API key that is redacted when formatted.
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::secret::ApiKey;
+///
+/// let key = ApiKey::from("my-api-key");
+/// assert_eq!(format!("{:?}", key), "ApiKey(***)");
+/// assert_eq!(key.to_string(), "***");
+/// assert_eq!(key.expose(), "my-api-key");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    /// Returns the API key itself, such as to send it elsewhere.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        ApiKey(key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        ApiKey(String::from(key))
+    }
+}
+
+impl PartialEq<str> for ApiKey {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for ApiKey {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKey({})", REDACTED)
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Replaces the value of the `apiKey` query parameter of `url`, if any.
+#[cfg(feature = "rest")]
+pub(crate) fn redact_url(url: &mut Url) {
+    if !url.query_pairs().any(|(name, _)| name == API_KEY_PARAM) {
+        return;
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if name == API_KEY_PARAM {
+                String::from(REDACTED)
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::secret::ApiKey;
+
+    #[test]
+    fn test_api_key_is_redacted() {
+        let key = ApiKey::from("secret");
+        assert_eq!(format!("{:?}", Some(&key)), "Some(ApiKey(***))");
+        assert_eq!(format!("{}", key), "***");
+        assert_eq!(key, "secret");
+        assert_eq!(key.expose(), "secret");
+    }
+
+    #[test]
+    #[cfg(feature = "rest")]
+    fn test_redact_url() {
+        use url::Url;
+
+        use crate::secret::redact_url;
+
+        let mut url =
+            Url::parse("https://api.polygon.io/v3/trades/MSFT?limit=10&apiKey=secret").unwrap();
+        redact_url(&mut url);
+        assert_eq!(
+            url.as_str(),
+            "https://api.polygon.io/v3/trades/MSFT?limit=10&apiKey=***"
+        );
+
+        let mut url = Url::parse("https://api.polygon.io/v3/trades/MSFT?limit=10").unwrap();
+        redact_url(&mut url);
+        assert_eq!(
+            url.as_str(),
+            "https://api.polygon.io/v3/trades/MSFT?limit=10"
+        );
+    }
+}
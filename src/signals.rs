@@ -0,0 +1,241 @@
+//! Trading signals computed from minute bars.
+//!
+//! A [`SignalPipeline`] keeps a rolling [`BarWindow`] of the most recent
+//! minute bars of each ticker and evaluates a set of named indicators on the
+//! window every time a bar arrives. Each indicator that produces a value
+//! emits a [`Signal`].
+//!
+//! The pipeline can be fed bars by hand with [`SignalPipeline::process()`],
+//! applied to any stream of events, or, with the `websocket` feature, run
+//! directly on a [`Dispatcher`](crate::websocket::dispatcher::Dispatcher)
+//! that it subscribes to minute aggregates.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use polygon_client::indicators;
+//! use polygon_client::signals::SignalPipeline;
+//! use polygon_client::websocket::dispatcher::Dispatcher;
+//! use polygon_client::websocket::STOCKS_CLUSTER;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let pipeline = SignalPipeline::new(20)
+//!         .indicator("sma20", |w| indicators::sma(&w.closes(), 20))
+//!         .indicator("roc5", |w| indicators::rate_of_change(&w.closes(), 5));
+//!
+//!     let dispatcher = Dispatcher::connect(STOCKS_CLUSTER, None).await.unwrap();
+//!     let signals = pipeline.run(dispatcher, &["MSFT", "AAPL"]).await.unwrap();
+//!     let mut signals = Box::pin(signals);
+//!     while let Some(signal) = signals.next().await {
+//!         let signal = signal.unwrap();
+//!         println!("{} {} = {}", signal.ticker, signal.name, signal.value);
+//!     }
+//! }
+//! ```
+use std::sync::Arc;
+
+use futures::{Stream, StreamExt};
+
+use crate::aggregation::{BarWindow, BarWindows};
+use crate::error::Error;
+use crate::events::{BarEvent, PolygonEvent};
+
+/// The value of an indicator for a ticker at the end of a bar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signal {
+    pub ticker: String,
+    /// The name the indicator was registered with.
+    pub name: Arc<str>,
+    pub value: f64,
+    /// The end of the bar the value was computed on, in Unix milliseconds.
+    pub timestamp: u64,
+}
+
+type Indicator = Box<dyn Fn(&BarWindow) -> Option<f64> + Send + Sync>;
+
+/// Computes signals from the minute bars of any number of tickers.
+pub struct SignalPipeline {
+    windows: BarWindows,
+    indicators: Vec<(Arc<str>, Indicator)>,
+}
+
+// Ensure the pipeline can be moved into a task.
+assert_send_sync!(SignalPipeline);
+
+impl SignalPipeline {
+    /// Returns a pipeline without indicators that keeps the last `window`
+    /// bars of each ticker. A window of zero keeps the last bar.
+    pub fn new(window: usize) -> Self {
+        SignalPipeline {
+            windows: BarWindows::new(window),
+            indicators: Vec::new(),
+        }
+    }
+
+    /// Adds an indicator that is evaluated on the window of a ticker after
+    /// each of its bars. Returning `None`, such as while the window is still
+    /// filling up, emits no signal.
+    pub fn indicator<F>(mut self, name: &str, indicator: F) -> Self
+    where
+        F: Fn(&BarWindow) -> Option<f64> + Send + Sync + 'static,
+    {
+        self.indicators.push((Arc::from(name), Box::new(indicator)));
+        self
+    }
+
+    /// Adds a bar to the window of its ticker and returns the signals of
+    /// every indicator, in the order they were added.
+    ///
+    /// Bars older than the latest bar of the ticker are ignored and produce
+    /// no signals.
+    pub fn process(&mut self, bar: BarEvent) -> Vec<Signal> {
+        let window = match self.windows.push(bar) {
+            Some(window) => window,
+            None => return Vec::new(),
+        };
+        let last = match window.last() {
+            Some(last) => last,
+            None => return Vec::new(),
+        };
+
+        self.indicators
+            .iter()
+            .filter_map(|(name, indicator)| {
+                Some(Signal {
+                    ticker: last.symbol.clone(),
+                    name: name.clone(),
+                    value: indicator(window)?,
+                    timestamp: last.end_timestamp,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the window of a ticker.
+    pub fn window(&self, ticker: &str) -> Option<&BarWindow> {
+        self.windows.get(ticker)
+    }
+
+    /// Converts a stream of events into a stream of signals.
+    ///
+    /// Minute bars are processed and all other events are ignored. Errors in
+    /// the event stream are passed through.
+    pub fn signals<E>(mut self, events: E) -> impl Stream<Item = Result<Signal, Error>>
+    where
+        E: Stream<Item = Result<PolygonEvent, Error>>,
+    {
+        events
+            .map(move |event| match event {
+                Ok(PolygonEvent::MinuteBar(bar)) => self.process(bar).into_iter().map(Ok).collect(),
+                Ok(_) => Vec::new(),
+                Err(e) => vec![Err(e)],
+            })
+            .flat_map(futures::stream::iter)
+    }
+
+    /// Subscribes a dispatcher to the minute aggregates of `tickers` and
+    /// returns the stream of signals computed from them.
+    #[cfg(feature = "websocket")]
+    pub async fn run<S: AsRef<str>>(
+        self,
+        mut dispatcher: crate::websocket::dispatcher::Dispatcher,
+        tickers: &[S],
+    ) -> Result<impl Stream<Item = Result<Signal, Error>> + Send, Error> {
+        let channels: Vec<_> = tickers
+            .iter()
+            .map(|t| crate::websocket::Channel::minute_aggregates(t.as_ref()))
+            .collect();
+        dispatcher.subscribe(&channels).await?;
+
+        Ok(self.signals(dispatcher.into_stream()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use crate::events::{parse_events, ParseOptions, PolygonEvent};
+    use crate::indicators;
+    use crate::signals::SignalPipeline;
+
+    fn pipeline() -> SignalPipeline {
+        SignalPipeline::new(2)
+            .indicator("sma2", |w| indicators::sma(&w.closes(), 2))
+            .indicator("close", |w| w.last().map(|b| b.close))
+    }
+
+    const BARS: &str = r#"[
+        {"ev":"AM","sym":"MSFT","v":100,"o":1.0,"c":2.0,"h":2.0,"l":1.0,"s":0,"e":60000},
+        {"ev":"T","sym":"MSFT","i":"1","x":4,"p":114.125,"s":100,"t":1536068000000},
+        {"ev":"AM","sym":"AAPL","v":100,"o":1.0,"c":5.0,"h":5.0,"l":1.0,"s":0,"e":60000},
+        {"ev":"AM","sym":"MSFT","v":100,"o":2.0,"c":4.0,"h":4.0,"l":2.0,"s":60000,"e":120000}]"#;
+
+    #[test]
+    fn test_signals() {
+        tokio_test::block_on(async {
+            let events = parse_events(BARS, ParseOptions::default()).unwrap();
+            let signals: Vec<_> = pipeline()
+                .signals(futures::stream::iter(events.into_iter().map(Ok)))
+                .map(|s| s.unwrap())
+                .map(|s| (s.ticker, s.name.to_string(), s.value, s.timestamp))
+                .collect()
+                .await;
+
+            assert_eq!(
+                signals,
+                vec![
+                    (String::from("MSFT"), String::from("close"), 2.0, 60000),
+                    (String::from("AAPL"), String::from("close"), 5.0, 60000),
+                    (String::from("MSFT"), String::from("sma2"), 3.0, 120000),
+                    (String::from("MSFT"), String::from("close"), 4.0, 120000),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_late_bar_emits_no_signals() {
+        let mut pipeline = pipeline();
+        for event in parse_events(BARS, ParseOptions::default()).unwrap() {
+            if let PolygonEvent::MinuteBar(bar) = event {
+                pipeline.process(bar);
+            }
+        }
+
+        let late =
+            r#"[{"ev":"AM","sym":"MSFT","v":1,"o":9.0,"c":9.0,"h":9.0,"l":9.0,"s":0,"e":60000}]"#;
+        match parse_events(late, ParseOptions::default())
+            .unwrap()
+            .remove(0)
+        {
+            PolygonEvent::MinuteBar(bar) => assert!(pipeline.process(bar).is_empty()),
+            e => panic!("expected a bar, got {:?}", e),
+        }
+        assert_eq!(pipeline.window("MSFT").unwrap().closes(), vec![2.0, 4.0]);
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn test_run() {
+        use crate::websocket::dispatcher::Dispatcher;
+        use crate::websocket::test_server::TestServer;
+
+        tokio_test::block_on(async {
+            let server = TestServer::start(2, vec![String::from(BARS)]).await;
+            let dispatcher = Dispatcher::connect_url(&server.url(), "test")
+                .await
+                .unwrap();
+
+            let signals = pipeline().run(dispatcher, &["MSFT", "AAPL"]).await.unwrap();
+            let signals: Vec<_> = signals.collect().await;
+            assert_eq!(signals.len(), 4);
+            assert_eq!(
+                server.received()[1],
+                r#"{"action":"subscribe","params":"AM.MSFT,AM.AAPL"}"#
+            );
+        });
+    }
+}
@@ -0,0 +1,441 @@
+//! Durable capture of events as JSON Lines files.
+//!
+//! A [`JsonlSink`] writes each value, such as a [`PolygonEvent`] or a
+//! [`BarEvent`](crate::events::BarEvent), as one line of JSON. Files are
+//! rotated once they reach a maximum size or age, and can be compressed with
+//! gzip, so a process can capture a stream for days without a separate
+//! message broker. Events are written in the same form as the streaming
+//! messages they were parsed from, so the files can be read back with
+//! `serde_json`.
+//!
+//! The sink is enabled by the `jsonl-sink` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use polygon_client::sink::JsonlSink;
+//! use polygon_client::websocket::dispatcher::Dispatcher;
+//! use polygon_client::websocket::{Channel, STOCKS_CLUSTER};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut dispatcher = Dispatcher::connect(STOCKS_CLUSTER, None).await.unwrap();
+//!     dispatcher.subscribe(&[Channel::trades("MSFT")]).await.unwrap();
+//!
+//!     let mut sink = JsonlSink::new("/var/lib/polygon/capture", "trades")
+//!         .max_file_size(256 * 1024 * 1024)
+//!         .max_file_age(Duration::from_secs(3600))
+//!         .gzip(true);
+//!     sink.write_stream(dispatcher.into_stream()).await.unwrap();
+//! }
+//! ```
+//!
+//! # File names
+//!
+//! Each file is named `{prefix}-{millis}-{sequence}.jsonl`, with a `.gz`
+//! extension when compressed, where `millis` is the time the file was opened
+//! in Unix milliseconds and `sequence` counts the files opened by the sink.
+//! The names therefore sort in the order the files were written.
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use crate::error::Error;
+#[cfg(doc)]
+use crate::events::PolygonEvent;
+
+/// The largest number of values that [`JsonlSink::write_stream()`] hands to
+/// the blocking thread pool at once.
+const WRITE_BATCH: usize = 1024;
+
+/// A writer of JSON Lines files that rotates the file it writes to.
+#[derive(Debug)]
+pub struct JsonlSink {
+    dir: PathBuf,
+    prefix: String,
+    max_file_size: Option<u64>,
+    max_file_age: Option<Duration>,
+    gzip: bool,
+    sequence: u64,
+    current: Option<OpenFile>,
+}
+
+// Ensure the sink can be moved into a task.
+assert_send_sync!(JsonlSink);
+
+/// The file that lines are currently written to.
+#[derive(Debug)]
+struct OpenFile {
+    path: PathBuf,
+    output: Output,
+    opened: Instant,
+    /// The number of uncompressed bytes written to the file.
+    written: u64,
+}
+
+#[derive(Debug)]
+enum Output {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Output {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Output::Plain(w) => w,
+            Output::Gzip(w) => w,
+        }
+    }
+
+    /// Flushes buffered lines and, for gzip, writes the trailer.
+    fn finish(self) -> io::Result<()> {
+        let mut file = match self {
+            Output::Plain(w) => w.into_inner().map_err(|e| e.into_error())?,
+            Output::Gzip(w) => w.finish()?.into_inner().map_err(|e| e.into_error())?,
+        };
+        file.flush()?;
+        file.sync_all()
+    }
+}
+
+impl JsonlSink {
+    /// Returns a sink that writes files named after `prefix` into `dir`. The
+    /// directory is created when the first value is written.
+    ///
+    /// By default, files are not compressed and are never rotated.
+    pub fn new<P: Into<PathBuf>>(dir: P, prefix: &str) -> Self {
+        JsonlSink {
+            dir: dir.into(),
+            prefix: String::from(prefix),
+            max_file_size: None,
+            max_file_age: None,
+            gzip: false,
+            sequence: 0,
+            current: None,
+        }
+    }
+
+    /// Starts a new file once the current file holds at least `bytes` bytes
+    /// of JSON, counted before compression.
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Starts a new file once the current file has been open for at least
+    /// `age`. The age is checked when a value is written, so a file is not
+    /// rotated while nothing is written to it.
+    pub fn max_file_age(mut self, age: Duration) -> Self {
+        self.max_file_age = Some(age);
+        self
+    }
+
+    /// Sets whether files are compressed with gzip.
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    /// Returns the path of the file currently written to, if any.
+    pub fn current_path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|f| f.path.as_path())
+    }
+
+    /// Writes a value as a single line, rotating the file first if it has
+    /// reached its maximum size or age.
+    pub fn write<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.write_line(&to_line(value)?)
+    }
+
+    /// Writes a serialized line, rotating the file first if it has reached
+    /// its maximum size or age.
+    fn write_line(&mut self, line: &[u8]) -> Result<(), Error> {
+        if self.should_rotate() {
+            self.rotate()?;
+        }
+
+        let mut file = match self.current.take() {
+            Some(file) => file,
+            None => self.open()?,
+        };
+        let res = file.output.writer().write_all(line);
+        file.written += line.len() as u64;
+        self.current = Some(file);
+        Ok(res?)
+    }
+
+    /// Writes every value of a stream, such as the events returned by
+    /// [`Dispatcher::into_stream()`](crate::websocket::dispatcher::Dispatcher::into_stream),
+    /// and returns the number of values written once the stream ends.
+    ///
+    /// The values are serialized as they arrive and written to the files on
+    /// tokio's blocking thread pool, so that the file I/O does not hold up
+    /// other tasks. The current file is flushed when the stream ends. The
+    /// first error from the stream or from writing ends the capture and is
+    /// returned.
+    pub async fn write_stream<S, T>(&mut self, stream: S) -> Result<u64, Error>
+    where
+        S: Stream<Item = Result<T, Error>>,
+        T: Serialize,
+    {
+        let chunks = stream.ready_chunks(WRITE_BATCH);
+        futures::pin_mut!(chunks);
+        let mut count = 0;
+        while let Some(chunk) = chunks.next().await {
+            let mut lines = Vec::with_capacity(chunk.len());
+            let mut stream_error = None;
+            for value in chunk {
+                match value {
+                    Ok(value) => lines.push(to_line(&value)?),
+                    Err(e) => {
+                        stream_error = Some(e);
+                        break;
+                    }
+                }
+            }
+
+            let written = lines.len() as u64;
+            self.blocking(move |sink| lines.iter().try_for_each(|line| sink.write_line(line)))
+                .await?;
+            count += written;
+            if let Some(e) = stream_error {
+                return Err(e);
+            }
+        }
+        self.blocking(JsonlSink::flush).await?;
+        Ok(count)
+    }
+
+    /// Runs `f` on the sink on the blocking thread pool.
+    ///
+    /// If the returned future is dropped before `f` completes, the file it
+    /// writes to is completed once `f` returns, and this sink goes on with a
+    /// new file.
+    async fn blocking<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut JsonlSink) -> Result<(), Error> + Send + 'static,
+    {
+        let detached = JsonlSink {
+            dir: self.dir.clone(),
+            prefix: self.prefix.clone(),
+            max_file_size: self.max_file_size,
+            max_file_age: self.max_file_age,
+            gzip: self.gzip,
+            sequence: self.sequence,
+            current: None,
+        };
+        let mut sink = mem::replace(self, detached);
+        let task = tokio::task::spawn_blocking(move || {
+            let res = f(&mut sink);
+            (sink, res)
+        });
+        let (sink, res) = match task.await {
+            Ok(done) => done,
+            Err(e) => match e.try_into_panic() {
+                Ok(panic) => std::panic::resume_unwind(panic),
+                // The runtime is shutting down and dropped the task along
+                // with the open file, so the next write starts a new one.
+                Err(e) => return Err(Error::Io(io::Error::other(e))),
+            },
+        };
+        *self = sink;
+        res
+    }
+
+    /// Flushes buffered lines to the current file.
+    ///
+    /// Compressed data is only readable once the file is completed by
+    /// [`JsonlSink::rotate()`] or [`JsonlSink::close()`].
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if let Some(file) = &mut self.current {
+            file.output.writer().flush()?;
+        }
+        Ok(())
+    }
+
+    /// Completes the current file. The next value is written to a new file.
+    pub fn rotate(&mut self) -> Result<(), Error> {
+        if let Some(file) = self.current.take() {
+            file.output.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Completes the current file and consumes the sink.
+    ///
+    /// Dropping the sink also completes the current file, but ignores any
+    /// error in doing so.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.rotate()
+    }
+
+    fn should_rotate(&self) -> bool {
+        let file = match &self.current {
+            Some(file) => file,
+            None => return false,
+        };
+
+        self.max_file_size.is_some_and(|max| file.written >= max)
+            || self
+                .max_file_age
+                .is_some_and(|max| file.opened.elapsed() >= max)
+    }
+
+    fn open(&mut self) -> Result<OpenFile, Error> {
+        fs::create_dir_all(&self.dir)?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let extension = if self.gzip { "jsonl.gz" } else { "jsonl" };
+        let name = format!(
+            "{}-{}-{:06}.{}",
+            self.prefix, millis, self.sequence, extension
+        );
+        self.sequence += 1;
+
+        let path = self.dir.join(name);
+        let writer = BufWriter::new(File::create(&path)?);
+        let output = if self.gzip {
+            Output::Gzip(GzEncoder::new(writer, Compression::default()))
+        } else {
+            Output::Plain(writer)
+        };
+
+        Ok(OpenFile {
+            path,
+            output,
+            opened: Instant::now(),
+            written: 0,
+        })
+    }
+}
+
+/// Serializes a value as a line of JSON.
+fn to_line<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut line = serde_json::to_vec(value).map_err(io::Error::from)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+impl Drop for JsonlSink {
+    fn drop(&mut self) {
+        let _ = self.rotate();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::events::{BarEvent, PolygonEvent, TradeEvent};
+    use crate::sink::JsonlSink;
+    use flate2::read::GzDecoder;
+    use std::fs;
+    use std::io::Read;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "polygon-client-sink-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn files(dir: &PathBuf) -> Vec<PathBuf> {
+        let mut files: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_rotate_by_size() {
+        let dir = temp_dir("size");
+        let mut sink = JsonlSink::new(&dir, "trades").max_file_size(1);
+
+        for price in &[1.0, 2.0, 3.0] {
+            let trade = PolygonEvent::Trade(TradeEvent::new("MSFT", *price, 100.0, 1));
+            sink.write(&trade).unwrap();
+        }
+        sink.close().unwrap();
+
+        let files = files(&dir);
+        assert_eq!(files.len(), 3);
+        let name = files[0].file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("trades-"));
+        assert!(name.ends_with("-000000.jsonl"));
+
+        let line = fs::read_to_string(&files[2]).unwrap();
+        let event: PolygonEvent = serde_json::from_str(line.trim_end()).unwrap();
+        assert_eq!(event.symbol(), "MSFT");
+        assert!(matches!(event, PolygonEvent::Trade(t) if t.price == 3.0));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_stream_gzip() {
+        let dir = temp_dir("gzip");
+        let mut sink = JsonlSink::new(&dir, "bars")
+            .max_file_age(Duration::from_secs(3600))
+            .gzip(true);
+
+        let bars = (0..3).map(|i| Ok(BarEvent::new("MSFT", i * 60_000, (i + 1) * 60_000)));
+        let count = tokio_test::block_on(sink.write_stream(futures::stream::iter(bars))).unwrap();
+        assert_eq!(count, 3);
+        let path = sink.current_path().unwrap().to_path_buf();
+        assert!(path.to_str().unwrap().ends_with(".jsonl.gz"));
+        drop(sink);
+
+        let mut text = String::new();
+        GzDecoder::new(fs::File::open(&path).unwrap())
+            .read_to_string(&mut text)
+            .unwrap();
+        let bars: Vec<BarEvent> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(bars.len(), 3);
+        assert_eq!(bars[2].start_timestamp, 120_000);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_stream_stops_at_error() {
+        let dir = temp_dir("error");
+        let mut sink = JsonlSink::new(&dir, "bars");
+
+        let values = vec![
+            Ok(BarEvent::new("MSFT", 0, 60_000)),
+            Err(Error::MissingAuthKey),
+            Ok(BarEvent::new("MSFT", 60_000, 120_000)),
+        ];
+        let res = tokio_test::block_on(sink.write_stream(futures::stream::iter(values)));
+        assert!(matches!(res, Err(Error::MissingAuthKey)));
+        sink.close().unwrap();
+
+        // The values received before the error are written.
+        let files = files(&dir);
+        assert_eq!(files.len(), 1);
+        assert_eq!(fs::read_to_string(&files[0]).unwrap().lines().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+//! Accounting of requests and WebSocket messages through the
+//! [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! When the `metrics` feature is enabled, the clients record the following
+//! metrics with whichever recorder the application installs, such as
+//! `metrics-exporter-prometheus`:
+//!
+//! * `polygon_requests_total` - a counter of REST responses by `endpoint` and
+//!   HTTP `status`
+//! * `polygon_request_errors_total` - a counter of failed REST requests by
+//!   `endpoint` and error `kind`, including requests that got no response,
+//!   with each retry of a request counted separately
+//! * `polygon_request_duration_seconds` - a histogram of the time until the
+//!   headers of each REST response arrive by `endpoint`
+//! * `polygon_response_bytes_total` - a counter of the bytes of REST response
+//!   bodies by `endpoint`
+//! * `polygon_ws_messages_total` - a counter of WebSocket messages received
+//!   by a [`Dispatcher`](crate::websocket::dispatcher::Dispatcher), whose rate
+//!   is the number of messages per second
+//! * `polygon_ws_events_total` - a counter of the events parsed from those
+//!   messages by event `type`
+//!
+//! To keep the number of series small, the `endpoint` label is the request
+//! path with its parameters, such as tickers and dates, replaced by `{}`, as
+//! returned by [`endpoint_label()`].
+#[cfg(feature = "rest")]
+use std::time::Duration;
+
+#[cfg(feature = "rest")]
+use crate::error::Error;
+#[cfg(feature = "websocket")]
+use crate::events::PolygonEvent;
+
+/// Returns the path of a request with every segment that is a parameter
+/// replaced by `{}`, such as `/v2/aggs/ticker/{}/range/{}/day/{}/{}` for a
+/// request of aggregates.
+///
+/// A segment is considered a parameter unless it contains a lowercase letter
+/// and no `:`, which holds for tickers, dates, and numbers.
+pub fn endpoint_label(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            let is_static =
+                segment.bytes().any(|b| b.is_ascii_lowercase()) && !segment.contains(':');
+            if segment.is_empty() || is_static {
+                segment
+            } else {
+                "{}"
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns the `endpoint` label of a request URL.
+#[cfg(feature = "rest")]
+fn url_label(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(url) => endpoint_label(url.path()),
+        Err(_) => String::from("{}"),
+    }
+}
+
+/// Records the status of a response to a REST request and the time it took
+/// to arrive.
+#[cfg(feature = "rest")]
+pub(crate) fn record_response(url: &str, status: u16, elapsed: Duration) {
+    let endpoint = url_label(url);
+    metrics::counter!(
+        "polygon_requests_total",
+        "endpoint" => endpoint.clone(),
+        "status" => status.to_string()
+    )
+    .increment(1);
+    metrics::histogram!("polygon_request_duration_seconds", "endpoint" => endpoint)
+        .record(elapsed.as_secs_f64());
+}
+
+/// Records the length of the body of a response to a REST request.
+#[cfg(feature = "rest")]
+pub(crate) fn record_body(url: &str, bytes: usize) {
+    metrics::counter!("polygon_response_bytes_total", "endpoint" => url_label(url))
+        .increment(bytes as u64);
+}
+
+/// Records a failed attempt at a REST request.
+#[cfg(feature = "rest")]
+pub(crate) fn record_error(url: &str, err: &Error) {
+    metrics::counter!(
+        "polygon_request_errors_total",
+        "endpoint" => url_label(url),
+        "kind" => format!("{:?}", err.kind())
+    )
+    .increment(1);
+}
+
+/// Records a WebSocket message and the events parsed from it.
+#[cfg(feature = "websocket")]
+pub(crate) fn record_ws_message(events: &[PolygonEvent]) {
+    metrics::counter!("polygon_ws_messages_total").increment(1);
+    for event in events {
+        let kind = match event {
+            PolygonEvent::Trade(_) => "trade",
+            PolygonEvent::Quote(_) => "quote",
+            PolygonEvent::MinuteBar(_) => "minute_bar",
+            PolygonEvent::SecondBar(_) => "second_bar",
+            PolygonEvent::CryptoBook(_) => "crypto_book",
+        };
+        metrics::counter!("polygon_ws_events_total", "type" => kind).increment(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::telemetry::endpoint_label;
+
+    #[test]
+    fn test_endpoint_label() {
+        assert_eq!(
+            endpoint_label("/v2/aggs/ticker/MSFT/range/1/day/2020-10-14/2020-10-14"),
+            "/v2/aggs/ticker/{}/range/{}/day/{}/{}"
+        );
+        assert_eq!(
+            endpoint_label("/v2/snapshot/locale/us/markets/stocks/tickers"),
+            "/v2/snapshot/locale/us/markets/stocks/tickers"
+        );
+        assert_eq!(endpoint_label("/v3/trades/X:BTC-USD"), "/v3/trades/{}");
+        assert_eq!(
+            endpoint_label("/v1/meta/conditions/trades"),
+            "/v1/meta/conditions/trades"
+        );
+    }
+}
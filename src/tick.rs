@@ -0,0 +1,96 @@
+//! Tick sizes and price rounding for US equities.
+//!
+//! Under Rule 612 of Regulation NMS, stocks priced at $1.00 or more are quoted
+//! in increments of $0.01, and stocks priced below $1.00 in increments of
+//! $0.0001.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::tick;
+//!
+//! assert_eq!(tick::tick_size(25.0), 0.01);
+//! assert_eq!(tick::round_to_tick(25.037), 25.04);
+//! assert_eq!(tick::spread_in_ticks(25.01, 25.04), 3.0);
+//! ```
+
+/// The minimum price increment for stocks priced at $1.00 or more.
+pub const PENNY: f64 = 0.01;
+
+/// The minimum price increment for stocks priced below $1.00.
+pub const SUB_PENNY: f64 = 0.0001;
+
+/// The price at and above which [`PENNY`] increments apply.
+pub const PENNY_THRESHOLD: f64 = 1.0;
+
+/// Prices are rounded to this many decimal places after snapping to a tick,
+/// which removes the floating point error introduced by the division.
+const PRICE_SCALE: f64 = 10_000.0;
+
+/// Returns the minimum price increment for a stock quoted at `price`.
+pub fn tick_size(price: f64) -> f64 {
+    if price >= PENNY_THRESHOLD {
+        PENNY
+    } else {
+        SUB_PENNY
+    }
+}
+
+/// Rounds `price` to the nearest valid tick.
+pub fn round_to_tick(price: f64) -> f64 {
+    snap(price, f64::round)
+}
+
+/// Rounds `price` down to a valid tick, such as for a buy limit order.
+pub fn floor_to_tick(price: f64) -> f64 {
+    snap(price, f64::floor)
+}
+
+/// Rounds `price` up to a valid tick, such as for a sell limit order.
+pub fn ceil_to_tick(price: f64) -> f64 {
+    snap(price, f64::ceil)
+}
+
+/// Returns whether `price` is a valid tick.
+pub fn is_on_tick(price: f64) -> bool {
+    (round_to_tick(price) - price).abs() < f64::EPSILON * PRICE_SCALE
+}
+
+/// Returns the spread between `bid` and `ask` measured in ticks. The tick
+/// size is taken from the bid, so a quote of `0.9999 x 1.00` is one tick wide.
+pub fn spread_in_ticks(bid: f64, ask: f64) -> f64 {
+    ((ask - bid) / tick_size(bid) * PRICE_SCALE).round() / PRICE_SCALE
+}
+
+fn snap(price: f64, round: fn(f64) -> f64) -> f64 {
+    let tick = tick_size(price);
+    // Round away the floating point error before snapping so that a price
+    // such as 1.1 is not floored to 1.09.
+    let ticks = (price / tick * PRICE_SCALE).round() / PRICE_SCALE;
+    (round(ticks) * tick * PRICE_SCALE).round() / PRICE_SCALE
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tick::*;
+
+    #[test]
+    fn test_round_to_tick() {
+        assert_eq!(round_to_tick(10.005), 10.01);
+        assert_eq!(round_to_tick(10.004), 10.0);
+        assert_eq!(round_to_tick(0.12345), 0.1235);
+        assert_eq!(floor_to_tick(1.1), 1.1);
+        assert_eq!(floor_to_tick(10.019), 10.01);
+        assert_eq!(ceil_to_tick(10.011), 10.02);
+        assert_eq!(ceil_to_tick(0.50001), 0.5001);
+        assert!(is_on_tick(10.01));
+        assert!(!is_on_tick(10.015));
+    }
+
+    #[test]
+    fn test_spread_in_ticks() {
+        assert_eq!(spread_in_ticks(10.0, 10.01), 1.0);
+        assert_eq!(spread_in_ticks(0.5, 0.5003), 3.0);
+        assert_eq!(spread_in_ticks(0.9999, 1.0), 1.0);
+    }
+}
@@ -0,0 +1,859 @@
+//! Helpers for validating and normalizing ticker symbols.
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::types::{AssetClass, OptionsContractType};
+
+/// The maximum length of a stock ticker, excluding the share class.
+const MAX_STOCK_TICKER_LEN: usize = 10;
+
+/// The maximum length of a crypto pair such as `BTCUSD`.
+const MAX_CRYPTO_PAIR_LEN: usize = 20;
+
+/// The reason a ticker symbol could not be normalized.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TickerError {
+    /// The ticker is empty.
+    Empty,
+    /// The ticker contains a character that is not valid for its asset class.
+    InvalidCharacter(char),
+    /// The ticker is longer than allowed for its asset class.
+    TooLong,
+    /// The ticker has a prefix that belongs to a different asset class, such
+    /// as `X:BTCUSD` when a stock ticker was expected.
+    WrongAssetClass {
+        expected: AssetClass,
+        found: AssetClass,
+    },
+    /// The ticker is not a valid currency pair.
+    InvalidPair(String),
+    /// The ticker is not a valid OCC option symbol.
+    InvalidOptionSymbol(String),
+    /// The ticker belongs to an asset class that the request does not
+    /// support.
+    UnsupportedAssetClass(AssetClass),
+}
+
+impl fmt::Display for TickerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TickerError::Empty => write!(f, "ticker is empty"),
+            TickerError::InvalidCharacter(c) => write!(f, "invalid character '{}' in ticker", c),
+            TickerError::TooLong => write!(f, "ticker is too long"),
+            TickerError::WrongAssetClass { expected, found } => write!(
+                f,
+                "expected a ticker in the {} market but found one in the {} market",
+                expected, found
+            ),
+            TickerError::InvalidPair(s) => write!(f, "'{}' is not a valid currency pair", s),
+            TickerError::InvalidOptionSymbol(s) => {
+                write!(f, "'{}' is not a valid OCC option symbol", s)
+            }
+            TickerError::UnsupportedAssetClass(class) => {
+                write!(f, "tickers in the {} market are not supported", class)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TickerError {}
+
+/// Normalizes a user-provided ticker symbol into the form expected by
+/// polygon.io for the given asset class.
+///
+/// The ticker is trimmed and uppercased, and the asset class prefix (such as
+/// `X:` for crypto) is added if it is missing. For stocks, share class
+/// separators are mapped to the `.` notation used by polygon.io, so `BRK-B`,
+/// `BRK/B`, and `BRK B` all become `BRK.B`. Tickers written without a
+/// separator, such as `BRKB`, are left as-is since they cannot be told apart
+/// from a ticker that has no share class.
+///
+/// For crypto and forex, separators between the currencies are removed, so
+/// `btc-usd` becomes `X:BTCUSD` and `EUR/USD` becomes `C:EURUSD`.
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::ticker::normalize_ticker;
+/// use polygon_client::types::AssetClass;
+///
+/// assert_eq!(normalize_ticker("brk-b", AssetClass::Stocks).unwrap(), "BRK.B");
+/// assert_eq!(normalize_ticker("btc/usd", AssetClass::Crypto).unwrap(), "X:BTCUSD");
+/// assert!(normalize_ticker("X:BTCUSD", AssetClass::Stocks).is_err());
+/// ```
+pub fn normalize_ticker(input: &str, market: AssetClass) -> Result<String, TickerError> {
+    let upper = input.trim().to_uppercase();
+
+    let symbol = match split_prefix(&upper) {
+        Some((found, _)) if found != market => {
+            return Err(TickerError::WrongAssetClass {
+                expected: market,
+                found,
+            })
+        }
+        Some((_, rest)) => rest,
+        None => upper.as_str(),
+    };
+
+    if symbol.is_empty() {
+        return Err(TickerError::Empty);
+    }
+
+    let normalized = match market {
+        AssetClass::Stocks | AssetClass::Otc => normalize_stock(symbol)?,
+        AssetClass::Options => normalize_option(symbol)?,
+        AssetClass::Crypto => normalize_crypto(symbol)?,
+        AssetClass::Forex => normalize_forex(symbol)?,
+        AssetClass::Indices => normalize_index(symbol)?,
+        market => return Err(TickerError::UnsupportedAssetClass(market)),
+    };
+
+    match market.ticker_prefix() {
+        Some(prefix) => Ok(format!("{}{}", prefix, normalized)),
+        None => Ok(normalized),
+    }
+}
+
+/// Returns the asset class of a ticker based on its prefix, such as `X:` for
+/// crypto. Tickers without a prefix are stock tickers.
+pub fn asset_class(ticker: &str) -> AssetClass {
+    split_prefix(ticker)
+        .map(|(class, _)| class)
+        .unwrap_or(AssetClass::Stocks)
+}
+
+/// Quote currencies of crypto pairs, longest first so that `USDT` is matched
+/// before `USD`.
+const CRYPTO_QUOTE_CURRENCIES: &[&str] = &[
+    "USDT", "USDC", "USD", "EUR", "GBP", "JPY", "CAD", "AUD", "BTC", "ETH",
+];
+
+/// Splits a normalized forex or crypto ticker, such as `C:EURUSD` or
+/// `X:BTCUSDT`, into its base and quote currencies.
+pub fn split_pair(ticker: &str) -> Result<(&str, &str), TickerError> {
+    let invalid = || TickerError::InvalidPair(String::from(ticker));
+
+    match split_prefix(ticker) {
+        Some((AssetClass::Forex, pair)) if pair.len() == 6 && pair.is_char_boundary(3) => {
+            Ok(pair.split_at(3))
+        }
+        Some((AssetClass::Crypto, pair)) => CRYPTO_QUOTE_CURRENCIES
+            .iter()
+            .find(|quote| pair.len() > quote.len() && pair.ends_with(*quote))
+            .map(|quote| pair.split_at(pair.len() - quote.len()))
+            .ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
+/// The largest strike price that fits in the eight digits of an OCC option
+/// symbol, in thousandths of a dollar.
+const MAX_OPTION_STRIKE_THOUSANDTHS: u64 = 99_999_999;
+
+/// An OCC option symbol, such as `O:SPY251219C00650000` for a call on `SPY`
+/// expiring on 2025-12-19 with a strike price of 650.
+///
+/// The symbol is parsed with [`str::parse()`], with or without the `O:`
+/// prefix, and formatted with the prefix by [`ToString::to_string()`].
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use polygon_client::ticker::OptionSymbol;
+/// use polygon_client::types::OptionsContractType;
+///
+/// let symbol: OptionSymbol = "O:SPY251219C00650000".parse().unwrap();
+/// assert_eq!(symbol.underlying(), "SPY");
+/// assert_eq!(symbol.expiration(), NaiveDate::from_ymd_opt(2025, 12, 19).unwrap());
+/// assert_eq!(symbol.contract_type(), OptionsContractType::Call);
+/// assert_eq!(symbol.strike(), 650.0);
+///
+/// let expiration = NaiveDate::from_ymd_opt(2024, 1, 19).unwrap();
+/// let symbol = OptionSymbol::new("AAPL", expiration, OptionsContractType::Put, 182.5).unwrap();
+/// assert_eq!(symbol.to_string(), "O:AAPL240119P00182500");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OptionSymbol {
+    underlying: String,
+    expiration: NaiveDate,
+    contract_type: OptionsContractType,
+    strike_thousandths: u64,
+}
+
+impl OptionSymbol {
+    /// Returns the symbol of the contract on `underlying` with the given
+    /// expiration date, type, and strike price.
+    ///
+    /// The underlying root is uppercased and must be one to six letters or
+    /// digits. The expiration must be in the years 2000 to 2099, the contract
+    /// type must be a call or a put, and the strike price must be at least 0
+    /// and below 100,000, as only these fit in the symbol. The strike price
+    /// is rounded to thousandths of a dollar.
+    pub fn new(
+        underlying: &str,
+        expiration: NaiveDate,
+        contract_type: OptionsContractType,
+        strike: f64,
+    ) -> Result<Self, TickerError> {
+        let underlying = underlying.trim().to_uppercase();
+        if let Some(c) = underlying.chars().find(|c| !c.is_ascii_alphanumeric()) {
+            return Err(TickerError::InvalidCharacter(c));
+        }
+
+        let strike_thousandths = (strike * 1000.0).round();
+        if underlying.is_empty()
+            || underlying.len() > 6
+            || !(2000..=2099).contains(&expiration.year())
+            || contract_type == OptionsContractType::Other
+            || !(0.0..=MAX_OPTION_STRIKE_THOUSANDTHS as f64).contains(&strike_thousandths)
+        {
+            return Err(TickerError::InvalidOptionSymbol(format!(
+                "{} {} {} {}",
+                underlying, expiration, contract_type, strike
+            )));
+        }
+
+        Ok(OptionSymbol {
+            underlying,
+            expiration,
+            contract_type,
+            strike_thousandths: strike_thousandths as u64,
+        })
+    }
+
+    /// Returns the root symbol of the underlying, such as `SPY`.
+    pub fn underlying(&self) -> &str {
+        &self.underlying
+    }
+
+    /// Returns the expiration date.
+    pub fn expiration(&self) -> NaiveDate {
+        self.expiration
+    }
+
+    /// Returns whether the contract is a call or a put.
+    pub fn contract_type(&self) -> OptionsContractType {
+        self.contract_type
+    }
+
+    /// Returns the strike price.
+    pub fn strike(&self) -> f64 {
+        self.strike_thousandths as f64 / 1000.0
+    }
+}
+
+impl FromStr for OptionSymbol {
+    type Err = TickerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TickerError::InvalidOptionSymbol(String::from(s));
+        let symbol = s.strip_prefix("O:").unwrap_or(s);
+
+        // An OCC symbol is the underlying root followed by a six digit
+        // expiration date, a `C` or `P`, and an eight digit strike price.
+        if !symbol.is_ascii() || symbol.len() < 16 || symbol.len() > 21 {
+            return Err(invalid());
+        }
+
+        let (underlying, contract) = symbol.split_at(symbol.len() - 15);
+        let (date, contract) = contract.split_at(6);
+        let (contract_type, strike) = contract.split_at(1);
+
+        if !underlying
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            || !date.chars().all(|c| c.is_ascii_digit())
+            || !strike.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let expiration =
+            NaiveDate::parse_from_str(&format!("20{}", date), "%Y%m%d").map_err(|_| invalid())?;
+        let contract_type = match contract_type {
+            "C" => OptionsContractType::Call,
+            "P" => OptionsContractType::Put,
+            _ => return Err(invalid()),
+        };
+        let strike_thousandths = strike.parse().map_err(|_| invalid())?;
+
+        Ok(OptionSymbol {
+            underlying: String::from(underlying),
+            expiration,
+            contract_type,
+            strike_thousandths,
+        })
+    }
+}
+
+impl fmt::Display for OptionSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let contract_type = match self.contract_type {
+            OptionsContractType::Put => 'P',
+            _ => 'C',
+        };
+        write!(
+            f,
+            "{}{}{}{}{:08}",
+            AssetClass::Options.ticker_prefix().unwrap_or_default(),
+            self.underlying,
+            self.expiration.format("%y%m%d"),
+            contract_type,
+            self.strike_thousandths
+        )
+    }
+}
+
+/// Defines a ticker newtype of an asset class.
+macro_rules! ticker_newtype {
+    ($(#[$meta:meta])* $name:ident => $class:expr, $variant:ident) => {
+        $(#[$meta])*
+        ///
+        /// The ticker is validated and normalized as with
+        /// [`normalize_ticker()`] when it is created, so a ticker of another
+        /// asset class cannot be passed where this type is expected.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(String);
+
+        impl $name {
+            /// Validates and normalizes `ticker`.
+            pub fn new(ticker: &str) -> Result<Self, TickerError> {
+                normalize_ticker(ticker, $class).map($name)
+            }
+
+            /// Returns the ticker, with the prefix of its asset class, if
+            /// any.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Wraps a ticker taken from a request or response of this
+            /// crate without validating it again.
+            #[allow(dead_code)]
+            pub(crate) fn unchecked(ticker: &str) -> Self {
+                $name(String::from(ticker))
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = TickerError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::new(s)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = TickerError;
+
+            fn try_from(ticker: &str) -> Result<Self, Self::Error> {
+                $name::new(ticker)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = TickerError;
+
+            fn try_from(ticker: String) -> Result<Self, Self::Error> {
+                $name::new(&ticker)
+            }
+        }
+
+        impl From<&$name> for $name {
+            fn from(ticker: &$name) -> Self {
+                ticker.clone()
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(ticker: $name) -> Self {
+                ticker.0
+            }
+        }
+
+        impl From<$name> for Ticker {
+            fn from(ticker: $name) -> Self {
+                Ticker::$variant(ticker)
+            }
+        }
+
+        impl From<&$name> for Ticker {
+            fn from(ticker: &$name) -> Self {
+                Ticker::$variant(ticker.clone())
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+ticker_newtype!(
+    /// A stock ticker, such as `MSFT` or `BRK.B`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use polygon_client::ticker::StockTicker;
+    ///
+    /// let ticker: StockTicker = "brk-b".parse().unwrap();
+    /// assert_eq!(ticker.as_str(), "BRK.B");
+    /// assert!(StockTicker::new("X:BTCUSD").is_err());
+    /// ```
+    StockTicker => AssetClass::Stocks, Stock
+);
+
+ticker_newtype!(
+    /// An option contract ticker with the `O:` prefix, such as
+    /// `O:SPY251219C00650000`.
+    OptionTicker => AssetClass::Options, Option
+);
+
+ticker_newtype!(
+    /// A crypto pair ticker with the `X:` prefix, such as `X:BTCUSD`.
+    CryptoTicker => AssetClass::Crypto, Crypto
+);
+
+ticker_newtype!(
+    /// A forex pair ticker with the `C:` prefix, such as `C:EURUSD`.
+    ForexTicker => AssetClass::Forex, Forex
+);
+
+ticker_newtype!(
+    /// An index ticker with the `I:` prefix, such as `I:SPX`.
+    IndexTicker => AssetClass::Indices, Index
+);
+
+impl OptionTicker {
+    /// Returns the OCC option symbol of the ticker.
+    pub fn symbol(&self) -> Result<OptionSymbol, TickerError> {
+        self.0.parse()
+    }
+}
+
+impl From<&OptionSymbol> for OptionTicker {
+    fn from(symbol: &OptionSymbol) -> Self {
+        OptionTicker(symbol.to_string())
+    }
+}
+
+impl From<OptionSymbol> for OptionTicker {
+    fn from(symbol: OptionSymbol) -> Self {
+        OptionTicker(symbol.to_string())
+    }
+}
+
+/// A validated ticker of any asset class, for the methods that accept the
+/// tickers of several asset classes, such as
+/// [`RESTClient::aggregates()`](crate::rest::RESTClient::aggregates).
+///
+/// Parsing a ticker takes its asset class from its prefix, as with
+/// [`asset_class()`], so tickers without a prefix are stock tickers.
+///
+/// # Example
+///
+/// ```
+/// use polygon_client::ticker::{CryptoTicker, Ticker};
+/// use polygon_client::types::AssetClass;
+///
+/// let ticker = Ticker::from(CryptoTicker::new("btc/usd").unwrap());
+/// assert_eq!(ticker.as_str(), "X:BTCUSD");
+/// assert_eq!("msft".parse::<Ticker>().unwrap().asset_class(), AssetClass::Stocks);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Ticker {
+    /// A stock ticker.
+    Stock(StockTicker),
+    /// An option contract ticker.
+    Option(OptionTicker),
+    /// A crypto pair ticker.
+    Crypto(CryptoTicker),
+    /// A forex pair ticker.
+    Forex(ForexTicker),
+    /// An index ticker.
+    Index(IndexTicker),
+}
+
+impl Ticker {
+    /// Returns the ticker, with the prefix of its asset class, if any.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Ticker::Stock(t) => t.as_str(),
+            Ticker::Option(t) => t.as_str(),
+            Ticker::Crypto(t) => t.as_str(),
+            Ticker::Forex(t) => t.as_str(),
+            Ticker::Index(t) => t.as_str(),
+        }
+    }
+
+    /// Returns the asset class of the ticker.
+    pub fn asset_class(&self) -> AssetClass {
+        match self {
+            Ticker::Stock(_) => AssetClass::Stocks,
+            Ticker::Option(_) => AssetClass::Options,
+            Ticker::Crypto(_) => AssetClass::Crypto,
+            Ticker::Forex(_) => AssetClass::Forex,
+            Ticker::Index(_) => AssetClass::Indices,
+        }
+    }
+}
+
+impl FromStr for Ticker {
+    type Err = TickerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match asset_class(s.trim()) {
+            AssetClass::Stocks => StockTicker::new(s).map(Ticker::Stock),
+            AssetClass::Options => OptionTicker::new(s).map(Ticker::Option),
+            AssetClass::Crypto => CryptoTicker::new(s).map(Ticker::Crypto),
+            AssetClass::Forex => ForexTicker::new(s).map(Ticker::Forex),
+            AssetClass::Indices => IndexTicker::new(s).map(Ticker::Index),
+            class => Err(TickerError::UnsupportedAssetClass(class)),
+        }
+    }
+}
+
+impl From<&Ticker> for Ticker {
+    fn from(ticker: &Ticker) -> Self {
+        ticker.clone()
+    }
+}
+
+impl AsRef<str> for Ticker {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for Ticker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Splits a known asset class prefix, such as `X:`, from a ticker.
+fn split_prefix(ticker: &str) -> Option<(AssetClass, &str)> {
+    [
+        AssetClass::Options,
+        AssetClass::Crypto,
+        AssetClass::Forex,
+        AssetClass::Indices,
+    ]
+    .iter()
+    .find_map(|class| {
+        class
+            .ticker_prefix()
+            .and_then(|prefix| ticker.strip_prefix(prefix))
+            .map(|rest| (class.clone(), rest))
+    })
+}
+
+fn normalize_stock(symbol: &str) -> Result<String, TickerError> {
+    let mut normalized = String::with_capacity(symbol.len());
+    let mut has_class = false;
+
+    for c in symbol.chars() {
+        match c {
+            'A'..='Z' | '0'..='9' => normalized.push(c),
+            '.' | '-' | '/' | ' ' if !has_class && !normalized.is_empty() => {
+                has_class = true;
+                normalized.push('.');
+            }
+            _ => return Err(TickerError::InvalidCharacter(c)),
+        }
+    }
+
+    if normalized.ends_with('.') {
+        return Err(TickerError::InvalidCharacter('.'));
+    }
+
+    let base_len = normalized.split('.').next().map(|s| s.len()).unwrap_or(0);
+    if base_len > MAX_STOCK_TICKER_LEN {
+        return Err(TickerError::TooLong);
+    }
+
+    Ok(normalized)
+}
+
+fn normalize_option(symbol: &str) -> Result<String, TickerError> {
+    if let Some(c) = symbol.chars().find(|c| !c.is_ascii_alphanumeric()) {
+        return Err(TickerError::InvalidCharacter(c));
+    }
+
+    // Parsing also checks that the expiration is a real date, so every option
+    // ticker has an `OptionSymbol`.
+    symbol.parse::<OptionSymbol>()?;
+    Ok(String::from(symbol))
+}
+
+fn strip_pair_separators(symbol: &str) -> Result<String, TickerError> {
+    let mut normalized = String::with_capacity(symbol.len());
+
+    for c in symbol.chars() {
+        match c {
+            'A'..='Z' | '0'..='9' => normalized.push(c),
+            '-' | '/' | '_' | ' ' => {}
+            _ => return Err(TickerError::InvalidCharacter(c)),
+        }
+    }
+
+    Ok(normalized)
+}
+
+fn normalize_crypto(symbol: &str) -> Result<String, TickerError> {
+    let normalized = strip_pair_separators(symbol)?;
+
+    if normalized.len() < 6 {
+        return Err(TickerError::InvalidPair(String::from(symbol)));
+    }
+
+    if normalized.len() > MAX_CRYPTO_PAIR_LEN {
+        return Err(TickerError::TooLong);
+    }
+
+    Ok(normalized)
+}
+
+fn normalize_forex(symbol: &str) -> Result<String, TickerError> {
+    let normalized = strip_pair_separators(symbol)?;
+
+    if normalized.len() != 6 || !normalized.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(TickerError::InvalidPair(String::from(symbol)));
+    }
+
+    Ok(normalized)
+}
+
+fn normalize_index(symbol: &str) -> Result<String, TickerError> {
+    if let Some(c) = symbol.chars().find(|c| !c.is_ascii_alphanumeric()) {
+        return Err(TickerError::InvalidCharacter(c));
+    }
+
+    if symbol.len() > MAX_STOCK_TICKER_LEN {
+        return Err(TickerError::TooLong);
+    }
+
+    Ok(String::from(symbol))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use chrono::NaiveDate;
+
+    use crate::ticker::{
+        asset_class, normalize_ticker, split_pair, CryptoTicker, ForexTicker, IndexTicker,
+        OptionSymbol, OptionTicker, StockTicker, Ticker, TickerError,
+    };
+    use crate::types::{AssetClass, OptionsContractType};
+
+    #[test]
+    fn test_normalize_stock() {
+        assert_eq!(
+            normalize_ticker(" msft ", AssetClass::Stocks).unwrap(),
+            "MSFT"
+        );
+        for input in &["BRK.B", "BRK-B", "brk/b", "BRK B"] {
+            assert_eq!(
+                normalize_ticker(input, AssetClass::Stocks).unwrap(),
+                "BRK.B"
+            );
+        }
+        assert_eq!(
+            normalize_ticker("BRKB", AssetClass::Stocks).unwrap(),
+            "BRKB"
+        );
+        assert_eq!(
+            normalize_ticker("", AssetClass::Stocks),
+            Err(TickerError::Empty)
+        );
+        assert_eq!(
+            normalize_ticker("MS$FT", AssetClass::Stocks),
+            Err(TickerError::InvalidCharacter('$'))
+        );
+        assert_eq!(
+            normalize_ticker("BRK.B.C", AssetClass::Stocks),
+            Err(TickerError::InvalidCharacter('.'))
+        );
+        assert_eq!(
+            normalize_ticker("ABCDEFGHIJK", AssetClass::Stocks),
+            Err(TickerError::TooLong)
+        );
+        assert_eq!(
+            normalize_ticker("X:BTCUSD", AssetClass::Stocks),
+            Err(TickerError::WrongAssetClass {
+                expected: AssetClass::Stocks,
+                found: AssetClass::Crypto
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize_crypto_and_forex() {
+        assert_eq!(
+            normalize_ticker("btc-usd", AssetClass::Crypto).unwrap(),
+            "X:BTCUSD"
+        );
+        assert_eq!(
+            normalize_ticker("X:ETHUSD", AssetClass::Crypto).unwrap(),
+            "X:ETHUSD"
+        );
+        assert_eq!(
+            normalize_ticker("EUR/USD", AssetClass::Forex).unwrap(),
+            "C:EURUSD"
+        );
+        assert!(matches!(
+            normalize_ticker("EUR/US", AssetClass::Forex),
+            Err(TickerError::InvalidPair(_))
+        ));
+        assert!(matches!(
+            normalize_ticker("BTC", AssetClass::Crypto),
+            Err(TickerError::InvalidPair(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_options_and_indices() {
+        assert_eq!(
+            normalize_ticker("spy251219c00650000", AssetClass::Options).unwrap(),
+            "O:SPY251219C00650000"
+        );
+        assert!(matches!(
+            normalize_ticker("O:SPY251219X00650000", AssetClass::Options),
+            Err(TickerError::InvalidOptionSymbol(_))
+        ));
+        assert!(matches!(
+            "O:SPY251399C00650000".parse::<OptionTicker>(),
+            Err(TickerError::InvalidOptionSymbol(_))
+        ));
+        assert_eq!(
+            normalize_ticker("spx", AssetClass::Indices).unwrap(),
+            "I:SPX"
+        );
+        assert_eq!(
+            normalize_ticker("I:NDX", AssetClass::Indices).unwrap(),
+            "I:NDX"
+        );
+    }
+
+    #[test]
+    fn test_asset_class_and_split_pair() {
+        assert_eq!(asset_class("MSFT"), AssetClass::Stocks);
+        assert_eq!(asset_class("O:SPY251219C00650000"), AssetClass::Options);
+        assert_eq!(asset_class("X:BTCUSD"), AssetClass::Crypto);
+        assert_eq!(split_pair("C:EURUSD").unwrap(), ("EUR", "USD"));
+        assert_eq!(split_pair("X:BTCUSDT").unwrap(), ("BTC", "USDT"));
+        assert_eq!(split_pair("X:ETHBTC").unwrap(), ("ETH", "BTC"));
+        assert!(split_pair("X:USD").is_err());
+        assert!(split_pair("MSFT").is_err());
+    }
+
+    #[test]
+    fn test_option_symbol() {
+        let symbol: OptionSymbol = "O:SPY251219P00650500".parse().unwrap();
+        assert_eq!(symbol.underlying(), "SPY");
+        assert_eq!(
+            symbol.expiration(),
+            NaiveDate::from_ymd_opt(2025, 12, 19).unwrap()
+        );
+        assert_eq!(symbol.contract_type(), OptionsContractType::Put);
+        assert_eq!(symbol.strike(), 650.5);
+        assert_eq!(symbol.to_string(), "O:SPY251219P00650500");
+
+        // The prefix is optional and roots may contain digits.
+        let symbol: OptionSymbol = "SPY1240119C00000500".parse().unwrap();
+        assert_eq!(symbol.underlying(), "SPY1");
+        assert_eq!(symbol.strike(), 0.5);
+        assert_eq!(symbol.to_string(), "O:SPY1240119C00000500");
+
+        for invalid in &[
+            "O:SPY251219X00650000",
+            "O:SPY251319C00650000",
+            "O:SPY25121C00650000",
+            "O:spy251219C00650000",
+            "O:TOOLONG251219C00650000",
+            "O:251219C00650000",
+        ] {
+            assert!(
+                matches!(
+                    invalid.parse::<OptionSymbol>(),
+                    Err(TickerError::InvalidOptionSymbol(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+
+        let expiration = NaiveDate::from_ymd_opt(2024, 1, 19).unwrap();
+        let symbol = OptionSymbol::new("brk.b", expiration, OptionsContractType::Call, 1.0);
+        assert_eq!(symbol, Err(TickerError::InvalidCharacter('.')));
+        let symbol = OptionSymbol::new("AAPL", expiration, OptionsContractType::Call, 100_000.0);
+        assert!(matches!(symbol, Err(TickerError::InvalidOptionSymbol(_))));
+        let symbol = OptionSymbol::new("AAPL", expiration, OptionsContractType::Other, 100.0);
+        assert!(matches!(symbol, Err(TickerError::InvalidOptionSymbol(_))));
+        let symbol =
+            OptionSymbol::new("aapl", expiration, OptionsContractType::Call, 182.5).unwrap();
+        assert_eq!(symbol.to_string(), "O:AAPL240119C00182500");
+        assert_eq!(symbol.to_string().parse::<OptionSymbol>().unwrap(), symbol);
+    }
+
+    #[test]
+    fn test_ticker_newtypes() {
+        assert_eq!("brk-b".parse::<StockTicker>().unwrap().as_str(), "BRK.B");
+        assert_eq!(
+            "btc/usd".parse::<CryptoTicker>().unwrap().as_str(),
+            "X:BTCUSD"
+        );
+        assert_eq!(
+            "eur-usd".parse::<ForexTicker>().unwrap().to_string(),
+            "C:EURUSD"
+        );
+        assert_eq!("spx".parse::<IndexTicker>().unwrap().as_str(), "I:SPX");
+        assert_eq!(
+            "X:BTCUSD".parse::<StockTicker>(),
+            Err(TickerError::WrongAssetClass {
+                expected: AssetClass::Stocks,
+                found: AssetClass::Crypto
+            })
+        );
+        assert_eq!("".parse::<CryptoTicker>(), Err(TickerError::Empty));
+
+        let ticker: OptionTicker = "AAPL240119C00182500".parse().unwrap();
+        assert_eq!(ticker.as_str(), "O:AAPL240119C00182500");
+        let symbol = ticker.symbol().unwrap();
+        assert_eq!(symbol.underlying(), "AAPL");
+        assert_eq!(OptionTicker::from(symbol), ticker);
+
+        let ticker = StockTicker::try_from("msft").unwrap();
+        assert_eq!(StockTicker::from(&ticker), ticker);
+        assert_eq!(StockTicker::try_from(String::from("MSFT")).unwrap(), ticker);
+        assert!(StockTicker::try_from("C:EURUSD").is_err());
+        assert_eq!(String::from(ticker.clone()), "MSFT");
+
+        let ticker = Ticker::from(ticker);
+        assert_eq!(ticker.asset_class(), AssetClass::Stocks);
+        assert_eq!("X:BTC-USD".parse::<Ticker>().unwrap().as_str(), "X:BTCUSD");
+        assert_eq!(
+            "O:SPY251219C00650000"
+                .parse::<Ticker>()
+                .unwrap()
+                .asset_class(),
+            AssetClass::Options
+        );
+        assert_eq!("I:spx".parse::<Ticker>().unwrap().to_string(), "I:SPX");
+    }
+}
@@ -1,23 +1,445 @@
 //! Data types associated with the REST interfaces.
 use serde;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
+
+#[cfg(feature = "chrono-accessors")]
+use chrono::{DateTime, Utc};
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::adjust::Adjustment;
+use crate::error::Error;
+
+//
+// Dates and times
+//
+
+/// Parses a date sent as `2021-11-17`.
+pub(crate) fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Parses a date and time in UTC, such as `2021-04-25T00:00:00Z`, which the
+/// API may also send as a date alone, such as `2021-01-01`, for midnight of
+/// that date.
+#[cfg(feature = "chrono-accessors")]
+fn parse_utc_date_time(date_time: &str) -> Option<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(date_time) {
+        Ok(dt) => Some(dt.with_timezone(&Utc)),
+        Err(_) => Some(parse_date(date_time)?.and_hms_opt(0, 0, 0)?.and_utc()),
+    }
+}
+
+//
+// Asset classes
+//
+
+/// The asset class, or market, of a ticker.
+///
+/// It is serialized as the name polygon.io uses in the `market` field of
+/// tickers and the `market` query parameter, such as `fx` for forex. Markets
+/// added by polygon.io are kept in [`AssetClass::Other`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+#[non_exhaustive]
+pub enum AssetClass {
+    Stocks,
+    /// Stocks traded over the counter, a market of reference data whose
+    /// tickers are stock tickers.
+    Otc,
+    Options,
+    Crypto,
+    Forex,
+    Indices,
+    Other(String),
+}
+
+impl AssetClass {
+    /// Returns the name of the asset class, such as `fx` for forex.
+    pub fn as_str(&self) -> &str {
+        match self {
+            AssetClass::Stocks => "stocks",
+            AssetClass::Otc => "otc",
+            AssetClass::Options => "options",
+            AssetClass::Crypto => "crypto",
+            AssetClass::Forex => "fx",
+            AssetClass::Indices => "indices",
+            AssetClass::Other(market) => market,
+        }
+    }
+
+    /// Returns the prefix used by polygon.io for tickers of this asset class,
+    /// such as `X:` for crypto. Stock tickers and tickers of unknown asset
+    /// classes do not have a prefix.
+    pub fn ticker_prefix(&self) -> Option<&'static str> {
+        match self {
+            AssetClass::Stocks | AssetClass::Otc | AssetClass::Other(_) => None,
+            AssetClass::Options => Some("O:"),
+            AssetClass::Crypto => Some("X:"),
+            AssetClass::Forex => Some("C:"),
+            AssetClass::Indices => Some("I:"),
+        }
+    }
+}
+
+impl From<String> for AssetClass {
+    fn from(market: String) -> Self {
+        match market.as_str() {
+            "stocks" => AssetClass::Stocks,
+            "otc" => AssetClass::Otc,
+            "options" => AssetClass::Options,
+            "crypto" => AssetClass::Crypto,
+            "fx" => AssetClass::Forex,
+            "indices" => AssetClass::Indices,
+            _ => AssetClass::Other(market),
+        }
+    }
+}
+
+impl From<AssetClass> for String {
+    fn from(asset_class: AssetClass) -> Self {
+        match asset_class {
+            AssetClass::Other(market) => market,
+            asset_class => String::from(asset_class.as_str()),
+        }
+    }
+}
+
+impl fmt::Display for AssetClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The type of a ticker, as in the `type` field of tickers and the `type`
+/// query parameter that filters them, such as `CS` for common stock.
+///
+/// Types added by polygon.io are kept in [`TickerType::Other`]. The codes
+/// and descriptions of every type are listed by
+/// [`RESTClient::reference_ticker_types()`](crate::rest::RESTClient::reference_ticker_types).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+#[non_exhaustive]
+pub enum TickerType {
+    /// `CS`
+    CommonStock,
+    /// `OS`
+    OrdinaryShares,
+    /// `PFD`
+    PreferredStock,
+    /// `ADRC`
+    AdrCommon,
+    /// `ADRP`
+    AdrPreferred,
+    /// `ADRR`
+    AdrRights,
+    /// `ADRW`
+    AdrWarrants,
+    /// `GDR`
+    Gdr,
+    /// `NYRS`
+    NyRegistryShares,
+    /// `ETF`
+    Etf,
+    /// `ETN`
+    Etn,
+    /// `ETV`
+    Etv,
+    /// `ETS`
+    Ets,
+    /// `FUND`
+    Fund,
+    /// `UNIT`
+    Unit,
+    /// `RIGHT`
+    Rights,
+    /// `WARRANT`
+    Warrant,
+    /// `SP`
+    StructuredProduct,
+    /// `BASKET`
+    Basket,
+    /// `LT`
+    LiquidatingTrust,
+    /// `BOND`
+    Bond,
+    /// `AGEN`
+    AgencyBond,
+    /// `EQLK`
+    EquityLinkedBond,
+    /// `INDEX`
+    Index,
+    Other(String),
+}
+
+impl TickerType {
+    /// Returns the code of the type, such as `CS`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            TickerType::CommonStock => "CS",
+            TickerType::OrdinaryShares => "OS",
+            TickerType::PreferredStock => "PFD",
+            TickerType::AdrCommon => "ADRC",
+            TickerType::AdrPreferred => "ADRP",
+            TickerType::AdrRights => "ADRR",
+            TickerType::AdrWarrants => "ADRW",
+            TickerType::Gdr => "GDR",
+            TickerType::NyRegistryShares => "NYRS",
+            TickerType::Etf => "ETF",
+            TickerType::Etn => "ETN",
+            TickerType::Etv => "ETV",
+            TickerType::Ets => "ETS",
+            TickerType::Fund => "FUND",
+            TickerType::Unit => "UNIT",
+            TickerType::Rights => "RIGHT",
+            TickerType::Warrant => "WARRANT",
+            TickerType::StructuredProduct => "SP",
+            TickerType::Basket => "BASKET",
+            TickerType::LiquidatingTrust => "LT",
+            TickerType::Bond => "BOND",
+            TickerType::AgencyBond => "AGEN",
+            TickerType::EquityLinkedBond => "EQLK",
+            TickerType::Index => "INDEX",
+            TickerType::Other(code) => code,
+        }
+    }
+}
+
+impl From<String> for TickerType {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "CS" => TickerType::CommonStock,
+            "OS" => TickerType::OrdinaryShares,
+            "PFD" => TickerType::PreferredStock,
+            "ADRC" => TickerType::AdrCommon,
+            "ADRP" => TickerType::AdrPreferred,
+            "ADRR" => TickerType::AdrRights,
+            "ADRW" => TickerType::AdrWarrants,
+            "GDR" => TickerType::Gdr,
+            "NYRS" => TickerType::NyRegistryShares,
+            "ETF" => TickerType::Etf,
+            "ETN" => TickerType::Etn,
+            "ETV" => TickerType::Etv,
+            "ETS" => TickerType::Ets,
+            "FUND" => TickerType::Fund,
+            "UNIT" => TickerType::Unit,
+            "RIGHT" => TickerType::Rights,
+            "WARRANT" => TickerType::Warrant,
+            "SP" => TickerType::StructuredProduct,
+            "BASKET" => TickerType::Basket,
+            "LT" => TickerType::LiquidatingTrust,
+            "BOND" => TickerType::Bond,
+            "AGEN" => TickerType::AgencyBond,
+            "EQLK" => TickerType::EquityLinkedBond,
+            "INDEX" => TickerType::Index,
+            _ => TickerType::Other(code),
+        }
+    }
+}
+
+impl From<TickerType> for String {
+    fn from(ticker_type: TickerType) -> Self {
+        match ticker_type {
+            TickerType::Other(code) => code,
+            ticker_type => String::from(ticker_type.as_str()),
+        }
+    }
+}
+
+impl fmt::Display for TickerType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The market segment of the stocks grouped daily and snapshot paths, such as
+/// `/v2/aggs/grouped/locale/us/market/{market}/{date}`.
+///
+/// Indices have their own snapshot endpoint with a different schema, which
+/// is available through
+/// [`RESTClient::indices_snapshot()`](crate::rest::RESTClient::indices_snapshot).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StocksMarket {
+    /// Stocks listed on an exchange.
+    Stocks,
+    /// Stocks traded over the counter.
+    Otc,
+}
+
+impl fmt::Display for StocksMarket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            StocksMarket::Stocks => "stocks",
+            StocksMarket::Otc => "otc",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+//
+// Query parameters
+//
+
+/// The order in which a list endpoint returns its results, given as the
+/// `order` query parameter, or as the `sort` query parameter of aggregates.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use polygon_client::types::{Order, Sort};
+///
+/// let mut query_params = HashMap::new();
+/// query_params.insert("order", Order::Desc.as_str());
+/// query_params.insert("sort", Sort::PublishedUtc.as_str());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    /// Oldest or smallest first.
+    Asc,
+    /// Newest or largest first.
+    Desc,
+}
+
+impl Order {
+    /// Returns the value of the query parameter, such as `desc`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Order::Asc => "asc",
+            Order::Desc => "desc",
+        }
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The field by which a list endpoint sorts its results, given as the `sort`
+/// query parameter.
+///
+/// Which fields can be sorted by differs between endpoints. Fields that are
+/// not listed here can be given with [`Sort::Other`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Sort {
+    Timestamp,
+    Ticker,
+    Name,
+    Market,
+    Locale,
+    PrimaryExchange,
+    Type,
+    CurrencyName,
+    Cik,
+    LastUpdatedUtc,
+    DelistedUtc,
+    PublishedUtc,
+    ExDividendDate,
+    PayDate,
+    DeclarationDate,
+    RecordDate,
+    CashAmount,
+    ExecutionDate,
+    ExpirationDate,
+    StrikePrice,
+    FilingDate,
+    PeriodOfReportDate,
+    /// A field given by its name in the API, such as `fiscal_year`.
+    Other(&'static str),
+}
+
+impl Sort {
+    /// Returns the value of the query parameter, such as `published_utc`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sort::Timestamp => "timestamp",
+            Sort::Ticker => "ticker",
+            Sort::Name => "name",
+            Sort::Market => "market",
+            Sort::Locale => "locale",
+            Sort::PrimaryExchange => "primary_exchange",
+            Sort::Type => "type",
+            Sort::CurrencyName => "currency_name",
+            Sort::Cik => "cik",
+            Sort::LastUpdatedUtc => "last_updated_utc",
+            Sort::DelistedUtc => "delisted_utc",
+            Sort::PublishedUtc => "published_utc",
+            Sort::ExDividendDate => "ex_dividend_date",
+            Sort::PayDate => "pay_date",
+            Sort::DeclarationDate => "declaration_date",
+            Sort::RecordDate => "record_date",
+            Sort::CashAmount => "cash_amount",
+            Sort::ExecutionDate => "execution_date",
+            Sort::ExpirationDate => "expiration_date",
+            Sort::StrikePrice => "strike_price",
+            Sort::FilingDate => "filing_date",
+            Sort::PeriodOfReportDate => "period_of_report_date",
+            Sort::Other(field) => field,
+        }
+    }
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+//
+// Error responses
+//
+
+/// The body of an unsuccessful response, such as
+/// `{"status":"ERROR","request_id":"...","error":"Unknown API Key"}`.
+#[derive(Clone, Deserialize, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ErrorResponse {
+    /// The status of the response, such as `ERROR` or `NOT_AUTHORIZED`.
+    pub status: Option<String>,
+    pub request_id: Option<String>,
+    pub error: Option<String>,
+    pub message: Option<String>,
+}
+
+impl ErrorResponse {
+    /// Returns the reason given for the failure, such as `Unknown API Key`
+    /// or `exceeded maximum requests`.
+    pub fn reason(&self) -> Option<&str> {
+        self.error.as_deref().or(self.message.as_deref())
+    }
+
+    /// Returns `true` if the status of the body reports a failure, which the
+    /// server sometimes does with a successful status code.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self.status.as_deref(),
+            Some("ERROR" | "NOT_AUTHORIZED" | "NOT_FOUND")
+        )
+    }
+}
 
 //
 // v3/reference/tickers
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct ReferenceTickersResponseTickerV3 {
     pub ticker: String,
     pub name: String,
-    pub market: String,
+    pub market: AssetClass,
     pub locale: String,
     pub primary_exchange: String,
     #[serde(rename = "type")]
-    pub ticker_type: Option<String>,
+    pub ticker_type: Option<TickerType>,
     pub active: bool,
     pub currency_name: String,
     pub cik: Option<String>,
@@ -26,13 +448,25 @@ pub struct ReferenceTickersResponseTickerV3 {
     pub last_updated_utc: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[cfg(feature = "chrono-accessors")]
+impl ReferenceTickersResponseTickerV3 {
+    /// Returns `last_updated_utc` as a date and time, or `None` if it is not valid.
+    pub fn last_updated_utc(&self) -> Option<DateTime<Utc>> {
+        parse_utc_date_time(&self.last_updated_utc)
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct ReferenceTickersResponseV3 {
     pub results: Vec<ReferenceTickersResponseTickerV3>,
     pub status: String,
     pub request_id: String,
     pub count: u32,
     pub next_url: Option<String>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
 }
 
 pub type ReferenceTickersResponse = ReferenceTickersResponseV3;
@@ -41,14 +475,16 @@ pub type ReferenceTickersResponse = ReferenceTickersResponseV3;
 // v2/reference/types
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct ReferenceTickerTypesResultsV2 {
     pub types: HashMap<String, String>,
     #[serde(rename = "indexTypes")]
     pub index_types: HashMap<String, String>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct ReferenceTickerTypesResponseV2 {
     pub status: String,
     pub results: ReferenceTickerTypesResultsV2,
@@ -60,7 +496,8 @@ pub type ReferenceTickerTypesResponse = ReferenceTickerTypesResponseV2;
 // v1/meta/symbols/{stocksTicker}/company
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct ReferenceTickerDetailsResponseV1 {
     pub logo: String,
     pub exchange: String,
@@ -73,7 +510,9 @@ pub struct ReferenceTickerDetailsResponseV1 {
     pub listdate: String,
     pub cik: String,
     pub bloomberg: String,
-    pub fiji: Option<String>,
+    /// The Financial Instrument Global Identifier of the ticker.
+    #[serde(alias = "fiji")]
+    pub figi: Option<String>,
     pub sic: u32,
     pub country: String,
     pub industry: String,
@@ -92,42 +531,106 @@ pub struct ReferenceTickerDetailsResponseV1 {
     pub active: bool,
 }
 
+impl ReferenceTickerDetailsResponseV1 {
+    /// Returns the Financial Instrument Global Identifier of the ticker.
+    #[deprecated(note = "use the `figi` field")]
+    pub fn fiji(&self) -> Option<&str> {
+        self.figi.as_deref()
+    }
+}
+
 pub type ReferenceTickerDetailsResponse = ReferenceTickerDetailsResponseV1;
 
 //
 // vX/reference/tickers/{ticker}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct Address {
     pub address1: String,
     pub city: String,
     pub state: String,
+    pub postal_code: Option<String>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceTickerDetailsResultsVX {
     pub ticker: String,
     pub name: String,
-    pub market: String,
+    pub market: AssetClass,
     pub locale: String,
     pub primary_exchange: String,
     #[serde(rename = "type")]
-    pub ticker_type: String,
+    pub ticker_type: TickerType,
     pub active: bool,
     pub currency_name: String,
     pub cik: String,
-    pub composite_fiji: Option<String>,
-    pub share_class_fiji: Option<String>,
+    #[serde(alias = "composite_fiji")]
+    pub composite_figi: Option<String>,
+    #[serde(alias = "share_class_fiji")]
+    pub share_class_figi: Option<String>,
     pub last_updated_utc: String,
     pub delisted_utc: Option<String>,
-    pub outstanding_shares: f64,
+    /// The number of shares outstanding, or `None` for tickers without
+    /// shares, such as some funds.
+    #[serde(default, deserialize_with = "deserialize_share_count")]
+    pub outstanding_shares: Option<u64>,
+    /// The market capitalization in units of `currency_name`.
     pub market_cap: f64,
     pub phone_number: String,
     pub address: Address,
+    /// The Standard Industrial Classification code of the company.
+    pub sic_code: Option<String>,
+    pub sic_description: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_share_count")]
+    pub weighted_shares_outstanding: Option<u64>,
+    /// The number of shares in a round lot, usually 100.
+    pub round_lot: Option<u64>,
+}
+
+#[cfg(feature = "chrono-accessors")]
+impl ReferenceTickerDetailsResultsVX {
+    /// Returns `last_updated_utc` as a date and time, or `None` if it is not valid.
+    pub fn last_updated_utc(&self) -> Option<DateTime<Utc>> {
+        parse_utc_date_time(&self.last_updated_utc)
+    }
+
+    /// Returns `delisted_utc` as a date and time, or `None` if it is missing or
+    /// not valid.
+    pub fn delisted_utc(&self) -> Option<DateTime<Utc>> {
+        self.delisted_utc.as_deref().and_then(parse_utc_date_time)
+    }
+}
+
+impl ReferenceTickerDetailsResultsVX {
+    /// Returns the composite Financial Instrument Global Identifier.
+    #[deprecated(note = "use the `composite_figi` field")]
+    pub fn composite_fiji(&self) -> Option<&str> {
+        self.composite_figi.as_deref()
+    }
+
+    /// Returns the share class Financial Instrument Global Identifier.
+    #[deprecated(note = "use the `share_class_figi` field")]
+    pub fn share_class_fiji(&self) -> Option<&str> {
+        self.share_class_figi.as_deref()
+    }
+
+    /// Returns the market capitalization in billions of `currency_name`.
+    pub fn market_cap_billions(&self) -> f64 {
+        self.market_cap / 1e9
+    }
+
+    /// Returns the Standard Industrial Classification code as a number, or
+    /// `None` if it is missing or not numeric.
+    pub fn sic_code_number(&self) -> Option<u16> {
+        self.sic_code.as_deref()?.trim().parse().ok()
+    }
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceTickerDetailsResponseVX {
     pub results: ReferenceTickerDetailsResultsVX,
     pub status: String,
@@ -135,11 +638,121 @@ pub struct ReferenceTickerDetailsResponseVX {
     pub count: u32,
 }
 
+//
+// v3/reference/tickers/{ticker}
+//
+
+/// The logo and icon of a company. The images are served by polygon.io and
+/// require the API key to download.
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Branding {
+    pub logo_url: Option<String>,
+    pub icon_url: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ReferenceTickerDetailsResultsV3 {
+    pub ticker: String,
+    pub name: String,
+    pub market: AssetClass,
+    pub locale: String,
+    pub primary_exchange: Option<String>,
+    #[serde(rename = "type")]
+    pub ticker_type: Option<TickerType>,
+    pub active: bool,
+    pub currency_name: Option<String>,
+    pub cik: Option<String>,
+    pub composite_figi: Option<String>,
+    pub share_class_figi: Option<String>,
+    pub delisted_utc: Option<String>,
+    /// The market capitalization in units of `currency_name`.
+    pub market_cap: Option<f64>,
+    pub phone_number: Option<String>,
+    pub address: Option<Address>,
+    pub description: Option<String>,
+    /// The Standard Industrial Classification code of the company.
+    pub sic_code: Option<String>,
+    pub sic_description: Option<String>,
+    pub ticker_root: Option<String>,
+    pub ticker_suffix: Option<String>,
+    pub homepage_url: Option<String>,
+    pub total_employees: Option<u64>,
+    /// The date the ticker was first listed.
+    pub list_date: Option<NaiveDate>,
+    pub branding: Option<Branding>,
+    /// The number of shares outstanding of this share class.
+    #[serde(default, deserialize_with = "deserialize_share_count")]
+    pub share_class_shares_outstanding: Option<u64>,
+    /// The number of shares outstanding of every share class of the company,
+    /// weighted by their conversion ratios.
+    #[serde(default, deserialize_with = "deserialize_share_count")]
+    pub weighted_shares_outstanding: Option<u64>,
+    /// The number of shares in a round lot, usually 100.
+    pub round_lot: Option<u64>,
+}
+
+#[cfg(feature = "chrono-accessors")]
+impl ReferenceTickerDetailsResultsV3 {
+    /// Returns `delisted_utc` as a date and time, or `None` if it is missing or
+    /// not valid.
+    pub fn delisted_utc(&self) -> Option<DateTime<Utc>> {
+        self.delisted_utc.as_deref().and_then(parse_utc_date_time)
+    }
+}
+
+impl ReferenceTickerDetailsResultsV3 {
+    /// Returns the market capitalization in billions of `currency_name`.
+    pub fn market_cap_billions(&self) -> Option<f64> {
+        self.market_cap.map(|cap| cap / 1e9)
+    }
+
+    /// Returns the market capitalization in millions of `currency_name`.
+    pub fn market_cap_millions(&self) -> Option<f64> {
+        self.market_cap.map(|cap| cap / 1e6)
+    }
+
+    /// Returns the price per share implied by the market capitalization and
+    /// the weighted shares outstanding.
+    pub fn implied_share_price(&self) -> Option<f64> {
+        match (self.market_cap, self.weighted_shares_outstanding) {
+            (Some(cap), Some(shares)) if shares > 0 => Some(cap / shares as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the Standard Industrial Classification code as a number, or
+    /// `None` if it is missing or not numeric.
+    pub fn sic_code_number(&self) -> Option<u16> {
+        self.sic_code.as_deref()?.trim().parse().ok()
+    }
+}
+
+/// Deserializes a number of shares, which the API may send as a float, into
+/// a whole number of shares.
+fn deserialize_share_count<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let shares = Option::<f64>::deserialize(deserializer)?;
+    Ok(shares.filter(|s| *s >= 0.0).map(|s| s.round() as u64))
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ReferenceTickerDetailsResponseV3 {
+    pub results: ReferenceTickerDetailsResultsV3,
+    pub status: String,
+    pub request_id: String,
+}
+
 //
 // v2/reference/news
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct Publisher {
     pub name: String,
     pub homepage_url: String,
@@ -147,7 +760,8 @@ pub struct Publisher {
     pub favicon_url: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct ReferenceTickerNewsResultsV2 {
     pub id: String,
     pub publisher: Publisher,
@@ -160,30 +774,236 @@ pub struct ReferenceTickerNewsResultsV2 {
     pub image_url: Option<String>,
     pub description: Option<String>,
     pub keywords: Option<Vec<String>>,
+    /// The sentiment of the article towards each ticker it mentions.
+    #[serde(default)]
+    pub insights: Vec<NewsInsight>,
+}
+
+#[cfg(feature = "chrono-accessors")]
+impl ReferenceTickerNewsResultsV2 {
+    /// Returns `published_utc` as a date and time, or `None` if it is not valid.
+    pub fn published_utc(&self) -> Option<DateTime<Utc>> {
+        parse_utc_date_time(&self.published_utc)
+    }
+}
+
+impl ReferenceTickerNewsResultsV2 {
+    /// Returns the insight of the article about a ticker, if any.
+    pub fn insight(&self, ticker: &str) -> Option<&NewsInsight> {
+        self.insights.iter().find(|i| i.ticker == ticker)
+    }
+}
+
+/// The sentiment of a news article towards a ticker.
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Sentiment {
+    Positive,
+    Neutral,
+    Negative,
+    #[serde(other)]
+    Other,
+}
+
+impl fmt::Display for Sentiment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Sentiment::Positive => "positive",
+            Sentiment::Neutral => "neutral",
+            Sentiment::Negative => "negative",
+            Sentiment::Other => "other",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// An analysis of how a news article relates to one of its tickers.
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct NewsInsight {
+    pub ticker: String,
+    pub sentiment: Sentiment,
+    /// The explanation of the sentiment.
+    #[serde(default)]
+    pub sentiment_reasoning: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct ReferenceTickerNewsResponseV2 {
     pub results: Vec<ReferenceTickerNewsResultsV2>,
     pub status: String,
     pub request_id: String,
     pub count: u32,
     pub next_url: Option<String>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
 }
 
 pub type ReferenceTickerNewsResponse = ReferenceTickerNewsResponseV2;
 
+//
+// Ticker overview
+//
+
+/// An overview of a stock ticker combining several endpoints, as returned by
+/// [`RESTClient::ticker_overview()`](crate::rest::RESTClient::ticker_overview).
+///
+/// The parts are only missing when the client uses
+/// [`CompositePolicy::Partial`](crate::rest::CompositePolicy::Partial) and
+/// their requests failed, in which case `errors` lists why.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct TickerOverview {
+    pub details: Option<ReferenceTickerDetailsResultsV3>,
+    pub previous_close: Option<StockEquitiesAggregates>,
+    pub last_trade: Option<Trade>,
+    pub latest_news: Option<ReferenceTickerNewsResultsV2>,
+    pub errors: Vec<PartError>,
+}
+
+/// The error of a request made by a helper that combines several endpoints,
+/// whose part was left out of the result.
+#[derive(Clone, Debug)]
+pub struct PartError {
+    /// The name of the part, such as `details`.
+    pub part: &'static str,
+    pub error: Arc<Error>,
+}
+
+//
+// Daily series
+//
+
+/// A daily bar of a [`DailySeries`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct DailyBar {
+    pub date: NaiveDate,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub vwap: Option<f64>,
+    /// Whether the bar was filled in for a trading day without any trades,
+    /// using the previous close as its prices and a volume of zero.
+    pub filled: bool,
+}
+
+/// A continuous series of daily bars with one bar per trading day, as
+/// returned by [`RESTClient::daily_series()`](crate::rest::RESTClient::daily_series).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct DailySeries {
+    pub ticker: String,
+    pub adjustment: Adjustment,
+    pub bars: Vec<DailyBar>,
+}
+
+//
+// Movers report
+//
+
+/// A top gainer or loser of the day with its latest news headlines.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Mover {
+    pub snapshot: StockEquitiesTickerSnapshot,
+    /// The most recent articles about the ticker, newest first.
+    pub headlines: Vec<ReferenceTickerNewsResultsV2>,
+}
+
+/// The top gainers and losers of the day, as returned by
+/// [`RESTClient::movers_report()`](crate::rest::RESTClient::movers_report).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct MoversReport {
+    pub gainers: Vec<Mover>,
+    pub losers: Vec<Mover>,
+}
+
+//
+// Dividend screener
+//
+
+/// The trailing dividend yield of a stock, as returned by
+/// [`RESTClient::dividend_screener()`](crate::rest::RESTClient::dividend_screener).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct DividendYield {
+    pub ticker: String,
+    /// The previous day's close.
+    pub close: f64,
+    /// The sum of the dividends with an ex-dividend date in the past year.
+    pub trailing_dividends: f64,
+    /// The number of dividends in the past year.
+    pub dividend_count: usize,
+    /// The trailing dividends as a percentage of the close.
+    pub yield_percent: f64,
+}
+
+//
+// Today's bar
+//
+
+/// The endpoint that a [`TodayBar`] was taken from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TodayBarSource {
+    /// The `day` bar of the ticker snapshot.
+    Snapshot,
+    /// The daily bar from the aggregates endpoint.
+    Aggregates,
+}
+
+/// Today's bar of a stock, reconciled from the snapshot and aggregates
+/// endpoints, as returned by [`RESTClient::today_bar()`](crate::rest::RESTClient::today_bar).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct TodayBar {
+    /// The trading day in US Eastern time.
+    pub date: NaiveDate,
+    /// The bar from the fresher of the two sources.
+    pub bar: StockEquitiesAggregates,
+    pub source: TodayBarSource,
+    /// Whether the other source is missing the bar or lags behind it.
+    pub stale: bool,
+    /// The snapshot's bar for today, if any.
+    pub snapshot: Option<StockEquitiesAggregates>,
+    /// The aggregates endpoint's bar for today, if any.
+    pub aggregate: Option<StockEquitiesAggregates>,
+}
+
+//
+// Most active tickers
+//
+
+/// How [`RESTClient::most_active_by()`](crate::rest::RESTClient::most_active_by)
+/// ranks tickers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActivityMeasure {
+    /// The number of shares traded today.
+    Volume,
+    /// The value of the shares traded today, estimated as the day's volume
+    /// times its volume weighted average price, or its close if the snapshot
+    /// has no average price.
+    DollarVolume,
+}
+
 //
 // v2/reference/markets
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct Market {
     pub market: String,
     pub desc: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct ReferenceMarketsResponseV2 {
     pub status: String,
     pub results: Vec<Market>,
@@ -195,13 +1015,15 @@ pub type ReferenceMarketsResponse = ReferenceMarketsResponseV2;
 // v2/reference/locales
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct Locale {
     pub locale: String,
     pub name: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct ReferenceLocalesResponseV2 {
     pub status: String,
     pub results: Vec<Locale>,
@@ -213,7 +1035,8 @@ pub type ReferenceLocalesResponse = ReferenceLocalesResponseV2;
 // v2/reference/splits/{stockTicker}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceStockSplitsResultV2 {
     pub ticker: String,
     #[serde(rename = "exDate")]
@@ -227,7 +1050,26 @@ pub struct ReferenceStockSplitsResultV2 {
     pub forfactor: Option<u32>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[cfg(feature = "chrono-accessors")]
+impl ReferenceStockSplitsResultV2 {
+    /// Returns `ex_date` as a date, or `None` if it is not valid.
+    pub fn ex_date(&self) -> Option<NaiveDate> {
+        parse_date(&self.ex_date)
+    }
+
+    /// Returns `payment_date` as a date, or `None` if it is not valid.
+    pub fn payment_date(&self) -> Option<NaiveDate> {
+        parse_date(&self.payment_date)
+    }
+
+    /// Returns `declared_date` as a date, or `None` if it is missing or not valid.
+    pub fn declared_date(&self) -> Option<NaiveDate> {
+        self.declared_date.as_deref().and_then(parse_date)
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceStockSplitsResponseV2 {
     pub status: String,
     pub count: u32,
@@ -240,7 +1082,8 @@ pub type ReferenceStockSplitsResponse = ReferenceStockSplitsResponseV2;
 // v2/reference/dividends/{stocksTicker}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceStockDividendsResultV2 {
     pub ticker: String,
     #[serde(rename = "exDate")]
@@ -252,7 +1095,26 @@ pub struct ReferenceStockDividendsResultV2 {
     pub amount: f64,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[cfg(feature = "chrono-accessors")]
+impl ReferenceStockDividendsResultV2 {
+    /// Returns `ex_date` as a date, or `None` if it is not valid.
+    pub fn ex_date(&self) -> Option<NaiveDate> {
+        parse_date(&self.ex_date)
+    }
+
+    /// Returns `payment_date` as a date, or `None` if it is not valid.
+    pub fn payment_date(&self) -> Option<NaiveDate> {
+        parse_date(&self.payment_date)
+    }
+
+    /// Returns `record_date` as a date, or `None` if it is not valid.
+    pub fn record_date(&self) -> Option<NaiveDate> {
+        parse_date(&self.record_date)
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceStockDividendsResponseV2 {
     pub status: String,
     pub count: u32,
@@ -261,11 +1123,70 @@ pub struct ReferenceStockDividendsResponseV2 {
 
 pub type ReferenceStockDividendsResponse = ReferenceStockDividendsResponseV2;
 
+//
+// v3/reference/dividends
+//
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ReferenceStockDividendsResultV3 {
+    pub cash_amount: f64,
+    pub currency: Option<String>,
+    pub declaration_date: Option<String>,
+    /// `CD` for a regular cash dividend, `SC` for a special one, and `LT` or
+    /// `ST` for long-term or short-term capital gains.
+    pub dividend_type: String,
+    pub ex_dividend_date: String,
+    /// The number of times a year the dividend is paid, or 0 if it is not
+    /// recurring.
+    pub frequency: u32,
+    pub pay_date: Option<String>,
+    pub record_date: Option<String>,
+    pub ticker: String,
+}
+
+#[cfg(feature = "chrono-accessors")]
+impl ReferenceStockDividendsResultV3 {
+    /// Returns `declaration_date` as a date, or `None` if it is missing or not valid.
+    pub fn declaration_date(&self) -> Option<NaiveDate> {
+        self.declaration_date.as_deref().and_then(parse_date)
+    }
+
+    /// Returns `ex_dividend_date` as a date, or `None` if it is not valid.
+    pub fn ex_dividend_date(&self) -> Option<NaiveDate> {
+        parse_date(&self.ex_dividend_date)
+    }
+
+    /// Returns `pay_date` as a date, or `None` if it is missing or not valid.
+    pub fn pay_date(&self) -> Option<NaiveDate> {
+        self.pay_date.as_deref().and_then(parse_date)
+    }
+
+    /// Returns `record_date` as a date, or `None` if it is missing or not valid.
+    pub fn record_date(&self) -> Option<NaiveDate> {
+        self.record_date.as_deref().and_then(parse_date)
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ReferenceStockDividendsResponseV3 {
+    #[serde(default)]
+    pub results: Vec<ReferenceStockDividendsResultV3>,
+    pub status: String,
+    pub request_id: String,
+    pub next_url: Option<String>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
+}
+
 //
 // v2/reference/financials/{stocksTicker}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceStockFinancialsResultV2 {
     pub ticker: String,
     pub period: String,
@@ -475,7 +1396,8 @@ pub struct ReferenceStockFinancialsResultV2 {
     pub working_capital: Option<i64>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceStockFinancialsResponseV2 {
     pub status: String,
     pub results: Vec<ReferenceStockFinancialsResultV2>,
@@ -764,7 +1686,8 @@ lazy_static! {
     };
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct FundamentalAccountingConcept {
     pub formula: Option<String>,
     pub label: Option<String>,
@@ -772,15 +1695,21 @@ pub struct FundamentalAccountingConcept {
     pub unit: Option<String>,
     pub value: Option<f64>,
 }
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct FinancialDimensions {
+    #[serde(default)]
     pub balance_sheet: HashMap<String, FundamentalAccountingConcept>,
+    #[serde(default)]
     pub cash_flow_statement: HashMap<String, FundamentalAccountingConcept>,
+    #[serde(default)]
     pub comprehensive_income: HashMap<String, FundamentalAccountingConcept>,
+    #[serde(default)]
     pub income_statement: HashMap<String, FundamentalAccountingConcept>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceStockFinancialsVXResult {
     pub cik: String,
     pub company_name: String,
@@ -790,22 +1719,36 @@ pub struct ReferenceStockFinancialsVXResult {
     pub fiscal_year: String,
     pub source_filing_file_url: String,
     pub start_date: Option<String>,
+    /// The period covered by the filing, such as `annual`, `quarterly`, or
+    /// `ttm`.
+    pub timeframe: Option<String>,
+    pub filing_date: Option<String>,
+    /// The time the filing was accepted by the SEC.
+    pub acceptance_datetime: Option<String>,
+    /// The Standard Industrial Classification code of the company.
+    #[serde(alias = "sic")]
+    pub sic_code: Option<String>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ReferenceStockFinancialsVXResponse {
     pub count: u32,
-    pub next_url: String,
+    pub next_url: Option<String>,
     pub request_id: String,
     pub results: Vec<ReferenceStockFinancialsVXResult>,
     pub status: String,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
 }
 
 //
 // v1/marketstatus/upcoming
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct MarketStatusUpcoming {
     pub exchange: String,
     pub name: String,
@@ -821,7 +1764,8 @@ pub type ReferenceMarketStatusUpcomingResponse = Vec<MarketStatusUpcoming>;
 // v1/marketstatus/now
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct ReferenceMarketStatusNowResponseV1 {
     pub market: String,
     #[serde(rename = "earlyHours")]
@@ -840,7 +1784,8 @@ pub type ReferenceMarketStatusNowResponse = ReferenceMarketStatusNowResponseV1;
 // v1/meta/exchanges
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct StockEquitiesExchangeV1 {
     pub id: u64,
     #[serde(rename = "type")]
@@ -858,7 +1803,7 @@ pub type StockEquitiesExchangesResponse = Vec<StockEquitiesExchangeV1>;
 // v1/meta/conditions/{ticktype}
 //
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum TickType {
     Trades,
     Quotes,
@@ -876,7 +1821,8 @@ pub type StockEquitiesConditionMappingsResponse = HashMap<u32, String>;
 // v1/meta/crypto-exchanges
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub struct CryptoExchange {
     pub id: u32,
     #[serde(rename = "type")]
@@ -894,8 +1840,46 @@ pub type CryptoCryptoExchangesResponse = Vec<CryptoExchange>;
 // v2/last/trade/{ticker}
 //
 
+/// A single trade, as returned by the last trade endpoint and in snapshots.
+/// Timestamps are in Unix nanoseconds.
+///
+/// This replaces [`StockEquitiesHistoricTrade`], whose fields were named
+/// after the single-letter keys of the response.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Trade {
+    #[serde(rename = "T")]
+    pub ticker: Option<String>,
+    #[serde(rename = "c")]
+    pub conditions: Option<Vec<u64>>,
+    #[serde(rename = "e")]
+    pub correction: Option<u64>,
+    #[serde(rename = "f")]
+    pub trf_timestamp: Option<u64>,
+    #[serde(rename = "i")]
+    pub id: Option<String>,
+    #[serde(rename = "p")]
+    pub price: Option<f64>,
+    #[serde(rename = "q")]
+    pub sequence_number: Option<u64>,
+    #[serde(rename = "r")]
+    pub trf_id: Option<u64>,
+    #[serde(rename = "s")]
+    pub size: Option<f64>,
+    #[serde(rename = "t")]
+    pub sip_timestamp: Option<u64>,
+    #[serde(rename = "x")]
+    pub exchange: Option<u64>,
+    #[serde(rename = "y")]
+    pub participant_timestamp: Option<u64>,
+    #[serde(rename = "z")]
+    pub tape: Option<u64>,
+}
+
+#[deprecated(note = "use `Trade`, whose fields have descriptive names")]
 #[allow(non_snake_case)]
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesHistoricTrade {
     pub T: Option<String>,
     pub f: Option<u64>,
@@ -912,11 +1896,12 @@ pub struct StockEquitiesHistoricTrade {
     pub z: Option<u64>,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesHistoricTradesV2Response {
     pub request_id: String,
     pub status: String,
-    pub results: StockEquitiesHistoricTrade,
+    pub results: Trade,
 }
 
 pub type StockEquitiesHistoricTradesResponse = StockEquitiesHistoricTradesV2Response;
@@ -925,42 +1910,191 @@ pub type StockEquitiesHistoricTradesResponse = StockEquitiesHistoricTradesV2Resp
 // v2/last/nbbo/{ticker}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+/// A single NBBO quote, as returned by the last quote endpoint and in
+/// snapshots. Timestamps are in Unix nanoseconds.
+///
+/// This replaces [`StockEquitiesQuote`], whose fields were named after the
+/// single-letter keys of the response.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Quote {
+    #[serde(rename = "T")]
+    pub ticker: Option<String>,
+    #[serde(rename = "P")]
+    pub ask_price: Option<f64>,
+    #[serde(rename = "S")]
+    pub ask_size: Option<f64>,
+    #[serde(rename = "X")]
+    pub ask_exchange: Option<u64>,
+    #[serde(rename = "p")]
+    pub bid_price: Option<f64>,
+    #[serde(rename = "s")]
+    pub bid_size: Option<f64>,
+    #[serde(rename = "x")]
+    pub bid_exchange: Option<u64>,
+    #[serde(rename = "c")]
+    pub conditions: Option<Vec<u64>>,
+    #[serde(rename = "i")]
+    pub indicators: Option<Vec<u64>>,
+    #[serde(rename = "f")]
+    pub trf_timestamp: Option<u64>,
+    #[serde(rename = "q")]
+    pub sequence_number: Option<u64>,
+    #[serde(rename = "t")]
+    pub sip_timestamp: Option<u64>,
+    #[serde(rename = "y")]
+    pub participant_timestamp: Option<u64>,
+    #[serde(rename = "z")]
+    pub tape: Option<u64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesLastQuoteForASymbolV2Response {
     pub request_id: String,
     pub status: String,
-    pub results: StockEquitiesHistoricTrade,
+    pub results: Quote,
 }
 
 pub type StockEquitiesLastQuoteForASymbolResponse = StockEquitiesLastQuoteForASymbolV2Response;
 
 //
-// v1/open-close/{ticker}/{date}
+// v3/trades/{stocksTicker}
 //
 
-#[derive(Clone, Deserialize, Debug)]
-pub struct StockEquitiesDailyOpenCloseResponse {
-    #[serde(rename = "afterHours")]
-    pub after_hours: f64,
-    pub close: f64,
-    pub from: String,
-    pub high: f64,
-    pub low: f64,
-    pub open: f64,
-    #[serde(rename = "preMarket")]
-    pub pre_market: f64,
-    pub status: String,
-    pub symbol: String,
-    pub volume: f64,
+/// A single trade. Timestamps are in Unix nanoseconds.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct StockEquitiesTradeV3 {
+    #[serde(default)]
+    pub conditions: Vec<u64>,
+    pub correction: Option<u64>,
+    pub exchange: u64,
+    pub id: String,
+    pub participant_timestamp: Option<u64>,
+    pub price: f64,
+    pub sequence_number: u64,
+    pub sip_timestamp: u64,
+    pub size: f64,
+    pub tape: Option<u64>,
+    pub trf_id: Option<u64>,
+    pub trf_timestamp: Option<u64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct StockEquitiesTradesResponseV3 {
+    pub request_id: String,
+    pub status: String,
+    #[serde(default)]
+    pub results: Vec<StockEquitiesTradeV3>,
+    pub next_url: Option<String>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
+}
+
+//
+// v3/quotes/{stocksTicker}
+//
+
+/// A single NBBO quote. Timestamps are in Unix nanoseconds.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct StockEquitiesQuoteV3 {
+    pub ask_exchange: Option<u64>,
+    pub ask_price: f64,
+    pub ask_size: f64,
+    pub bid_exchange: Option<u64>,
+    pub bid_price: f64,
+    pub bid_size: f64,
+    #[serde(default)]
+    pub conditions: Vec<u64>,
+    #[serde(default)]
+    pub indicators: Vec<u64>,
+    pub participant_timestamp: Option<u64>,
+    pub sequence_number: u64,
+    pub sip_timestamp: u64,
+    pub tape: Option<u64>,
+    pub trf_timestamp: Option<u64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct StockEquitiesQuotesResponseV3 {
+    pub request_id: String,
+    pub status: String,
+    #[serde(default)]
+    pub results: Vec<StockEquitiesQuoteV3>,
+    pub next_url: Option<String>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
+}
+
+//
+// v1/open-close/{ticker}/{date}
+//
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct StockEquitiesDailyOpenCloseResponse {
+    #[serde(rename = "afterHours")]
+    pub after_hours: f64,
+    pub close: f64,
+    pub from: String,
+    pub high: f64,
+    pub low: f64,
+    pub open: f64,
+    #[serde(rename = "preMarket")]
+    pub pre_market: f64,
+    pub status: String,
+    pub symbol: String,
+    pub volume: f64,
 }
 
 //
 // v2/aggs/ticker/{ticker}/range/{multiplier}/{timespan}/{from}/{to}
 //
 
+/// The unit of the window of each aggregate bar, which is `multiplier` units
+/// long.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Timespan {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl fmt::Display for Timespan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Timespan::Second => "second",
+            Timespan::Minute => "minute",
+            Timespan::Hour => "hour",
+            Timespan::Day => "day",
+            Timespan::Week => "week",
+            Timespan::Month => "month",
+            Timespan::Quarter => "quarter",
+            Timespan::Year => "year",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// An aggregate bar of a ticker in any asset class, as returned by the
+/// aggregates, grouped daily, and previous close endpoints of stocks, options,
+/// forex, crypto, and indices.
 #[allow(non_snake_case)]
-#[derive(Clone, Deserialize, Debug)]
-pub struct StockEquitiesAggregates {
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Aggregate {
     pub T: Option<String>,
     pub av: Option<u64>,
     pub c: f64,
@@ -971,9 +2105,42 @@ pub struct StockEquitiesAggregates {
     pub t: Option<u64>,
     pub v: f64,
     pub vw: Option<f64>,
+    /// Whether the ticker trades over the counter. Only included in grouped
+    /// daily bars requested with OTC tickers.
+    #[serde(default)]
+    pub otc: bool,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+impl Aggregate {
+    /// Returns a bar starting at `t`, in Unix milliseconds, with the given
+    /// prices and volume. The remaining fields are empty and can be set
+    /// directly.
+    pub fn new(t: u64, o: f64, h: f64, l: f64, c: f64, v: f64) -> Self {
+        Aggregate {
+            T: None,
+            av: None,
+            c,
+            h,
+            l,
+            n: None,
+            o,
+            t: Some(t),
+            v,
+            vw: None,
+            otc: false,
+        }
+    }
+}
+
+/// An aggregate bar of a ticker in any asset class, as returned by
+/// [`RESTClient::aggregates()`](crate::rest::RESTClient::aggregates).
+pub type Bar = Aggregate;
+
+/// An aggregate bar of a stock, which is an [`Aggregate`].
+pub type StockEquitiesAggregates = Aggregate;
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesAggregatesResponse {
     pub ticker: String,
     pub adjusted: bool,
@@ -982,16 +2149,27 @@ pub struct StockEquitiesAggregatesResponse {
     pub request_id: String,
     #[serde(rename = "resultsCount")]
     pub results_count: u32,
+    #[serde(default)]
     pub count: u32,
     pub status: String,
-    pub results: Vec<StockEquitiesAggregates>,
+    #[serde(default)]
+    pub results: Vec<Aggregate>,
+    pub next_url: Option<String>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
 }
 
+/// The aggregate bars of a ticker in any asset class, as returned by
+/// [`RESTClient::aggregates()`](crate::rest::RESTClient::aggregates).
+pub type AggregatesResponse = StockEquitiesAggregatesResponse;
+
 //
 // v2/aggs/grouped/locale/{locale}/market/{market}/{date}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesGroupedDailyResponse {
     pub adjusted: bool,
     #[serde(rename = "queryCount")]
@@ -999,14 +2177,15 @@ pub struct StockEquitiesGroupedDailyResponse {
     #[serde(rename = "resultsCount")]
     pub results_count: u32,
     pub status: String,
-    pub results: Vec<StockEquitiesAggregates>,
+    pub results: Vec<Aggregate>,
 }
 
 //
 // v2/aggs/ticker/{ticker}/prev
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesPreviousCloseResponse {
     pub ticker: String,
     pub adjusted: bool,
@@ -1016,15 +2195,17 @@ pub struct StockEquitiesPreviousCloseResponse {
     pub results_count: u32,
     pub count: u32,
     pub status: String,
-    pub results: Vec<StockEquitiesAggregates>,
+    pub results: Vec<Aggregate>,
 }
 
 //
 // v2/snapshot/locale/{locale}/markets/{market}/tickers
 //
 
+#[deprecated(note = "use `Quote`, whose fields have descriptive names")]
 #[allow(non_snake_case)]
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesQuote {
     pub P: f64,
     pub S: u64,
@@ -1033,14 +2214,48 @@ pub struct StockEquitiesQuote {
     pub t: u64,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+/// The most recent minute bar of a ticker snapshot.
+///
+/// Unlike daily bars, the minute bar carries the volume accumulated over the
+/// whole day so far. The bar is empty outside of trading hours, in which case
+/// `timestamp` is `None` and the prices and volume are zero.
+#[derive(Clone, Default, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct SnapshotMinuteBar {
+    #[serde(rename = "o", default)]
+    pub open: f64,
+    #[serde(rename = "h", default)]
+    pub high: f64,
+    #[serde(rename = "l", default)]
+    pub low: f64,
+    #[serde(rename = "c", default)]
+    pub close: f64,
+    /// The volume traded during the minute.
+    #[serde(rename = "v", default)]
+    pub volume: f64,
+    /// The volume traded during the day up to the end of the minute.
+    #[serde(rename = "av")]
+    pub accumulated_volume: Option<f64>,
+    #[serde(rename = "vw")]
+    pub vwap: Option<f64>,
+    /// The number of transactions during the minute.
+    #[serde(rename = "n")]
+    pub transactions: Option<u64>,
+    /// The start of the minute in Unix milliseconds.
+    #[serde(rename = "t")]
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesTickerSnapshot {
     pub day: StockEquitiesAggregates,
     #[serde(rename = "lastQuote")]
-    pub last_quote: StockEquitiesQuote,
+    pub last_quote: Quote,
     #[serde(rename = "lastTrade")]
-    pub last_trade: StockEquitiesHistoricTrade,
-    pub min: StockEquitiesAggregates,
+    pub last_trade: Trade,
+    #[serde(default)]
+    pub min: SnapshotMinuteBar,
     #[serde(rename = "prevDay")]
     pub prev_day: StockEquitiesAggregates,
     pub ticker: String,
@@ -1051,7 +2266,8 @@ pub struct StockEquitiesTickerSnapshot {
     pub updated: u64,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesSnapshotAllTickersResponse {
     pub count: u32,
     pub status: String,
@@ -1062,7 +2278,8 @@ pub struct StockEquitiesSnapshotAllTickersResponse {
 // v2/snapshot/locale/us/markets/stocks/{direction}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct StockEquitiesSnapshotGainersLosersResponse {
     pub status: String,
     pub tickers: Vec<StockEquitiesTickerSnapshot>,
@@ -1072,45 +2289,50 @@ pub struct StockEquitiesSnapshotGainersLosersResponse {
 // v2/aggs/ticker/{ticker}/range/{multiplier}/{timespan}/{from}/{to}
 //
 
-#[allow(non_snake_case)]
-#[derive(Clone, Deserialize, Debug)]
-pub struct ForexEquitiesAggregates {
-    pub T: Option<String>,
-    pub c: f64,
-    pub h: f64,
-    pub l: f64,
-    pub n: Option<f64>,
-    pub o: f64,
-    pub t: Option<u64>,
-    pub v: f64,
-    pub vw: Option<f64>,
-}
+/// An aggregate bar of a forex pair, which is an [`Aggregate`].
+pub type ForexEquitiesAggregates = Aggregate;
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ForexCurrenciesAggregatesResponse {
     pub ticker: String,
     #[serde(rename = "queryCount")]
     pub query_count: u32,
     #[serde(rename = "resultsCount")]
     pub results_count: u32,
-    pub results: Vec<ForexEquitiesAggregates>,
+    pub results: Vec<Aggregate>,
     pub status: String,
     pub request_id: String,
     pub count: u32,
 }
 
+impl From<AggregatesResponse> for ForexCurrenciesAggregatesResponse {
+    fn from(resp: AggregatesResponse) -> Self {
+        ForexCurrenciesAggregatesResponse {
+            ticker: resp.ticker,
+            query_count: resp.query_count,
+            results_count: resp.results_count,
+            results: resp.results,
+            status: resp.status,
+            request_id: resp.request_id,
+            count: resp.count,
+        }
+    }
+}
+
 //
 // v2/aggs/grouped/locale/global/market/fx/{date}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ForexCurrenciesGroupedDailyResponse {
     #[serde(rename = "queryCount")]
     pub query_count: u32,
     #[serde(rename = "resultsCount")]
     pub results_count: u32,
     pub adjusted: bool,
-    pub results: Vec<ForexEquitiesAggregates>,
+    pub results: Vec<Aggregate>,
     pub status: String,
     pub request_id: String,
     pub count: u32,
@@ -1120,7 +2342,8 @@ pub struct ForexCurrenciesGroupedDailyResponse {
 // v2/aggs/ticker/{forex_ticker}/prev
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct ForexCurrenciesPreviousCloseResponse {
     pub ticker: String,
     #[serde(rename = "queryCount")]
@@ -1128,7 +2351,7 @@ pub struct ForexCurrenciesPreviousCloseResponse {
     #[serde(rename = "resultsCount")]
     pub results_count: u32,
     pub adjusted: bool,
-    pub results: Vec<ForexEquitiesAggregates>,
+    pub results: Vec<Aggregate>,
     pub status: String,
     pub request_id: String,
     pub count: u32,
@@ -1138,7 +2361,8 @@ pub struct ForexCurrenciesPreviousCloseResponse {
 // v1/open-close/crypto/{from}/{to}/{date}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct CryptoOpenTrades {
     pub x: u32,
     pub p: f64,
@@ -1148,7 +2372,8 @@ pub struct CryptoOpenTrades {
     pub t: u64,
 }
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct CryptoDailyOpenCloseResponse {
     pub symbol: String,
     #[serde(rename = "isUTC")]
@@ -1164,45 +2389,50 @@ pub struct CryptoDailyOpenCloseResponse {
 // v2/aggs/ticker/{cryptoTicker}/range/{multiplier}/{timespan}/{from}/{to}
 //
 
-#[allow(non_snake_case)]
-#[derive(Clone, Deserialize, Debug)]
-pub struct CryptoAggregates {
-    pub T: Option<String>,
-    pub c: f64,
-    pub h: f64,
-    pub l: f64,
-    pub n: Option<f64>,
-    pub o: f64,
-    pub t: Option<u64>,
-    pub v: f64,
-    pub vw: Option<f64>,
-}
+/// An aggregate bar of a crypto pair, which is an [`Aggregate`].
+pub type CryptoAggregates = Aggregate;
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct CryptoAggregatesResponse {
     pub ticker: String,
     #[serde(rename = "queryCount")]
     pub query_count: u32,
     #[serde(rename = "resultsCount")]
     pub results_count: u32,
-    pub results: Vec<CryptoAggregates>,
+    pub results: Vec<Aggregate>,
     pub status: String,
     pub request_id: String,
     pub count: u32,
 }
 
+impl From<AggregatesResponse> for CryptoAggregatesResponse {
+    fn from(resp: AggregatesResponse) -> Self {
+        CryptoAggregatesResponse {
+            ticker: resp.ticker,
+            query_count: resp.query_count,
+            results_count: resp.results_count,
+            results: resp.results,
+            status: resp.status,
+            request_id: resp.request_id,
+            count: resp.count,
+        }
+    }
+}
+
 //
 // v2/aggs/grouped/locale/global/market/crypto/{date}
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct CryptoGroupedDailyResponse {
     #[serde(rename = "queryCount")]
     pub query_count: u32,
     #[serde(rename = "resultsCount")]
     pub results_count: u32,
     pub adjusted: bool,
-    pub results: Vec<CryptoAggregates>,
+    pub results: Vec<Aggregate>,
     pub status: String,
     pub request_id: String,
     pub count: u32,
@@ -1212,7 +2442,8 @@ pub struct CryptoGroupedDailyResponse {
 // v2/aggs/ticker/{crypto_ticker}/prev
 //
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
 pub struct CryptoPreviousCloseResponse {
     pub ticker: String,
     #[serde(rename = "queryCount")]
@@ -1220,8 +2451,924 @@ pub struct CryptoPreviousCloseResponse {
     #[serde(rename = "resultsCount")]
     pub results_count: u32,
     pub adjusted: bool,
-    pub results: Vec<CryptoAggregates>,
+    pub results: Vec<Aggregate>,
     pub status: String,
     pub request_id: String,
     pub count: u32,
 }
+
+//
+// v3/quotes/{fxTicker}
+//
+
+/// A single forex BBO quote. Timestamps are in Unix nanoseconds.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ForexQuoteV3 {
+    pub ask_exchange: Option<u64>,
+    pub ask_price: f64,
+    pub bid_exchange: Option<u64>,
+    pub bid_price: f64,
+    pub participant_timestamp: u64,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ForexQuotesResponseV3 {
+    pub request_id: String,
+    pub status: String,
+    #[serde(default)]
+    pub results: Vec<ForexQuoteV3>,
+    pub next_url: Option<String>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
+}
+
+//
+// v1/last_quote/currencies/{from}/{to}
+//
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ForexLastQuote {
+    pub ask: f64,
+    pub bid: f64,
+    pub exchange: Option<u64>,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ForexLastQuoteResponse {
+    pub last: ForexLastQuote,
+    pub request_id: String,
+    pub status: String,
+    pub symbol: String,
+}
+
+//
+// v1/last/crypto/{from}/{to}
+//
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct CryptoLastTrade {
+    pub conditions: Option<Vec<u64>>,
+    pub exchange: Option<u64>,
+    pub price: f64,
+    pub size: f64,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct CryptoLastTradeResponse {
+    pub last: CryptoLastTrade,
+    pub request_id: String,
+    pub status: String,
+    pub symbol: String,
+}
+
+//
+// v3/trades/{cryptoTicker}
+//
+
+/// A single crypto trade. Timestamps are in Unix nanoseconds.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct CryptoTradeV3 {
+    #[serde(default)]
+    pub conditions: Vec<u64>,
+    pub exchange: u64,
+    pub id: String,
+    pub participant_timestamp: u64,
+    pub price: f64,
+    pub size: f64,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct CryptoTradesResponseV3 {
+    pub request_id: String,
+    pub status: String,
+    #[serde(default)]
+    pub results: Vec<CryptoTradeV3>,
+    pub next_url: Option<String>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
+}
+
+//
+// v2/snapshot/locale/global/markets/crypto/tickers/{ticker}/book
+//
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct CryptoL2BookLevel {
+    pub p: f64,
+    /// The size at the price, keyed by exchange ID.
+    pub x: HashMap<String, f64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct CryptoL2Book {
+    pub ticker: String,
+    #[serde(rename = "bidCount")]
+    pub bid_count: Option<f64>,
+    #[serde(rename = "askCount")]
+    pub ask_count: Option<f64>,
+    pub spread: Option<f64>,
+    pub updated: Option<u64>,
+    #[serde(default)]
+    pub bids: Vec<CryptoL2BookLevel>,
+    #[serde(default)]
+    pub asks: Vec<CryptoL2BookLevel>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct CryptoL2BookResponse {
+    pub status: String,
+    pub data: CryptoL2Book,
+}
+
+//
+// Last price
+//
+
+/// The most recent trade of a ticker, as part of a [`LastPrice`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct LastTradePrice {
+    pub ticker: String,
+    pub price: f64,
+    pub size: Option<f64>,
+    pub exchange: Option<u64>,
+    /// The time of the trade in Unix milliseconds.
+    pub timestamp: u64,
+}
+
+/// The most recent quote of a ticker, as part of a [`LastPrice`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct LastQuotePrice {
+    pub ticker: String,
+    pub bid: f64,
+    pub ask: f64,
+    pub exchange: Option<u64>,
+    /// The time of the quote in Unix milliseconds.
+    pub timestamp: u64,
+}
+
+impl LastQuotePrice {
+    /// Returns the midpoint between the bid and ask prices.
+    pub fn midpoint(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+}
+
+/// The most recent price of a ticker, as returned by
+/// [`RESTClient::last_price()`](crate::rest::RESTClient::last_price).
+#[derive(Clone, Debug, PartialEq)]
+pub enum LastPrice {
+    Stock(LastTradePrice),
+    Option(LastTradePrice),
+    Crypto(LastTradePrice),
+    Forex(LastQuotePrice),
+}
+
+impl LastPrice {
+    /// Returns the asset class of the ticker.
+    pub fn asset_class(&self) -> AssetClass {
+        match self {
+            LastPrice::Stock(_) => AssetClass::Stocks,
+            LastPrice::Option(_) => AssetClass::Options,
+            LastPrice::Crypto(_) => AssetClass::Crypto,
+            LastPrice::Forex(_) => AssetClass::Forex,
+        }
+    }
+
+    /// Returns the ticker.
+    pub fn ticker(&self) -> &str {
+        match self {
+            LastPrice::Stock(t) | LastPrice::Option(t) | LastPrice::Crypto(t) => &t.ticker,
+            LastPrice::Forex(q) => &q.ticker,
+        }
+    }
+
+    /// Returns the price of the last trade or, for forex, the midpoint of the
+    /// last quote.
+    pub fn price(&self) -> f64 {
+        match self {
+            LastPrice::Stock(t) | LastPrice::Option(t) | LastPrice::Crypto(t) => t.price,
+            LastPrice::Forex(q) => q.midpoint(),
+        }
+    }
+
+    /// Returns the time of the last trade or quote in Unix milliseconds.
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            LastPrice::Stock(t) | LastPrice::Option(t) | LastPrice::Crypto(t) => t.timestamp,
+            LastPrice::Forex(q) => q.timestamp,
+        }
+    }
+}
+
+//
+// v3/snapshot/options/{underlyingAsset}
+//
+
+/// The type of an options contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionsContractType {
+    Call,
+    Put,
+    #[serde(other)]
+    Other,
+}
+
+impl fmt::Display for OptionsContractType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            OptionsContractType::Call => "call",
+            OptionsContractType::Put => "put",
+            OptionsContractType::Other => "other",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsContractDay {
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+    pub close: Option<f64>,
+    pub high: Option<f64>,
+    pub last_updated: Option<u64>,
+    pub low: Option<f64>,
+    pub open: Option<f64>,
+    pub previous_close: Option<f64>,
+    pub volume: Option<f64>,
+    pub vwap: Option<f64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsContractDetails {
+    pub contract_type: OptionsContractType,
+    pub exercise_style: Option<String>,
+    pub expiration_date: String,
+    pub shares_per_contract: Option<f64>,
+    pub strike_price: f64,
+    pub ticker: String,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsContractGreeks {
+    pub delta: Option<f64>,
+    pub gamma: Option<f64>,
+    pub theta: Option<f64>,
+    pub vega: Option<f64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsContractLastQuote {
+    pub ask: Option<f64>,
+    pub ask_size: Option<f64>,
+    pub bid: Option<f64>,
+    pub bid_size: Option<f64>,
+    pub last_updated: Option<u64>,
+    pub midpoint: Option<f64>,
+    pub timeframe: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsContractLastTrade {
+    pub conditions: Option<Vec<u64>>,
+    pub exchange: Option<u64>,
+    pub price: Option<f64>,
+    pub sip_timestamp: Option<u64>,
+    pub size: Option<f64>,
+    pub timeframe: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsUnderlyingAsset {
+    pub change_to_break_even: Option<f64>,
+    pub last_updated: Option<u64>,
+    pub price: Option<f64>,
+    pub ticker: String,
+    pub timeframe: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsContractSnapshot {
+    pub break_even_price: Option<f64>,
+    pub day: Option<OptionsContractDay>,
+    pub details: OptionsContractDetails,
+    pub greeks: Option<OptionsContractGreeks>,
+    pub implied_volatility: Option<f64>,
+    pub last_quote: Option<OptionsContractLastQuote>,
+    pub last_trade: Option<OptionsContractLastTrade>,
+    pub open_interest: Option<f64>,
+    pub underlying_asset: Option<OptionsUnderlyingAsset>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsChainSnapshotResponse {
+    pub request_id: String,
+    pub status: String,
+    pub next_url: Option<String>,
+    pub results: Vec<OptionsContractSnapshot>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
+}
+
+//
+// v3/reference/options/contracts
+//
+
+/// An options contract as listed by the reference API, without market data.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsContract {
+    pub ticker: String,
+    pub underlying_ticker: String,
+    pub contract_type: OptionsContractType,
+    pub exercise_style: Option<String>,
+    pub expiration_date: String,
+    pub strike_price: f64,
+    pub shares_per_contract: Option<f64>,
+    pub primary_exchange: Option<String>,
+    pub cfi: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsContractsResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<OptionsContract>,
+    pub next_url: Option<String>,
+}
+
+//
+// Options chain
+//
+
+/// The calls and puts at a single strike price of an [`OptionsChain`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsChainStrike<C = OptionsContractSnapshot> {
+    pub strike_price: f64,
+    pub call: Option<C>,
+    pub put: Option<C>,
+}
+
+/// The strikes of an [`OptionsChain`] that share an expiration date, sorted by
+/// strike price.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsChainExpiration<C = OptionsContractSnapshot> {
+    pub expiration_date: String,
+    pub strikes: Vec<OptionsChainStrike<C>>,
+}
+
+/// An options chain grouped by expiration date and then by strike price, both
+/// in ascending order.
+///
+/// The current chain holds a snapshot of each contract. A chain reconstructed
+/// for a past date by
+/// [`RESTClient::options_chain_as_of()`](crate::rest::RESTClient::options_chain_as_of)
+/// holds an [`OptionsContractAsOf`] instead.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsChain<C = OptionsContractSnapshot> {
+    pub underlying_ticker: String,
+    /// The price of the underlying asset, if it is known. This is the price
+    /// in the snapshot for the current chain, and the close on the as-of
+    /// date for a past chain.
+    pub underlying_price: Option<f64>,
+    pub expirations: Vec<OptionsChainExpiration<C>>,
+}
+
+/// An options contract as it was listed on a past date, along with its daily
+/// bar on that date.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct OptionsContractAsOf {
+    pub details: OptionsContract,
+    /// The daily bar of the contract, or `None` if it did not trade that day.
+    pub bar: Option<Bar>,
+}
+
+impl OptionsContractAsOf {
+    /// Returns the close of the contract's daily bar, which approximates its
+    /// settlement price, or `None` if the contract did not trade that day.
+    pub fn settlement_price(&self) -> Option<f64> {
+        self.bar.as_ref().map(|bar| bar.c)
+    }
+}
+
+//
+// fed/v1/inflation
+//
+
+/// Monthly inflation measures. Values are index levels or percentages as
+/// published, and are `None` where the source has not published them.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct EconomyInflation {
+    pub date: NaiveDate,
+    /// The Consumer Price Index for All Urban Consumers.
+    pub cpi: Option<f64>,
+    /// The CPI excluding food and energy.
+    pub cpi_core: Option<f64>,
+    /// The year over year change in the CPI, in percent.
+    pub cpi_year_over_year: Option<f64>,
+    /// The Personal Consumption Expenditures price index.
+    pub pce: Option<f64>,
+    /// The PCE price index excluding food and energy.
+    pub pce_core: Option<f64>,
+    /// Personal consumption expenditures, in billions of dollars.
+    pub pce_spending: Option<f64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct EconomyInflationResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<EconomyInflation>,
+    pub next_url: Option<String>,
+}
+
+//
+// fed/v1/inflation-expectations
+//
+
+/// Monthly inflation expectations derived from market prices and from the
+/// Federal Reserve Bank of Cleveland's model, in percent per year.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct EconomyInflationExpectations {
+    pub date: NaiveDate,
+    pub market_5_year: Option<f64>,
+    pub market_10_year: Option<f64>,
+    /// The expected average inflation over the five years starting five
+    /// years from now.
+    pub forward_years_5_to_10: Option<f64>,
+    pub model_1_year: Option<f64>,
+    pub model_5_year: Option<f64>,
+    pub model_10_year: Option<f64>,
+    pub model_30_year: Option<f64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct EconomyInflationExpectationsResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<EconomyInflationExpectations>,
+    pub next_url: Option<String>,
+}
+
+//
+// fed/v1/treasury-yields
+//
+
+/// Daily market yields of US Treasury securities at constant maturities, in
+/// percent.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct EconomyTreasuryYields {
+    pub date: NaiveDate,
+    pub yield_1_month: Option<f64>,
+    pub yield_3_month: Option<f64>,
+    pub yield_6_month: Option<f64>,
+    pub yield_1_year: Option<f64>,
+    pub yield_2_year: Option<f64>,
+    pub yield_3_year: Option<f64>,
+    pub yield_5_year: Option<f64>,
+    pub yield_7_year: Option<f64>,
+    pub yield_10_year: Option<f64>,
+    pub yield_20_year: Option<f64>,
+    pub yield_30_year: Option<f64>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct EconomyTreasuryYieldsResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<EconomyTreasuryYields>,
+    pub next_url: Option<String>,
+}
+
+//
+// futures/vX/products
+//
+
+/// A futures product, such as the E-mini S&P 500, of which individual
+/// contracts are listed for different delivery months.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FuturesProduct {
+    /// The code identifying the product on its exchange, such as `ES`.
+    pub product_code: String,
+    pub name: String,
+    /// The MIC code of the exchange on which the product trades, such as
+    /// `XCME`.
+    pub trading_venue: Option<String>,
+    #[serde(rename = "type")]
+    pub product_type: Option<String>,
+    pub asset_class: Option<String>,
+    pub asset_sub_class: Option<String>,
+    pub sector: Option<String>,
+    pub sub_sector: Option<String>,
+    /// The currency in which the product is quoted and traded.
+    pub trade_currency_code: Option<String>,
+    /// The currency in which the product is settled.
+    pub settlement_currency_code: Option<String>,
+    /// How contracts are settled, such as `cash` or `physical`.
+    pub settlement_method: Option<String>,
+    pub settlement_type: Option<String>,
+    pub price_quotation: Option<String>,
+    /// The unit of the underlying, such as `barrels`.
+    pub unit: Option<String>,
+    /// The number of units of the underlying in one contract.
+    pub unit_quantity: Option<f64>,
+    pub last_updated: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FuturesProductsResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<FuturesProduct>,
+    pub next_url: Option<String>,
+}
+
+//
+// futures/vX/contracts
+//
+
+/// A futures contract, which is a product, such as the E-mini S&P 500, for
+/// delivery in a particular month.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FuturesContract {
+    /// The ticker of the contract, such as `ESZ4`.
+    pub ticker: String,
+    /// The code of the product, such as `ES`.
+    pub product_code: String,
+    /// The MIC code of the exchange on which the contract trades.
+    pub trading_venue: Option<String>,
+    /// Whether the contract is a `single` contract or a `combo` of several
+    /// contracts.
+    #[serde(rename = "type")]
+    pub contract_type: Option<String>,
+    #[serde(default)]
+    pub active: bool,
+    pub first_trade_date: Option<NaiveDate>,
+    pub last_trade_date: Option<NaiveDate>,
+    pub settlement_date: Option<NaiveDate>,
+    /// How the contract is settled, such as `cash` or `physical`.
+    pub settlement_type: Option<String>,
+    pub days_to_maturity: Option<u32>,
+    pub group_code: Option<String>,
+    pub min_order_quantity: Option<u32>,
+    pub max_order_quantity: Option<u32>,
+    pub trade_tick_size: Option<f64>,
+    pub settlement_tick_size: Option<f64>,
+}
+
+impl FuturesContract {
+    /// Returns the delivery month of the contract, from 1 for January to 12
+    /// for December, as given by the month code that follows the product code
+    /// in the ticker, such as `Z` in `ESZ4`.
+    ///
+    /// Returns `None` for tickers that do not have a month code, such as
+    /// combos.
+    pub fn contract_month(&self) -> Option<u32> {
+        let code = self
+            .ticker
+            .strip_prefix(&self.product_code)?
+            .chars()
+            .next()?;
+        let month = match code {
+            'F' => 1,
+            'G' => 2,
+            'H' => 3,
+            'J' => 4,
+            'K' => 5,
+            'M' => 6,
+            'N' => 7,
+            'Q' => 8,
+            'U' => 9,
+            'V' => 10,
+            'X' => 11,
+            'Z' => 12,
+            _ => return None,
+        };
+        Some(month)
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FuturesContractsResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<FuturesContract>,
+    pub next_url: Option<String>,
+}
+
+//
+// futures/vX/trades/{ticker}
+//
+
+/// A single trade of a futures contract.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FuturesTrade {
+    pub ticker: String,
+    pub price: f64,
+    pub size: f64,
+    /// The time of the trade in Unix nanoseconds.
+    pub timestamp: u64,
+    /// The last day of the trading session the trade belongs to, which can
+    /// differ from the calendar date of `timestamp` for overnight sessions.
+    pub session_end_date: Option<NaiveDate>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FuturesTradesResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<FuturesTrade>,
+    pub next_url: Option<String>,
+}
+
+//
+// futures/vX/quotes/{ticker}
+//
+
+/// A single top of book quote of a futures contract. Timestamps are in Unix
+/// nanoseconds.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FuturesQuote {
+    pub ticker: String,
+    pub bid_price: Option<f64>,
+    pub bid_size: Option<f64>,
+    /// The time the bid was last updated.
+    pub bid_timestamp: Option<u64>,
+    pub ask_price: Option<f64>,
+    pub ask_size: Option<f64>,
+    /// The time the ask was last updated.
+    pub ask_timestamp: Option<u64>,
+    /// The time of the quote.
+    pub timestamp: u64,
+    /// The last day of the trading session the quote belongs to.
+    pub session_end_date: Option<NaiveDate>,
+}
+
+impl FuturesQuote {
+    /// Returns the difference between the ask and the bid, or `None` if
+    /// either side of the book is empty.
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.ask_price? - self.bid_price?)
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FuturesQuotesResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<FuturesQuote>,
+    pub next_url: Option<String>,
+}
+
+//
+// benzinga/v1/guidance
+//
+
+/// When a company released guidance relative to the regular trading session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReleaseTiming {
+    /// Before the 9:30 Eastern open.
+    PreMarket,
+    /// During the regular session.
+    DuringMarket,
+    /// At or after the 16:00 Eastern close.
+    AfterMarket,
+}
+
+/// Earnings per share and revenue guidance issued by a company for a fiscal
+/// period, as reported by Benzinga.
+///
+/// Guidance is given as a range from the `min_` to the `max_` value, or as a
+/// single estimate. The `previous_` values are those of the guidance this
+/// record revises, if any.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct BenzingaGuidance {
+    pub benzinga_id: String,
+    pub ticker: String,
+    pub company_name: Option<String>,
+    /// The date the guidance was released.
+    pub date: NaiveDate,
+    /// The time the guidance was released in US Eastern time.
+    pub time: Option<NaiveTime>,
+    /// The fiscal period the guidance covers, such as `Q1` or `FY`.
+    pub fiscal_period: Option<String>,
+    pub fiscal_year: Option<i32>,
+    /// The currency of the EPS and revenue values.
+    pub currency: Option<String>,
+    pub min_eps_guidance: Option<f64>,
+    pub max_eps_guidance: Option<f64>,
+    pub estimated_eps_guidance: Option<f64>,
+    pub previous_min_eps_guidance: Option<f64>,
+    pub previous_max_eps_guidance: Option<f64>,
+    pub min_revenue_guidance: Option<f64>,
+    pub max_revenue_guidance: Option<f64>,
+    pub estimated_revenue_guidance: Option<f64>,
+    pub previous_min_revenue_guidance: Option<f64>,
+    pub previous_max_revenue_guidance: Option<f64>,
+    /// Whether the guidance is GAAP or adjusted, such as `primary`.
+    pub positioning: Option<String>,
+    /// The type of release, such as `official` or `preliminary`.
+    pub release_type: Option<String>,
+    /// How important Benzinga considers the guidance, from 0 to 5.
+    pub importance: Option<u8>,
+    pub notes: Option<String>,
+    pub last_updated: Option<String>,
+}
+
+impl BenzingaGuidance {
+    /// Returns the EPS guidance range as `(min, max)`, using the estimate
+    /// for a bound that is missing.
+    pub fn eps_range(&self) -> Option<(f64, f64)> {
+        guidance_range(
+            self.min_eps_guidance,
+            self.max_eps_guidance,
+            self.estimated_eps_guidance,
+        )
+    }
+
+    /// Returns the revenue guidance range as `(min, max)`, using the estimate
+    /// for a bound that is missing.
+    pub fn revenue_range(&self) -> Option<(f64, f64)> {
+        guidance_range(
+            self.min_revenue_guidance,
+            self.max_revenue_guidance,
+            self.estimated_revenue_guidance,
+        )
+    }
+
+    /// Returns when the guidance was released relative to the regular
+    /// trading session, or `None` if the time is not known.
+    pub fn timing(&self) -> Option<ReleaseTiming> {
+        let time = self.time?;
+        let timing = if time < NaiveTime::from_hms_opt(9, 30, 0)? {
+            ReleaseTiming::PreMarket
+        } else if time < NaiveTime::from_hms_opt(16, 0, 0)? {
+            ReleaseTiming::DuringMarket
+        } else {
+            ReleaseTiming::AfterMarket
+        };
+        Some(timing)
+    }
+}
+
+fn guidance_range(min: Option<f64>, max: Option<f64>, estimate: Option<f64>) -> Option<(f64, f64)> {
+    let min = min.or(estimate).or(max)?;
+    let max = max.or(estimate).unwrap_or(min);
+    Some((min, max))
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct BenzingaGuidanceResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<BenzingaGuidance>,
+    pub next_url: Option<String>,
+}
+
+//
+// v3/snapshot/indices
+//
+
+/// The change of an index over the current or most recent session.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IndexSession {
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+    pub close: Option<f64>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub open: Option<f64>,
+    pub previous_close: Option<f64>,
+}
+
+/// A snapshot of an index such as `I:SPX`.
+///
+/// Tickers that are not found are returned with `error` and `message` set
+/// and no value.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IndexSnapshot {
+    pub ticker: String,
+    pub name: Option<String>,
+    pub value: Option<f64>,
+    pub session: Option<IndexSession>,
+    pub market_status: Option<String>,
+    /// Whether the value is `REAL-TIME` or `DELAYED`.
+    pub timeframe: Option<String>,
+    /// The time of the value in Unix nanoseconds.
+    pub last_updated: Option<u64>,
+    pub error: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IndicesSnapshotResponse {
+    pub status: String,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub results: Vec<IndexSnapshot>,
+    pub next_url: Option<String>,
+}
+
+//
+// Slim responses
+//
+
+/// A response whose results are decoded into a slim struct, such as
+/// [`ClosesOnly`], that selects only the fields the caller needs.
+///
+/// The other fields of the response and of each result are skipped while
+/// parsing rather than decoded and stored, which saves time and memory on
+/// large responses such as the grouped daily bars of the whole market.
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub struct SlimResponse<T> {
+    pub status: String,
+    #[serde(default)]
+    pub results: Vec<T>,
+    pub next_url: Option<String>,
+    /// The limit the server applied when it was lower than the requested one.
+    #[serde(skip)]
+    pub applied_limit: Option<u32>,
+}
+
+/// The close of an aggregate bar.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ClosesOnly {
+    /// The ticker, which is only included in grouped daily bars.
+    #[serde(rename = "T")]
+    pub ticker: Option<String>,
+    /// The start of the bar in Unix milliseconds.
+    pub t: Option<u64>,
+    pub c: f64,
+}
+
+/// The price, size, and time of a v3 trade.
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct TradePricesOnly {
+    pub price: f64,
+    pub size: f64,
+    /// The time the SIP received the trade in Unix nanoseconds.
+    pub sip_timestamp: Option<u64>,
+}
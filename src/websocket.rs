@@ -13,14 +13,14 @@
 //!
 //! #[tokio::main]
 //! async fn main() {
-//!     let mut client = WebSocketClient::new(STOCKS_CLUSTER, None);
+//!     let mut client = WebSocketClient::connect(STOCKS_CLUSTER, None)
+//!         .expect("failed to connect");
 //!     let res = client.receive();
 //!     let msg_text = res.unwrap().into_text().unwrap();
 //!     println!("msg: {}", msg_text);
 //! }
 //! ```
 use std::env;
-use url::Url;
 
 use serde;
 use serde::Deserialize;
@@ -28,10 +28,24 @@ use serde::Deserialize;
 use tungstenite::client::connect;
 use tungstenite::{Message, WebSocket};
 
+use crate::error::Error;
+use crate::secret::ApiKey;
+
+pub mod broadcast;
+pub mod dispatcher;
+pub mod protocol;
+pub mod subscriptions;
+#[cfg(test)]
+pub(crate) mod test_server;
+
+pub use protocol::Channel;
+pub use subscriptions::{SubscriptionDelta, Subscriptions};
+
 pub const STOCKS_CLUSTER: &str = "stocks";
 pub const FOREX_CLUSTER: &str = "forex";
 pub const CRYPTO_CLUSTER: &str = "crypto";
 
+#[allow(dead_code)]
 #[derive(Clone, Deserialize, Debug)]
 struct ConnectedMessage {
     pub ev: String,
@@ -40,10 +54,13 @@ struct ConnectedMessage {
 }
 
 pub struct WebSocketClient {
-    pub auth_key: String,
+    pub auth_key: ApiKey,
     websocket: WebSocket<tungstenite::stream::MaybeTlsStream<std::net::TcpStream>>,
 }
 
+// Ensure the client can be moved to, and shared between, threads.
+assert_send_sync!(WebSocketClient);
+
 static DEFAULT_WS_HOST: &str = "wss://socket.polygon.io";
 
 impl WebSocketClient {
@@ -56,63 +73,71 @@ impl WebSocketClient {
     /// authentication. If `None` is provided, then the API key specified in the
     /// `POLYGON_AUTH_KEY` environment variable is used.
     ///
+    /// Use [`WebSocketClient::connect()`] to handle these failures instead.
+    ///
     /// # Panics
     ///
     /// This function will panic if `auth_key` is `None` and the
-    /// `POLYGON_AUTH_KEY` environment variable is not set.
+    /// `POLYGON_AUTH_KEY` environment variable is not set, or if the
+    /// connection or authentication fails.
+    #[allow(clippy::panic)]
     pub fn new(cluster: &str, auth_key: Option<&str>) -> Self {
-        let auth_key_actual = match auth_key {
+        match WebSocketClient::connect(cluster, auth_key) {
+            Ok(client) => client,
+            Err(Error::MissingAuthKey) => panic!("POLYGON_AUTH_KEY not set"),
+            Err(e) => panic!("failed to connect: {}", e),
+        }
+    }
+
+    /// Connects to a cluster and authenticates.
+    ///
+    /// The `cluster` parameter can be one of `STOCKS_CLUSTER`, `FOREX_CLUSTER`,
+    /// or `CRYPTO_CLUSTER`. If `auth_key` is `None`, then the API key
+    /// specified in the `POLYGON_AUTH_KEY` environment variable is used, and
+    /// [`Error::MissingAuthKey`] is returned if it is not set.
+    pub fn connect(cluster: &str, auth_key: Option<&str>) -> Result<Self, Error> {
+        let auth_key = match auth_key {
             Some(v) => String::from(v),
-            _ => match env::var("POLYGON_AUTH_KEY") {
-                Ok(v) => v,
-                _ => panic!("POLYGON_AUTH_KEY not set"),
-            },
+            None => env::var("POLYGON_AUTH_KEY").map_err(|_| Error::MissingAuthKey)?,
         };
 
-        let url_str = format!("{}/{}", DEFAULT_WS_HOST, cluster);
-        let url = Url::parse(&url_str).unwrap();
-        let sock = connect(url).expect("failed to connect").0;
+        let url = format!("{}/{}", DEFAULT_WS_HOST, cluster);
+        WebSocketClient::connect_url(&url, auth_key)
+    }
+
+    pub(crate) fn connect_url(url: &str, auth_key: String) -> Result<Self, Error> {
+        let (websocket, _) = connect(url)?;
 
         let mut wsc = WebSocketClient {
-            auth_key: auth_key_actual,
-            websocket: sock,
+            auth_key: ApiKey::from(auth_key),
+            websocket,
         };
 
-        wsc._authenticate();
-
-        wsc
-    }
+        let msg = protocol::auth_message(wsc.auth_key.expose());
+        wsc.websocket.write_message(Message::Text(msg))?;
 
-    fn _authenticate(&mut self) {
-        let msg = format!("{{\"action\":\"auth\",\"params\":\"{}\"}}", self.auth_key);
-        self.websocket
-            .write_message(Message::Text(msg))
-            .expect("failed to authenticate");
+        Ok(wsc)
     }
 
     /// Subscribes to one or more ticker.
-    pub fn subscribe(&mut self, params: &[&str]) {
-        let msg = format!(
-            "{{\"action\":\"subscribe\",\"params\":\"{}\"}}",
-            params.join(",")
-        );
-        self.websocket
-            .write_message(Message::Text(msg))
-            .expect("failed to subscribe");
+    ///
+    /// Each parameter is a channel such as `T.MSFT`, given either as a string
+    /// or as a [`Channel`].
+    pub fn subscribe<S: AsRef<str>>(&mut self, params: &[S]) -> Result<(), Error> {
+        let msg = protocol::subscribe_message(params);
+        self.websocket.write_message(Message::Text(msg))?;
+        Ok(())
     }
 
     /// Unscribes from one or more ticker.
-    pub fn unsubscribe(&mut self, params: &[&str]) {
-        let msg = format!(
-            "{{\"action\":\"unsubscribe\",\"params\":\"{}\"}}",
-            params.join(",")
-        );
-        self.websocket
-            .write_message(Message::Text(msg))
-            .expect("failed to unsubscribe");
+    pub fn unsubscribe<S: AsRef<str>>(&mut self, params: &[S]) -> Result<(), Error> {
+        let msg = protocol::unsubscribe_message(params);
+        self.websocket.write_message(Message::Text(msg))?;
+        Ok(())
     }
 
     /// Receives a single message.
+    #[allow(clippy::result_large_err)]
     pub fn receive(&mut self) -> tungstenite::error::Result<Message> {
         self.websocket.read_message()
     }
@@ -120,6 +145,8 @@ impl WebSocketClient {
 
 #[cfg(test)]
 mod tests {
+    use crate::error::{Error, ErrorKind};
+    use crate::websocket::test_server::TestServer;
     use crate::websocket::ConnectedMessage;
     use crate::websocket::WebSocketClient;
     use crate::websocket::STOCKS_CLUSTER;
@@ -128,16 +155,45 @@ mod tests {
     fn test_subscribe() {
         let mut socket = WebSocketClient::new(STOCKS_CLUSTER, None);
         let params = vec!["T.MSFT"];
-        socket.subscribe(&params);
+        socket.subscribe(&params).unwrap();
+    }
+
+    #[test]
+    fn test_connect_url() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(TestServer::start(2, Vec::new()));
+
+        let mut socket = WebSocketClient::connect_url(&server.url(), String::from("key")).unwrap();
+        socket.subscribe(&["T.MSFT"]).unwrap();
+
+        // Wait for the server to close the connection once it has seen both
+        // messages.
+        while socket.receive().is_ok() {}
+        let received = server.received();
+        assert!(received[0].contains("\"auth\""));
+        assert!(received[1].contains("T.MSFT"));
+
+        // Writing to a closed connection is an error rather than a panic.
+        let err = socket.subscribe(&["T.AAPL"]).unwrap_err();
+        assert!(matches!(err, Error::WebSocket(_)));
+    }
+
+    #[test]
+    fn test_connect_failure() {
+        // Nothing listens on port 1 of the loopback address.
+        let err = WebSocketClient::connect_url("ws://127.0.0.1:1", String::from("key"))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::Transport);
     }
 
     #[test]
     fn test_receive() {
         let mut socket = WebSocketClient::new(STOCKS_CLUSTER, None);
         let res = socket.receive();
-        assert_eq!(res.is_ok(), true);
+        assert!(res.is_ok());
         let msg = res.unwrap();
-        assert_eq!(msg.is_text(), true);
+        assert!(msg.is_text());
         let msg_str = msg.into_text().unwrap();
         let messages: Vec<ConnectedMessage> = serde_json::from_str(&msg_str).unwrap();
         let connected = messages.first().unwrap();
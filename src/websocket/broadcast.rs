@@ -0,0 +1,263 @@
+//! Fan-out of the events of one WebSocket connection to several consumers.
+//!
+//! A [`Dispatcher`] yields each event once, to a single consumer. Converting
+//! it with [`Dispatcher::into_broadcast()`] instead drives the connection on a
+//! background task and hands a copy of every event to each [`EventReceiver`]
+//! obtained from the resulting [`Broadcaster`], so that independent
+//! components of an application can each consume the same stream.
+//!
+//! Every receiver buffers up to the capacity given to
+//! [`Dispatcher::into_broadcast()`]. A receiver that falls further behind
+//! skips the oldest events and is told how many it missed with
+//! [`BroadcastEvent::Lagged`], rather than slowing down the connection or the
+//! other receivers.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use polygon_client::websocket::broadcast::BroadcastEvent;
+//! use polygon_client::websocket::dispatcher::Dispatcher;
+//! use polygon_client::websocket::{Channel, STOCKS_CLUSTER};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut dispatcher = Dispatcher::connect(STOCKS_CLUSTER, None).await.unwrap();
+//!     dispatcher.subscribe(&[Channel::trades("MSFT")]).await.unwrap();
+//!
+//!     let broadcaster = dispatcher.into_broadcast(1024);
+//!     for name in ["logger", "strategy"] {
+//!         let mut events = broadcaster.subscribe();
+//!         tokio::spawn(async move {
+//!             while let Some(event) = events.recv().await {
+//!                 match event {
+//!                     BroadcastEvent::Event(e) => println!("{}: {:?}", name, e),
+//!                     BroadcastEvent::Lagged(n) => println!("{}: missed {} events", name, n),
+//!                     BroadcastEvent::Error(e) => println!("{}: {}", name, e),
+//!                 }
+//!             }
+//!         });
+//!     }
+//!
+//!     broadcaster.closed().await;
+//! }
+//! ```
+use std::sync::Arc;
+
+use futures::Stream;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::error::Error;
+use crate::events::PolygonEvent;
+use crate::websocket::dispatcher::Dispatcher;
+
+/// An item received by an [`EventReceiver`].
+#[derive(Clone, Debug)]
+pub enum BroadcastEvent {
+    /// An event received from the connection.
+    Event(PolygonEvent),
+    /// The receiver fell behind and this many of the oldest events were
+    /// skipped.
+    Lagged(u64),
+    /// An error returned by the connection, shared between all receivers.
+    Error(Arc<Error>),
+}
+
+/// Drives a [`Dispatcher`] on a background task and hands every event to each
+/// of its receivers.
+///
+/// The connection is closed when the broadcaster is dropped.
+pub struct Broadcaster {
+    // The task owns the only strong sender, so that receivers see the channel
+    // close once the connection ends.
+    sender: broadcast::WeakSender<Result<PolygonEvent, Arc<Error>>>,
+    task: JoinHandle<()>,
+}
+
+/// Receives the events of a [`Broadcaster`].
+pub struct EventReceiver {
+    receiver: broadcast::Receiver<Result<PolygonEvent, Arc<Error>>>,
+}
+
+// Ensure the broadcaster and its receivers can be moved to, and shared
+// between, threads.
+assert_send_sync!(Broadcaster, EventReceiver);
+
+impl Dispatcher {
+    /// Converts the dispatcher into a [`Broadcaster`] whose receivers each
+    /// buffer up to `capacity` events.
+    ///
+    /// Subscribe to channels before converting the dispatcher, as the
+    /// broadcaster does not change subscriptions. Events received before the
+    /// first call to [`Broadcaster::subscribe()`] are dropped.
+    ///
+    /// A capacity of zero buffers one event.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if it is not called from within a Tokio
+    /// runtime.
+    pub fn into_broadcast(mut self, capacity: usize) -> Broadcaster {
+        let (events, _) = broadcast::channel(capacity.max(1));
+        let sender = events.downgrade();
+
+        let task = tokio::spawn(async move {
+            while let Some(event) = self.next_event().await {
+                // Sending only fails when there are no receivers, in which
+                // case the event is dropped.
+                let _ = events.send(event.map_err(Arc::new));
+            }
+        });
+
+        Broadcaster { sender, task }
+    }
+}
+
+impl Broadcaster {
+    /// Returns a new receiver of the events received from now on.
+    ///
+    /// If the connection has already been closed, the receiver returns
+    /// `None` immediately.
+    pub fn subscribe(&self) -> EventReceiver {
+        let receiver = match self.sender.upgrade() {
+            Some(sender) => sender.subscribe(),
+            None => broadcast::channel(1).1,
+        };
+        EventReceiver { receiver }
+    }
+
+    /// Returns the number of receivers that have not been dropped, or zero if
+    /// the connection has been closed.
+    pub fn receiver_count(&self) -> usize {
+        self.sender
+            .upgrade()
+            .map_or(0, |sender| sender.receiver_count())
+    }
+
+    /// Waits until the connection has been closed by the server or has
+    /// failed.
+    pub async fn closed(self) {
+        let mut broadcaster = self;
+        let _ = (&mut broadcaster.task).await;
+    }
+}
+
+impl Drop for Broadcaster {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl EventReceiver {
+    /// Receives the next item.
+    ///
+    /// Returns `None` once the connection has been closed and every event
+    /// buffered for this receiver has been received.
+    pub async fn recv(&mut self) -> Option<BroadcastEvent> {
+        match self.receiver.recv().await {
+            Ok(Ok(event)) => Some(BroadcastEvent::Event(event)),
+            Ok(Err(e)) => Some(BroadcastEvent::Error(e)),
+            Err(broadcast::error::RecvError::Lagged(n)) => Some(BroadcastEvent::Lagged(n)),
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    }
+
+    /// Converts the receiver into a stream that ends once the connection has
+    /// been closed.
+    pub fn into_stream(self) -> impl Stream<Item = BroadcastEvent> + Send {
+        futures::stream::unfold(self, |mut receiver| async move {
+            let item = receiver.recv().await?;
+            Some((item, receiver))
+        })
+    }
+}
+
+impl Clone for EventReceiver {
+    /// Returns a receiver that starts at the same position as this one.
+    fn clone(&self) -> Self {
+        EventReceiver {
+            receiver: self.receiver.resubscribe(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+
+    use crate::events::PolygonEvent;
+    use crate::websocket::broadcast::BroadcastEvent;
+    use crate::websocket::dispatcher::Dispatcher;
+    use crate::websocket::test_server::TestServer;
+
+    fn trades(prices: &[f64]) -> String {
+        let trades: Vec<_> = prices
+            .iter()
+            .map(|p| {
+                format!(
+                    r#"{{"ev":"T","sym":"MSFT","p":{},"s":100,"t":1536068000000}}"#,
+                    p
+                )
+            })
+            .collect();
+        format!("[{}]", trades.join(","))
+    }
+
+    fn price(item: &BroadcastEvent) -> f64 {
+        match item {
+            BroadcastEvent::Event(PolygonEvent::Trade(t)) => t.price,
+            e => panic!("expected a trade, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_broadcast_to_several_receivers() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(1, vec![trades(&[1.0, 2.0]), trades(&[3.0])]).await;
+            let dispatcher = Dispatcher::connect_url(&server.url(), "test")
+                .await
+                .unwrap();
+
+            let broadcaster = dispatcher.into_broadcast(16);
+            let first = broadcaster.subscribe();
+            let second = broadcaster.subscribe();
+            assert_eq!(broadcaster.receiver_count(), 2);
+
+            let first: Vec<_> = first.into_stream().collect().await;
+            let second: Vec<_> = second.into_stream().collect().await;
+            assert_eq!(
+                first.iter().map(price).collect::<Vec<_>>(),
+                vec![1.0, 2.0, 3.0]
+            );
+            assert_eq!(
+                second.iter().map(price).collect::<Vec<_>>(),
+                vec![1.0, 2.0, 3.0]
+            );
+        });
+    }
+
+    #[test]
+    fn test_broadcast_lagged() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(1, vec![trades(&[1.0, 2.0, 3.0])]).await;
+            let dispatcher = Dispatcher::connect_url(&server.url(), "test")
+                .await
+                .unwrap();
+
+            let broadcaster = dispatcher.into_broadcast(1);
+            let mut receiver = broadcaster.subscribe();
+
+            // Let the connection deliver every event before receiving any.
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            assert!(matches!(
+                receiver.recv().await,
+                Some(BroadcastEvent::Lagged(2))
+            ));
+            assert_eq!(price(&receiver.recv().await.unwrap()), 3.0);
+            assert!(receiver.recv().await.is_none());
+        });
+    }
+}
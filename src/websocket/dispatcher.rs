@@ -0,0 +1,374 @@
+//! An asynchronous WebSocket connection that yields typed events.
+//!
+//! Unlike [`WebSocketClient`](crate::websocket::WebSocketClient), which hands
+//! back raw messages, the [`Dispatcher`] parses every message into
+//! [`PolygonEvent`]s and can be consumed as a [`Stream`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use polygon_client::websocket::dispatcher::Dispatcher;
+//! use polygon_client::websocket::{Channel, STOCKS_CLUSTER};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut dispatcher = Dispatcher::connect(STOCKS_CLUSTER, None).await.unwrap();
+//!     dispatcher.subscribe(&[Channel::trades("MSFT")]).await.unwrap();
+//!
+//!     let mut events = Box::pin(dispatcher.into_stream());
+//!     while let Some(event) = events.next().await {
+//!         println!("{:?}", event.unwrap());
+//!     }
+//! }
+//! ```
+use std::collections::VecDeque;
+use std::env;
+use std::time::Duration;
+
+use futures::{SinkExt, Stream, StreamExt};
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tungstenite::Message;
+
+use crate::error::Error;
+use crate::events::{parse_events, ParseOptions, PolygonEvent};
+use crate::websocket::subscriptions::{SubscriptionDelta, Subscriptions};
+use crate::websocket::{protocol, DEFAULT_WS_HOST};
+
+/// An authenticated WebSocket connection that parses incoming messages into
+/// events.
+pub struct Dispatcher {
+    websocket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    options: ParseOptions,
+    pending: VecDeque<PolygonEvent>,
+    watchlist: Watchlist,
+}
+
+/// The channels managed by [`Dispatcher::set_watchlist()`].
+struct Watchlist {
+    prefixes: Vec<String>,
+    debounce: Duration,
+    current: Subscriptions,
+    target: Option<(Subscriptions, Instant)>,
+}
+
+// Ensure the dispatcher can be moved to, and shared between, threads.
+assert_send_sync!(Dispatcher);
+
+impl Dispatcher {
+    /// Connects to a cluster and authenticates.
+    ///
+    /// The `cluster` parameter can be one of `STOCKS_CLUSTER`, `FOREX_CLUSTER`,
+    /// or `CRYPTO_CLUSTER`. If `auth_key` is `None`, then the API key
+    /// specified in the `POLYGON_AUTH_KEY` environment variable is used, and
+    /// [`Error::MissingAuthKey`] is returned if it is not set.
+    pub async fn connect(cluster: &str, auth_key: Option<&str>) -> Result<Self, Error> {
+        let auth_key = match auth_key {
+            Some(v) => String::from(v),
+            None => env::var("POLYGON_AUTH_KEY").map_err(|_| Error::MissingAuthKey)?,
+        };
+
+        let url = format!("{}/{}", DEFAULT_WS_HOST, cluster);
+        Dispatcher::connect_url(&url, &auth_key).await
+    }
+
+    pub(crate) async fn connect_url(url: &str, auth_key: &str) -> Result<Self, Error> {
+        let (websocket, _) = tokio_tungstenite::connect_async(url).await?;
+
+        let mut dispatcher = Dispatcher {
+            websocket,
+            options: ParseOptions::default(),
+            pending: VecDeque::new(),
+            watchlist: Watchlist {
+                prefixes: vec![String::from(protocol::TRADES)],
+                debounce: Duration::from_secs(0),
+                current: Subscriptions::new(),
+                target: None,
+            },
+        };
+        dispatcher.send(protocol::auth_message(auth_key)).await?;
+
+        Ok(dispatcher)
+    }
+
+    /// Sets the options used to parse incoming messages.
+    pub fn parse_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the channel prefixes that [`Dispatcher::set_watchlist()`]
+    /// subscribes to for each ticker. Defaults to
+    /// [`TRADES`](protocol::TRADES).
+    pub fn watchlist_prefixes<S: AsRef<str>>(mut self, prefixes: &[S]) -> Self {
+        self.watchlist.prefixes = prefixes.iter().map(|p| String::from(p.as_ref())).collect();
+        self
+    }
+
+    /// Sets how long the watchlist must stay unchanged before a change made
+    /// with [`Dispatcher::set_watchlist()`] is sent to the server. By default,
+    /// changes are sent immediately.
+    pub fn watchlist_debounce(mut self, debounce: Duration) -> Self {
+        self.watchlist.debounce = debounce;
+        self
+    }
+
+    /// Subscribes to one or more channels, such as `AM.MSFT`, given either as
+    /// strings or as [`Channel`](crate::websocket::Channel)s.
+    pub async fn subscribe<S: AsRef<str>>(&mut self, channels: &[S]) -> Result<(), Error> {
+        self.send(protocol::subscribe_message(channels)).await
+    }
+
+    /// Unsubscribes from one or more channels.
+    pub async fn unsubscribe<S: AsRef<str>>(&mut self, channels: &[S]) -> Result<(), Error> {
+        self.send(protocol::unsubscribe_message(channels)).await
+    }
+
+    /// Replaces the watchlist with `tickers`.
+    ///
+    /// Only the channels that changed since the watchlist was last sent are
+    /// subscribed to or unsubscribed from, using the prefixes set with
+    /// [`Dispatcher::watchlist_prefixes()`]. If a debounce is set with
+    /// [`Dispatcher::watchlist_debounce()`], the change is held until the
+    /// watchlist has not changed for that long, so that a list that is
+    /// updated several times in quick succession is only sent once. Held
+    /// changes are sent while waiting in [`Dispatcher::next_event()`] or by
+    /// calling [`Dispatcher::flush_watchlist()`].
+    ///
+    /// The watchlist owns the channels it subscribes to: a channel that was
+    /// also subscribed to with [`Dispatcher::subscribe()`] is unsubscribed
+    /// from when its ticker leaves the watchlist. Returns an error without
+    /// changing the watchlist if a ticker is not valid.
+    pub async fn set_watchlist<S: AsRef<str>>(&mut self, tickers: &[S]) -> Result<(), Error> {
+        let mut target = Subscriptions::new();
+        for prefix in &self.watchlist.prefixes {
+            target = target.union(Subscriptions::for_tickers(prefix, tickers)?);
+        }
+
+        let deadline = Instant::now() + self.watchlist.debounce;
+        self.watchlist.target = Some((target, deadline));
+
+        if self.watchlist.debounce.is_zero() {
+            self.flush_watchlist().await?;
+        }
+        Ok(())
+    }
+
+    /// Sends any watchlist change held by the debounce immediately and returns
+    /// the channels that were subscribed to and unsubscribed from.
+    ///
+    /// If the change cannot be sent, it is dropped and
+    /// [`Dispatcher::watchlist()`] keeps the channels sent before it.
+    pub async fn flush_watchlist(&mut self) -> Result<SubscriptionDelta, Error> {
+        let (target, _) = match self.watchlist.target.take() {
+            Some(target) => target,
+            None => return Ok(SubscriptionDelta::default()),
+        };
+
+        let delta = self.watchlist.current.diff(&target);
+        self.send_delta(&delta).await?;
+        self.watchlist.current = target;
+
+        Ok(delta)
+    }
+
+    /// Unsubscribes from and subscribes to the channels of a watchlist
+    /// change, subscribing again to the channels it unsubscribed from if the
+    /// subscription fails.
+    async fn send_delta(&mut self, delta: &SubscriptionDelta) -> Result<(), Error> {
+        if !delta.unsubscribe.is_empty() {
+            self.unsubscribe(&delta.unsubscribe).await?;
+        }
+        if !delta.subscribe.is_empty() {
+            if let Err(e) = self.subscribe(&delta.subscribe).await {
+                if !delta.unsubscribe.is_empty() {
+                    // The original error is more useful than that of the
+                    // rollback, which fails on a broken connection as well.
+                    let _ = self.subscribe(&delta.unsubscribe).await;
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the channels of the watchlist that have been sent to the
+    /// server.
+    pub fn watchlist(&self) -> &Subscriptions {
+        &self.watchlist.current
+    }
+
+    async fn send(&mut self, msg: String) -> Result<(), Error> {
+        self.websocket.send(Message::Text(msg)).await?;
+        Ok(())
+    }
+
+    /// Receives the next event.
+    ///
+    /// Status messages and events of unknown types are skipped. Returns
+    /// `None` once the connection has been closed. A held watchlist change is
+    /// sent once its debounce has elapsed.
+    pub async fn next_event(&mut self) -> Option<Result<PolygonEvent, Error>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+
+            let next = match self.watchlist.target.as_ref() {
+                Some((_, deadline)) => {
+                    tokio::select! {
+                        next = self.websocket.next() => next,
+                        _ = tokio::time::sleep_until(*deadline) => {
+                            if let Err(e) = self.flush_watchlist().await {
+                                return Some(Err(e));
+                            }
+                            continue;
+                        }
+                    }
+                }
+                None => self.websocket.next().await,
+            };
+
+            let text = match next? {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => return None,
+                Ok(_) => continue,
+                Err(e) => return Some(Err(e.into())),
+            };
+
+            match parse_events(&text, self.options) {
+                Ok(events) => {
+                    #[cfg(feature = "metrics")]
+                    crate::telemetry::record_ws_message(&events);
+                    self.pending.extend(events)
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Converts the dispatcher into a stream of events that ends once the
+    /// connection has been closed.
+    pub fn into_stream(self) -> impl Stream<Item = Result<PolygonEvent, Error>> + Send {
+        futures::stream::unfold(self, |mut dispatcher| async move {
+            let event = dispatcher.next_event().await?;
+            Some((event, dispatcher))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+
+    use crate::error::Error;
+    use crate::events::{ParseOptions, PolygonEvent};
+    use crate::websocket::dispatcher::Dispatcher;
+    use crate::websocket::test_server::TestServer;
+    use crate::websocket::Channel;
+
+    #[test]
+    fn test_dispatcher() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(
+                2,
+                vec![
+                    String::from(r#"[{"ev":"status","status":"auth_success"}]"#),
+                    String::from(
+                        r#"[{"ev":"T","sym":"MSFT","i":"1","x":4,"p":114.125,"s":100,"t":1536068000000},
+                            {"ev":"Q","sym":"MSFT","bx":4,"bp":114.1,"bs":1,"ax":7,"ap":114.2,"as":2,"t":1536068000001}]"#,
+                    ),
+                    String::from(r#"[{"ev":"T","sym":"MSFT","p":"bad"}]"#),
+                ],
+            )
+            .await;
+
+            let mut dispatcher = Dispatcher::connect_url(&server.url(), "test")
+                .await
+                .unwrap()
+                .parse_options(ParseOptions { tag_sessions: true });
+            dispatcher
+                .subscribe(&[Channel::trades("MSFT")])
+                .await
+                .unwrap();
+
+            let events: Vec<_> = dispatcher.into_stream().collect().await;
+            assert_eq!(events.len(), 3);
+            match &events[0] {
+                Ok(PolygonEvent::Trade(t)) => assert!(t.session.is_some()),
+                e => panic!("expected a trade, got {:?}", e),
+            }
+            assert!(matches!(events[1], Ok(PolygonEvent::Quote(_))));
+            assert!(matches!(events[2], Err(Error::Decode(_))));
+
+            assert_eq!(
+                server.received(),
+                vec![
+                    r#"{"action":"auth","params":"test"}"#,
+                    r#"{"action":"subscribe","params":"T.MSFT"}"#,
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_set_watchlist() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(
+                2,
+                vec![String::from(
+                    r#"[{"ev":"T","sym":"NVDA","i":"1","x":4,"p":114.125,"s":100,"t":1536068000000}]"#,
+                )],
+            )
+            .await;
+
+            let mut dispatcher = Dispatcher::connect_url(&server.url(), "test")
+                .await
+                .unwrap()
+                .watchlist_prefixes(&["T", "Q"])
+                .watchlist_debounce(Duration::from_millis(20));
+            dispatcher.set_watchlist(&["AAPL"]).await.unwrap();
+            dispatcher.set_watchlist(&["MSFT", "NVDA"]).await.unwrap();
+            assert!(dispatcher.watchlist().is_empty());
+            assert!(dispatcher.set_watchlist(&["BRK A"]).await.is_err());
+
+            // The pending change is sent while waiting for the next event.
+            let event = dispatcher.next_event().await.unwrap().unwrap();
+            assert!(matches!(event, PolygonEvent::Trade(_)));
+            assert_eq!(dispatcher.watchlist().len(), 4);
+            assert_eq!(
+                server.received(),
+                vec![
+                    r#"{"action":"auth","params":"test"}"#,
+                    r#"{"action":"subscribe","params":"Q.MSFT,Q.NVDA,T.MSFT,T.NVDA"}"#,
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_set_watchlist_failure_keeps_watchlist() {
+        tokio_test::block_on(async {
+            let server = TestServer::start(2, Vec::new()).await;
+
+            let mut dispatcher = Dispatcher::connect_url(&server.url(), "test")
+                .await
+                .unwrap();
+            dispatcher.set_watchlist(&["AAPL"]).await.unwrap();
+            assert!(dispatcher.next_event().await.is_none());
+
+            // The connection is closed, so the change cannot be sent.
+            assert!(dispatcher.set_watchlist(&["MSFT"]).await.is_err());
+            let channels: Vec<_> = dispatcher
+                .watchlist()
+                .channels()
+                .map(|c| c.as_str())
+                .collect();
+            assert_eq!(channels, vec!["T.AAPL"]);
+        });
+    }
+}
@@ -0,0 +1,293 @@
+//! Channel prefixes and message builders for the WebSocket protocol.
+//!
+//! These are used by [`WebSocketClient`](crate::websocket::WebSocketClient)
+//! and can also be used directly to drive a WebSocket connection by hand.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::websocket::protocol::{subscribe_message, Channel};
+//!
+//! let channels = [Channel::trades("MSFT"), Channel::crypto_quotes("BTC-USD")];
+//! assert_eq!(channels[0].to_string(), "T.MSFT");
+//! assert_eq!(
+//!     subscribe_message(&channels),
+//!     r#"{"action":"subscribe","params":"T.MSFT,XQ.BTC-USD"}"#
+//! );
+//! ```
+use std::fmt;
+use std::str::FromStr;
+
+/// Stock and options trades.
+pub const TRADES: &str = "T";
+/// Stock and options quotes.
+pub const QUOTES: &str = "Q";
+/// Stock, options, and index aggregates per second.
+pub const SECOND_AGGREGATES: &str = "A";
+/// Stock, options, and index aggregates per minute.
+pub const MINUTE_AGGREGATES: &str = "AM";
+/// Crypto trades.
+pub const CRYPTO_TRADES: &str = "XT";
+/// Crypto quotes.
+pub const CRYPTO_QUOTES: &str = "XQ";
+/// Crypto aggregates per minute.
+pub const CRYPTO_MINUTE_AGGREGATES: &str = "XA";
+/// Crypto aggregates per second.
+pub const CRYPTO_SECOND_AGGREGATES: &str = "XAS";
+/// Crypto level 2 book updates.
+pub const CRYPTO_BOOKS: &str = "XL2";
+/// Forex quotes.
+pub const FOREX_QUOTES: &str = "C";
+/// Forex aggregates per minute.
+pub const FOREX_MINUTE_AGGREGATES: &str = "CA";
+/// Forex aggregates per second.
+pub const FOREX_SECOND_AGGREGATES: &str = "CAS";
+/// Index values.
+pub const INDEX_VALUES: &str = "V";
+
+/// Every channel prefix supported by the WebSocket API.
+pub const PREFIXES: &[&str] = &[
+    TRADES,
+    QUOTES,
+    SECOND_AGGREGATES,
+    MINUTE_AGGREGATES,
+    CRYPTO_TRADES,
+    CRYPTO_QUOTES,
+    CRYPTO_MINUTE_AGGREGATES,
+    CRYPTO_SECOND_AGGREGATES,
+    CRYPTO_BOOKS,
+    FOREX_QUOTES,
+    FOREX_MINUTE_AGGREGATES,
+    FOREX_SECOND_AGGREGATES,
+    INDEX_VALUES,
+];
+
+/// The ticker used to subscribe to every ticker on a channel.
+pub const ALL_TICKERS: &str = "*";
+
+/// The prefix of options contract tickers.
+const OPTIONS_TICKER_PREFIX: &str = "O:";
+
+/// A WebSocket subscription channel, such as `T.MSFT`, made up of a channel
+/// prefix and a ticker.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Channel {
+    name: String,
+    prefix_len: usize,
+}
+
+impl Channel {
+    /// Returns a channel with the given prefix and ticker.
+    pub fn new(prefix: &str, ticker: &str) -> Self {
+        Channel {
+            name: format!("{}.{}", prefix, ticker),
+            prefix_len: prefix.len(),
+        }
+    }
+
+    /// Returns the channel for a stock's trades.
+    pub fn trades(ticker: &str) -> Self {
+        Channel::new(TRADES, ticker)
+    }
+
+    /// Returns the channel for a stock's quotes.
+    pub fn quotes(ticker: &str) -> Self {
+        Channel::new(QUOTES, ticker)
+    }
+
+    /// Returns the channel for a stock's aggregates per second.
+    pub fn second_aggregates(ticker: &str) -> Self {
+        Channel::new(SECOND_AGGREGATES, ticker)
+    }
+
+    /// Returns the channel for a stock's aggregates per minute.
+    pub fn minute_aggregates(ticker: &str) -> Self {
+        Channel::new(MINUTE_AGGREGATES, ticker)
+    }
+
+    /// Returns the channel for a crypto pair's trades, such as `BTC-USD`.
+    pub fn crypto_trades(pair: &str) -> Self {
+        Channel::new(CRYPTO_TRADES, pair)
+    }
+
+    /// Returns the channel for a crypto pair's quotes, such as `BTC-USD`.
+    pub fn crypto_quotes(pair: &str) -> Self {
+        Channel::new(CRYPTO_QUOTES, pair)
+    }
+
+    /// Returns the channel for a crypto pair's aggregates per minute.
+    pub fn crypto_minute_aggregates(pair: &str) -> Self {
+        Channel::new(CRYPTO_MINUTE_AGGREGATES, pair)
+    }
+
+    /// Returns the channel for a crypto pair's aggregates per second.
+    pub fn crypto_second_aggregates(pair: &str) -> Self {
+        Channel::new(CRYPTO_SECOND_AGGREGATES, pair)
+    }
+
+    /// Returns the channel for a currency pair's quotes, such as `EUR/USD`.
+    pub fn forex_quotes(pair: &str) -> Self {
+        Channel::new(FOREX_QUOTES, pair)
+    }
+
+    /// Returns the channel for a currency pair's aggregates per minute.
+    pub fn forex_minute_aggregates(pair: &str) -> Self {
+        Channel::new(FOREX_MINUTE_AGGREGATES, pair)
+    }
+
+    /// Returns the channel for a currency pair's aggregates per second.
+    pub fn forex_second_aggregates(pair: &str) -> Self {
+        Channel::new(FOREX_SECOND_AGGREGATES, pair)
+    }
+
+    /// Returns the channel for an index's values, such as `I:SPX`.
+    pub fn index_values(ticker: &str) -> Self {
+        Channel::new(INDEX_VALUES, ticker)
+    }
+
+    /// Returns the channel for an options contract's trades. The `O:` prefix
+    /// is added to the contract ticker if it is missing.
+    pub fn options_trades(contract: &str) -> Self {
+        Channel::new(TRADES, &options_ticker(contract))
+    }
+
+    /// Returns the channel for an options contract's quotes. The `O:` prefix
+    /// is added to the contract ticker if it is missing.
+    pub fn options_quotes(contract: &str) -> Self {
+        Channel::new(QUOTES, &options_ticker(contract))
+    }
+
+    /// Returns the channel for an options contract's aggregates per minute.
+    /// The `O:` prefix is added to the contract ticker if it is missing.
+    pub fn options_minute_aggregates(contract: &str) -> Self {
+        Channel::new(MINUTE_AGGREGATES, &options_ticker(contract))
+    }
+
+    /// Returns the channel prefix, such as `T`.
+    pub fn prefix(&self) -> &str {
+        &self.name[..self.prefix_len]
+    }
+
+    /// Returns the ticker, such as `MSFT`.
+    pub fn ticker(&self) -> &str {
+        &self.name[self.prefix_len + 1..]
+    }
+
+    /// Returns the full channel name, such as `T.MSFT`.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+fn options_ticker(contract: &str) -> String {
+    if contract.starts_with(OPTIONS_TICKER_PREFIX) {
+        String::from(contract)
+    } else {
+        format!("{}{}", OPTIONS_TICKER_PREFIX, contract)
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl AsRef<str> for Channel {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The error returned when parsing a string that is not of the form
+/// `PREFIX.TICKER` into a [`Channel`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseChannelError(pub(crate) String);
+
+impl fmt::Display for ParseChannelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' is not a valid channel", self.0)
+    }
+}
+
+impl std::error::Error for ParseChannelError {}
+
+impl FromStr for Channel {
+    type Err = ParseChannelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('.') {
+            Some((prefix, ticker)) if !prefix.is_empty() && !ticker.is_empty() => {
+                Ok(Channel::new(prefix, ticker))
+            }
+            _ => Err(ParseChannelError(String::from(s))),
+        }
+    }
+}
+
+/// Returns the message that authenticates a connection.
+pub fn auth_message(auth_key: &str) -> String {
+    action_message("auth", &[auth_key])
+}
+
+/// Returns the message that subscribes to the given channels.
+pub fn subscribe_message<S: AsRef<str>>(channels: &[S]) -> String {
+    action_message("subscribe", channels)
+}
+
+/// Returns the message that unsubscribes from the given channels.
+pub fn unsubscribe_message<S: AsRef<str>>(channels: &[S]) -> String {
+    action_message("unsubscribe", channels)
+}
+
+fn action_message<S: AsRef<str>>(action: &str, params: &[S]) -> String {
+    let params: Vec<&str> = params.iter().map(|p| p.as_ref()).collect();
+    format!(
+        "{{\"action\":\"{}\",\"params\":\"{}\"}}",
+        action,
+        params.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::websocket::protocol::*;
+
+    #[test]
+    fn test_channel_names() {
+        assert_eq!(Channel::trades("MSFT").as_str(), "T.MSFT");
+        assert_eq!(Channel::minute_aggregates(ALL_TICKERS).as_str(), "AM.*");
+        assert_eq!(Channel::crypto_trades("BTC-USD").as_str(), "XT.BTC-USD");
+        assert_eq!(Channel::forex_quotes("EUR/USD").as_str(), "C.EUR/USD");
+        assert_eq!(
+            Channel::options_trades("SPY251219C00650000").as_str(),
+            "T.O:SPY251219C00650000"
+        );
+        assert_eq!(
+            Channel::options_quotes("O:SPY251219C00650000").as_str(),
+            "Q.O:SPY251219C00650000"
+        );
+
+        let channel = Channel::index_values("I:SPX");
+        assert_eq!(channel.prefix(), "V");
+        assert_eq!(channel.ticker(), "I:SPX");
+    }
+
+    #[test]
+    fn test_parse_channel() {
+        let channel: Channel = "AM.O:SPY251219C00650000".parse().unwrap();
+        assert_eq!(channel.prefix(), "AM");
+        assert_eq!(channel.ticker(), "O:SPY251219C00650000");
+        assert!("MSFT".parse::<Channel>().is_err());
+        assert!("T.".parse::<Channel>().is_err());
+    }
+
+    #[test]
+    fn test_messages() {
+        assert_eq!(auth_message("key"), r#"{"action":"auth","params":"key"}"#);
+        assert_eq!(
+            unsubscribe_message(&["T.MSFT", "Q.MSFT"]),
+            r#"{"action":"unsubscribe","params":"T.MSFT,Q.MSFT"}"#
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Sets of validated subscription channels and the changes between them.
+//!
+//! A [`Subscriptions`] set only holds channels with a known prefix and a
+//! ticker that can be sent in a subscription message. Comparing the set that
+//! is currently subscribed with a new one produces a [`SubscriptionDelta`]
+//! holding only the channels to subscribe to and unsubscribe from, so a
+//! changing watchlist can be followed without resubscribing to everything.
+//!
+//! # Example
+//!
+//! ```
+//! use polygon_client::websocket::Subscriptions;
+//!
+//! let mut current = Subscriptions::trades(&["MSFT", "AAPL"]).unwrap();
+//! let delta = current.apply(Subscriptions::trades(&["MSFT", "NVDA"]).unwrap());
+//!
+//! assert_eq!(delta.subscribe[0].as_str(), "T.NVDA");
+//! assert_eq!(delta.unsubscribe[0].as_str(), "T.AAPL");
+//! assert_eq!(current.len(), 2);
+//! ```
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::websocket::protocol::{
+    self, Channel, ParseChannelError, MINUTE_AGGREGATES, QUOTES, TRADES,
+};
+
+/// The name of the S&P 500 constituents list passed to a
+/// [`TickerListLoader`].
+pub const SP500: &str = "sp500";
+
+/// A source of named ticker lists, such as the constituents of an index,
+/// used to build subscription presets.
+///
+/// The crate does not ship index constituents, which change over time.
+/// Implement this trait, or pass a closure, to load them from a file, a
+/// database, or the REST API.
+pub trait TickerListLoader {
+    /// Returns the tickers in the list called `name`.
+    fn load(&self, name: &str) -> Result<Vec<String>, Error>;
+}
+
+impl<F> TickerListLoader for F
+where
+    F: Fn(&str) -> Result<Vec<String>, Error>,
+{
+    fn load(&self, name: &str) -> Result<Vec<String>, Error> {
+        self(name)
+    }
+}
+
+/// A set of validated subscription channels.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Subscriptions {
+    channels: BTreeSet<Channel>,
+}
+
+impl Subscriptions {
+    /// Returns an empty set.
+    pub fn new() -> Self {
+        Subscriptions::default()
+    }
+
+    /// Returns a set of channels with the given prefix, such as
+    /// [`TRADES`](protocol::TRADES), for each ticker.
+    pub fn for_tickers<S: AsRef<str>>(prefix: &str, tickers: &[S]) -> Result<Self, Error> {
+        let mut subscriptions = Subscriptions::new();
+        for ticker in tickers {
+            subscriptions.insert(Channel::new(prefix, ticker.as_ref().trim()))?;
+        }
+        Ok(subscriptions)
+    }
+
+    /// Returns the trade channels of the given stock tickers.
+    pub fn trades<S: AsRef<str>>(tickers: &[S]) -> Result<Self, Error> {
+        Subscriptions::for_tickers(TRADES, tickers)
+    }
+
+    /// Returns the quote channels of the given stock tickers.
+    pub fn quotes<S: AsRef<str>>(tickers: &[S]) -> Result<Self, Error> {
+        Subscriptions::for_tickers(QUOTES, tickers)
+    }
+
+    /// Returns the minute aggregate channels of the given stock tickers.
+    pub fn minute_aggregates<S: AsRef<str>>(tickers: &[S]) -> Result<Self, Error> {
+        Subscriptions::for_tickers(MINUTE_AGGREGATES, tickers)
+    }
+
+    /// Returns the channels with the given prefix for each ticker in the list
+    /// called `name` provided by `loader`.
+    pub fn from_list<L: TickerListLoader + ?Sized>(
+        loader: &L,
+        name: &str,
+        prefix: &str,
+    ) -> Result<Self, Error> {
+        Subscriptions::for_tickers(prefix, &loader.load(name)?)
+    }
+
+    /// Returns the trade channels of the S&P 500 constituents provided by
+    /// `loader` under the name [`SP500`].
+    pub fn sp500_trades<L: TickerListLoader + ?Sized>(loader: &L) -> Result<Self, Error> {
+        Subscriptions::from_list(loader, SP500, TRADES)
+    }
+
+    /// Returns the minute aggregate channels of the S&P 500 constituents
+    /// provided by `loader` under the name [`SP500`].
+    pub fn sp500_minute_aggregates<L: TickerListLoader + ?Sized>(
+        loader: &L,
+    ) -> Result<Self, Error> {
+        Subscriptions::from_list(loader, SP500, MINUTE_AGGREGATES)
+    }
+
+    /// Reads channels, such as `T.MSFT`, from a file.
+    ///
+    /// Channels are separated by commas or line breaks. Blank lines and lines
+    /// starting with `#` are ignored. Returns an error naming the first
+    /// channel that is not valid.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let contents = fs::read_to_string(path)?;
+
+        let mut subscriptions = Subscriptions::new();
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('#') {
+                continue;
+            }
+            for name in line.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                subscriptions.insert(name.parse()?)?;
+            }
+        }
+        Ok(subscriptions)
+    }
+
+    /// Adds a channel to the set. Returns an error if the channel has an
+    /// unknown prefix or its ticker cannot be sent in a subscription message.
+    pub fn insert(&mut self, channel: Channel) -> Result<(), Error> {
+        validate(&channel)?;
+        self.channels.insert(channel);
+        Ok(())
+    }
+
+    /// Removes a channel from the set and returns whether it was present.
+    pub fn remove(&mut self, channel: &Channel) -> bool {
+        self.channels.remove(channel)
+    }
+
+    /// Returns whether the set contains a channel.
+    pub fn contains(&self, channel: &Channel) -> bool {
+        self.channels.contains(channel)
+    }
+
+    /// Returns the number of channels in the set.
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Returns whether the set has no channels.
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Returns the channels in the set, sorted by name.
+    pub fn channels(&self) -> impl Iterator<Item = &Channel> + '_ {
+        self.channels.iter()
+    }
+
+    /// Adds the channels of another set to this one.
+    pub fn union(mut self, other: Subscriptions) -> Self {
+        self.channels.extend(other.channels);
+        self
+    }
+
+    /// Returns the changes needed to go from this set to `target`.
+    pub fn diff(&self, target: &Subscriptions) -> SubscriptionDelta {
+        SubscriptionDelta {
+            subscribe: target
+                .channels
+                .difference(&self.channels)
+                .cloned()
+                .collect(),
+            unsubscribe: self
+                .channels
+                .difference(&target.channels)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Replaces this set with `target` and returns the changes between them.
+    pub fn apply(&mut self, target: Subscriptions) -> SubscriptionDelta {
+        let delta = self.diff(&target);
+        *self = target;
+        delta
+    }
+}
+
+impl IntoIterator for Subscriptions {
+    type Item = Channel;
+    type IntoIter = std::collections::btree_set::IntoIter<Channel>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.channels.into_iter()
+    }
+}
+
+/// The channels to subscribe to and unsubscribe from to change from one set
+/// of [`Subscriptions`] to another.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubscriptionDelta {
+    /// The channels in the new set that are not in the old one, sorted by
+    /// name.
+    pub subscribe: Vec<Channel>,
+    /// The channels in the old set that are not in the new one, sorted by
+    /// name.
+    pub unsubscribe: Vec<Channel>,
+}
+
+impl SubscriptionDelta {
+    /// Returns whether the two sets are the same.
+    pub fn is_empty(&self) -> bool {
+        self.subscribe.is_empty() && self.unsubscribe.is_empty()
+    }
+}
+
+/// Checks that a channel has a known prefix and a ticker without the
+/// characters that separate channels in a subscription message.
+fn validate(channel: &Channel) -> Result<(), ParseChannelError> {
+    let ticker = channel.ticker();
+    let valid = protocol::PREFIXES.contains(&channel.prefix())
+        && !ticker.is_empty()
+        && !ticker
+            .chars()
+            .any(|c| c == ',' || c == '"' || c == '\\' || c.is_whitespace());
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ParseChannelError(String::from(channel.as_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::error::Error;
+    use crate::websocket::protocol::Channel;
+    use crate::websocket::subscriptions::*;
+
+    #[test]
+    fn test_presets() {
+        let loader = |name: &str| match name {
+            SP500 => Ok(vec![String::from("MSFT"), String::from(" AAPL ")]),
+            _ => Err(Error::MissingAuthKey),
+        };
+
+        let subs = Subscriptions::sp500_trades(&loader).unwrap();
+        let names: Vec<_> = subs.channels().map(|c| c.as_str()).collect();
+        assert_eq!(names, vec!["T.AAPL", "T.MSFT"]);
+        assert!(Subscriptions::from_list(&loader, "dow30", TRADES).is_err());
+
+        assert!(matches!(
+            Subscriptions::trades(&["MSFT", "BRK A"]),
+            Err(Error::InvalidChannel(_))
+        ));
+        assert!(Subscriptions::for_tickers("ZZ", &["MSFT"]).is_err());
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = std::env::temp_dir().join(format!(
+            "polygon-client-subscriptions-{}.txt",
+            std::process::id()
+        ));
+        fs::write(&path, "# Watchlist\nT.MSFT, Q.MSFT\n\nXT.BTC-USD\n").unwrap();
+        let subs = Subscriptions::from_file(&path).unwrap();
+        assert_eq!(subs.len(), 3);
+        assert!(subs.contains(&Channel::crypto_trades("BTC-USD")));
+
+        fs::write(&path, "T.MSFT\nMSFT\n").unwrap();
+        let err = Subscriptions::from_file(&path).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid channel: 'MSFT' is not a valid channel"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut current = Subscriptions::trades(&["MSFT", "AAPL"])
+            .unwrap()
+            .union(Subscriptions::quotes(&["MSFT"]).unwrap());
+        let target = Subscriptions::trades(&["MSFT"]).unwrap();
+
+        let delta = current.diff(&target);
+        assert!(delta.subscribe.is_empty());
+        assert_eq!(
+            delta.unsubscribe,
+            vec![Channel::quotes("MSFT"), Channel::trades("AAPL")]
+        );
+
+        assert!(!current.apply(target.clone()).is_empty());
+        assert_eq!(current, target);
+        assert!(current.diff(&target).is_empty());
+    }
+}
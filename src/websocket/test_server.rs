@@ -0,0 +1,57 @@
+//! A minimal WebSocket server that replays scripted messages in tests.
+use std::sync::{Arc, Mutex};
+
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tungstenite::Message;
+
+/// A server that accepts a single connection, waits for the client's first
+/// `wait_for` messages, then sends each scripted message and closes the
+/// connection. Every text message received from the client is recorded.
+pub(crate) struct TestServer {
+    url: String,
+    received: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestServer {
+    /// Starts a server on a random local port.
+    pub(crate) async fn start(wait_for: usize, messages: Vec<String>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let recorded = received.clone();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+            while recorded.lock().unwrap().len() < wait_for {
+                match ws.next().await {
+                    Some(Ok(Message::Text(text))) => recorded.lock().unwrap().push(text),
+                    Some(Ok(_)) => continue,
+                    _ => return,
+                }
+            }
+
+            for msg in messages {
+                if ws.send(Message::Text(msg)).await.is_err() {
+                    return;
+                }
+            }
+
+            let _ = ws.close(None).await;
+        });
+
+        TestServer { url, received }
+    }
+
+    /// Returns the URL of the server.
+    pub(crate) fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Returns the text messages received from the client so far.
+    pub(crate) fn received(&self) -> Vec<String> {
+        self.received.lock().unwrap().clone()
+    }
+}